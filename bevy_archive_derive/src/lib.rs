@@ -0,0 +1,66 @@
+//! Derive macros for [bevy_archive](https://github.com/chengts95/bevy_archive), enabled by
+//! its `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{DeriveInput, Ident, LitStr, parse_macro_input};
+
+/// Submit the component to the collector read by `SnapshotRegistry::with_auto_registered`.
+///
+/// Options go in a `#[snapshot(...)]` attribute:
+/// - `mode = "EmplaceIfNotExists"`: the `SnapshotMode` to register with, `Full` by default;
+/// - `name = "..."`: register under this name instead of the registry's naming policy.
+#[proc_macro_derive(Snapshot, attributes(snapshot))]
+pub fn derive_snapshot(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Snapshot cannot be derived for generic types; register each instance by hand",
+        ));
+    }
+    let mut mode = Ident::new("Full", Span::call_site());
+    let mut name: Option<LitStr> = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("snapshot")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("mode") {
+                let value: LitStr = meta.value()?.parse()?;
+                mode = Ident::new(&value.value(), value.span());
+                Ok(())
+            } else if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `mode` or `name`"))
+            }
+        })?;
+    }
+
+    let ident = &input.ident;
+    let name = match name {
+        Some(name) => quote!(::core::option::Option::Some(#name)),
+        None => quote!(::core::option::Option::None),
+    };
+    Ok(quote! {
+        const _: () = {
+            fn register(registry: &mut ::bevy_archive::prelude::SnapshotRegistry) {
+                ::bevy_archive::auto_register::register_derived::<#ident>(
+                    registry,
+                    #name,
+                    ::bevy_archive::prelude::SnapshotMode::#mode,
+                );
+            }
+            ::bevy_archive::auto_register::inventory::submit! {
+                ::bevy_archive::auto_register::AutoRegistration(register)
+            }
+        };
+    })
+}
@@ -7,9 +7,6 @@ use bevy_archive::{
     bevy_cmdbuffer::HarvardCommandBuffer,
 };
 use bevy_ecs::prelude::*;
-use bevy_ecs::ptr::OwningPtr;
-use std::ptr::NonNull;
-use bumpalo::Bump;
 use serde::{Deserialize, Serialize};
 use std::fs;
 
@@ -54,11 +51,10 @@ macro_rules! fixed_archetypes {
 
 fn insert_comp<T: Component>(world: &mut World, buffer: &mut HarvardCommandBuffer, entity: Entity, val: T) {
     let comp_id = world.component_id::<T>().unwrap_or_else(|| world.register_component::<T>());
-    // Unsafe fix for simultaneous borrow:
-    let bump_ptr = buffer.data_bump() as *const Bump;
-    let ptr = unsafe { (*bump_ptr).alloc(val) as *mut T };
-    let abox = unsafe { ArenaBox::new::<T>(OwningPtr::new(NonNull::new_unchecked(ptr.cast()))) };
-    buffer.insert_box(entity, comp_id, abox);
+    buffer.record(|rec| {
+        let payload = rec.alloc_component(val);
+        rec.insert_box_checked(world, entity, comp_id, payload).unwrap();
+    });
 }
 
 // rustfmt::skip
@@ -132,8 +128,8 @@ fn old_load_world_manifest(
     manifest: &AuroraWorldManifest,
     registry: &SnapshotRegistry,
 ) -> Result<(), String> {
-    let snapshot: WorldArchSnapshot = (&manifest.world).into();
-    load_world_arch_snapshot(world, &snapshot, registry); //this is old one
+    let snapshot = WorldArchSnapshot::try_from(&manifest.world)?;
+    load_world_arch_snapshot(world, &snapshot, registry).map_err(|e| e.to_string())?; //this is old one
     Ok(())
 }
 
@@ -202,8 +202,8 @@ pub fn load_world_manifest(
     manifest: &AuroraWorldManifest,
     registry: &SnapshotRegistry,
 ) -> Result<(), String> {
-    let snapshot: WorldArchSnapshot = (&manifest.world).into();
-    load_world_arch_snapshot(world, &snapshot, registry);
+    let snapshot = WorldArchSnapshot::try_from(&manifest.world)?;
+    load_world_arch_snapshot(world, &snapshot, registry).map_err(|e| e.to_string())?;
     Ok(())
 }
 fn test_roundtrip_with_children() {
@@ -124,7 +124,7 @@ fn test_roundtrip_with_children() {
     let loaded = load_snapshot_from_file(path).expect("Failed to load snapshot");
     let mut new_world = World::new();
     let registry = setup_registry();
-    load_world_snapshot(&mut new_world, &loaded, &registry);
+    load_world_snapshot(&mut new_world, &loaded, &registry).unwrap();
 
     let snapshot = save_world_snapshot(&new_world, &registry);
     println!(
@@ -5,7 +5,6 @@ use bevy_archive::zip; // re-exported
 use bevy_ecs::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::io::Cursor;
-use std::io::Write;
 
 #[derive(Component, Serialize, Deserialize)]
 struct Position {
@@ -111,31 +110,12 @@ fn main() {
 }
 
 fn save_to_zip_memory(world: &World, reg: &SnapshotRegistry, guidance: &ExportGuidance) -> Vec<u8> {
-    let mut buffer = Vec::new();
-    let cursor = Cursor::new(&mut buffer);
-    let mut zip = zip::ZipWriter::new(cursor);
-    let options = zip::write::SimpleFileOptions::default();
-
-    // Generate Aurora Manifest (Hybrid)
-    let manifest = WorldWithAurora::from_guided(world, reg, guidance);
-
-    // Write external blobs
-    for (path, bytes) in &manifest.external_payloads {
-        zip.start_file(path, options).unwrap();
-        zip.write_all(bytes).unwrap();
+    // Any SnapshotContainer works here: a directory, a tar, an in-memory map...
+    let mut zip = ZipWriterContainer::new(Cursor::new(Vec::new()));
+    let manifest =
+        save_world_manifest_to_container(world, reg, guidance, &mut zip, "manifest.toml").unwrap();
+    for (path, bytes) in &manifest.world.external_payloads {
         println!("Wrote {} to zip ({} bytes)", path, bytes.len());
     }
-
-    // Write manifest
-    // Create wrapper
-    let wrapper = AuroraWorldManifest {
-        metadata: None,
-        world: manifest,
-    };
-    let toml = toml::to_string_pretty(&wrapper).unwrap();
-    zip.start_file("manifest.toml", options).unwrap();
-    zip.write_all(toml.as_bytes()).unwrap();
-
-    zip.finish().unwrap();
-    buffer
+    zip.finish().unwrap().into_inner()
 }
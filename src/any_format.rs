@@ -0,0 +1,198 @@
+//! Loading a snapshot file without knowing its format.
+//!
+//! [`load_any`] detects the [`ArchiveFormat`] of a file from its magic bytes (zip, parquet),
+//! then its extension, then its content, and applies it with the matching loader:
+//! - JSON and TOML files holding a top-level `entities` list load as the legacy per-entity
+//!   [`WorldSnapshot`], other JSON and TOML files as an [`AuroraWorldManifest`];
+//! - MessagePack files as a [`MsgPackArchive`];
+//! - zips as a [`WorldArrowSnapshot`](crate::binary_archive::WorldArrowSnapshot) and parquet
+//!   files as a single archetype, both requiring the `arrow_rs` feature.
+//!
+//! Entities are written by their stored index, like the `apply` of each format.
+
+use std::error::Error;
+use std::path::Path;
+
+use bevy_ecs::prelude::*;
+
+use crate::aurora_archive::AuroraWorldManifest;
+use crate::bevy_registry::SnapshotRegistry;
+use crate::binary_archive::MsgPackArchive;
+use crate::entity_archive::WorldSnapshot;
+use crate::traits::Archive;
+
+/// Snapshot file formats [`load_any`] recognizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// Aurora manifest or legacy entity snapshot as JSON.
+    Json,
+    /// Aurora manifest or legacy entity snapshot as TOML.
+    Toml,
+    /// [`MsgPackArchive`].
+    MsgPack,
+    /// Arrow snapshot zip.
+    Zip,
+    /// One archetype as a parquet file.
+    Parquet,
+}
+
+impl ArchiveFormat {
+    /// Detect the format of `bytes`, the content of `path`.
+    ///
+    /// Zip and parquet magic bytes win over the extension; files with an unknown extension
+    /// are JSON when they start with `{`, MessagePack when they start with a map or array
+    /// marker, and TOML when they are UTF-8.
+    pub fn detect(path: impl AsRef<Path>, bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+            return Some(Self::Zip);
+        }
+        if bytes.starts_with(b"PAR1") {
+            return Some(Self::Parquet);
+        }
+        let ext = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match ext.as_str() {
+            "json" => return Some(Self::Json),
+            "toml" => return Some(Self::Toml),
+            "msgpack" | "mpk" => return Some(Self::MsgPack),
+            "zip" => return Some(Self::Zip),
+            "parquet" => return Some(Self::Parquet),
+            _ => {}
+        }
+        match bytes.first() {
+            Some(0x80..=0x9f | 0xdc..=0xdf) => Some(Self::MsgPack),
+            _ => {
+                let text = std::str::from_utf8(bytes).ok()?;
+                if text.trim_start().starts_with('{') {
+                    Some(Self::Json)
+                } else {
+                    Some(Self::Toml)
+                }
+            }
+        }
+    }
+}
+
+/// Load the snapshot file at `path` into `world`, whatever its format, returning the
+/// detected format.
+pub fn load_any(
+    path: impl AsRef<Path>,
+    world: &mut World,
+    registry: &SnapshotRegistry,
+) -> Result<ArchiveFormat, Box<dyn Error + Send + Sync>> {
+    let path = path.as_ref();
+    registry.limits.check_blob(std::fs::metadata(path)?.len())?;
+    let bytes = std::fs::read(path)?;
+    let format = ArchiveFormat::detect(path, &bytes)
+        .ok_or_else(|| format!("cannot detect the snapshot format of {}", path.display()))?;
+    match format {
+        ArchiveFormat::Json => {
+            let value: serde_json::Value = serde_json::from_slice(&bytes)?;
+            if value.get("entities").is_some() {
+                serde_json::from_value::<WorldSnapshot>(value)?.apply(world, registry)?;
+            } else {
+                serde_json::from_value::<AuroraWorldManifest>(value)?.apply(world, registry)?;
+            }
+        }
+        ArchiveFormat::Toml => {
+            let table: toml::Table = toml::from_str(std::str::from_utf8(&bytes)?)?;
+            if table.contains_key("entities") {
+                table.try_into::<WorldSnapshot>()?.apply(world, registry)?;
+            } else {
+                table
+                    .try_into::<AuroraWorldManifest>()?
+                    .apply(world, registry)?;
+            }
+        }
+        ArchiveFormat::MsgPack => MsgPackArchive::from_bytes(&bytes)?.apply(world, registry)?,
+        #[cfg(feature = "arrow_rs")]
+        ArchiveFormat::Zip => {
+            crate::binary_archive::WorldArrowSnapshot::from_bytes(&bytes)?.apply(world, registry)?
+        }
+        #[cfg(feature = "arrow_rs")]
+        ArchiveFormat::Parquet => {
+            crate::binary_archive::load_arrow_archetype_from_parquet(world, registry, &bytes)
+                .map_err(|e| e.to_string())?
+        }
+        #[cfg(not(feature = "arrow_rs"))]
+        ArchiveFormat::Zip | ArchiveFormat::Parquet => {
+            return Err(
+                format!("loading {format:?} snapshots requires the arrow_rs feature").into(),
+            );
+        }
+    }
+    Ok(format)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::entity_archive::save_world_snapshot;
+
+    #[derive(Component, Serialize, Deserialize, Debug, PartialEq)]
+    struct Ammo(u32);
+
+    #[test]
+    fn test_load_any() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Ammo>();
+        let mut world = World::new();
+        world.spawn(Ammo(12));
+        let dir = std::env::temp_dir().join(format!("bevy_archive_any_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let manifest = AuroraWorldManifest::create(&world, &registry).unwrap();
+        let legacy = save_world_snapshot(&world, &registry);
+        let files = [
+            (
+                "world.save",
+                manifest.to_bytes().unwrap(),
+                ArchiveFormat::Toml,
+            ),
+            (
+                "world.json",
+                serde_json::to_vec(&manifest).unwrap(),
+                ArchiveFormat::Json,
+            ),
+            (
+                "legacy.dat",
+                legacy.to_bytes().unwrap(),
+                ArchiveFormat::Json,
+            ),
+            (
+                "world.bin",
+                MsgPackArchive::create(&world, &registry)
+                    .unwrap()
+                    .to_bytes()
+                    .unwrap(),
+                ArchiveFormat::MsgPack,
+            ),
+        ];
+        for (name, bytes, format) in files {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            let mut loaded = World::new();
+            assert_eq!(load_any(&path, &mut loaded, &registry).unwrap(), format);
+            let ammo = loaded.query::<&Ammo>().single(&loaded).unwrap();
+            assert_eq!(ammo, &Ammo(12), "{name}");
+        }
+        #[cfg(feature = "arrow_rs")]
+        {
+            let zip = crate::binary_archive::WorldArrowSnapshot::create(&world, &registry)
+                .unwrap()
+                .to_bytes()
+                .unwrap();
+            let path = dir.join("world.save");
+            std::fs::write(&path, zip).unwrap();
+            let format = load_any(&path, &mut World::new(), &registry).unwrap();
+            assert_eq!(format, ArchiveFormat::Zip);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,18 +1,31 @@
 use bevy_ecs::{
+    change_detection::Tick,
     component::{ComponentId, StorageType},
     entity::EntityIndex,
     prelude::*,
     ptr::PtrMut,
+    query::QueryFilter,
     resource::IS_RESOURCE,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{collections::HashMap, vec};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    ops::Range,
+    time::{Duration, Instant},
+    vec,
+};
 
 use crate::{
-    bevy_registry::{IDRemapRegistry, EntityRemapper, SnapshotMode, SnapshotRegistry, reserve_entity_slots},
-    bevy_cmdbuffer::HarvardCommandBuffer,
+    bevy_registry::{
+        ArenaBox, IDRemapRegistry, EntityRemapper, LimitError, LoadLimits, RowDefaultFn,
+        RowErrorPolicy, SnapshotMode, SnapshotRegistry, merge_prototype, try_reserve_entity_slots,
+    },
+    bevy_cmdbuffer::{ArenaRecorder, HarvardCommandBuffer},
+    error::ArchiveError,
+    load_events::trigger_load_events,
     prelude::codec::DynBuilderFn,
+    unknown_components::{attach_unknown_components, stash_unknown_columns},
 };
 
 use super::entity_archive::{self as archive, *};
@@ -36,26 +49,247 @@ struct ComponentLoaderInfo<'a> {
     comp_id: ComponentId,
     mode: SnapshotMode,
     ctor: DynBuilderFn,
+    row_default: Option<RowDefaultFn>,
+    prototype: Option<Value>,
     hook: Option<&'a (dyn Fn(PtrMut, &dyn EntityRemapper) + Send + Sync)>,
 }
 
+/// How a row that failed to deserialize was recovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowRecovery {
+    /// The component's registered row default was inserted instead.
+    Defaulted,
+    /// None of the row's components were inserted.
+    SkippedEntity,
+}
+
+/// One component value that failed to deserialize during a load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    /// Index of the archetype within the snapshot.
+    pub archetype: usize,
+    pub row: usize,
+    /// Entity id as stored in the snapshot.
+    pub entity: u32,
+    pub component: String,
+    /// The raw value that was rejected.
+    pub value: Value,
+    pub message: String,
+    pub recovery: RowRecovery,
+}
+
+/// Where the time of loading one archetype went.
+///
+/// Compare [`decode`](Self::decode) across archetypes to find the components whose serde
+/// path is slow, and [`insert`](Self::insert) to find the archetypes that are costly to
+/// write into the world.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchetypeLoadStats {
+    /// Components of the archetype, in snapshot order.
+    pub components: Vec<String>,
+    pub rows: usize,
+    /// Size of the blob the archetype was read from, for blob-based formats.
+    pub blob_bytes: Option<u64>,
+    /// Reading and parsing the blob, if any, and deserializing the component values.
+    pub decode: Duration,
+    /// Writing the decoded components into the world.
+    pub insert: Duration,
+}
+
+impl ArchetypeLoadStats {
+    pub fn total(&self) -> Duration {
+        self.decode + self.insert
+    }
+
+    /// Rows loaded per second of [`total`](Self::total) time; `0.0` when nothing was timed.
+    pub fn entities_per_second(&self) -> f64 {
+        let secs = self.total().as_secs_f64();
+        if secs > 0.0 {
+            self.rows as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// What a load wrote and what it had to repair or skip.
+///
+/// Loaders return the report of their own load and also merge it into the world's
+/// `LoadReport` resource, which is inserted by the first load that is not
+/// [clean](Self::is_clean); clear or remove it once the problems have been reported.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct LoadReport {
+    /// Per-entity failures: component values that failed to deserialize.
+    pub row_errors: Vec<RowError>,
+    /// Snapshot components and resources without a registered factory.
+    pub unknown_types: BTreeSet<String>,
+    /// Number of values per component that were not inserted because the component is
+    /// unknown or could not be registered with the stored storage type.
+    pub skipped_components: BTreeMap<String, usize>,
+    /// Entities that had their snapshot row written.
+    pub entities_loaded: usize,
+    /// Component values written.
+    pub components_loaded: usize,
+    /// Timings of every archetype loaded, in load order.
+    pub archetype_stats: Vec<ArchetypeLoadStats>,
+}
+
+impl LoadReport {
+    pub fn is_clean(&self) -> bool {
+        self.row_errors.is_empty()
+            && self.unknown_types.is_empty()
+            && self.skipped_components.is_empty()
+    }
+
+    /// Add the problems and counts of `other` to this report.
+    pub fn merge(&mut self, other: &LoadReport) {
+        self.row_errors.extend(other.row_errors.iter().cloned());
+        self.unknown_types
+            .extend(other.unknown_types.iter().cloned());
+        for (name, count) in &other.skipped_components {
+            *self.skipped_components.entry(name.clone()).or_default() += count;
+        }
+        self.entities_loaded += other.entities_loaded;
+        self.components_loaded += other.components_loaded;
+        self.archetype_stats
+            .extend(other.archetype_stats.iter().cloned());
+    }
+
+    /// Note `count` values of `name` that were not inserted.
+    pub(crate) fn skip(&mut self, name: &str, count: usize, unknown: bool) {
+        if unknown {
+            self.unknown_types.insert(name.to_string());
+        }
+        *self.skipped_components.entry(name.to_string()).or_default() += count;
+    }
+
+    /// Merge this report into the world's `LoadReport` resource.
+    pub(crate) fn record(&self, world: &mut World) {
+        match world.get_resource_mut::<LoadReport>() {
+            Some(mut report) => report.merge(self),
+            None if !self.is_clean() => world.insert_resource(self.clone()),
+            None => {}
+        }
+    }
+}
+
+/// Build every loadable component of `row`, recovering from bad values per `policy`.
+///
+/// Returns the components in `infos` order, or `None` when the entity must be skipped.
+fn build_row<'b>(
+    arch: &ArchetypeSnapshot,
+    arch_idx: usize,
+    infos: &[ComponentLoaderInfo],
+    row: usize,
+    bump: &'b bumpalo::Bump,
+    policy: RowErrorPolicy,
+    errors: &mut Vec<RowError>,
+) -> Option<Vec<ArenaBox<'b>>> {
+    let mut built = Vec::with_capacity(infos.len());
+    for info in infos {
+        let value = &arch.columns[info.col_idx][row];
+        let filled = info.prototype.as_ref().map(|p| merge_prototype(p, value));
+        let message = match (info.ctor)(filled.as_ref().unwrap_or(value), bump) {
+            Ok(ptr) => {
+                built.push(ptr);
+                continue;
+            }
+            Err(message) => message,
+        };
+        let fallback = match policy {
+            RowErrorPolicy::DefaultOrSkip => info.row_default,
+            RowErrorPolicy::SkipEntity => None,
+        };
+        let recovery = if fallback.is_some() {
+            RowRecovery::Defaulted
+        } else {
+            RowRecovery::SkippedEntity
+        };
+        errors.push(RowError {
+            archetype: arch_idx,
+            row,
+            entity: arch.entities[row],
+            component: arch.component_types[info.col_idx].clone(),
+            value: value.clone(),
+            message,
+            recovery,
+        });
+        match fallback {
+            Some(default) => built.push(default(bump)),
+            None => {
+                built.into_iter().for_each(ArenaBox::manual_drop);
+                return None;
+            }
+        }
+    }
+    Some(built)
+}
+
+/// Queue `comp_ptr` for `entity`, honoring the component's [`SnapshotMode`].
+fn stage_component<'b>(
+    world: &World,
+    rec: &mut ArenaRecorder<'b>,
+    entity: Entity,
+    info: &ComponentLoaderInfo,
+    comp_ptr: ArenaBox<'b>,
+) {
+    // SAFETY: `comp_ptr` was built by the factory registered for `info.comp_id`.
+    match info.mode {
+        SnapshotMode::Full => {
+            unsafe { rec.insert_box(entity, info.comp_id, comp_ptr) };
+        }
+        SnapshotMode::EmplaceIfNotExists => {
+            if !world.entity(entity).contains_id(info.comp_id) {
+                unsafe { rec.insert_box(entity, info.comp_id, comp_ptr) };
+            } else {
+                comp_ptr.manual_drop();
+            }
+        }
+    }
+}
+
 fn prepare_loader_info<'a>(
     world: &mut World,
     reg: &SnapshotRegistry,
     id_reg: Option<&'a IDRemapRegistry>,
     arch: &ArchetypeSnapshot,
+    report: &mut LoadReport,
 ) -> Vec<ComponentLoaderInfo<'a>> {
+    let names: Vec<&str> = arch.component_types.iter().map(String::as_str).collect();
     arch.component_types
         .iter()
         .enumerate()
         .filter_map(|(col_idx, type_name)| {
-            let factory = reg.get_factory(type_name)?;
-            let comp_id = reg.comp_id_by_name(type_name.as_str(), world)
-                .or_else(|| Some(reg.reg_by_name(type_name, world)))?; 
+            if !reg.duplicate_components.keeps(&names, col_idx) {
+                report.skip(type_name, arch.entities.len(), false);
+                return None;
+            }
+            let Some(factory) = reg.get_factory(type_name) else {
+                report.skip(type_name, arch.entities.len(), true);
+                return None;
+            };
+            let comp_id = match arch.storage_types.get(col_idx) {
+                Some(storage) => match reg.reg_by_name_with_storage(type_name, world, storage) {
+                    Ok(id) => id,
+                    Err(e) => {
+                        archive_warn!("{}", e);
+                        let Some(id) = reg.comp_id_by_name(type_name.as_str(), world) else {
+                            report.skip(type_name, arch.entities.len(), false);
+                            return None;
+                        };
+                        id
+                    }
+                },
+                None => reg
+                    .comp_id_by_name(type_name.as_str(), world)
+                    .unwrap_or_else(|| reg.reg_by_name(type_name, world)),
+            };
             
             let mode = factory.mode;
-            let type_id = reg.type_registry.get(type_name.as_str()).cloned();
-            let ctor = factory.js_value.dyn_ctor;
+            let type_id = reg.type_id_by_name(type_name);
+            let ctor = factory.js_value.dyn_ctor.clone();
+            let row_default = factory.row_default;
+            let prototype = factory.prototype.clone();
             
             let hook = id_reg.and_then(|r| type_id.and_then(|tid| r.get_hook(tid)));
 
@@ -64,6 +298,8 @@ fn prepare_loader_info<'a>(
                 comp_id,
                 mode,
                 ctor,
+                row_default,
+                prototype,
                 hook,
             })
         })
@@ -76,54 +312,63 @@ pub fn load_world_arch_snapshot_with_remap(
     reg: &SnapshotRegistry,
     id_reg: &IDRemapRegistry,
     mapper: &dyn EntityRemapper,
-) {
+) -> Result<LoadReport, LimitError> {
+    reg.limits.check_archetypes(snapshot.archetypes.len())?;
+    let rows: usize = snapshot.archetypes.iter().map(|arch| arch.entities().len()).sum();
+    reg.limits.check_entities(rows.max(snapshot.entities.len()) as u64)?;
+    reg.check_known(snapshot_component_names(snapshot))?;
+    check_duplicate_columns(snapshot, reg)?;
     let mut buffer = HarvardCommandBuffer::new();
-    for arch in &snapshot.archetypes {
+    let mut report = LoadReport::default();
+    let mut loaded = Vec::new();
+    for (arch_idx, arch) in snapshot.archetypes.iter().enumerate() {
         let entities = arch.entities();
-        let arch_info = prepare_loader_info(world, reg, Some(id_reg), arch);
-        let bump_ptr = buffer.data_bump() as *const bumpalo::Bump;
+        let _span = archive_span!("load_archetype", index = arch_idx, rows = entities.len());
+        let arch_info = prepare_loader_info(world, reg, Some(id_reg), arch, &mut report);
+        let decode_start = Instant::now();
         
-        for (row, old_entity_id) in entities.iter().enumerate() {
-             let current_entity = mapper.map(*old_entity_id);
-             if current_entity == Entity::PLACEHOLDER {
-                panic!("Entity mapping failure: Old ID {} mapped to PLACEHOLDER", old_entity_id);
-             }
-
-            for info in &arch_info {
-                let col = &arch.columns[info.col_idx];
-                
-                match (info.ctor)(&col[row], unsafe { &*bump_ptr }) {
-                    Ok(mut comp_ptr) => {
-                        if let Some(h) = info.hook {
-                            let ptr_mut: PtrMut = comp_ptr.get_ptr_mut();
-                            h(ptr_mut, mapper);
-                        }
-
-                        match info.mode {
-                            SnapshotMode::Full => {
-                                buffer.insert_box(current_entity, info.comp_id, comp_ptr);
-                            }
-                            SnapshotMode::EmplaceIfNotExists => {
-                                if !world.entity(current_entity).contains_id(info.comp_id) {
-                                     buffer.insert_box(current_entity, info.comp_id, comp_ptr);
-                                } else {
-                                    comp_ptr.manual_drop();
-                                }
-                            }
-                        }
-                    },
-                    Err(e) => {
-                         eprintln!("Error constructing component: {}", e);
+        let mut targets = Vec::with_capacity(entities.len());
+        buffer.record(|rec| {
+            for (row, old_entity_id) in entities.iter().enumerate() {
+                 let current_entity = mapper.map(*old_entity_id);
+                 if current_entity == Entity::PLACEHOLDER {
+                    panic!("Entity mapping failure: Old ID {} mapped to PLACEHOLDER", old_entity_id);
+                 }
+                targets.push(current_entity);
+                let Some(built) = build_row(arch, arch_idx, &arch_info, row, rec.bump(), reg.row_errors, &mut report.row_errors)
+                else {
+                    continue;
+                };
+                report.entities_loaded += 1;
+                report.components_loaded += built.len();
+
+                for (info, mut comp_ptr) in arch_info.iter().zip(built) {
+                    if let Some(h) = info.hook {
+                        let ptr_mut: PtrMut = comp_ptr.get_ptr_mut();
+                        h(ptr_mut, mapper);
                     }
+                    stage_component(world, rec, current_entity, info, comp_ptr);
                 }
             }
-        }
+        });
+        let decode = decode_start.elapsed();
+        let insert_start = Instant::now();
         buffer.apply(world);
         buffer.reset();
+        report
+            .archetype_stats
+            .push(arch.load_stats(decode, insert_start.elapsed()));
+        stash_unknown_columns(world, reg, arch, &targets);
+        if reg.tracks_loaded() {
+            loaded.extend(entities.iter().copied().zip(targets));
+        }
     }
+    report.record(world);
+    trigger_load_events(world, reg, &loaded);
+    Ok(report)
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StorageTypeFlag {
     /// 直接存储
     #[default]
@@ -132,6 +377,24 @@ pub enum StorageTypeFlag {
     SparseSet,
 }
 
+impl From<StorageType> for StorageTypeFlag {
+    fn from(value: StorageType) -> Self {
+        match value {
+            StorageType::Table => StorageTypeFlag::Table,
+            StorageType::SparseSet => StorageTypeFlag::SparseSet,
+        }
+    }
+}
+
+impl From<StorageTypeFlag> for StorageType {
+    fn from(value: StorageTypeFlag) -> Self {
+        match value {
+            StorageTypeFlag::Table => StorageType::Table,
+            StorageTypeFlag::SparseSet => StorageType::SparseSet,
+        }
+    }
+}
+
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct ArchetypeSnapshot {
     pub component_types: Vec<String>,         // 顺序确定！
@@ -143,9 +406,9 @@ impl ArchetypeSnapshot {
     pub fn is_empty(&self) -> bool {
         self.entities.is_empty()
     }
-    fn get_column_index_or_err(&self, type_name: &str) -> Result<usize, String> {
+    fn get_column_index_or_err(&self, type_name: &str) -> Result<usize, ArchiveError> {
         self.get_column_index(type_name)
-            .ok_or_else(|| format!("Component '{}' not found", type_name))
+            .ok_or_else(|| ArchiveError::Schema(format!("Component '{}' not found", type_name)))
     }
     pub fn get_column_index(&self, type_name: &str) -> Option<usize> {
         self.component_types.iter().position(|t| t == type_name)
@@ -180,15 +443,24 @@ impl ArchetypeSnapshot {
     pub fn entities(&self) -> &Vec<u32> {
         &self.entities
     }
+    fn load_stats(&self, decode: Duration, insert: Duration) -> ArchetypeLoadStats {
+        ArchetypeLoadStats {
+            components: self.component_types.clone(),
+            rows: self.entities.len(),
+            blob_bytes: None,
+            decode,
+            insert,
+        }
+    }
     pub fn insert_component(
         &mut self,
         entity_idx: usize,
         type_name: &str,
         value: serde_json::Value,
-    ) -> Result<(), String> {
+    ) -> Result<(), ArchiveError> {
         let idx = self.get_column_index_or_err(type_name)?;
         if entity_idx >= self.entities.len() {
-            return Err("Invalid entity index".into());
+            return Err(ArchiveError::schema("Invalid entity index"));
         }
         self.columns[idx][entity_idx] = value;
         Ok(())
@@ -212,27 +484,77 @@ impl ArchetypeSnapshot {
         }
     }
 
-    pub fn validate_snapshot(snapshot: &ArchetypeSnapshot) -> Result<(), String> {
+    pub fn validate_snapshot(snapshot: &ArchetypeSnapshot) -> Result<(), ArchiveError> {
         let n_types = snapshot.component_types.len();
         let n_entities = snapshot.entities.len();
 
         if snapshot.columns.len() != n_types {
-            return Err("Component type count mismatch".to_string());
+            return Err(ArchiveError::schema("Component type count mismatch"));
         }
 
         for (i, col) in snapshot.columns.iter().enumerate() {
             if col.len() != n_entities {
-                return Err(format!(
+                return Err(ArchiveError::Schema(format!(
                     "Column {} has length {}, expected {}",
                     i,
                     col.len(),
                     n_entities
-                ));
+                )));
             }
         }
 
         Ok(())
     }
+
+    /// Spawn a fresh entity for each row of `rows`, ignoring the stored entity ids, e.g. to
+    /// instantiate canned rows of a fixture snapshot in a test or a procedural generator.
+    ///
+    /// Components without a registered factory are skipped and rows that fail to
+    /// deserialize are handled per the registry's [`RowErrorPolicy`], as in a full load;
+    /// the problems are recorded in the world's [`LoadReport`]. No entity id remapping
+    /// hooks or load events run. Returns the spawned entities in row order, without the
+    /// rows that were skipped.
+    pub fn spawn_into(
+        &self,
+        world: &mut World,
+        reg: &SnapshotRegistry,
+        rows: Range<usize>,
+    ) -> Result<Vec<Entity>, ArchiveError> {
+        Self::validate_snapshot(self)?;
+        if rows.start > rows.end || rows.end > self.entities.len() {
+            return Err(ArchiveError::Schema(format!(
+                "Row range {:?} out of bounds for {} rows",
+                rows,
+                self.entities.len()
+            )));
+        }
+        reg.limits.check_entities(rows.len() as u64)?;
+        reg.check_known(self.component_types.iter().map(String::as_str))?;
+        reg.check_duplicates(self.component_types.iter().map(String::as_str))?;
+
+        let mut buffer = HarvardCommandBuffer::new();
+        let mut report = LoadReport::default();
+        let arch_info = prepare_loader_info(world, reg, None, self, &mut report);
+        let mut spawned = Vec::with_capacity(rows.len());
+        buffer.record(|rec| {
+            for row in rows {
+                let Some(built) = build_row(self, 0, &arch_info, row, rec.bump(), reg.row_errors, &mut report.row_errors)
+                else {
+                    continue;
+                };
+                let entity = world.spawn_empty().id();
+                spawned.push(entity);
+                report.entities_loaded += 1;
+                report.components_loaded += built.len();
+                for (info, comp_ptr) in arch_info.iter().zip(built) {
+                    stage_component(world, rec, entity, info, comp_ptr);
+                }
+            }
+        });
+        buffer.apply(world);
+        report.record(world);
+        Ok(spawned)
+    }
 }
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WorldArchSnapshot {
@@ -248,12 +570,32 @@ impl WorldArchSnapshot {
         //we may want to deduplicate entities here
         self.entities.sort_unstable();
     }
+
+    /// Encode as a TOML document. Fails on `null` cells, e.g. a component some rows of an
+    /// archetype lack, which TOML cannot hold.
+    pub fn to_toml_string(&self) -> Result<String, ArchiveError> {
+        archive::to_toml_string(self)
+    }
+
+    pub fn from_toml_str(s: &str) -> Result<Self, ArchiveError> {
+        archive::from_toml_str(s)
+    }
+
+    pub fn to_json_string(&self) -> Result<String, ArchiveError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, ArchiveError> {
+        Ok(serde_json::from_str(s)?)
+    }
 }
 pub fn load_world_resource(
-    data: &HashMap<String, serde_json::Value>,
+    data: &BTreeMap<String, serde_json::Value>,
     world: &mut World,
     reg: &SnapshotRegistry,
-) {
+) -> Result<LoadReport, LimitError> {
+    reg.check_known(data.keys().map(String::as_str))?;
+    let mut report = LoadReport::default();
     let loadable_resource = data.keys();
     for res in loadable_resource {
         let factory = reg.get_res_factory(res);
@@ -263,16 +605,18 @@ pub fn load_world_resource(
                     .unwrap();
             }
             None => {
-                //may need to emit warnings here
+                report.skip(res, 1, true);
             }
         }
     }
+    report.record(world);
+    Ok(report)
 }
 pub fn save_world_resource(
     world: &World,
     reg: &SnapshotRegistry,
-) -> HashMap<String, serde_json::Value> {
-    let mut map = HashMap::new();
+) -> BTreeMap<String, serde_json::Value> {
+    let mut map = BTreeMap::new();
     let saveable_resource = reg.resource_entries.keys();
     for res in saveable_resource {
         let value = (reg.get_res_factory(res).unwrap().js_value.export)(
@@ -291,6 +635,7 @@ pub fn save_single_archetype_snapshot(
     reg: &SnapshotRegistry,
     reg_comp_ids: &HashMap<ComponentId, &str>,
 ) -> ArchetypeSnapshot {
+    let _span = archive_span!("save_archetype", rows = archetype.len());
     let can_be_stored = archetype
         .components()
         .iter()
@@ -298,26 +643,30 @@ pub fn save_single_archetype_snapshot(
     if !can_be_stored {
         return ArchetypeSnapshot::default();
     }
+    let entities: Vec<Entity> = archetype.entities().iter().map(|x| x.id()).collect();
+    save_archetype_rows(world, archetype, reg, reg_comp_ids, &entities)
+}
+
+/// Snapshot the registered columns of `archetype` for `entities`, which must belong to it.
+fn save_archetype_rows(
+    world: &World,
+    archetype: &bevy_ecs::archetype::Archetype,
+    reg: &SnapshotRegistry,
+    reg_comp_ids: &HashMap<ComponentId, &str>,
+    entities: &[Entity],
+) -> ArchetypeSnapshot {
     let mut archetype_snapshot = ArchetypeSnapshot::default();
-    let entities: Vec<u32> = archetype
-        .entities()
-        .iter()
-        .map(|x| x.id().index_u32())
-        .collect();
-    archetype_snapshot.entities.extend(entities.as_slice());
-    let iter = entities;
+    archetype_snapshot
+        .entities
+        .extend(entities.iter().map(|e| e.index_u32()));
     archetype.components().iter().for_each(|x| {
         if reg_comp_ids.contains_key(&x) {
             let type_name = reg_comp_ids[&x];
-            let t = archetype.get_storage_type(*x).map(|x| match x {
-                StorageType::Table => StorageTypeFlag::Table,
-                StorageType::SparseSet => StorageTypeFlag::SparseSet,
-            });
-            let f = reg.get_factory(type_name).unwrap().js_value.export;
+            let t = archetype.get_storage_type(*x).map(StorageTypeFlag::from);
+            let f = &reg.get_factory(type_name).unwrap().js_value.export;
             archetype_snapshot.add_type(type_name, t);
             let col = archetype_snapshot.get_column_mut(type_name).unwrap();
-            for (idx, &entity) in iter.iter().enumerate() {
-                let entity = world.entities().resolve_from_index(EntityIndex::from_raw_u32(entity).unwrap());
+            for (idx, &entity) in entities.iter().enumerate() {
                 let serialized = f(world, entity).unwrap();
                 col[idx] = serialized;
             }
@@ -347,6 +696,8 @@ pub fn save_world_arch_snapshot(world: &World, reg: &SnapshotRegistry) -> WorldA
     let snap = archetypes
         .map(|archetype| save_single_archetype_snapshot(world, archetype, reg, &reg_comp_ids));
     world_snapshot.archetypes.extend(snap);
+    world_snapshot.archetypes.retain(|a| !a.is_empty());
+    attach_unknown_components(world, &mut world_snapshot.archetypes);
 
     // Purge entity IDs that don't appear in any stored archetype
     // (safety net for any unforeseen engine-internal entities)
@@ -357,92 +708,355 @@ pub fn save_world_arch_snapshot(world: &World, reg: &SnapshotRegistry) -> WorldA
     world_snapshot
 }
 
-fn count_entities(snapshot: &WorldArchSnapshot) -> u32 {
-    snapshot.entities.last().map(|x| *x).unwrap_or(0) + 1
+/// Save only the entities matching the query filter `F`, e.g. `With<Persistent>`.
+///
+/// Entities are captured with all their registered components, like
+/// [`save_world_arch_snapshot`]. Stashed unknown components are not carried over. If `F`
+/// names a component the world has never seen, nothing matches and the snapshot is empty.
+pub fn save_world_arch_snapshot_filtered<F: QueryFilter>(
+    world: &World,
+    reg: &SnapshotRegistry,
+) -> WorldArchSnapshot {
+    let Some(mut query) = world.try_query_filtered::<Entity, F>() else {
+        return WorldArchSnapshot::default();
+    };
+    let matched: HashSet<Entity> = query.iter(world).collect();
+    save_world_arch_snapshot_where(world, reg, |_, entity| matched.contains(&entity))
+}
+
+/// Save only the entities that have every component in `names`, by registered name.
+///
+/// The dynamic counterpart of [`save_world_arch_snapshot_filtered`] with `With` filters,
+/// for names read from configuration. A name that is not registered, or not present in the
+/// world, matches nothing.
+pub fn save_world_arch_snapshot_with_components(
+    world: &World,
+    reg: &SnapshotRegistry,
+    names: &[&str],
+) -> WorldArchSnapshot {
+    let Some(required) = names
+        .iter()
+        .map(|name| reg.comp_id_by_name(name, world))
+        .collect::<Option<Vec<ComponentId>>>()
+    else {
+        return WorldArchSnapshot::default();
+    };
+    save_world_arch_snapshot_where(world, reg, |archetype, _| {
+        required.iter().all(|&cid| archetype.contains(cid))
+    })
+}
+
+/// Save only `entities`, e.g. the objects selected in an editor.
+///
+/// Each entity is captured with all its registered components, grouped by archetype like
+/// [`save_world_arch_snapshot`]. Despawned entities and entities without registered
+/// components are skipped.
+pub fn save_entities_snapshot(
+    world: &World,
+    reg: &SnapshotRegistry,
+    entities: &[Entity],
+) -> WorldArchSnapshot {
+    let selected: HashSet<Entity> = entities.iter().copied().collect();
+    save_world_arch_snapshot_where(world, reg, |_, entity| selected.contains(&entity))
+}
+
+fn save_world_arch_snapshot_where(
+    world: &World,
+    reg: &SnapshotRegistry,
+    keep: impl Fn(&bevy_ecs::archetype::Archetype, Entity) -> bool,
+) -> WorldArchSnapshot {
+    let reg_comp_ids: HashMap<ComponentId, &str> = reg
+        .type_registry
+        .keys()
+        .filter_map(|&name| reg.comp_id_by_name(name, world).map(|cid| (cid, name)))
+        .collect();
+    let archetypes = world
+        .archetypes()
+        .iter()
+        .filter(|x| !x.is_empty() && !x.contains(IS_RESOURCE));
+
+    let mut world_snapshot = WorldArchSnapshot::default();
+    for archetype in archetypes {
+        if !archetype
+            .components()
+            .iter()
+            .any(|x| reg_comp_ids.contains_key(x))
+        {
+            continue;
+        }
+        let entities: Vec<Entity> = archetype
+            .entities()
+            .iter()
+            .map(|x| x.id())
+            .filter(|&e| keep(archetype, e))
+            .collect();
+        if entities.is_empty() {
+            continue;
+        }
+        world_snapshot
+            .entities
+            .extend(entities.iter().map(|e| e.index_u32()));
+        world_snapshot.archetypes.push(save_archetype_rows(
+            world,
+            archetype,
+            reg,
+            &reg_comp_ids,
+            &entities,
+        ));
+    }
+    world_snapshot.entities.sort_unstable();
+    world_snapshot.purge_null();
+    world_snapshot
+}
+
+/// Save only the registered components added or changed after `last_run_tick`.
+///
+/// The result is a delta: each archetype holds the entities that share the same set of
+/// changed components, and loading it on top of a world restored from the base snapshot
+/// (with [`load_world_arch_snapshot`]) brings that world up to date. Removed components and
+/// despawned entities are not recorded; use [`diff_archive`](crate::diff_archive) when
+/// those matter.
+///
+/// Take the baseline with [`World::increment_change_tick`] right after saving the base, so
+/// that later changes carry a newer tick.
+pub fn save_world_arch_snapshot_since(
+    world: &World,
+    reg: &SnapshotRegistry,
+    last_run_tick: Tick,
+) -> WorldArchSnapshot {
+    let this_run = world.read_change_tick();
+    let reg_comp_ids: HashMap<ComponentId, &str> = reg
+        .type_registry
+        .keys()
+        .filter_map(|&name| reg.comp_id_by_name(name, world).map(|cid| (cid, name)))
+        .collect();
+
+    // Group entities by the sorted names of their changed components.
+    let mut groups: BTreeMap<Vec<&str>, Vec<Entity>> = BTreeMap::new();
+    let archetypes = world
+        .archetypes()
+        .iter()
+        .filter(|x| !x.is_empty() && !x.contains(IS_RESOURCE));
+    for archetype in archetypes {
+        let registered: Vec<(ComponentId, &str)> = archetype
+            .components()
+            .iter()
+            .filter_map(|cid| reg_comp_ids.get(cid).map(|&name| (*cid, name)))
+            .collect();
+        if registered.is_empty() {
+            continue;
+        }
+        for entity in archetype.entities().iter().map(|e| e.id()) {
+            let Ok(entity_ref) = world.get_entity(entity) else {
+                continue;
+            };
+            let mut changed: Vec<&str> = registered
+                .iter()
+                .filter(|(cid, _)| {
+                    entity_ref
+                        .get_change_ticks_by_id(*cid)
+                        .is_some_and(|ticks| ticks.is_changed(last_run_tick, this_run))
+                })
+                .map(|(_, name)| *name)
+                .collect();
+            if changed.is_empty() {
+                continue;
+            }
+            changed.sort_unstable();
+            groups.entry(changed).or_default().push(entity);
+        }
+    }
+
+    let mut world_snapshot = WorldArchSnapshot::default();
+    for (names, entities) in groups {
+        let mut arch = ArchetypeSnapshot {
+            entities: entities.iter().map(|e| e.index_u32()).collect(),
+            ..Default::default()
+        };
+        for name in names {
+            let Some(factory) = reg.get_factory(name) else {
+                continue;
+            };
+            let storage = reg
+                .comp_id_by_name(name, world)
+                .and_then(|cid| world.components().get_info(cid))
+                .map(|info| StorageTypeFlag::from(info.storage_type()));
+            arch.add_type(name, storage);
+            let col = arch.get_column_mut(name).unwrap();
+            for (slot, &entity) in col.iter_mut().zip(&entities) {
+                *slot = (factory.js_value.export)(world, entity).unwrap_or_default();
+            }
+        }
+        world_snapshot.archetypes.push(arch);
+    }
+    world_snapshot.purge_null();
+    world_snapshot
+}
+
+fn snapshot_component_names(snapshot: &WorldArchSnapshot) -> impl Iterator<Item = &str> {
+    snapshot
+        .archetypes
+        .iter()
+        .flat_map(|arch| arch.component_types.iter().map(String::as_str))
+}
+
+/// [`SnapshotRegistry::check_duplicates`] for every archetype of `snapshot`.
+fn check_duplicate_columns(
+    snapshot: &WorldArchSnapshot,
+    reg: &SnapshotRegistry,
+) -> Result<(), LimitError> {
+    snapshot
+        .archetypes
+        .iter()
+        .try_for_each(|arch| reg.check_duplicates(arch.component_types.iter().map(String::as_str)))
+}
+
+/// Highest entity index named by `snapshot`, in its entity list or any archetype.
+fn max_entity_index(snapshot: &WorldArchSnapshot) -> Option<u32> {
+    snapshot
+        .archetypes
+        .iter()
+        .flat_map(|arch| arch.entities().iter())
+        .chain(&snapshot.entities)
+        .copied()
+        .max()
+}
+
+/// Reserve every slot `snapshot` writes to. Fails before touching `world` if the highest
+/// index is over the limit, which also rules out `u32::MAX` (not a valid [`EntityIndex`]).
+fn reserve_snapshot_slots(
+    world: &mut World,
+    snapshot: &WorldArchSnapshot,
+    limits: &LoadLimits,
+) -> Result<(), LimitError> {
+    match max_entity_index(snapshot) {
+        Some(max) => try_reserve_entity_slots(world, max, limits),
+        None => Ok(()),
+    }
+}
+
+/// The [`EntityIndex`] of a snapshot id already checked by [`reserve_snapshot_slots`].
+fn checked_index(id: u32) -> EntityIndex {
+    EntityIndex::from_raw_u32(id).expect("entity index checked against the load limits")
 }
 
 pub fn load_world_arch_snapshot(
     world: &mut World,
     snapshot: &WorldArchSnapshot,
     reg: &SnapshotRegistry,
-) {
-    reserve_entity_slots(world, count_entities(snapshot));
+) -> Result<LoadReport, LimitError> {
+    reg.limits.check_archetypes(snapshot.archetypes.len())?;
+    reg.check_known(snapshot_component_names(snapshot))?;
+    check_duplicate_columns(snapshot, reg)?;
+    reserve_snapshot_slots(world, snapshot, &reg.limits)?;
     world.flush();
 
     let mut buffer = HarvardCommandBuffer::new();
-    for arch in &snapshot.archetypes {
+    let mut report = LoadReport::default();
+    let mut loaded = Vec::new();
+    for (arch_idx, arch) in snapshot.archetypes.iter().enumerate() {
         let entities = arch.entities();
-        let arch_info = prepare_loader_info(world, reg, None, arch);
-        let bump_ptr = buffer.data_bump() as *const bumpalo::Bump;
-
-        for (row, entity_id) in entities.iter().enumerate() {
-            let entity = Entity::from_index(EntityIndex::from_raw_u32(*entity_id).unwrap());
-            
-            for info in &arch_info {
-                let col = &arch.columns[info.col_idx];
-                let (id, comp_ptr) = (info.comp_id, (info.ctor)(&col[row], unsafe { &*bump_ptr }).unwrap());
-                
-                // load_world_arch_snapshot implies simple load, but we should respect mode?
-                // The old implementation just called 'import'.
-                // 'import' usually inserts.
-                // We'll use SnapshotMode from factory.
-                match info.mode {
-                    SnapshotMode::Full => {
-                        buffer.insert_box(entity, id, comp_ptr);
-                    }
-                    SnapshotMode::EmplaceIfNotExists => {
-                        if !world.entity(entity).contains_id(id) {
-                            buffer.insert_box(entity, id, comp_ptr);
-                        } else {
-                            comp_ptr.manual_drop();
-                        }
-                    }
+        let _span = archive_span!("load_archetype", index = arch_idx, rows = entities.len());
+        let arch_info = prepare_loader_info(world, reg, None, arch, &mut report);
+        let decode_start = Instant::now();
+
+        let mut targets = Vec::with_capacity(entities.len());
+        buffer.record(|rec| {
+            for (row, entity_id) in entities.iter().enumerate() {
+                let entity = Entity::from_index(checked_index(*entity_id));
+                targets.push(entity);
+                let Some(built) = build_row(arch, arch_idx, &arch_info, row, rec.bump(), reg.row_errors, &mut report.row_errors)
+                else {
+                    continue;
+                };
+                report.entities_loaded += 1;
+                report.components_loaded += built.len();
+                for (info, comp_ptr) in arch_info.iter().zip(built) {
+                    stage_component(world, rec, entity, info, comp_ptr);
                 }
             }
-        }
+        });
+        let decode = decode_start.elapsed();
+        let insert_start = Instant::now();
         buffer.apply(world);
         buffer.reset();
+        report
+            .archetype_stats
+            .push(arch.load_stats(decode, insert_start.elapsed()));
+        stash_unknown_columns(world, reg, arch, &targets);
+        if reg.tracks_loaded() {
+            loaded.extend(entities.iter().copied().zip(targets));
+        }
     }
+    report.record(world);
+    trigger_load_events(world, reg, &loaded);
+    Ok(report)
 }
 
 pub fn load_world_arch_snapshot_defragment(
     world: &mut World,
     snapshot: &WorldArchSnapshot,
     reg: &SnapshotRegistry,
-) {
-    reserve_entity_slots(world, count_entities(snapshot));
+) -> Result<LoadReport, LimitError> {
+    let mut loaded = Vec::new();
+    let report = load_defragment_quiet(world, snapshot, reg, &mut loaded)?;
+    trigger_load_events(world, reg, &loaded);
+    Ok(report)
+}
+
+/// [`load_world_arch_snapshot_defragment`] without the load events, for callers that load
+/// a snapshot in several passes. Appends the (snapshot id, entity) pairs it wrote to
+/// `loaded`.
+pub(crate) fn load_defragment_quiet(
+    world: &mut World,
+    snapshot: &WorldArchSnapshot,
+    reg: &SnapshotRegistry,
+    loaded: &mut Vec<(u32, Entity)>,
+) -> Result<LoadReport, LimitError> {
+    reg.limits.check_archetypes(snapshot.archetypes.len())?;
+    reg.check_known(snapshot_component_names(snapshot))?;
+    check_duplicate_columns(snapshot, reg)?;
+    reserve_snapshot_slots(world, snapshot, &reg.limits)?;
     world.flush();
 
     let mut buffer = HarvardCommandBuffer::new();
-    for arch in &snapshot.archetypes {
+    let mut report = LoadReport::default();
+    for (arch_idx, arch) in snapshot.archetypes.iter().enumerate() {
         let entities = arch.entities();
-        let arch_info = prepare_loader_info(world, reg, None, arch);
-        let bump_ptr = buffer.data_bump() as *const bumpalo::Bump;
+        let _span = archive_span!("load_archetype", index = arch_idx, rows = entities.len());
+        let arch_info = prepare_loader_info(world, reg, None, arch, &mut report);
+        let decode_start = Instant::now();
         
-        for (row, entity) in entities.iter().enumerate() {
-            let current_entity = world.entities().resolve_from_index(EntityIndex::from_raw_u32(*entity).unwrap());
-
-            for info in &arch_info {
-                let col = &arch.columns[info.col_idx];
-                let (id, comp_ptr) = (info.comp_id, (info.ctor)(&col[row], unsafe { &*bump_ptr }).unwrap());
-                match info.mode {
-                    SnapshotMode::Full => {
-                        buffer.insert_box(current_entity, id, comp_ptr);
-                    }
-
-                    SnapshotMode::EmplaceIfNotExists => {
-                         if !world.entity(current_entity).contains_id(id) {
-                            buffer.insert_box(current_entity, id, comp_ptr);
-                        } else {
-                            comp_ptr.manual_drop();
-                        }
-                    }
+        let mut targets = Vec::with_capacity(entities.len());
+        buffer.record(|rec| {
+            for (row, entity) in entities.iter().enumerate() {
+                let current_entity = world.entities().resolve_from_index(checked_index(*entity));
+                targets.push(current_entity);
+                let Some(built) = build_row(arch, arch_idx, &arch_info, row, rec.bump(), reg.row_errors, &mut report.row_errors)
+                else {
+                    continue;
+                };
+                report.entities_loaded += 1;
+                report.components_loaded += built.len();
+                for (info, comp_ptr) in arch_info.iter().zip(built) {
+                    stage_component(world, rec, current_entity, info, comp_ptr);
                 }
             }
-        }
+        });
+        let decode = decode_start.elapsed();
+        let insert_start = Instant::now();
         buffer.apply(world);
         buffer.reset();
+        report
+            .archetype_stats
+            .push(arch.load_stats(decode, insert_start.elapsed()));
+        stash_unknown_columns(world, reg, arch, &targets);
+        if reg.tracks_loaded() {
+            loaded.extend(entities.iter().copied().zip(targets));
+        }
     }
+    report.record(world);
+    Ok(report)
 }
 
 impl From<&WorldArchSnapshot> for archive::WorldSnapshot {
@@ -463,7 +1077,7 @@ impl From<&archive::WorldSnapshot> for WorldArchSnapshot {
 }
 fn convert_to_archetype_snapshot(entities: &[EntitySnapshot]) -> Vec<ArchetypeSnapshot> {
     // Grouped by component type sets
-    let mut archetype_map: HashMap<Vec<String>, ArchetypeSnapshot> = HashMap::new();
+    let mut archetype_map: BTreeMap<Vec<String>, ArchetypeSnapshot> = BTreeMap::new();
 
     for ent in entities {
         // 先按组件名排序作为分类 key（顺序必须稳定）
@@ -530,7 +1144,7 @@ mod tests {
     use super::*;
     use bevy_ecs::world::World;
 
-    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Component)]
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, Component)]
     struct TestComponentA {
         pub value: i32,
     }
@@ -611,6 +1225,261 @@ mod tests {
         (world, registry)
     }
 
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Component)]
+    struct Health(i32);
+    impl Default for Health {
+        fn default() -> Self {
+            Health(100)
+        }
+    }
+    #[derive(Serialize, Deserialize, Component)]
+    #[require(Health)]
+    struct Player;
+    #[derive(Resource, Default)]
+    struct HealthInserts(usize);
+
+    #[test]
+    fn test_required_components_snapshot_wins() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Player>();
+        registry.register::<Health>();
+        let mut src = World::new();
+        src.spawn((Player, Health(5)));
+        let mut snapshot = save_world_arch_snapshot(&src, &registry);
+        // Requiring component first: a per-column insert would default Health first.
+        for arch in &mut snapshot.archetypes {
+            let mut cols: Vec<_> = arch
+                .component_types
+                .drain(..)
+                .zip(arch.columns.drain(..))
+                .zip(arch.storage_types.drain(..))
+                .collect();
+            cols.sort_by_key(|((t, _), _)| !t.ends_with("Player"));
+            for ((t, c), st) in cols {
+                arch.component_types.push(t);
+                arch.columns.push(c);
+                arch.storage_types.push(st);
+            }
+        }
+
+        for load in [load_world_arch_snapshot, load_world_arch_snapshot_defragment] {
+            let mut world = World::new();
+            world.init_resource::<HealthInserts>();
+            world.add_observer(|_: On<Insert, Health>, mut n: ResMut<HealthInserts>| n.0 += 1);
+            load(&mut world, &snapshot, &registry).unwrap();
+            let mut q = world.query_filtered::<&Health, With<Player>>();
+            assert_eq!(q.iter(&world).collect::<Vec<_>>(), vec![&Health(5)]);
+            assert_eq!(world.resource::<HealthInserts>().0, 1);
+        }
+    }
+
+    #[test]
+    fn test_load_limits() {
+        let (world, mut registry) = init_world();
+        let mut snapshot = save_world_arch_snapshot(&world, &registry);
+        snapshot.entities.push(4_000_000_000);
+
+        let mut target = World::new();
+        let err = load_world_arch_snapshot(&mut target, &snapshot, &registry).unwrap_err();
+        assert!(matches!(err, LimitError::TooManyEntities { .. }));
+        assert!(target.entities().count_spawned() < 1000);
+
+        snapshot.entities.pop();
+        // Ids only listed by an archetype count too; u32::MAX never fits, even unlimited.
+        registry.limits = LoadLimits::unlimited();
+        snapshot.archetypes[0].entities[0] = u32::MAX;
+        let err = load_world_arch_snapshot_defragment(&mut target, &snapshot, &registry).unwrap_err();
+        assert!(matches!(err, LimitError::TooManyEntities { .. }));
+
+        registry.limits.max_archetypes = 1;
+        let err = load_world_arch_snapshot_defragment(&mut target, &snapshot, &registry).unwrap_err();
+        assert!(matches!(err, LimitError::TooManyArchetypes { .. }));
+    }
+
+    #[test]
+    fn test_bad_rows_are_defaulted_or_skipped() {
+        let (world, mut registry) = init_world();
+        registry.register_row_default::<TestComponentA>();
+        let mut snapshot = save_world_arch_snapshot(&world, &registry);
+        let (arch_idx, arch) = snapshot
+            .archetypes
+            .iter_mut()
+            .enumerate()
+            .find(|(_, a)| a.component_types.len() == 2 && a.has_component("TestComponentA"))
+            .unwrap();
+        let (bad_a, bad_b) = (arch.entities[0], arch.entities[1]);
+        arch.get_column_mut("TestComponentA").unwrap()[0] = Value::from("oops");
+        arch.get_column_mut("TestComponentB").unwrap()[1] = Value::from("nope");
+
+        let mut loaded = World::new();
+        load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        let e = |id| Entity::from_index(EntityIndex::from_raw_u32(id).unwrap());
+        assert_eq!(loaded.get::<TestComponentA>(e(bad_a)), Some(&TestComponentA::default()));
+        assert!(loaded.get::<TestComponentB>(e(bad_a)).is_some());
+        assert!(loaded.get::<TestComponentA>(e(bad_b)).is_none());
+        assert_eq!(loaded.query::<&TestComponentA>().iter(&loaded).count(), 29);
+
+        let report = loaded.resource::<LoadReport>();
+        assert_eq!(report.row_errors.len(), 2);
+        let err = &report.row_errors[0];
+        assert_eq!((err.archetype, err.row, err.entity), (arch_idx, 0, bad_a));
+        assert_eq!(err.component, "TestComponentA");
+        assert_eq!(err.value, Value::from("oops"));
+        assert_eq!(err.recovery, RowRecovery::Defaulted);
+        assert_eq!(report.row_errors[1].recovery, RowRecovery::SkippedEntity);
+
+        registry.row_errors = RowErrorPolicy::SkipEntity;
+        let mut strict = World::new();
+        load_world_arch_snapshot_defragment(&mut strict, &snapshot, &registry).unwrap();
+        assert_eq!(strict.query::<&TestComponentA>().iter(&strict).count(), 28);
+        assert!(
+            strict
+                .resource::<LoadReport>()
+                .row_errors
+                .iter()
+                .all(|e| e.recovery == RowRecovery::SkippedEntity)
+        );
+    }
+
+    #[test]
+    fn test_load_report_and_strict() {
+        let (world, _) = init_world();
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<TestComponentA>();
+        registry.register::<TestComponentC>();
+        registry.register::<TestComponentD>();
+        registry.register::<TestComponentE>();
+        registry.register::<TestComponentF>();
+        let (_, full) = init_world();
+        let snapshot = save_world_arch_snapshot(&world, &full);
+
+        let mut loaded = World::new();
+        let report = load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        assert_eq!(report.entities_loaded, 50);
+        assert_eq!(report.components_loaded, 120);
+        assert_eq!(
+            report.unknown_types,
+            BTreeSet::from(["TestComponentB".to_string()])
+        );
+        assert_eq!(report.skipped_components["TestComponentB"], 30);
+        assert!(!report.is_clean());
+        assert_eq!(loaded.resource::<LoadReport>().components_loaded, 120);
+        let rows: usize = report.archetype_stats.iter().map(|s| s.rows).sum();
+        assert_eq!(rows, 50);
+        assert!(
+            report
+                .archetype_stats
+                .iter()
+                .all(|s| s.blob_bytes.is_none())
+        );
+
+        registry.load_options.strict = true;
+        let mut strict = World::new();
+        let spawned = strict.entities().count_spawned();
+        let err = load_world_arch_snapshot(&mut strict, &snapshot, &registry).unwrap_err();
+        assert_eq!(err, LimitError::UnknownType("TestComponentB".to_string()));
+        assert_eq!(strict.entities().count_spawned(), spawned);
+
+        let resources = BTreeMap::from([("Missing".to_string(), Value::Null)]);
+        assert!(load_world_resource(&resources, &mut strict, &registry).is_err());
+        let report = load_world_arch_snapshot(&mut strict, &snapshot, &full).unwrap();
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn test_toml_json_strings() {
+        let (world, registry) = init_world();
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        let entity_snapshot = archive::WorldSnapshot::from(&snapshot);
+
+        let toml = snapshot.to_toml_string().unwrap();
+        let back = WorldArchSnapshot::from_toml_str(&toml).unwrap();
+        assert_eq!(back.to_json_string().unwrap(), snapshot.to_json_string().unwrap());
+        let json = snapshot.to_json_string().unwrap();
+        let back = WorldArchSnapshot::from_json_str(&json).unwrap();
+        assert_eq!(back.entities, snapshot.entities);
+
+        let toml = entity_snapshot.to_toml_string().unwrap();
+        let back = archive::WorldSnapshot::from_toml_str(&toml).unwrap();
+        assert_eq!(back.entities, entity_snapshot.entities);
+        let json = entity_snapshot.to_json_string().unwrap();
+        let back = archive::WorldSnapshot::from_json_str(&json).unwrap();
+        assert_eq!(back.entities.len(), entity_snapshot.entities.len());
+
+        let mut with_null = snapshot.clone();
+        with_null.archetypes[0].columns[0][0] = Value::Null;
+        assert!(matches!(with_null.to_toml_string(), Err(ArchiveError::Parse(_))));
+        assert!(WorldArchSnapshot::from_toml_str("entities = [").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_columns() {
+        use crate::bevy_registry::DuplicatePolicy;
+
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<TestComponentA>();
+        let mut arch = ArchetypeSnapshot::default();
+        arch.entities.push(0);
+        for value in [1, 2] {
+            arch.add_type("TestComponentA", None);
+            arch.columns.last_mut().unwrap()[0] = serde_json::json!({ "value": value });
+        }
+        let snapshot = WorldArchSnapshot {
+            entities: vec![0],
+            archetypes: vec![arch],
+        };
+
+        for (policy, expected) in [(DuplicatePolicy::ReplaceLast, 2), (DuplicatePolicy::KeepFirst, 1)] {
+            registry.duplicate_components = policy;
+            let mut world = World::new();
+            let report = load_world_arch_snapshot(&mut world, &snapshot, &registry).unwrap();
+            assert_eq!(report.skipped_components["TestComponentA"], 1);
+            let value = world.query::<&TestComponentA>().single(&world).unwrap().value;
+            assert_eq!(value, expected);
+        }
+
+        registry.duplicate_components = DuplicatePolicy::Error;
+        let mut world = World::new();
+        let err = load_world_arch_snapshot(&mut world, &snapshot, &registry).unwrap_err();
+        assert_eq!(err, LimitError::DuplicateComponent("TestComponentA".to_string()));
+        assert_eq!(world.query::<&TestComponentA>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn test_prototype_fills_missing_fields() {
+        #[derive(Serialize, Deserialize, Component)]
+        struct OldPosition {
+            x: f32,
+        }
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Component)]
+        struct Position {
+            x: f32,
+            y: f32,
+        }
+        let mut old = SnapshotRegistry::default();
+        old.register_named::<OldPosition>("Position");
+        let mut world = World::new();
+        world.spawn(OldPosition { x: 1.0 });
+
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Position>();
+        registry.set_prototype(Position { x: 0.0, y: 5.0 });
+        let expected = vec![&Position { x: 1.0, y: 5.0 }];
+
+        let snapshot = save_world_arch_snapshot(&world, &old);
+        let mut loaded = World::new();
+        let report = load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        assert!(report.is_clean());
+        let mut query = loaded.query::<&Position>();
+        assert_eq!(query.iter(&loaded).collect::<Vec<_>>(), expected);
+
+        let snapshot = archive::save_world_snapshot(&world, &old);
+        let mut loaded = World::new();
+        archive::load_world_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        let mut query = loaded.query::<&Position>();
+        assert_eq!(query.iter(&loaded).collect::<Vec<_>>(), expected);
+    }
+
     #[test]
     fn test_multi_archetype_snapshot() {
         let (world, registry) = init_world();
@@ -637,7 +1506,7 @@ mod tests {
 
         // 第三步：构建空世界并加载
         let mut world_new = World::new();
-        load_world_arch_snapshot_defragment(&mut world_new, &snapshot_1, &registry);
+        load_world_arch_snapshot_defragment(&mut world_new, &snapshot_1, &registry).unwrap();
 
         // 第四步：再次保存快照
         let snapshot_2 = save_world_arch_snapshot(&world_new, &registry);
@@ -666,7 +1535,7 @@ mod tests {
 
         // Load into a fresh, clean world
         let mut world_b = World::new();
-        load_world_arch_snapshot_defragment(&mut world_b, &snap, &registry);
+        load_world_arch_snapshot_defragment(&mut world_b, &snap, &registry).unwrap();
 
         // Verify: roundtrip must produce identical snapshot
         let snap_b = save_world_arch_snapshot(&world_b, &registry);
@@ -708,7 +1577,7 @@ mod tests {
         }
 
         let id_registry = IDRemapRegistry::default();
-        load_world_arch_snapshot_with_remap(&mut world_b, &snap, &registry, &id_registry, &mapper);
+        load_world_arch_snapshot_with_remap(&mut world_b, &snap, &registry, &id_registry, &mapper).unwrap();
 
         // Verify entities exist at remapped IDs
         // TestComponentA appears in 3 of 5 archetypes = 30 out of 50 entities
@@ -747,4 +1616,143 @@ mod tests {
         let entities: WorldArchSnapshot = (&snapshot).into();
         println!("{}", serde_json::to_string(&entities).unwrap());
     }
+
+    #[test]
+    fn test_snapshot_since_tick() {
+        let (mut world, registry) = init_world();
+        let base = save_world_arch_snapshot(&world, &registry);
+        let baseline = world.increment_change_tick();
+        let unchanged = save_world_arch_snapshot_since(&world, &registry, baseline);
+        assert!(unchanged.archetypes.is_empty());
+
+        let (a, b) = {
+            let mut query =
+                world.query_filtered::<Entity, (With<TestComponentA>, With<TestComponentB>)>();
+            let mut it = query.iter(&world);
+            (it.next().unwrap(), it.next().unwrap())
+        };
+        world.get_mut::<TestComponentA>(a).unwrap().value = 100;
+        world.entity_mut(b).insert(TestComponentD { value: true });
+        let born = world.spawn(TestComponentC { value: "new".into() }).id();
+
+        let delta = save_world_arch_snapshot_since(&world, &registry, baseline);
+        assert_eq!(delta.entities.len(), 3);
+        let columns = |id: Entity| {
+            delta
+                .archetypes
+                .iter()
+                .find(|arch| arch.entities.contains(&id.index_u32()))
+                .map(|arch| arch.component_types.clone())
+                .unwrap()
+        };
+        assert_eq!(columns(a), vec!["TestComponentA"]);
+        assert_eq!(columns(b), vec!["TestComponentD"]);
+        assert_eq!(columns(born), vec!["TestComponentC"]);
+
+        let mut restored = World::new();
+        load_world_arch_snapshot(&mut restored, &base, &registry).unwrap();
+        load_world_arch_snapshot(&mut restored, &delta, &registry).unwrap();
+        let at = |e: Entity| restored.entities().resolve_from_index(e.index());
+        let (a, b, born) = (at(a), at(b), at(born));
+        assert_eq!(restored.get::<TestComponentA>(a).unwrap().value, 100);
+        assert!(restored.get::<TestComponentB>(a).is_some());
+        assert_eq!(
+            restored.get::<TestComponentD>(b),
+            Some(&TestComponentD { value: true })
+        );
+        assert_eq!(restored.get::<TestComponentC>(born).unwrap().value, "new");
+    }
+
+    #[test]
+    fn test_filtered_snapshot() {
+        let (world, registry) = init_world();
+        let with_a = save_world_arch_snapshot_filtered::<With<TestComponentA>>(&world, &registry);
+        assert_eq!(with_a.entities.len(), 30);
+        assert!(
+            with_a
+                .archetypes
+                .iter()
+                .all(|a| a.has_component("TestComponentA"))
+        );
+
+        let only_a = save_world_arch_snapshot_filtered::<(
+            With<TestComponentA>,
+            Without<TestComponentB>,
+        )>(&world, &registry);
+        assert_eq!(only_a.entities.len(), 10);
+        let mut restored = World::new();
+        load_world_arch_snapshot(&mut restored, &only_a, &registry).unwrap();
+        let a_count = restored.query::<&TestComponentA>().iter(&restored).count();
+        let b_count = restored.query::<&TestComponentB>().iter(&restored).count();
+        let d_count = restored.query::<&TestComponentD>().iter(&restored).count();
+        assert_eq!((a_count, b_count, d_count), (10, 0, 10));
+
+        let dynamic = save_world_arch_snapshot_with_components(
+            &world,
+            &registry,
+            &["TestComponentA", "TestComponentC"],
+        );
+        assert_eq!(dynamic.entities.len(), 20);
+        let missing = save_world_arch_snapshot_with_components(&world, &registry, &["Missing"]);
+        assert!(missing.archetypes.is_empty());
+        let unseen = save_world_arch_snapshot_filtered::<With<Health>>(&world, &registry);
+        assert!(unseen.entities.is_empty());
+    }
+
+    #[test]
+    fn test_entities_snapshot() {
+        let (mut world, registry) = init_world();
+        let picked: Vec<Entity> = world
+            .query_filtered::<Entity, With<TestComponentE>>()
+            .iter(&world)
+            .step_by(4)
+            .collect();
+        let gone = world.spawn(TestComponentA { value: 0 }).id();
+        world.despawn(gone);
+        let mut selection = picked.clone();
+        selection.push(gone);
+
+        let snapshot = save_entities_snapshot(&world, &registry, &selection);
+        let mut expected: Vec<u32> = picked.iter().map(|e| e.index_u32()).collect();
+        expected.sort_unstable();
+        assert_eq!(snapshot.entities, expected);
+        assert_eq!(snapshot.archetypes.len(), 2);
+
+        let mut restored = World::new();
+        load_world_arch_snapshot(&mut restored, &snapshot, &registry).unwrap();
+        for e in picked {
+            let at = restored.entities().resolve_from_index(e.index());
+            assert_eq!(
+                restored.get::<TestComponentE>(at),
+                world.get::<TestComponentE>(e)
+            );
+        }
+    }
+
+    #[test]
+    fn test_spawn_into() {
+        let (world, registry) = init_world();
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        let arch = snapshot
+            .archetypes
+            .iter()
+            .find(|a| a.component_types == ["TestComponentA", "TestComponentB"])
+            .unwrap();
+
+        let mut target = World::new();
+        let taken = target.spawn(TestComponentD { value: false }).id();
+        let spawned = arch.spawn_into(&mut target, &registry, 2..5).unwrap();
+        assert_eq!(spawned.len(), 3);
+        assert!(!spawned.contains(&taken));
+        for (entity, row) in spawned.iter().zip(2..5) {
+            let expected = arch.get_column("TestComponentA").unwrap()[row].clone();
+            let value = serde_json::to_value(target.get::<TestComponentA>(*entity).unwrap());
+            assert_eq!(value.unwrap(), expected);
+            assert!(target.get::<TestComponentB>(*entity).is_some());
+        }
+
+        let again = arch.spawn_into(&mut target, &registry, 2..5).unwrap();
+        assert!(again.iter().all(|e| !spawned.contains(e)));
+        assert!(arch.spawn_into(&mut target, &registry, 5..100).is_err());
+    }
 }
@@ -1,15 +1,81 @@
 use std::collections::{BTreeMap, HashMap};
-use std::io::Cursor;
+use std::fmt;
+use std::io::{Cursor, Write};
+use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::archetype_archive::ArchetypeSnapshot;
 use crate::binary_archive::arrow_column::ArrowColumn;
-use arrow::array::RecordBatch;
+use arrow::array::{ArrayRef, Int64Array, RecordBatch};
 use arrow::compute::concat_batches;
+use arrow::datatypes::{Field, FieldRef};
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::ipc::writer::{FileWriter, StreamWriter};
 use parquet::arrow::ArrowWriter;
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReaderBuilder, RowSelection, RowSelector};
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
 use parquet::file::reader::ChunkReader;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_arrow::schema::{SchemaLike, TracingOptions};
+use serde_arrow::utils::Items;
+/// Parquet codec for one archetype table.
+///
+/// Text form (used in `meta.toml`): `none`, `snappy`, `lz4` or `zstd(<level>)`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParquetCompression {
+    /// For tables whose payload is already compressed.
+    #[default]
+    None,
+    /// Fast, light compression; suits float-heavy tables.
+    Snappy,
+    Lz4,
+    /// Strong compression for text-heavy tables, at the given level (1..=22).
+    Zstd(i32),
+}
+
+impl ParquetCompression {
+    pub fn to_parquet(self) -> Result<Compression, parquet::errors::ParquetError> {
+        Ok(match self {
+            ParquetCompression::None => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Lz4 => Compression::LZ4_RAW,
+            ParquetCompression::Zstd(level) => Compression::ZSTD(ZstdLevel::try_new(level)?),
+        })
+    }
+}
+
+impl fmt::Display for ParquetCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParquetCompression::None => f.write_str("none"),
+            ParquetCompression::Snappy => f.write_str("snappy"),
+            ParquetCompression::Lz4 => f.write_str("lz4"),
+            ParquetCompression::Zstd(level) => write!(f, "zstd({level})"),
+        }
+    }
+}
+
+impl FromStr for ParquetCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" | "uncompressed" => Ok(ParquetCompression::None),
+            "snappy" => Ok(ParquetCompression::Snappy),
+            "lz4" => Ok(ParquetCompression::Lz4),
+            "zstd" => Ok(ParquetCompression::Zstd(ZstdLevel::default().compression_level())),
+            other => other
+                .strip_prefix("zstd(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .and_then(|level| level.parse().ok())
+                .map(ParquetCompression::Zstd)
+                .ok_or_else(|| format!("unknown parquet compression: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct ComponentTable {
     pub columns: BTreeMap<String, ArrowColumn>,
@@ -21,18 +87,144 @@ pub struct EntityID {
     pub id: u32,
 }
 
+/// Default name of the entity id column.
+pub const ID_COLUMN: &str = "id";
+/// Field metadata key marking an entity id column not named [`ID_COLUMN`].
+const ID_COLUMN_KEY: &str = "id_column";
+/// Field metadata key marking an [`IndexColumn`], which loaders skip.
+const INDEX_COLUMN_KEY: &str = "index_column";
+
+type DeriveIndex =
+    dyn Fn(&ComponentTable) -> Result<ArrayRef, Box<dyn std::error::Error>> + Send + Sync;
+
+/// A column derived from the table when it is written, e.g. chunk coordinates for
+/// Hive-style partitioning of the emitted Parquet files. Readers of the table skip it.
+#[derive(Clone)]
+pub struct IndexColumn {
+    pub name: String,
+    derive: Arc<DeriveIndex>,
+}
+
+impl fmt::Debug for IndexColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexColumn")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl IndexColumn {
+    /// Column `name` computed by `derive`, which returns one value per row of the table.
+    pub fn new(
+        name: impl Into<String>,
+        derive: impl Fn(&ComponentTable) -> Result<ArrayRef, Box<dyn std::error::Error>>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            derive: Arc::new(derive),
+        }
+    }
+
+    /// The column for `table`, one value per row.
+    pub fn values(&self, table: &ComponentTable) -> Result<ArrayRef, Box<dyn std::error::Error>> {
+        let array = (self.derive)(table)?;
+        if array.len() != table.entities.len() {
+            return Err(format!(
+                "index column {} has {} rows, the table {}",
+                self.name,
+                array.len(),
+                table.entities.len()
+            )
+            .into());
+        }
+        Ok(array)
+    }
+
+    /// Column `name` holding `index` of each value of `component`, stored as `T`. Tables
+    /// without the component get a null column.
+    pub fn from_component<T: DeserializeOwned>(
+        name: impl Into<String>,
+        component: impl Into<String>,
+        index: impl Fn(&T) -> i64 + Send + Sync + 'static,
+    ) -> Self {
+        let component = component.into();
+        Self::new(name, move |table| {
+            let values: Vec<Option<i64>> = match table.get_column(&component) {
+                Some(col) => col.to_vec::<T>()?.iter().map(|v| Some(index(v))).collect(),
+                None => vec![None; table.entities.len()],
+            };
+            Ok(Arc::new(Int64Array::from(values)) as ArrayRef)
+        })
+    }
+}
+
+/// Column layout of [`ComponentTable::to_record_batch_with`].
+#[derive(Debug, Clone)]
+pub struct RecordBatchLayout {
+    /// Name of the entity id column, [`ID_COLUMN`] by default.
+    pub id_column: String,
+    /// Columns written after the id column.
+    pub index_columns: Vec<IndexColumn>,
+}
+
+impl Default for RecordBatchLayout {
+    fn default() -> Self {
+        Self {
+            id_column: ID_COLUMN.to_string(),
+            index_columns: Vec::new(),
+        }
+    }
+}
+
+impl RecordBatchLayout {
+    pub fn with_id_column(mut self, name: impl Into<String>) -> Self {
+        self.id_column = name.into();
+        self
+    }
+
+    pub fn with_index_column(mut self, column: IndexColumn) -> Self {
+        self.index_columns.push(column);
+        self
+    }
+}
+
 impl ComponentTable {
     pub fn to_record_batch(&self) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+        self.to_record_batch_with(&RecordBatchLayout::default())
+    }
+
+    /// [`to_record_batch`](Self::to_record_batch) with the id column and index columns of
+    /// `layout`. [`from_record_batch`](Self::from_record_batch) reads the result back.
+    pub fn to_record_batch_with(
+        &self,
+        layout: &RecordBatchLayout,
+    ) -> Result<RecordBatch, Box<dyn std::error::Error>> {
         let mut fields = Vec::new();
         let mut arrays = Vec::new();
-        let mut type_map = HashMap::new();
+        let mut type_map = BTreeMap::new();
 
+        let id = layout.id_column.as_str();
         let ent = ArrowColumn::from_slice(&self.entities).unwrap();
-        type_map.insert("id".to_string(), vec!["id".to_string()]);
+        type_map.insert(id.to_string(), vec![id.to_string()]);
         for f in &ent.fields {
-            fields.push(f.clone());
+            let mut f = (**f).clone();
+            if id != ID_COLUMN {
+                let meta = HashMap::from([(ID_COLUMN_KEY.to_string(), "true".to_string())]);
+                f = f.with_name(id).with_metadata(meta);
+            }
+            fields.push(Arc::new(f));
             arrays.extend(ent.data.clone());
         }
+        for index in &layout.index_columns {
+            let array = index.values(self)?;
+            let meta = HashMap::from([(INDEX_COLUMN_KEY.to_string(), "true".to_string())]);
+            let field = Field::new(&index.name, array.data_type().clone(), true);
+            fields.push(Arc::new(field.with_metadata(meta)));
+            arrays.push(array);
+        }
         for (type_name, col) in &self.columns {
             let mut str_fields = Vec::with_capacity(col.fields.len());
             for f in &col.fields {
@@ -93,12 +285,21 @@ impl ComponentTable {
         let mut table_builder = HashMap::new();
 
         for field in fields.iter() {
-            let prefix = field.metadata().get("prefix").map_or(field.name(), |v| v);
+            if field.metadata().contains_key(INDEX_COLUMN_KEY) {
+                continue;
+            }
             let column = batch.column_by_name(field.name()).unwrap();
-            let final_name = field
-                .name()
-                .strip_prefix(&format!("{}.", prefix))
-                .unwrap_or(field.name());
+            // `EntityID` decodes from a field named `id` whatever the column is called.
+            let (prefix, final_name) = if field.metadata().contains_key(ID_COLUMN_KEY) {
+                (ID_COLUMN, ID_COLUMN)
+            } else {
+                let prefix = field.metadata().get("prefix").map_or(field.name(), |v| v);
+                let final_name = field
+                    .name()
+                    .strip_prefix(&format!("{}.", prefix))
+                    .unwrap_or(field.name());
+                (prefix.as_str(), final_name)
+            };
             let renamed_field = (**field).clone().with_name(final_name);
             table_builder
                 .entry(prefix.to_string())
@@ -111,7 +312,7 @@ impl ComponentTable {
                 fields: data.iter().map(|(f, _)| f.clone()).collect(),
                 data: data.iter().map(|(_, a)| a.clone()).collect(),
             };
-            if name == "id" {
+            if name == ID_COLUMN {
                 new_table.entities = column.to_vec::<EntityID>()?;
             } else {
                 new_table.insert_column(&name, column);
@@ -122,20 +323,62 @@ impl ComponentTable {
     }
 }
 
+/// Encode `record_batch` as Parquet with every column compressed by `compression`.
+pub fn record_batch_to_parquet(
+    record_batch: &RecordBatch,
+    compression: ParquetCompression,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut buffer = Vec::new();
+    write_record_batch_parquet(record_batch, compression, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// [`record_batch_to_parquet`], writing the Parquet file to `writer` a row group at a time.
+pub fn write_record_batch_parquet<W: Write + Send>(
+    record_batch: &RecordBatch,
+    compression: ParquetCompression,
+    writer: W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let props = WriterProperties::builder()
+        .set_compression(compression.to_parquet()?)
+        .build();
+    let mut arrow_writer = ArrowWriter::try_new(writer, record_batch.schema(), Some(props))?;
+    arrow_writer.write(record_batch)?;
+    arrow_writer.close()?;
+    Ok(())
+}
+
 impl ComponentTable {
     pub fn from_parquet_u8(buffer: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
         let bytes = bytes::Bytes::from_iter(buffer.iter().cloned());
         Self::from_parquet(bytes)
     }
     pub fn to_parquet(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        let record_batch = self.to_record_batch()?;
-        let mut buffer = Vec::new();
-        {
-            let mut arrow_writer = ArrowWriter::try_new(&mut buffer, record_batch.schema(), None)?;
-            arrow_writer.write(&record_batch)?;
-            arrow_writer.close()?;
-        }
-        Ok(buffer)
+        self.to_parquet_with(ParquetCompression::None)
+    }
+    /// Encode the table as Parquet with every column compressed by `compression`.
+    pub fn to_parquet_with(
+        &self,
+        compression: ParquetCompression,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        self.to_parquet_with_layout(compression, &RecordBatchLayout::default())
+    }
+    /// [`to_parquet_with`](Self::to_parquet_with), writing the Parquet file to `writer`
+    /// instead of a buffer.
+    pub fn write_parquet_with<W: Write + Send>(
+        &self,
+        compression: ParquetCompression,
+        writer: W,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        write_record_batch_parquet(&self.to_record_batch()?, compression, writer)
+    }
+    /// [`to_parquet_with`](Self::to_parquet_with), laying the columns out as `layout` says.
+    pub fn to_parquet_with_layout(
+        &self,
+        compression: ParquetCompression,
+        layout: &RecordBatchLayout,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        record_batch_to_parquet(&self.to_record_batch_with(layout)?, compression)
     }
     ///! Caution: this does not support nested struct due to arrow limitations.
     pub fn to_csv(&self) -> Result<String, Box<dyn std::error::Error>> {
@@ -166,20 +409,115 @@ impl ComponentTable {
 
         Self::from_record_batch(&batch)
     }
+
+    /// Decode only row `row` of a Parquet table; row groups before it are skipped.
+    pub fn from_parquet_row(buffer: &[u8], row: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = bytes::Bytes::copy_from_slice(buffer);
+        let selection = RowSelection::from(vec![RowSelector::skip(row), RowSelector::select(1)]);
+        let reader = ParquetRecordBatchReaderBuilder::try_new(bytes)?
+            .with_row_selection(selection)
+            .build()?;
+        let batches: Vec<_> = reader.collect::<Result<_, _>>()?;
+        if batches.is_empty() {
+            return Ok(ComponentTable::default());
+        }
+        let batch = concat_batches(&batches[0].schema(), &batches)?;
+        Self::from_record_batch(&batch)
+    }
+
+    /// Rows `offset..offset + len` of the table.
+    pub fn slice(&self, offset: usize, len: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let batch = self.to_record_batch()?;
+        if offset.saturating_add(len) > batch.num_rows() {
+            return Err(format!("rows {offset}..{} out of range", offset + len).into());
+        }
+        Self::from_record_batch(&batch.slice(offset, len))
+    }
+
+    /// Encode the table in the Arrow IPC file format (Feather v2).
+    pub fn to_ipc(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let record_batch = self.to_record_batch()?;
+        let mut buffer = Vec::new();
+        {
+            let mut writer = FileWriter::try_new(&mut buffer, &record_batch.schema())?;
+            writer.write(&record_batch)?;
+            writer.finish()?;
+        }
+        Ok(buffer)
+    }
+    /// Encode the table in the Arrow IPC streaming format.
+    pub fn to_ipc_stream(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let record_batch = self.to_record_batch()?;
+        let mut buffer = Vec::new();
+        {
+            let mut writer = StreamWriter::try_new(&mut buffer, &record_batch.schema())?;
+            writer.write(&record_batch)?;
+            writer.finish()?;
+        }
+        Ok(buffer)
+    }
+    /// Decode a table written by [`to_ipc`](Self::to_ipc) or
+    /// [`to_ipc_stream`](Self::to_ipc_stream); the variant is detected from the file magic.
+    pub fn from_ipc(buffer: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let (schema, batches) = if buffer.starts_with(IPC_FILE_MAGIC) {
+            let reader = FileReader::try_new(Cursor::new(buffer), None)?;
+            let schema = reader.schema();
+            (schema, reader.collect::<Result<Vec<_>, _>>()?)
+        } else {
+            let reader = StreamReader::try_new(Cursor::new(buffer), None)?;
+            let schema = reader.schema();
+            (schema, reader.collect::<Result<Vec<_>, _>>()?)
+        };
+        if batches.is_empty() {
+            return Ok(ComponentTable::default());
+        }
+        let batch = concat_batches(&schema, &batches)?;
+        Self::from_record_batch(&batch)
+    }
 }
+
+/// Leading bytes of an Arrow IPC file; streams start with a continuation marker instead.
+const IPC_FILE_MAGIC: &[u8] = b"ARROW1";
+
 pub struct ArrowTableConverstion;
 pub struct ArchetypeSnapshotCtx<'a, 'w> {
     pub arch: &'a ArchetypeSnapshot,
     pub reg: &'w ArrowTableConverstion,
 }
-// impl From<&ArchetypeSnapshot> for ComponentTable {
-//     fn from(comp: &ArchetypeSnapshot) -> Self {
-//         let mut table = ComponentTable::default();
-//         table.entities = comp.entities.iter().map(|x| EntityID { id: *x }).collect();
-//         table.columns.insert(key, value);
-//         table
-//     }
-// }
+/// Arrow column of the JSON values of one component, with fields traced from the values.
+/// Values that are not objects go to a single `item` field, like the registry's schema for
+/// non-struct components.
+fn column_from_json(
+    values: &[serde_json::Value],
+) -> Result<ArrowColumn, Box<dyn std::error::Error>> {
+    let options = TracingOptions::default().allow_null_fields(true);
+    if let Ok(fields) = Vec::<FieldRef>::from_samples(values, options.clone()) {
+        let data = serde_arrow::to_arrow(&fields, values)?;
+        return Ok(ArrowColumn { fields, data });
+    }
+    let fields = Vec::<FieldRef>::from_samples(Items(values), options)?;
+    let data = serde_arrow::to_arrow(&fields, Items(values))?;
+    Ok(ArrowColumn { fields, data })
+}
+
+/// Converts the JSON columns of a MessagePack archetype. Field types are traced from the
+/// values, so numbers come out as 64-bit and load through the registry like any Arrow table.
+impl TryFrom<&ArchetypeSnapshot> for ComponentTable {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(arch: &ArchetypeSnapshot) -> Result<Self, Self::Error> {
+        let mut table = ComponentTable {
+            entities: arch.entities.iter().map(|&id| EntityID { id }).collect(),
+            ..Default::default()
+        };
+        for (name, values) in arch.component_types.iter().zip(&arch.columns) {
+            let column = column_from_json(values)
+                .map_err(|e| format!("cannot convert column {name}: {e}"))?;
+            table.insert_column(name, column);
+        }
+        Ok(table)
+    }
+}
 
 // impl From<&ComponentTable> for ArchetypeSnapshot {
 //     fn from(comp: &ComponentTable) -> Self {
@@ -9,22 +9,35 @@
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 use bevy_ecs::component::ComponentId;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::name::Name;
 use bevy_ecs::world::World;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use crate::archetype_archive::{
-    ArchetypeSnapshot, StorageTypeFlag, WorldArchSnapshot,
-    load_world_arch_snapshot_defragment as load_world_arch_snapshot, load_world_resource,
-    save_world_arch_snapshot, save_world_resource, load_world_arch_snapshot_with_remap,
+    ArchetypeSnapshot, LoadReport, StorageTypeFlag, WorldArchSnapshot, load_defragment_quiet,
+    load_world_resource, save_world_arch_snapshot, save_world_resource, load_world_arch_snapshot_with_remap,
 };
 #[cfg(feature = "arrow_rs")]
-use crate::arrow_snapshot::ComponentTable;
-use crate::bevy_registry::{SnapshotRegistry, IDRemapRegistry, EntityRemapper, reserve_entity_slots};
-use crate::csv_archive::ColumnarCsv;
+use crate::arrow_snapshot::{ComponentTable, ParquetCompression};
+use crate::load_events::trigger_load_events;
+use crate::bevy_registry::{
+    EntityRemapper, IDRemapRegistry, LimitError, LoadLimits, SnapshotRegistry, try_reserve_entity_slots,
+};
+use crate::checksum::{BLOB_CHECKSUMS_KEY, blob_checksum, verify_checksum};
+use crate::container::{ContainerError, SnapshotContainer};
+use crate::csv_archive::{CSV_NULL_TOKEN_KEY, ColumnarCsv, CsvOptions};
+use crate::entity_index::{ENTITY_INDEX_KEY, EntityLocationIndex};
+use crate::error::ArchiveError;
+use crate::registry_schema::{REGISTRY_SCHEMA_KEY, RegistrySchema};
+use crate::save_profile::SaveProfile;
+use crate::entity_name::NAME_COLUMN;
 use crate::csv_archive::columnar_from_snapshot;
 use crate::traits::Archive;
 
@@ -32,6 +45,8 @@ use crate::traits::Archive;
 pub enum AuroraLocation {
     File(String),
     Embed(String),
+    /// Full `http://` or `https://` URL, fetched through the blob loader.
+    Http(String),
     Unknown(String),
 }
 
@@ -48,7 +63,8 @@ impl Archive for AuroraWorldManifest {
         world: &mut World,
         registry: &SnapshotRegistry,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        load_world_manifest(world, self, registry).map_err(|e| e.into())
+        load_world_manifest(world, self, registry)?;
+        Ok(())
     }
 
     fn apply_with_remap(
@@ -58,15 +74,20 @@ impl Archive for AuroraWorldManifest {
         id_registry: &IDRemapRegistry,
         mapper: &dyn EntityRemapper,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let snap: WorldArchSnapshot = self.into();
-        load_world_arch_snapshot_with_remap(world, &snap, registry, id_registry, mapper);
-        load_world_resource(&self.world.resources, world, registry);
+        let mut snap = manifest_arch_snapshot(self, &registry.limits)?;
+        let versions = self.component_versions();
+        for arch in &mut snap.archetypes {
+            migrate_archetype(arch, &versions, registry)?;
+        }
+        load_world_arch_snapshot_with_remap(world, &snap, registry, id_registry, mapper)?;
+        load_world_resource(&self.world.resources, world, registry)?;
         Ok(())
     }
 
     fn get_entities(&self) -> Vec<u32> {
-        let snap: WorldArchSnapshot = self.into();
-        snap.entities
+        WorldArchSnapshot::try_from(self)
+            .map(|snap| snap.entities)
+            .unwrap_or_default()
     }
 
     fn load_resources(
@@ -74,7 +95,7 @@ impl Archive for AuroraWorldManifest {
         world: &mut World,
         registry: &SnapshotRegistry,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        load_world_resource(&self.world.resources, world, registry);
+        load_world_resource(&self.world.resources, world, registry)?;
         Ok(())
     }
 
@@ -106,6 +127,22 @@ impl Archive for AuroraWorldManifest {
         let p = path.as_ref().to_str().ok_or("Invalid path")?;
         Self::from_file(p, None).map_err(|e| e.into())
     }
+
+    /// TOML. Blobs stored as files stay references to those files.
+    fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(toml::to_string_pretty(self)?.into_bytes())
+    }
+
+    /// TOML, or JSON when the content starts with `{`.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let content = std::str::from_utf8(bytes)?;
+        let format = if content.trim_start().starts_with('{') {
+            ManifestOutputFormat::Json
+        } else {
+            ManifestOutputFormat::Toml
+        };
+        Ok(parse_manifest(content, Path::new(""), Some(format))?)
+    }
 }
 
 impl From<&str> for AuroraLocation {
@@ -114,6 +151,8 @@ impl From<&str> for AuroraLocation {
             Self::File(rest.to_string())
         } else if let Some(rest) = s.strip_prefix("embed://") {
             Self::Embed(rest.to_string())
+        } else if s.starts_with("http://") || s.starts_with("https://") {
+            Self::Http(s.to_string())
         } else {
             Self::Unknown(s.to_string())
         }
@@ -128,6 +167,9 @@ pub enum AuroraFormat {
     CsvMsgPack, // csv in msgpack
     #[cfg(feature = "arrow_rs")]
     Parquet,
+    /// Arrow IPC file or stream (`.arrow`, `.arrows`, `.feather`, `.ipc`).
+    #[cfg(feature = "arrow_rs")]
+    ArrowIpc,
     Unknown,
 }
 
@@ -147,6 +189,12 @@ impl AuroraFormat {
                 if path.ends_with(".parquet") {
                     return Self::Parquet;
                 }
+                if [".arrow", ".arrows", ".feather", ".ipc"]
+                    .iter()
+                    .any(|ext| path.ends_with(ext))
+                {
+                    return Self::ArrowIpc;
+                }
             }
             Self::Unknown
         }
@@ -160,6 +208,8 @@ impl AuroraFormat {
             "csv.msgpack" => Self::CsvMsgPack,
             #[cfg(feature = "arrow_rs")]
             "parquet" => Self::Parquet,
+            #[cfg(feature = "arrow_rs")]
+            "arrow" | "arrows" | "feather" | "ipc" => Self::ArrowIpc,
             _ => Self::Unknown,
         }
     }
@@ -183,14 +233,17 @@ pub enum AuroraInternalFormat {
 /// - `loc`: The logical location (file path, embedded, etc.)
 /// - `embed_map`: Embedded blob map for `embed://` references.
 /// - `base_dir`: Base directory used to resolve `file://` relative paths.
+/// - `limits`: Caps the size of the blob, see [`LoadLimits::max_blob_bytes`].
 ///
 /// # Returns
-/// A `LoadedBlob` with its bytes and format.
+/// A `LoadedBlob` with its bytes and format. `http(s)://` locations are never fetched here
+/// and fail with `Unsupported`; load such manifests with an `HttpBlobLoader`.
 pub fn load_blob_from_location_with_base(
     loc: &AuroraLocation,
-    embed_map: &HashMap<String, EmbeddedBlob>,
+    embed_map: &BTreeMap<String, EmbeddedBlob>,
     base_dir: &Path,
-) -> Result<LoadedBlob, String> {
+    limits: &LoadLimits,
+) -> Result<LoadedBlob, ArchiveError> {
     match loc {
         AuroraLocation::File(raw_path) => {
             let relative_path = Path::new(raw_path);
@@ -200,8 +253,13 @@ pub fn load_blob_from_location_with_base(
                 base_dir.join(relative_path)
             };
 
-            let bytes = fs::read(&full_path)
-                .map_err(|e| format!("Failed to read {}: {}", full_path.display(), e))?;
+            let bytes = fs::read(&full_path).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("Failed to read {}: {}", full_path.display(), e),
+                )
+            })?;
+            limits.check_blob(bytes.len() as u64)?;
 
             let format = AuroraFormat::from_path(
                 full_path.file_name().and_then(|s| s.to_str()).unwrap_or(""),
@@ -212,58 +270,63 @@ pub fn load_blob_from_location_with_base(
 
         AuroraLocation::Embed(name) => {
             let blob = embed_map.get(name).ok_or_else(|| {
-                format!(
-                    "Embedded blob '{}' not found in manifest embed section.",
-                    name
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "Embedded blob '{}' not found in manifest embed section.",
+                        name
+                    ),
                 )
             })?;
 
+            limits.check_blob(blob.data.len() as u64)?;
             let format = AuroraFormat::from_str(&blob.format);
-
-            let bytes = match format {
-                AuroraFormat::MsgPack | AuroraFormat::CsvMsgPack => BASE64_STANDARD
-                    .decode(&blob.data)
-                    .map_err(|e| format!("Base64 decode failed: {}", e))?,
-                #[cfg(feature = "arrow_rs")]
-                AuroraFormat::Parquet => BASE64_STANDARD
-                    .decode(&blob.data)
-                    .map_err(|e| format!("Base64 decode failed: {}", e))?,
-                _ => blob.data.as_bytes().to_vec(),
-            };
+            let bytes = blob.decode(limits.max_blob_bytes)?;
 
             Ok(LoadedBlob { format, bytes })
         }
 
-        AuroraLocation::Unknown(s) => Err(format!("Unknown location type: {}", s)),
+        AuroraLocation::Http(url) => Err(ArchiveError::Io(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("Cannot fetch {}: load it through an HttpBlobLoader", url),
+        ))),
+
+        AuroraLocation::Unknown(s) => {
+            Err(ArchiveError::Parse(format!("Unknown location type: {}", s)))
+        }
     }
 }
 
 pub fn load_blob_from_location(
     loc: &AuroraLocation,
-    embed_map: &HashMap<String, EmbeddedBlob>,
-) -> Result<LoadedBlob, String> {
-    load_blob_from_location_with_base(loc, embed_map, Path::new("."))
+    embed_map: &BTreeMap<String, EmbeddedBlob>,
+    limits: &LoadLimits,
+) -> Result<LoadedBlob, ArchiveError> {
+    load_blob_from_location_with_base(loc, embed_map, Path::new("."), limits)
 }
 
-fn parse_blob(blob: &LoadedBlob) -> Result<AuroraInternalFormat, String> {
+fn parse_blob(blob: &LoadedBlob, csv: &CsvOptions) -> Result<AuroraInternalFormat, ArchiveError> {
     match &blob.format {
-        AuroraFormat::Csv => ColumnarCsv::from_csv_reader(&blob.bytes[..])
-            .map(AuroraInternalFormat::ColumnarCsv)
-            .map_err(|e| e.to_string()),
+        AuroraFormat::Csv => ColumnarCsv::from_csv_reader_with(&blob.bytes[..], csv)
+            .map(AuroraInternalFormat::ColumnarCsv),
         AuroraFormat::Json => serde_json::from_slice(&blob.bytes)
             .map(AuroraInternalFormat::ArchetypeSnapshot)
-            .map_err(|e| e.to_string()),
+            .map_err(ArchiveError::from),
         AuroraFormat::MsgPack => rmp_serde::from_slice(&blob.bytes)
             .map(AuroraInternalFormat::ArchetypeSnapshot)
-            .map_err(|e| e.to_string()),
+            .map_err(ArchiveError::from),
         AuroraFormat::CsvMsgPack => rmp_serde::from_slice(&blob.bytes)
             .map(AuroraInternalFormat::ColumnarCsv)
-            .map_err(|e| e.to_string()),
+            .map_err(ArchiveError::from),
         #[cfg(feature = "arrow_rs")]
         AuroraFormat::Parquet => ComponentTable::from_parquet_u8(&blob.bytes)
             .map(AuroraInternalFormat::ArrowComponentTable)
-            .map_err(|e| e.to_string()),
-        _ => Err("Cannot parse unknown format".into()),
+            .map_err(ArchiveError::parse),
+        #[cfg(feature = "arrow_rs")]
+        AuroraFormat::ArrowIpc => ComponentTable::from_ipc(&blob.bytes)
+            .map(AuroraInternalFormat::ArrowComponentTable)
+            .map_err(ArchiveError::parse),
+        _ => Err(ArchiveError::parse("Cannot parse unknown format")),
     }
 }
 
@@ -287,13 +350,75 @@ pub enum ExportFormat {
     CsvMsgPack,
     #[cfg(feature = "arrow_rs")]
     Parquet,
+    /// Arrow IPC file format, stored as `.arrow`.
+    #[cfg(feature = "arrow_rs")]
+    ArrowIpc,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct EmbeddedBlob {
     pub format: String,
+    /// Codec applied to the payload before base64 encoding. Only `"zstd"` is recognized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<String>,
     pub data: String,
 }
+
+impl EmbeddedBlob {
+    /// Decode the payload: base64 for binary formats or compressed blobs, then decompress.
+    ///
+    /// Decompression stops with an error once the output exceeds `max_bytes`.
+    pub fn decode(&self, max_bytes: u64) -> Result<Vec<u8>, ArchiveError> {
+        let binary = match AuroraFormat::from_str(&self.format) {
+            AuroraFormat::MsgPack | AuroraFormat::CsvMsgPack => true,
+            #[cfg(feature = "arrow_rs")]
+            AuroraFormat::Parquet | AuroraFormat::ArrowIpc => true,
+            _ => self.compression.is_some(),
+        };
+        let bytes = if binary {
+            BASE64_STANDARD
+                .decode(&self.data)
+                .map_err(|e| ArchiveError::Parse(format!("Base64 decode failed: {}", e)))?
+        } else {
+            self.data.as_bytes().to_vec()
+        };
+        match self.compression.as_deref() {
+            None => Ok(bytes),
+            Some("zstd") => zstd_decompress(&bytes, max_bytes),
+            Some(other) => Err(ArchiveError::Parse(format!(
+                "Unknown blob compression: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decompress(bytes: &[u8], max_bytes: u64) -> Result<Vec<u8>, ArchiveError> {
+    use std::io::Read;
+    let decoder = zstd::stream::read::Decoder::new(bytes)
+        .map_err(|e| ArchiveError::Parse(format!("Zstd decode failed: {}", e)))?;
+    let mut out = Vec::new();
+    decoder
+        .take(max_bytes.saturating_add(1))
+        .read_to_end(&mut out)
+        .map_err(|e| ArchiveError::Parse(format!("Zstd decode failed: {}", e)))?;
+    if out.len() as u64 > max_bytes {
+        return Err(ArchiveError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Decompressed blob exceeds {} bytes", max_bytes),
+        )));
+    }
+    Ok(out)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_bytes: &[u8], _max_bytes: u64) -> Result<Vec<u8>, ArchiveError> {
+    Err(ArchiveError::Io(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Blob is zstd-compressed; enable the `zstd` feature to load it",
+    )))
+}
 #[derive(Clone)]
 pub enum OutputStrategy {
     Embed(ExportFormat),
@@ -304,11 +429,36 @@ pub enum OutputStrategy {
     Return(ExportFormat, String),
 }
 
+/// Where an archetype blob too large to embed is written instead, see
+/// [`ExportGuidance::spill_embeds_over`].
+#[derive(Clone, Debug)]
+pub enum EmbedSpill {
+    /// Like [`OutputStrategy::File`], under this directory.
+    File(PathBuf),
+    /// Like [`OutputStrategy::Return`], under this virtual path.
+    Return(String),
+}
+
 #[derive(Clone)]
 pub struct ExportGuidance {
     pub default: OutputStrategy,
 
     pub per_arch: HashMap<usize, OutputStrategy>,
+    /// Parquet codec for archetypes exported as [`ExportFormat::Parquet`].
+    #[cfg(feature = "arrow_rs")]
+    pub default_compression: ParquetCompression,
+    #[cfg(feature = "arrow_rs")]
+    pub per_arch_compression: HashMap<usize, ParquetCompression>,
+    /// Zstd level for binary blobs embedded in the manifest; `None` leaves them uncompressed.
+    #[cfg(feature = "zstd")]
+    pub embed_zstd_level: Option<i32>,
+    /// Text options for archetypes exported as [`ExportFormat::Csv`]. A non-default null
+    /// token is recorded in the manifest metadata so loaders read it back.
+    pub csv: CsvOptions,
+    /// Largest embedded blob, in bytes of manifest text. Bigger `Embed` blobs go to
+    /// [`embed_spill`](Self::embed_spill), or fail the export when it is `None`.
+    pub max_embed_bytes: Option<u64>,
+    pub embed_spill: Option<EmbedSpill>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -317,41 +467,135 @@ pub struct WorldWithAurora {
     pub name: Option<String>,
     pub archetypes: Vec<ArchetypeSpec>,
     #[serde(default)]
-    pub embed: HashMap<String, EmbeddedBlob>,
+    pub embed: BTreeMap<String, EmbeddedBlob>,
     #[serde(skip)]
-    pub external_payloads: HashMap<String, Vec<u8>>,
-    pub resources: HashMap<String, serde_json::Value>,
+    pub external_payloads: BTreeMap<String, Vec<u8>>,
+    pub resources: BTreeMap<String, serde_json::Value>,
+    /// Stable names for snapshot entity ids, so hand-authored manifests can address
+    /// entities without hard-coding ids.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub names: BTreeMap<String, u32>,
+}
+
+/// Column written by the flecs exporter for entity names.
+const FLECS_NAME_COLUMN: &str = "NameID";
+
+/// Build a name table from `(id, name)` pairs; when two entities share a name, the
+/// lowest id wins.
+fn name_table(mut named: Vec<(u32, &str)>) -> BTreeMap<String, u32> {
+    named.sort_unstable();
+    let mut names = BTreeMap::new();
+    for (id, name) in named {
+        names.entry(name.to_string()).or_insert(id);
+    }
+    names
+}
+
+/// Collect `Name` components into a name table.
+fn collect_bevy_names(world: &World) -> BTreeMap<String, u32> {
+    let Some(mut query) = world.try_query::<(Entity, &Name)>() else {
+        return BTreeMap::new();
+    };
+    let named = query
+        .iter(world)
+        .map(|(e, name)| (e.index_u32(), name.as_str()))
+        .collect();
+    name_table(named)
 }
-fn serialize_arch_data(arch: &ArchetypeSnapshot, fmt: &ExportFormat) -> (Vec<u8>, &'static str) {
+
+/// Collect names stored in the snapshot's Bevy `Name` or flecs `NameID` columns.
+fn collect_snapshot_names(snapshot: &WorldArchSnapshot) -> BTreeMap<String, u32> {
+    let mut named: Vec<(u32, &str)> = Vec::new();
+    for arch in &snapshot.archetypes {
+        let Some(col) = arch
+            .get_column(NAME_COLUMN)
+            .or_else(|| arch.get_column(FLECS_NAME_COLUMN))
+        else {
+            continue;
+        };
+        named.extend(
+            arch.entities()
+                .iter()
+                .zip(col)
+                .filter_map(|(&id, v)| v.as_str().map(|name| (id, name))),
+        );
+    }
+    name_table(named)
+}
+
+fn serialize_arch_data(
+    arch: &ArchetypeSnapshot,
+    fmt: &ExportFormat,
+    csv_options: &CsvOptions,
+) -> Result<(Vec<u8>, &'static str), ArchiveError> {
     match fmt {
         ExportFormat::Csv => {
             let csv = columnar_from_snapshot(arch);
             let mut data = Vec::new();
-            csv.to_csv_writer(&mut data).unwrap();
-            (data, "csv")
+            csv.to_csv_writer_with(&mut data, csv_options)?;
+            Ok((data, "csv"))
         }
-        ExportFormat::Json => (serde_json::to_vec(arch).unwrap(), "json"),
-        ExportFormat::MsgPack => (rmp_serde::to_vec(arch).unwrap(), "msgpack"),
+        ExportFormat::Json => Ok((serde_json::to_vec(arch)?, "json")),
+        ExportFormat::MsgPack => Ok((
+            rmp_serde::to_vec(arch).map_err(ArchiveError::parse)?,
+            "msgpack",
+        )),
         ExportFormat::CsvMsgPack => {
             let csv = columnar_from_snapshot(arch);
-            (rmp_serde::to_vec(&csv).unwrap(), "csv.msgpack")
+            let bytes = rmp_serde::to_vec(&csv).map_err(ArchiveError::parse)?;
+            Ok((bytes, "csv.msgpack"))
         }
         #[cfg(feature = "arrow_rs")]
-        ExportFormat::Parquet => {
-            panic!("Parquet should utilize the binary pipeline, not ArchetypeSnapshot")
-        }
+        ExportFormat::Parquet | ExportFormat::ArrowIpc => Err(ArchiveError::parse(
+            "Arrow formats should utilize the binary pipeline, not ArchetypeSnapshot",
+        )),
     }
 }
 
+/// What [`WorldWithAurora::from_guided_indexed`] records in the manifest metadata.
+struct GuidedExport {
+    index: EntityLocationIndex,
+    /// Archetypes spilled out of the manifest.
+    spilled: Vec<String>,
+    /// Blob checksums by archetype source.
+    checksums: BTreeMap<String, String>,
+}
+
 impl WorldWithAurora {
+    /// # Panics
+    /// If an archetype fails to encode or a blob file cannot be written; see
+    /// [`try_from_guided`](Self::try_from_guided).
     pub fn from_guided(
         world: &World,
         registry: &SnapshotRegistry,
         guidance: &ExportGuidance,
     ) -> Self {
+        Self::try_from_guided(world, registry, guidance).unwrap()
+    }
+
+    /// [`from_guided`](Self::from_guided), returning encoding and file errors instead of
+    /// panicking.
+    pub fn try_from_guided(
+        world: &World,
+        registry: &SnapshotRegistry,
+        guidance: &ExportGuidance,
+    ) -> Result<Self, ArchiveError> {
+        Ok(Self::from_guided_indexed(world, registry, guidance)?.0)
+    }
+
+    /// [`try_from_guided`](Self::try_from_guided), also returning where each entity was
+    /// written, the archetypes spilled out of the manifest and the blob checksums.
+    fn from_guided_indexed(
+        world: &World,
+        registry: &SnapshotRegistry,
+        guidance: &ExportGuidance,
+    ) -> Result<(Self, GuidedExport), ArchiveError> {
         let mut archetypes = Vec::new();
-        let mut embed = HashMap::new();
-        let mut external_payloads: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut entity_ids: Vec<Vec<u32>> = Vec::new();
+        let mut embed = BTreeMap::new();
+        let mut spilled = Vec::new();
+        let mut checksums = BTreeMap::new();
+        let mut external_payloads: BTreeMap<String, Vec<u8>> = BTreeMap::new();
 
         let reg_comp_ids: HashMap<ComponentId, &str> = registry
             .type_registry
@@ -374,10 +618,10 @@ impl WorldWithAurora {
 
             let strat = guidance.per_arch.get(&i).unwrap_or(&guidance.default);
 
-            let (fmt, base_path, virtual_path) = match strat {
-                OutputStrategy::Embed(f) => (f, None, None),
-                OutputStrategy::File(f, p) => (f, Some(p), None),
-                OutputStrategy::Return(f, v) => (f, None, Some(v.clone())),
+            let (fmt, mut target) = match strat {
+                OutputStrategy::Embed(f) => (f, None),
+                OutputStrategy::File(f, p) => (f, Some(EmbedSpill::File(p.clone()))),
+                OutputStrategy::Return(f, v) => (f, Some(EmbedSpill::Return(v.clone()))),
             };
 
             let (bytes, ext) = match fmt {
@@ -388,9 +632,21 @@ impl WorldWithAurora {
                         registry,
                         arch,
                         &reg_comp_ids,
-                    )
-                    .unwrap();
-                    (table.to_parquet().unwrap(), "parquet")
+                    )?;
+                    let bytes = table
+                        .to_parquet_with(guidance.get_compression(i))
+                        .map_err(ArchiveError::parse)?;
+                    (bytes, "parquet")
+                }
+                #[cfg(feature = "arrow_rs")]
+                ExportFormat::ArrowIpc => {
+                    let table = crate::binary_archive::save_arrow_archetype_from_world(
+                        world,
+                        registry,
+                        arch,
+                        &reg_comp_ids,
+                    )?;
+                    (table.to_ipc().map_err(ArchiveError::parse)?, "arrow")
                 }
                 _ => {
                     let snap = crate::archetype_archive::save_single_archetype_snapshot(
@@ -399,45 +655,57 @@ impl WorldWithAurora {
                         registry,
                         &reg_comp_ids,
                     );
-                    serialize_arch_data(&snap, fmt)
+                    serialize_arch_data(&snap, fmt, &guidance.csv)?
                 }
             };
 
             let arch_name = format!("arch_{}", i);
-
-            let (source, blob_opt) = if let Some(base) = base_path {
-                let filename = format!("{}.{}", arch_name, ext);
-                let file_path = base.join(filename);
-                if let Some(parent) = file_path.parent() {
-                    std::fs::create_dir_all(parent).unwrap();
+            let checksum = blob_checksum(&bytes);
+
+            let mut blob_opt = None;
+            if target.is_none() {
+                let blob = embed_blob(&bytes, ext, fmt, guidance)?;
+                match guidance.max_embed_bytes {
+                    Some(max) if blob.data.len() as u64 > max => {
+                        let Some(spill) = &guidance.embed_spill else {
+                            return Err(ArchiveError::Io(io::Error::new(
+                                io::ErrorKind::FileTooLarge,
+                                format!(
+                                    "{arch_name} takes {} bytes embedded, over the limit of {max}",
+                                    blob.data.len()
+                                ),
+                            )));
+                        };
+                        target = Some(spill.clone());
+                        spilled.push(arch_name.clone());
+                    }
+                    _ => blob_opt = Some(blob),
                 }
-                std::fs::write(&file_path, &bytes).unwrap();
-                (Url(format!("file://{}", file_path.display())), None)
-            } else if let Some(v_path) = virtual_path {
-                let filename = format!("{}.{}", arch_name, ext);
-                let full_path = if v_path.ends_with('/') || v_path.is_empty() {
-                    format!("{}{}", v_path, filename)
-                } else {
-                    format!("{}/{}", v_path, filename)
-                };
-
-                external_payloads.insert(full_path.clone(), bytes);
-                (Url(format!("file://{}", full_path)), None)
-            } else {
-                let data_str = match fmt {
-                    ExportFormat::Csv | ExportFormat::Json => String::from_utf8(bytes).unwrap(),
-                    ExportFormat::MsgPack | ExportFormat::CsvMsgPack => {
-                        BASE64_STANDARD.encode(&bytes)
+            }
+            let source = match target {
+                Some(EmbedSpill::File(base)) => {
+                    let filename = format!("{}.{}", arch_name, ext);
+                    let file_path = base.join(filename);
+                    if let Some(parent) = file_path.parent() {
+                        std::fs::create_dir_all(parent)?;
                     }
-                    #[cfg(feature = "arrow_rs")]
-                    ExportFormat::Parquet => BASE64_STANDARD.encode(&bytes),
-                };
-                let blob = EmbeddedBlob {
-                    format: ext.to_string(),
-                    data: data_str,
-                };
-                (Url(format!("embed://{}", arch_name)), Some(blob))
+                    std::fs::write(&file_path, &bytes)?;
+                    Url(format!("file://{}", file_path.display()))
+                }
+                Some(EmbedSpill::Return(v_path)) => {
+                    let filename = format!("{}.{}", arch_name, ext);
+                    let full_path = if v_path.ends_with('/') || v_path.is_empty() {
+                        format!("{}{}", v_path, filename)
+                    } else {
+                        format!("{}/{}", v_path, filename)
+                    };
+
+                    external_payloads.insert(full_path.clone(), bytes);
+                    Url(format!("file://{}", full_path))
+                }
+                None => Url(format!("embed://{}", arch_name)),
             };
+            checksums.insert(source.0.clone(), checksum);
 
             let components: Vec<String> = arch
                 .components()
@@ -445,6 +713,7 @@ impl WorldWithAurora {
                 .filter_map(|id| reg_comp_ids.get(id).map(|s| s.to_string()))
                 .collect();
 
+            entity_ids.push(arch.entities().iter().map(|e| e.id().index_u32()).collect());
             archetypes.push(ArchetypeSpec {
                 name: Some(arch_name.clone()),
                 components,
@@ -457,21 +726,78 @@ impl WorldWithAurora {
             }
         }
 
-        Self {
+        let index = EntityLocationIndex::build(entity_ids.iter().map(Vec::as_slice));
+        let aurora = Self {
             version: "0.1".into(),
             archetypes,
             embed,
             external_payloads,
             name: None,
-            resources: HashMap::new(),
-        }
+            resources: BTreeMap::new(),
+            names: collect_bevy_names(world),
+        };
+        let export = GuidedExport {
+            index,
+            spilled,
+            checksums,
+        };
+        Ok((aurora, export))
+    }
+
+    /// Snapshot entity id registered under `name`.
+    pub fn entity_by_name(&self, name: &str) -> Option<u32> {
+        self.names.get(name).copied()
+    }
+
+    /// Name registered for the snapshot entity `id`, if any.
+    pub fn name_of(&self, id: u32) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|&(_, &v)| v == id)
+            .map(|(k, _)| k.as_str())
+    }
+
+    /// Register `name` for the snapshot entity `id`, returning the id it replaced.
+    pub fn set_name(&mut self, name: impl Into<String>, id: u32) -> Option<u32> {
+        self.names.insert(name.into(), id)
     }
 }
 
+/// The manifest text of an archetype blob: CSV and JSON as they are, binary formats
+/// base64-encoded, after zstd when the guidance asks for it.
+#[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+fn embed_blob(
+    bytes: &[u8],
+    ext: &str,
+    fmt: &ExportFormat,
+    guidance: &ExportGuidance,
+) -> Result<EmbeddedBlob, ArchiveError> {
+    let (compression, data) = match fmt {
+        ExportFormat::Csv | ExportFormat::Json => (
+            None,
+            String::from_utf8(bytes.to_vec()).map_err(ArchiveError::parse)?,
+        ),
+        #[cfg(feature = "zstd")]
+        _ if guidance.embed_zstd_level.is_some() => {
+            let level = guidance.embed_zstd_level.unwrap_or_default();
+            let packed = zstd::encode_all(bytes, level)?;
+            (Some("zstd".to_string()), BASE64_STANDARD.encode(packed))
+        }
+        ExportFormat::MsgPack | ExportFormat::CsvMsgPack => (None, BASE64_STANDARD.encode(bytes)),
+        #[cfg(feature = "arrow_rs")]
+        ExportFormat::Parquet | ExportFormat::ArrowIpc => (None, BASE64_STANDARD.encode(bytes)),
+    };
+    Ok(EmbeddedBlob {
+        format: ext.to_string(),
+        compression,
+        data,
+    })
+}
+
 impl From<&WorldArchSnapshot> for WorldWithAurora {
     fn from(world: &WorldArchSnapshot) -> Self {
         let mut archetypes = Vec::new();
-        let mut embed = HashMap::new();
+        let mut embed = BTreeMap::new();
 
         for (i, arch) in world.archetypes.iter().enumerate() {
             if arch.is_empty() {
@@ -480,10 +806,13 @@ impl From<&WorldArchSnapshot> for WorldWithAurora {
             let name = Some(format!("arch_{}", i));
             let source = Url(format!("embed://arch_{}", i));
 
-            let (bytes, _ext) = serialize_arch_data(arch, &ExportFormat::Csv);
+            let (bytes, _ext) =
+                serialize_arch_data(arch, &ExportFormat::Csv, &CsvOptions::default())
+                    .expect("CSV encoding into memory does not fail");
             let blob = EmbeddedBlob {
                 format: "csv".to_string(),
-                data: String::from_utf8(bytes).unwrap(),
+                compression: None,
+                data: String::from_utf8(bytes).expect("CSV output is UTF-8"),
             };
 
             embed.insert(format!("arch_{}", i), blob);
@@ -500,57 +829,100 @@ impl From<&WorldArchSnapshot> for WorldWithAurora {
             version: "0.1".into(),
             archetypes,
             embed,
-            external_payloads: HashMap::new(),
+            external_payloads: BTreeMap::new(),
             name: None,
-            resources: HashMap::new(),
+            resources: BTreeMap::new(),
+            names: collect_snapshot_names(world),
         }
     }
 }
 
-impl From<&AuroraWorldManifest> for WorldArchSnapshot {
-    fn from(manifest: &AuroraWorldManifest) -> Self {
-        (&manifest.world).into()
+impl TryFrom<&AuroraWorldManifest> for WorldArchSnapshot {
+    type Error = ArchiveError;
+
+    fn try_from(manifest: &AuroraWorldManifest) -> Result<Self, Self::Error> {
+        manifest_arch_snapshot(manifest, &LoadLimits::default())
     }
 }
 
-impl From<&WorldWithAurora> for WorldArchSnapshot {
-    #[allow(unreachable_patterns)]
-    fn from(world: &WorldWithAurora) -> Self {
-        let mut archetypes = Vec::new();
-        let mut all_entities: BTreeSet<u32> = BTreeSet::new();
-
-        for arch in &world.archetypes {
-            let loc = AuroraLocation::from(arch.source.0.as_str());
-            let blob = load_blob_from_location(&loc, &world.embed).unwrap();
-            let parsed = parse_blob(&blob).unwrap();
-
-            let snapshot = match parsed {
-                AuroraInternalFormat::ColumnarCsv(csv) => {
-                    let mut snap: ArchetypeSnapshot = (&csv).into();
-                    snap.storage_types =
-                        arch.storage
-                            .clone()
-                            .unwrap_or(vec![StorageTypeFlag::Table; snap.component_types.len()]);
-                    snap
-                }
-                AuroraInternalFormat::ArchetypeSnapshot(data) => data,
-                _ => panic!("not supported"),
-            };
+/// Decodes every archetype blob within the default [`LoadLimits`], reading `file://` blobs
+/// through a [jailed](FsBlobLoader::new) loader rooted at the current directory; fails on
+/// blobs that cannot be read or parsed, and on Arrow blobs, which have no
+/// [`ArchetypeSnapshot`] form. Blob checksums live in the manifest metadata, so convert
+/// the [`AuroraWorldManifest`] instead to have them verified.
+impl TryFrom<&WorldWithAurora> for WorldArchSnapshot {
+    type Error = ArchiveError;
+
+    fn try_from(world: &WorldWithAurora) -> Result<Self, Self::Error> {
+        let limits = LoadLimits::default();
+        let mut loader = FsBlobLoader::new(".");
+        world_arch_snapshot(world, |arch| read_blob(world, arch, &mut loader, &limits), &limits)
+    }
+}
 
-            all_entities.extend(snapshot.entities.clone());
-            archetypes.push(snapshot);
-        }
+/// [`WorldArchSnapshot`] of `manifest` with its component renames applied. Blobs are
+/// verified against the checksums recorded in the manifest metadata.
+fn manifest_arch_snapshot(
+    manifest: &AuroraWorldManifest,
+    limits: &LoadLimits,
+) -> Result<WorldArchSnapshot, ArchiveError> {
+    let mut loader = FsBlobLoader::new(".");
+    let read = |arch: &ArchetypeSpec| resolve_blob(manifest, arch, &mut loader, limits);
+    let mut snapshot = world_arch_snapshot(&manifest.world, read, limits)?;
+    let renames = manifest.component_renames();
+    for arch in &mut snapshot.archetypes {
+        rename_components(arch.component_types.iter_mut(), &renames);
+    }
+    Ok(snapshot)
+}
 
-        WorldArchSnapshot {
-            entities: all_entities.into_iter().collect(),
-            archetypes,
-        }
+/// Decode every archetype blob of `world`, as returned by `read`, into one
+/// [`WorldArchSnapshot`].
+#[allow(unreachable_patterns)]
+fn world_arch_snapshot(
+    world: &WorldWithAurora,
+    mut read: impl FnMut(&ArchetypeSpec) -> Result<LoadedBlob, ArchiveError>,
+    limits: &LoadLimits,
+) -> Result<WorldArchSnapshot, ArchiveError> {
+    limits.check_archetypes(world.archetypes.len())?;
+    let mut archetypes = Vec::new();
+    let mut all_entities: BTreeSet<u32> = BTreeSet::new();
+
+    for arch in &world.archetypes {
+        let blob = read(arch)?;
+        let parsed = parse_blob(&blob, &CsvOptions::default())?;
+
+        let snapshot = match parsed {
+            AuroraInternalFormat::ColumnarCsv(csv) => {
+                let mut snap: ArchetypeSnapshot = (&csv).into();
+                snap.storage_types =
+                    arch.storage
+                        .clone()
+                        .unwrap_or(vec![StorageTypeFlag::Table; snap.component_types.len()]);
+                snap
+            }
+            AuroraInternalFormat::ArchetypeSnapshot(data) => data,
+            _ => {
+                return Err(ArchiveError::Parse(format!(
+                    "{}: Arrow blobs cannot be converted to an ArchetypeSnapshot",
+                    arch.source.0
+                )));
+            }
+        };
+
+        all_entities.extend(snapshot.entities.clone());
+        archetypes.push(snapshot);
     }
+
+    Ok(WorldArchSnapshot {
+        entities: all_entities.into_iter().collect(),
+        archetypes,
+    })
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct AuroraWorldManifest {
-    pub metadata: Option<HashMap<String, Value>>,
+    pub metadata: Option<BTreeMap<String, Value>>,
     pub world: WorldWithAurora,
 }
 #[derive(Default)]
@@ -565,6 +937,15 @@ impl ExportGuidance {
         Self {
             default: OutputStrategy::Embed(format),
             per_arch: HashMap::new(),
+            #[cfg(feature = "arrow_rs")]
+            default_compression: ParquetCompression::None,
+            #[cfg(feature = "arrow_rs")]
+            per_arch_compression: HashMap::new(),
+            #[cfg(feature = "zstd")]
+            embed_zstd_level: None,
+            csv: CsvOptions::default(),
+            max_embed_bytes: None,
+            embed_spill: None,
         }
     }
 
@@ -573,6 +954,15 @@ impl ExportGuidance {
         Self {
             default: OutputStrategy::File(format.clone(), base),
             per_arch: HashMap::new(),
+            #[cfg(feature = "arrow_rs")]
+            default_compression: ParquetCompression::None,
+            #[cfg(feature = "arrow_rs")]
+            per_arch_compression: HashMap::new(),
+            #[cfg(feature = "zstd")]
+            embed_zstd_level: None,
+            csv: CsvOptions::default(),
+            max_embed_bytes: None,
+            embed_spill: None,
         }
     }
 
@@ -595,12 +985,72 @@ impl ExportGuidance {
         self.set_strategy_for(index, OutputStrategy::File(fmt, path.into()))
     }
 
+    /// Copy of the guidance with every `File` strategy turned into a `Return` of the same
+    /// path, so the blob becomes a container entry instead of a local file.
+    pub fn files_as_entries(&self) -> Self {
+        let as_entry = |strategy: &OutputStrategy| match strategy {
+            OutputStrategy::File(fmt, path) => OutputStrategy::Return(
+                fmt.clone(),
+                path.to_string_lossy().replace('\\', "/"),
+            ),
+            other => other.clone(),
+        };
+        let mut guidance = self.clone();
+        guidance.default = as_entry(&self.default);
+        for strategy in guidance.per_arch.values_mut() {
+            *strategy = as_entry(strategy);
+        }
+        if let Some(EmbedSpill::File(path)) = &self.embed_spill {
+            let path = path.to_string_lossy().replace('\\', "/");
+            guidance.embed_spill = Some(EmbedSpill::Return(path));
+        }
+        guidance
+    }
+
+    /// Write `Embed` blobs taking more than `max_bytes` of manifest text to `spill`
+    /// instead. The manifest lists the spilled archetypes in `metadata` under
+    /// [`EMBED_SPILLED_KEY`].
+    pub fn spill_embeds_over(&mut self, max_bytes: u64, spill: EmbedSpill) -> &mut Self {
+        self.max_embed_bytes = Some(max_bytes);
+        self.embed_spill = Some(spill);
+        self
+    }
+
     pub fn get_strategy(&self, index: usize) -> OutputStrategy {
         self.per_arch
             .get(&index)
             .cloned()
             .unwrap_or_else(|| self.default.clone())
     }
+
+    /// Compress every Parquet archetype with `compression` unless overridden.
+    #[cfg(feature = "arrow_rs")]
+    pub fn compress_all(&mut self, compression: ParquetCompression) -> &mut Self {
+        self.default_compression = compression;
+        self
+    }
+
+    /// Compress the Parquet blob of archetype `index` with `compression`.
+    #[cfg(feature = "arrow_rs")]
+    pub fn compress_as(&mut self, index: usize, compression: ParquetCompression) -> &mut Self {
+        self.per_arch_compression.insert(index, compression);
+        self
+    }
+
+    /// Zstd-compress embedded MsgPack/Parquet blobs at `level` before base64 encoding.
+    #[cfg(feature = "zstd")]
+    pub fn zstd_embedded(&mut self, level: i32) -> &mut Self {
+        self.embed_zstd_level = Some(level);
+        self
+    }
+
+    #[cfg(feature = "arrow_rs")]
+    pub fn get_compression(&self, index: usize) -> ParquetCompression {
+        self.per_arch_compression
+            .get(&index)
+            .copied()
+            .unwrap_or(self.default_compression)
+    }
 }
 
 impl AuroraWorldManifest {
@@ -611,8 +1061,12 @@ impl AuroraWorldManifest {
     /// - `format`: Optional format override (`Json` or `Toml`). If `None`, TOML is used.
     ///
     /// # Returns
-    /// Returns `Ok(())` on success, or an error message string.
-    pub fn to_file(&self, path: &str, format: Option<ManifestOutputFormat>) -> Result<(), String> {
+    /// Returns `Ok(())` on success, or the [`ArchiveError`] that stopped the write.
+    pub fn to_file(
+        &self,
+        path: &str,
+        format: Option<ManifestOutputFormat>,
+    ) -> Result<(), ArchiveError> {
         write_manifest_to_file(self, path, format.unwrap_or_default())
     }
 
@@ -623,10 +1077,166 @@ impl AuroraWorldManifest {
     /// - `format`: Optional format hint. If not provided, guessed from file extension.
     ///
     /// # Returns
-    /// The loaded `AuroraWorldManifest` structure.
-    pub fn from_file(path: &str, format: Option<ManifestOutputFormat>) -> Result<Self, String> {
+    /// The loaded `AuroraWorldManifest` structure. Signatures are not checked; use
+    /// `from_file_verified` (`signing`) to authenticate a manifest.
+    pub fn from_file(
+        path: &str,
+        format: Option<ManifestOutputFormat>,
+    ) -> Result<Self, ArchiveError> {
         read_manifest_from_file(path, format)
     }
+
+    /// CSV options recorded in `metadata` by the save, defaults otherwise.
+    pub fn csv_options(&self) -> CsvOptions {
+        self.metadata
+            .as_ref()
+            .and_then(|m| m.get(CSV_NULL_TOKEN_KEY))
+            .and_then(Value::as_str)
+            .map(CsvOptions::with_null_token)
+            .unwrap_or_default()
+    }
+
+    /// Component renames (`old name → new name`) stored in `metadata` under
+    /// [`COMPONENT_RENAME_KEY`].
+    ///
+    /// Loaders apply them to every archetype before looking up factories, so a save that
+    /// predates a component rename can be fixed with a data-only patch to its manifest.
+    /// Entries that are not strings are ignored.
+    pub fn component_renames(&self) -> BTreeMap<String, String> {
+        let Some(Value::Object(table)) = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(COMPONENT_RENAME_KEY))
+        else {
+            return BTreeMap::new();
+        };
+        table
+            .iter()
+            .filter_map(|(old, new)| Some((old.clone(), new.as_str()?.to_string())))
+            .collect()
+    }
+
+    /// Schema version each component was saved with, from `metadata` under
+    /// [`COMPONENT_VERSIONS_KEY`], keyed by the name it loads as after
+    /// [renames](Self::component_renames). Components missing from the table are at
+    /// version `0`.
+    pub fn component_versions(&self) -> BTreeMap<String, u32> {
+        let Some(Value::Object(table)) = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(COMPONENT_VERSIONS_KEY))
+        else {
+            return BTreeMap::new();
+        };
+        let renames = self.component_renames();
+        table
+            .iter()
+            .filter_map(|(name, version)| {
+                let name = renames.get(name).unwrap_or(name).clone();
+                Some((name, u32::try_from(version.as_u64()?).ok()?))
+            })
+            .collect()
+    }
+
+    /// Schema hash each component was saved with, from `metadata` under
+    /// [`SCHEMA_HASHES_KEY`], keyed by the name it loads as after
+    /// [renames](Self::component_renames).
+    pub fn schema_hashes(&self) -> BTreeMap<String, String> {
+        let Some(Value::Object(table)) = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(SCHEMA_HASHES_KEY))
+        else {
+            return BTreeMap::new();
+        };
+        let renames = self.component_renames();
+        table
+            .iter()
+            .filter_map(|(name, hash)| {
+                let name = renames.get(name).unwrap_or(name).clone();
+                Some((name, hash.as_str()?.to_string()))
+            })
+            .collect()
+    }
+
+    /// Checksum the blob of the archetype with `source` was saved with, from `metadata`
+    /// under [`BLOB_CHECKSUMS_KEY`]. Loaders reject blobs that do not match it.
+    pub fn blob_checksum(&self, source: &str) -> Option<&str> {
+        self.metadata
+            .as_ref()?
+            .get(BLOB_CHECKSUMS_KEY)?
+            .get(source)?
+            .as_str()
+    }
+
+    /// Archetypes the export asked to embed but wrote outside the manifest as they were
+    /// too large, from `metadata` under [`EMBED_SPILLED_KEY`].
+    pub fn spilled_embeds(&self) -> Vec<String> {
+        let Some(Value::Array(names)) = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(EMBED_SPILLED_KEY))
+        else {
+            return Vec::new();
+        };
+        names
+            .iter()
+            .filter_map(|name| Some(name.as_str()?.to_string()))
+            .collect()
+    }
+
+    /// The [registry schema](crate::registry_schema) the manifest was saved with, from
+    /// `metadata` under [`REGISTRY_SCHEMA_KEY`], with [renames](Self::component_renames)
+    /// applied. `None` for manifests saved before it was recorded.
+    pub fn registry_schema(&self) -> Option<RegistrySchema> {
+        let value = self.metadata.as_ref()?.get(REGISTRY_SCHEMA_KEY)?;
+        let schema: RegistrySchema = serde_json::from_value(value.clone()).ok()?;
+        Some(schema.renamed(&self.component_renames()))
+    }
+
+    /// Record that the component saved as `old` should load as `new`.
+    pub fn add_component_rename(&mut self, old: impl Into<String>, new: impl Into<String>) {
+        let metadata = self.metadata.get_or_insert_default();
+        let table = metadata
+            .entry(COMPONENT_RENAME_KEY.to_string())
+            .or_insert_with(|| Value::Object(Default::default()));
+        if !table.is_object() {
+            *table = Value::Object(Default::default());
+        }
+        if let Value::Object(table) = table {
+            table.insert(old.into(), Value::String(new.into()));
+        }
+    }
+
+    /// Entity location index stored in `metadata`, if the manifest has a valid one.
+    pub fn entity_index(&self) -> Option<EntityLocationIndex> {
+        let value = self.metadata.as_ref()?.get(ENTITY_INDEX_KEY)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    /// Snapshot entity id registered under `name` in the manifest's name table.
+    pub fn entity_by_name(&self, name: &str) -> Option<u32> {
+        self.world.entity_by_name(name)
+    }
+
+    /// Build an id remapper from `(name, target)` pairs for use with
+    /// [`Archive::apply_with_remap`].
+    ///
+    /// Names missing from the manifest are reported as an error instead of being
+    /// silently dropped, so a stale prefab override fails loudly.
+    pub fn remap_by_name<'a>(
+        &self,
+        targets: impl IntoIterator<Item = (&'a str, Entity)>,
+    ) -> Result<HashMap<u32, Entity>, String> {
+        targets
+            .into_iter()
+            .map(|(name, target)| {
+                self.entity_by_name(name)
+                    .map(|id| (id, target))
+                    .ok_or_else(|| format!("No entity named '{}' in manifest.", name))
+            })
+            .collect()
+    }
 }
 
 /// Save a snapshot of the ECS `World` into an `AuroraWorldManifest`, which includes
@@ -643,180 +1253,908 @@ impl AuroraWorldManifest {
 pub fn save_world_manifest(
     world: &World,
     registry: &SnapshotRegistry,
-) -> Result<AuroraWorldManifest, String> {
+) -> Result<AuroraWorldManifest, ArchiveError> {
     let snapshot = save_world_arch_snapshot(world, registry);
     let mut world_with_aurora = WorldWithAurora::from(&snapshot);
     world_with_aurora.resources = save_world_resource(world, registry);
+    world_with_aurora.names.extend(collect_bevy_names(world));
+    // `WorldWithAurora::from` skips empty archetypes; index the same ones.
+    let index = EntityLocationIndex::build(
+        snapshot
+            .archetypes
+            .iter()
+            .filter(|a| !a.is_empty())
+            .map(|a| a.entities.as_slice()),
+    );
+    let mut metadata = index_metadata(&index);
+    insert_blob_checksums(&mut metadata, embedded_checksums(&world_with_aurora, &registry.limits)?);
+    insert_component_versions(&mut metadata, registry);
+    insert_schema_hashes(&mut metadata, registry);
+    if let Ok(schema) = serde_json::to_value(RegistrySchema::of(registry)) {
+        metadata.insert(REGISTRY_SCHEMA_KEY.to_string(), schema);
+    }
     Ok(AuroraWorldManifest {
-        metadata: None,
+        metadata: Some(metadata),
         world: world_with_aurora,
     })
 }
 
-enum LoadedArchetype {
-    Legacy(ArchetypeSnapshot),
-    #[cfg(feature = "arrow_rs")]
-    Arrow(ComponentTable),
+/// Like [`save_world_manifest`], with only the components and resources `profile` allows.
+pub fn save_world_manifest_with_profile(
+    world: &World,
+    registry: &SnapshotRegistry,
+    profile: &SaveProfile,
+) -> Result<AuroraWorldManifest, ArchiveError> {
+    save_world_manifest(world, &profile.apply(registry))
 }
 
-/// Trait for abstracting blob loading (Filesystem, Zip, Memory, etc.)
-pub trait BlobLoader {
-    fn load_blob(&mut self, path: &str) -> Result<Vec<u8>, String>;
+fn index_metadata(index: &EntityLocationIndex) -> BTreeMap<String, Value> {
+    BTreeMap::from([(
+        ENTITY_INDEX_KEY.to_string(),
+        serde_json::to_value(index).unwrap_or_default(),
+    )])
 }
 
-/// Default filesystem loader
-pub struct FsBlobLoader {
-    pub base_dir: PathBuf,
+/// Metadata key of the component rename table, see
+/// [`AuroraWorldManifest::component_renames`].
+pub const COMPONENT_RENAME_KEY: &str = "rename";
+
+/// Metadata key of the per-component schema versions, see
+/// [`AuroraWorldManifest::component_versions`].
+pub const COMPONENT_VERSIONS_KEY: &str = "component_versions";
+
+/// Record the schema versions of `registry` in manifest metadata. Components at version
+/// `0` are left out, so manifests of registries without migrations are unchanged.
+fn insert_component_versions(metadata: &mut BTreeMap<String, Value>, registry: &SnapshotRegistry) {
+    let versions: serde_json::Map<String, Value> = registry
+        .entries
+        .iter()
+        .filter(|(_, factory)| factory.version > 0)
+        .map(|(name, factory)| (name.to_string(), Value::from(factory.version)))
+        .collect();
+    if !versions.is_empty() {
+        metadata.insert(COMPONENT_VERSIONS_KEY.to_string(), Value::Object(versions));
+    }
 }
-impl BlobLoader for FsBlobLoader {
-    fn load_blob(&mut self, path: &str) -> Result<Vec<u8>, String> {
-        let relative_path = Path::new(path);
-        let full_path = if relative_path.is_absolute() {
-            relative_path.to_path_buf()
-        } else {
-            self.base_dir.join(relative_path)
-        };
-        fs::read(&full_path).map_err(|e| format!("Failed to read {}: {}", full_path.display(), e))
+
+/// Metadata key of the archetypes written outside the manifest because they exceeded
+/// [`ExportGuidance::max_embed_bytes`], see [`AuroraWorldManifest::spilled_embeds`].
+pub const EMBED_SPILLED_KEY: &str = "embed_spilled";
+
+/// Record `checksums`, by archetype source, in manifest metadata under
+/// [`BLOB_CHECKSUMS_KEY`].
+fn insert_blob_checksums(metadata: &mut BTreeMap<String, Value>, checksums: BTreeMap<String, String>) {
+    if !checksums.is_empty() {
+        let table = checksums.into_iter().map(|(k, v)| (k, Value::String(v))).collect();
+        metadata.insert(BLOB_CHECKSUMS_KEY.to_string(), Value::Object(table));
     }
 }
 
-#[cfg(feature = "arrow_rs")]
-pub struct ZipBlobLoader<R: std::io::Read + std::io::Seek> {
-    pub archive: zip::ZipArchive<R>,
+/// Checksums of the decoded embedded blobs of `world`, by archetype source.
+fn embedded_checksums(
+    world: &WorldWithAurora,
+    limits: &LoadLimits,
+) -> Result<BTreeMap<String, String>, ArchiveError> {
+    let mut checksums = BTreeMap::new();
+    for arch in &world.archetypes {
+        if let AuroraLocation::Embed(name) = AuroraLocation::from(arch.source.0.as_str())
+            && let Some(blob) = world.embed.get(&name)
+        {
+            let bytes = blob.decode(limits.max_blob_bytes)?;
+            checksums.insert(arch.source.0.clone(), blob_checksum(&bytes));
+        }
+    }
+    Ok(checksums)
 }
 
-#[cfg(feature = "arrow_rs")]
-impl<R: std::io::Read + std::io::Seek> BlobLoader for ZipBlobLoader<R> {
-    fn load_blob(&mut self, path: &str) -> Result<Vec<u8>, String> {
-        use std::io::Read;
-        let mut file = self.archive.by_name(path).map_err(|e| e.to_string())?;
-        let mut buf = Vec::new();
-        file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
-        Ok(buf)
+/// Metadata key of the per-component schema hashes, see
+/// [`AuroraWorldManifest::schema_hashes`].
+pub const SCHEMA_HASHES_KEY: &str = "schema_hashes";
+
+/// Record the [schema hashes](SnapshotRegistry::schema_hash) of `registry` in manifest
+/// metadata.
+fn insert_schema_hashes(metadata: &mut BTreeMap<String, Value>, registry: &SnapshotRegistry) {
+    let hashes: serde_json::Map<String, Value> = registry
+        .entries
+        .keys()
+        .filter_map(|name| Some((name.to_string(), Value::String(registry.schema_hash(name)?))))
+        .collect();
+    if !hashes.is_empty() {
+        metadata.insert(SCHEMA_HASHES_KEY.to_string(), Value::Object(hashes));
     }
 }
 
-/// Load an ECS world from a manifest structure using a specific blob loader.
-pub fn load_world_manifest_with_loader<L: BlobLoader>(
-    world: &mut World,
+/// Fail before anything is spawned if the components of `names` do not match the saved
+/// [registry schema](crate::registry_schema), or one was saved with another schema hash
+/// than its registered type. Components saved at an older version are skipped, as their
+/// migration is expected to bridge the change.
+fn check_schema_hashes<'a>(
     manifest: &AuroraWorldManifest,
     registry: &SnapshotRegistry,
-    loader: &mut L,
-) -> Result<(), String> {
-    let resource = &manifest.world.resources;
-    load_world_resource(resource, world, registry);
-
-    // Parse all blobs first
-    let mut loaded_archetypes = Vec::new();
-    for arch in &manifest.world.archetypes {
-        let loc = AuroraLocation::from(arch.source.0.as_str());
-
-        // Resolve blob
-        let blob = match loc {
-            AuroraLocation::File(path) => {
-                let bytes = loader.load_blob(&path)?;
-                let format = AuroraFormat::from_path(&path);
-                LoadedBlob { format, bytes }
-            }
-            AuroraLocation::Embed(name) => {
-                let blob =
-                    manifest.world.embed.get(&name).ok_or_else(|| {
-                        format!("Embedded blob '{}' not found in manifest.", name)
-                    })?;
-                let format = AuroraFormat::from_str(&blob.format);
-                let bytes = match format {
-                    AuroraFormat::MsgPack | AuroraFormat::CsvMsgPack => BASE64_STANDARD
-                        .decode(&blob.data)
-                        .map_err(|e| format!("Base64 decode failed: {}", e))?,
-                    #[cfg(feature = "arrow_rs")]
-                    AuroraFormat::Parquet => BASE64_STANDARD
-                        .decode(&blob.data)
-                        .map_err(|e| format!("Base64 decode failed: {}", e))?,
-                    _ => blob.data.as_bytes().to_vec(),
-                };
-                LoadedBlob { format, bytes }
-            }
-            AuroraLocation::Unknown(s) => return Err(format!("Unknown location: {}", s)),
-        };
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<(), LimitError> {
+    let names: BTreeSet<&str> = names.into_iter().collect();
+    if let Some(schema) = manifest.registry_schema() {
+        schema.check(registry, names.iter().copied())?;
+    }
+    let versions = manifest.component_versions();
+    let hashes = manifest.schema_hashes();
+    registry.check_schema_hashes(
+        hashes
+            .iter()
+            .filter(|(name, _)| {
+                names.contains(name.as_str())
+                    && versions.get(*name).copied().unwrap_or(0) == registry.component_version(name)
+            })
+            .map(|(name, hash)| (name.as_str(), hash.as_str())),
+    )
+}
 
-        let parsed = parse_blob(&blob).unwrap();
+/// Run the registry's migrations over the columns of `arch` saved at older versions.
+fn migrate_archetype(
+    arch: &mut ArchetypeSnapshot,
+    versions: &BTreeMap<String, u32>,
+    registry: &SnapshotRegistry,
+) -> Result<(), ArchiveError> {
+    for (name, column) in arch.component_types.iter().zip(arch.columns.iter_mut()) {
+        let from = versions.get(name).copied().unwrap_or(0);
+        if from == registry.component_version(name) {
+            continue;
+        }
+        for value in column.iter_mut() {
+            *value = registry
+                .migrate_value(name, from, std::mem::take(value))
+                .map_err(ArchiveError::Schema)?;
+        }
+    }
+    Ok(())
+}
 
-        match parsed {
-            AuroraInternalFormat::ColumnarCsv(csv) => {
-                let mut snap: ArchetypeSnapshot = (&csv).into();
-                snap.storage_types = arch
-                    .storage
-                    .clone()
-                    .unwrap_or(vec![StorageTypeFlag::Table; snap.component_types.len()]);
-                loaded_archetypes.push(LoadedArchetype::Legacy(snap));
-            }
-            AuroraInternalFormat::ArchetypeSnapshot(data) => {
-                loaded_archetypes.push(LoadedArchetype::Legacy(data));
-            }
-            #[cfg(feature = "arrow_rs")]
-            AuroraInternalFormat::ArrowComponentTable(table) => {
-                loaded_archetypes.push(LoadedArchetype::Arrow(table));
-            }
+fn rename_components<'a>(
+    names: impl Iterator<Item = &'a mut String>,
+    renames: &BTreeMap<String, String>,
+) {
+    for name in names {
+        if let Some(new) = renames.get(name.as_str()) {
+            *name = new.clone();
         }
     }
+}
 
-    // Reserve entities
-    let mut max_entity = 0;
-    for arch in &loaded_archetypes {
-        let max = match arch {
-            LoadedArchetype::Legacy(s) => s.entities.iter().max().copied().unwrap_or(0),
-            #[cfg(feature = "arrow_rs")]
-            LoadedArchetype::Arrow(t) => t.entities.iter().map(|e| e.id).max().unwrap_or(0),
-        };
-        if max > max_entity {
-            max_entity = max;
+/// Where a [`LoadedArchetype`] came from: the index of its spec in the manifest, for error
+/// context, and the size and read time of its blob, for the load stats.
+#[derive(Clone, Copy)]
+struct ArchetypeOrigin {
+    idx: usize,
+    blob_bytes: u64,
+    read: Duration,
+}
+
+impl ArchetypeOrigin {
+    /// Attribute the blob to the stats `load` recorded for this archetype.
+    fn annotate(&self, load: &mut LoadReport) {
+        if let Some(stats) = load.archetype_stats.last_mut() {
+            stats.blob_bytes = Some(self.blob_bytes);
+            stats.decode += self.read;
         }
     }
-    reserve_entity_slots(world, max_entity + 1);
-    world.flush();
+}
 
-    // Load data
+/// Parsed archetype plus where it came from.
+enum LoadedArchetype {
+    Legacy(ArchetypeSnapshot, ArchetypeOrigin),
     #[cfg(feature = "arrow_rs")]
-    let mut buffer = crate::bevy_cmdbuffer::HarvardCommandBuffer::new();
+    Arrow(ComponentTable, ArchetypeOrigin),
+}
 
-    for arch in loaded_archetypes {
-        match arch {
-            LoadedArchetype::Legacy(snap) => {
-                let temp_snap = WorldArchSnapshot {
-                    entities: vec![], // Not used by defragment loader for reservation if we did it already
-                    archetypes: vec![snap],
-                };
-                load_world_arch_snapshot(world, &temp_snap, registry);
-            }
+impl LoadedArchetype {
+    fn entity_ids(&self) -> Box<dyn Iterator<Item = u32> + '_> {
+        match self {
+            LoadedArchetype::Legacy(s, _) => Box::new(s.entities.iter().copied()),
             #[cfg(feature = "arrow_rs")]
-            LoadedArchetype::Arrow(table) => {
-                crate::binary_archive::load_arrow_archetype_to_world(
+            LoadedArchetype::Arrow(t, _) => Box::new(t.entities.iter().map(|e| e.id)),
+        }
+    }
+
+    fn component_names(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        match self {
+            LoadedArchetype::Legacy(s, _) => Box::new(s.component_types.iter().map(String::as_str)),
+            #[cfg(feature = "arrow_rs")]
+            LoadedArchetype::Arrow(t, _) => Box::new(t.columns.keys().map(String::as_str)),
+        }
+    }
+
+    fn rename_components(&mut self, renames: &BTreeMap<String, String>) {
+        if renames.is_empty() {
+            return;
+        }
+        match self {
+            LoadedArchetype::Legacy(s, _) => {
+                rename_components(s.component_types.iter_mut(), renames)
+            }
+            #[cfg(feature = "arrow_rs")]
+            LoadedArchetype::Arrow(t, _) => {
+                t.columns = std::mem::take(&mut t.columns)
+                    .into_iter()
+                    .map(|(name, column)| (renames.get(&name).cloned().unwrap_or(name), column))
+                    .collect();
+            }
+        }
+    }
+
+    /// Keep only row `row`.
+    fn select_row(self, row: usize) -> Result<Self, ArchiveError> {
+        let out_of_range = || ArchiveError::schema("row out of range");
+        match self {
+            LoadedArchetype::Legacy(s, origin) => {
+                let entity = *s.entities.get(row).ok_or_else(out_of_range)?;
+                let columns = s
+                    .columns
+                    .into_iter()
+                    .map(|col| col.into_iter().nth(row).map(|v| vec![v]))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(out_of_range)?;
+                Ok(LoadedArchetype::Legacy(
+                    ArchetypeSnapshot {
+                        component_types: s.component_types,
+                        storage_types: s.storage_types,
+                        columns,
+                        entities: vec![entity],
+                    },
+                    origin,
+                ))
+            }
+            #[cfg(feature = "arrow_rs")]
+            LoadedArchetype::Arrow(t, origin) => t
+                .slice(row, 1)
+                .map(|t| LoadedArchetype::Arrow(t, origin))
+                .map_err(ArchiveError::schema),
+        }
+    }
+}
+
+/// Trait for abstracting blob loading (Filesystem, Zip, Memory, etc.)
+pub trait BlobLoader {
+    fn load_blob(&mut self, path: &str) -> Result<Vec<u8>, ArchiveError>;
+}
+
+/// Default filesystem loader
+///
+/// With `jail` enabled, blob paths must stay inside `base_dir`: absolute paths and `..`
+/// components are rejected, and the resolved path is canonicalized so symlinks cannot
+/// escape either. Use a jailed loader for any manifest that came from user content.
+pub struct FsBlobLoader {
+    pub base_dir: PathBuf,
+    pub jail: bool,
+}
+
+impl FsBlobLoader {
+    /// Jailed loader rooted at `base_dir`.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            jail: true,
+        }
+    }
+
+    /// Loader that follows any path, including absolute ones. Only for trusted manifests.
+    pub fn unjailed(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            jail: false,
+        }
+    }
+
+    /// Check that `path` is a plain relative path when jailed, and join it to `base_dir`.
+    fn join(&self, path: &str) -> Result<PathBuf, ContainerError> {
+        let relative_path = Path::new(path);
+        if !self.jail {
+            return Ok(if relative_path.is_absolute() {
+                relative_path.to_path_buf()
+            } else {
+                self.base_dir.join(relative_path)
+            });
+        }
+
+        use std::path::Component;
+        if relative_path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(ContainerError::Escape(path.to_string()));
+        }
+        Ok(self.base_dir.join(relative_path))
+    }
+
+    /// Fail if the existing path `full` resolves (through symlinks) outside `base_dir`.
+    fn check_inside(&self, path: &str, full: &Path) -> Result<(), ContainerError> {
+        if !self.jail {
+            return Ok(());
+        }
+        let base = self.base_dir.canonicalize()?;
+        if !full.canonicalize()?.starts_with(&base) {
+            return Err(ContainerError::Escape(path.to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl SnapshotContainer for FsBlobLoader {
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError> {
+        fn walk(dir: &Path, prefix: &str, out: &mut Vec<String>) -> std::io::Result<()> {
+            for entry in fs::read_dir(dir)? {
+                let entry = entry?;
+                let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+                if entry.file_type()?.is_dir() {
+                    walk(&entry.path(), &format!("{}/", name), out)?;
+                } else {
+                    out.push(name);
+                }
+            }
+            Ok(())
+        }
+        let mut out = Vec::new();
+        walk(&self.base_dir, "", &mut out)?;
+        out.sort();
+        Ok(out)
+    }
+
+    fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, ContainerError> {
+        let full_path = self.join(name)?;
+        self.check_inside(name, &full_path)?;
+        Ok(fs::read(&full_path)?)
+    }
+
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> Result<(), ContainerError> {
+        let full_path = self.join(name)?;
+        if let Some(parent) = full_path.parent() {
+            if self.jail {
+                // Check the deepest existing directory first, so a symlinked one cannot make
+                // `create_dir_all` build directories outside `base_dir`.
+                fs::create_dir_all(&self.base_dir)?;
+                let existing = parent.ancestors().find(|p| p.exists()).unwrap_or(&self.base_dir);
+                self.check_inside(name, existing)?;
+            }
+            fs::create_dir_all(parent)?;
+            self.check_inside(name, parent)?;
+        }
+        // An existing entry may itself be a symlink to a file outside `base_dir`.
+        if fs::symlink_metadata(&full_path).is_ok() {
+            self.check_inside(name, &full_path)?;
+        }
+        Ok(fs::write(&full_path, data)?)
+    }
+}
+
+/// Every [`SnapshotContainer`] can serve manifest blobs.
+impl<C: SnapshotContainer> BlobLoader for C {
+    fn load_blob(&mut self, path: &str) -> Result<Vec<u8>, ArchiveError> {
+        Ok(self.read_entry(path)?)
+    }
+}
+
+/// Loads blobs from entries of a zip archive (`zip` feature).
+#[cfg(feature = "zip")]
+pub struct ZipBlobLoader<R: std::io::Read + std::io::Seek> {
+    pub archive: zip::ZipArchive<R>,
+}
+
+#[cfg(feature = "zip")]
+impl<R: std::io::Read + std::io::Seek> SnapshotContainer for ZipBlobLoader<R> {
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError> {
+        Ok(self.archive.file_names().map(str::to_string).collect())
+    }
+
+    fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, ContainerError> {
+        self.read_entry_limited(name, u64::MAX)
+    }
+
+    fn write_entry(&mut self, _name: &str, _data: &[u8]) -> Result<(), ContainerError> {
+        Err(ContainerError::Unsupported("writing to a zip reader"))
+    }
+
+    fn read_entry_limited(&mut self, name: &str, max: u64) -> Result<Vec<u8>, ContainerError> {
+        let file = self.archive.by_name(name).map_err(|e| match e {
+            zip::result::ZipError::FileNotFound => ContainerError::NotFound(name.to_string()),
+            e => ContainerError::Archive(e.to_string()),
+        })?;
+        if file.size() > max {
+            return Err(ContainerError::TooLarge {
+                name: name.to_string(),
+                max,
+            });
+        }
+        crate::container::read_limited(file, name, max)
+    }
+}
+
+/// `url` without its query string and fragment, for format detection.
+fn url_path(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+/// Fetches blobs over HTTP(S) (`http` feature).
+///
+/// Absolute `http://`/`https://` locations are fetched as is. Other paths, such as the
+/// `file://` entries of a manifest that is itself hosted on a server, are resolved against
+/// `base_url` when one is set and rejected otherwise.
+#[cfg(feature = "http")]
+pub struct HttpBlobLoader {
+    pub agent: ureq::Agent,
+    pub base_url: Option<String>,
+    /// Largest response body accepted, in bytes.
+    pub max_bytes: u64,
+}
+
+#[cfg(feature = "http")]
+impl Default for HttpBlobLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "http")]
+impl HttpBlobLoader {
+    pub fn new() -> Self {
+        Self {
+            agent: ureq::Agent::new_with_defaults(),
+            base_url: None,
+            max_bytes: LoadLimits::default().max_blob_bytes,
+        }
+    }
+
+    /// Loader that also resolves relative paths against `base_url`.
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: Some(base_url.into()),
+            ..Self::new()
+        }
+    }
+
+    fn resolve(&self, path: &str) -> Result<String, ArchiveError> {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return Ok(path.to_string());
+        }
+        let base = self.base_url.as_deref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{}' is not a URL and no base URL is set", path),
+            )
+        })?;
+        Ok(format!(
+            "{}/{}",
+            base.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        ))
+    }
+}
+
+#[cfg(feature = "http")]
+impl BlobLoader for HttpBlobLoader {
+    fn load_blob(&mut self, path: &str) -> Result<Vec<u8>, ArchiveError> {
+        let url = self.resolve(path)?;
+        let mut response = self
+            .agent
+            .get(&url)
+            .call()
+            .map_err(|e| io::Error::other(format!("Failed to fetch {}: {}", url, e)))?;
+        let bytes = response
+            .body_mut()
+            .with_config()
+            .limit(self.max_bytes)
+            .read_to_vec()
+            .map_err(|e| io::Error::other(format!("Failed to read {}: {}", url, e)))?;
+        Ok(bytes)
+    }
+}
+
+/// Error raised while loading an Aurora manifest, tagged with the archetype it came from.
+#[derive(Debug, thiserror::Error)]
+pub enum AuroraLoadError {
+    /// The blob for an archetype could not be located or decoded into bytes.
+    #[error("archetype '{archetype}' ({source_url}): failed to resolve blob: {source}")]
+    Resolve {
+        archetype: String,
+        source_url: String,
+        source: ArchiveError,
+    },
+    /// The blob bytes were read but are not valid for the declared format.
+    #[error("archetype '{archetype}' ({source_url}): failed to parse blob: {source}")]
+    Parse {
+        archetype: String,
+        source_url: String,
+        source: ArchiveError,
+    },
+    /// The manifest itself could not be read or parsed.
+    #[error("failed to read manifest: {0}")]
+    Manifest(#[source] ArchiveError),
+    /// The manifest exceeds the registry's [`LoadLimits`].
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    /// The parsed archetype could not be applied to the world.
+    #[error("archetype '{archetype}' ({source_url}): failed to load: {source}")]
+    Apply {
+        archetype: String,
+        source_url: String,
+        source: ArchiveError,
+    },
+}
+
+impl From<AuroraLoadError> for String {
+    fn from(e: AuroraLoadError) -> Self {
+        e.to_string()
+    }
+}
+
+fn archetype_label(idx: usize, arch: &ArchetypeSpec) -> String {
+    arch.name.clone().unwrap_or_else(|| format!("#{}", idx))
+}
+
+fn resolve_blob<L: BlobLoader>(
+    manifest: &AuroraWorldManifest,
+    arch: &ArchetypeSpec,
+    loader: &mut L,
+    limits: &LoadLimits,
+) -> Result<LoadedBlob, ArchiveError> {
+    let blob = read_blob(&manifest.world, arch, loader, limits)?;
+    if let Some(expected) = manifest.blob_checksum(&arch.source.0) {
+        verify_checksum(&arch.source.0, &blob.bytes, expected)?;
+    }
+    Ok(blob)
+}
+
+/// Read the blob of `arch` from the embed section of `world` or through `loader`.
+fn read_blob<L: BlobLoader>(
+    world: &WorldWithAurora,
+    arch: &ArchetypeSpec,
+    loader: &mut L,
+    limits: &LoadLimits,
+) -> Result<LoadedBlob, ArchiveError> {
+    let blob = match AuroraLocation::from(arch.source.0.as_str()) {
+        AuroraLocation::File(path) => {
+            let bytes = loader.load_blob(&path)?;
+            limits.check_blob(bytes.len() as u64)?;
+            let format = AuroraFormat::from_path(&path);
+            LoadedBlob { format, bytes }
+        }
+        AuroraLocation::Embed(name) => {
+            let blob = world.embed.get(&name).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Embedded blob '{}' not found in manifest.", name),
+                )
+            })?;
+            limits.check_blob(blob.data.len() as u64)?;
+            let format = AuroraFormat::from_str(&blob.format);
+            let bytes = blob.decode(limits.max_blob_bytes)?;
+            LoadedBlob { format, bytes }
+        }
+        AuroraLocation::Http(url) => {
+            let bytes = loader.load_blob(&url)?;
+            limits.check_blob(bytes.len() as u64)?;
+            let format = AuroraFormat::from_path(url_path(&url));
+            LoadedBlob { format, bytes }
+        }
+        AuroraLocation::Unknown(s) => {
+            return Err(ArchiveError::Parse(format!("Unknown location: {}", s)));
+        }
+    };
+    Ok(blob)
+}
+
+/// Resolve and parse the blob of archetype `idx` of `manifest`.
+fn load_archetype_spec<L: BlobLoader>(
+    manifest: &AuroraWorldManifest,
+    idx: usize,
+    loader: &mut L,
+    limits: &LoadLimits,
+) -> Result<LoadedArchetype, AuroraLoadError> {
+    let arch = &manifest.world.archetypes[idx];
+    let _span = archive_span!(
+        "read_archetype_blob",
+        index = idx,
+        source = arch.source.0.as_str()
+    );
+    let read_start = Instant::now();
+    let blob = resolve_blob(manifest, arch, loader, limits).map_err(|source| {
+        AuroraLoadError::Resolve {
+            archetype: archetype_label(idx, arch),
+            source_url: arch.source.0.clone(),
+            source,
+        }
+    })?;
+
+    let parsed = parse_blob(&blob, &manifest.csv_options()).map_err(|source| AuroraLoadError::Parse {
+        archetype: archetype_label(idx, arch),
+        source_url: arch.source.0.clone(),
+        source,
+    })?;
+    let origin = ArchetypeOrigin {
+        idx,
+        blob_bytes: blob.bytes.len() as u64,
+        read: read_start.elapsed(),
+    };
+
+    let mut loaded = match parsed {
+        AuroraInternalFormat::ColumnarCsv(csv) => {
+            let mut snap: ArchetypeSnapshot = (&csv).into();
+            snap.storage_types = arch
+                .storage
+                .clone()
+                .unwrap_or(vec![StorageTypeFlag::Table; snap.component_types.len()]);
+            LoadedArchetype::Legacy(snap, origin)
+        }
+        AuroraInternalFormat::ArchetypeSnapshot(data) => LoadedArchetype::Legacy(data, origin),
+        #[cfg(feature = "arrow_rs")]
+        AuroraInternalFormat::ArrowComponentTable(table) => LoadedArchetype::Arrow(table, origin),
+    };
+    loaded.rename_components(&manifest.component_renames());
+    Ok(loaded)
+}
+
+/// Reserve entity slots for `loaded_archetypes` and write them into `world`.
+fn apply_loaded_archetypes(
+    world: &mut World,
+    manifest: &AuroraWorldManifest,
+    registry: &SnapshotRegistry,
+    loaded_archetypes: Vec<LoadedArchetype>,
+) -> Result<LoadReport, AuroraLoadError> {
+    let names = loaded_archetypes
+        .iter()
+        .flat_map(LoadedArchetype::component_names);
+    check_schema_hashes(manifest, registry, names)?;
+    let names = loaded_archetypes
+        .iter()
+        .flat_map(LoadedArchetype::component_names);
+    registry.check_known(names)?;
+    for arch in &loaded_archetypes {
+        registry.check_duplicates(arch.component_names())?;
+    }
+    let limits = &registry.limits;
+    // Reserve entities
+    let mut max_entity = 0;
+    for arch in &loaded_archetypes {
+        let max = arch.entity_ids().max().unwrap_or(0);
+        if max > max_entity {
+            max_entity = max;
+        }
+    }
+    try_reserve_entity_slots(world, max_entity.saturating_add(1), limits)?;
+    world.flush();
+
+    // Load data
+    let mut report = LoadReport::default();
+    let mut loaded = Vec::new();
+    #[cfg(feature = "arrow_rs")]
+    let mut buffer = crate::bevy_cmdbuffer::HarvardCommandBuffer::new();
+
+    let versions = manifest.component_versions();
+    for arch in loaded_archetypes {
+        match arch {
+            LoadedArchetype::Legacy(mut snap, origin) => {
+                let idx = origin.idx;
+                migrate_archetype(&mut snap, &versions, registry).map_err(|source| {
+                    let spec = &manifest.world.archetypes[idx];
+                    AuroraLoadError::Apply {
+                        archetype: archetype_label(idx, spec),
+                        source_url: spec.source.0.clone(),
+                        source,
+                    }
+                })?;
+                let temp_snap = WorldArchSnapshot {
+                    entities: vec![], // Not used by defragment loader for reservation if we did it already
+                    archetypes: vec![snap],
+                };
+                let mut arch_report =
+                    load_defragment_quiet(world, &temp_snap, registry, &mut loaded)?;
+                origin.annotate(&mut arch_report);
+                report.merge(&arch_report);
+            }
+            #[cfg(feature = "arrow_rs")]
+            LoadedArchetype::Arrow(table, origin) => {
+                let idx = origin.idx;
+                let stale = table.columns.keys().find(|name| {
+                    versions.get(name.as_str()).copied().unwrap_or(0)
+                        != registry.component_version(name)
+                });
+                if let Some(name) = stale {
+                    let spec = &manifest.world.archetypes[idx];
+                    return Err(AuroraLoadError::Apply {
+                        archetype: archetype_label(idx, spec),
+                        source_url: spec.source.0.clone(),
+                        source: ArchiveError::Schema(format!(
+                            "component '{}' needs a schema migration, which Arrow blobs do not support",
+                            name
+                        )),
+                    });
+                }
+                let mut table_report = crate::binary_archive::load_arrow_archetype_to_world(
                     world, &registry, &table, &mut buffer,
                 )
-                .map_err(|e| e.to_string())?;
+                .map_err(|e| {
+                    let spec = &manifest.world.archetypes[idx];
+                    AuroraLoadError::Apply {
+                        archetype: archetype_label(idx, spec),
+                        source_url: spec.source.0.clone(),
+                        source: e.into(),
+                    }
+                })?;
+                let insert_start = Instant::now();
                 buffer.apply(world);
                 buffer.reset();
+                origin.annotate(&mut table_report);
+                if let Some(stats) = table_report.archetype_stats.last_mut() {
+                    stats.insert += insert_start.elapsed();
+                }
+                report.merge(&table_report);
+                if registry.tracks_loaded() {
+                    loaded.extend(crate::binary_archive::arrow_loaded_entities(world, &table));
+                }
             }
         }
     }
 
-    Ok(())
+    trigger_load_events(world, registry, &loaded);
+    Ok(report)
+}
+
+/// Load an ECS world from a manifest structure using a specific blob loader.
+///
+/// Blobs are resolved and parsed before anything is written to `world`, so a malformed
+/// archetype, or an unknown type under a [strict](crate::bevy_registry::LoadOptions) load,
+/// fails the load without leaving a partially populated world.
+pub fn load_world_manifest_with_loader<L: BlobLoader>(
+    world: &mut World,
+    manifest: &AuroraWorldManifest,
+    registry: &SnapshotRegistry,
+    loader: &mut L,
+) -> Result<LoadReport, AuroraLoadError> {
+    let limits = &registry.limits;
+    limits.check_archetypes(manifest.world.archetypes.len())?;
+
+    // Parse all blobs first
+    let loaded_archetypes = (0..manifest.world.archetypes.len())
+        .map(|idx| load_archetype_spec(manifest, idx, loader, limits))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let names = loaded_archetypes
+        .iter()
+        .flat_map(LoadedArchetype::component_names);
+    check_schema_hashes(manifest, registry, names)?;
+    let names = loaded_archetypes
+        .iter()
+        .flat_map(LoadedArchetype::component_names);
+    registry.check_known(names)?;
+    for arch in &loaded_archetypes {
+        registry.check_duplicates(arch.component_names())?;
+    }
+
+    let resource = &manifest.world.resources;
+    let mut report = load_world_resource(resource, world, registry)?;
+
+    let archetypes = apply_loaded_archetypes(world, manifest, registry, loaded_archetypes)?;
+    report.merge(&archetypes);
+    Ok(report)
+}
+
+/// Load only entity `id` of `manifest` into `world`, at its saved index.
+///
+/// With an `entity_index` in the manifest metadata only the blob holding the entity is
+/// resolved; older manifests are scanned archetype by archetype until it is found.
+/// Resources are not loaded. Returns `false` when the manifest has no such entity.
+pub fn load_entity_from_manifest_with_loader<L: BlobLoader>(
+    world: &mut World,
+    manifest: &AuroraWorldManifest,
+    registry: &SnapshotRegistry,
+    loader: &mut L,
+    id: u32,
+) -> Result<bool, AuroraLoadError> {
+    let limits = &registry.limits;
+    let located = match manifest.entity_index() {
+        Some(index) => match index.locate(id) {
+            Some((idx, row)) if idx < manifest.world.archetypes.len() => {
+                Some((idx, load_archetype_spec(manifest, idx, loader, limits)?, row))
+            }
+            _ => None,
+        },
+        None => {
+            let mut found = None;
+            for idx in 0..manifest.world.archetypes.len() {
+                let arch = load_archetype_spec(manifest, idx, loader, limits)?;
+                let row = arch.entity_ids().position(|e| e == id);
+                if let Some(row) = row {
+                    found = Some((idx, arch, row));
+                    break;
+                }
+            }
+            found
+        }
+    };
+    let Some((idx, arch, row)) = located else {
+        return Ok(false);
+    };
+    let single = arch.select_row(row).map_err(|source| {
+        let spec = &manifest.world.archetypes[idx];
+        AuroraLoadError::Parse {
+            archetype: archetype_label(idx, spec),
+            source_url: spec.source.0.clone(),
+            source,
+        }
+    })?;
+    apply_loaded_archetypes(world, manifest, registry, vec![single])?;
+    Ok(true)
 }
 
 /// Load an ECS world from a manifest structure using default filesystem loading.
 ///
-/// This is a convenience wrapper around `load_world_manifest_with_loader`.
+/// This is a convenience wrapper around `load_world_manifest_with_loader`. Blob paths are
+/// jailed to the current directory; use [`FsBlobLoader::unjailed`] with
+/// `load_world_manifest_with_loader` to load trusted manifests that reference other paths.
 pub fn load_world_manifest(
     world: &mut World,
     manifest: &AuroraWorldManifest,
     registry: &SnapshotRegistry,
-) -> Result<(), String> {
-    let mut loader = FsBlobLoader {
-        base_dir: Path::new(".").to_path_buf(),
-    };
+) -> Result<LoadReport, AuroraLoadError> {
+    let mut loader = FsBlobLoader::new(".");
     load_world_manifest_with_loader(world, manifest, registry, &mut loader)
 }
 
+/// Blobs of a manifest resolved and parsed off the main thread, ready to be applied.
+///
+/// Returned by [`load_world_manifest_with_loader_async`]; call [`apply`](Self::apply) on
+/// the thread that owns the world.
+#[cfg(feature = "tokio")]
+pub struct PreparedManifestLoad {
+    manifest: AuroraWorldManifest,
+    archetypes: Vec<LoadedArchetype>,
+}
+
+#[cfg(feature = "tokio")]
+impl PreparedManifestLoad {
+    pub fn manifest(&self) -> &AuroraWorldManifest {
+        &self.manifest
+    }
+
+    /// Spawn the parsed archetypes and resources into `world`.
+    pub fn apply(self, world: &mut World, registry: &SnapshotRegistry) -> Result<LoadReport, AuroraLoadError> {
+        let names = self.archetypes.iter().flat_map(LoadedArchetype::component_names);
+        check_schema_hashes(&self.manifest, registry, names)?;
+        let names = self.archetypes.iter().flat_map(LoadedArchetype::component_names);
+        registry.check_known(names)?;
+        for arch in &self.archetypes {
+            registry.check_duplicates(arch.component_names())?;
+        }
+        let mut report = load_world_resource(&self.manifest.world.resources, world, registry)?;
+        let archetypes = apply_loaded_archetypes(world, &self.manifest, registry, self.archetypes)?;
+        report.merge(&archetypes);
+        Ok(report)
+    }
+}
+
+/// Async [`load_world_manifest_with_loader`]: blobs are read and parsed on tokio's
+/// blocking pool, so only [`PreparedManifestLoad::apply`] touches the world.
+///
+/// Must be polled inside a tokio runtime.
+#[cfg(feature = "tokio")]
+pub async fn load_world_manifest_with_loader_async<L: BlobLoader + Send + 'static>(
+    manifest: AuroraWorldManifest,
+    limits: LoadLimits,
+    mut loader: L,
+) -> Result<PreparedManifestLoad, AuroraLoadError> {
+    limits.check_archetypes(manifest.world.archetypes.len())?;
+    tokio::task::spawn_blocking(move || {
+        let archetypes = (0..manifest.world.archetypes.len())
+            .map(|idx| load_archetype_spec(&manifest, idx, &mut loader, &limits))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PreparedManifestLoad {
+            manifest,
+            archetypes,
+        })
+    })
+    .await
+    .map_err(|e| AuroraLoadError::Manifest(io::Error::other(e).into()))?
+}
+
 /// Write a manifest to a file in a specified format.
 ///
 /// # Parameters
@@ -825,21 +2163,39 @@ pub fn load_world_manifest(
 /// - `format`: Desired serialization format (JSON or TOML).
 ///
 /// # Returns
-/// Ok if written successfully, or a string with error message.
+/// Ok if written successfully, or the [`ArchiveError`] that stopped the write.
 pub fn write_manifest_to_file<P: AsRef<Path>>(
     manifest: &AuroraWorldManifest,
     path: P,
     format: ManifestOutputFormat,
-) -> Result<(), String> {
+) -> Result<(), ArchiveError> {
     let content = match format {
-        ManifestOutputFormat::Json => {
-            serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?
-        }
-        ManifestOutputFormat::Toml => {
-            toml::to_string_pretty(manifest).map_err(|e| e.to_string())?
-        }
+        ManifestOutputFormat::Json => serde_json::to_string_pretty(manifest)?,
+        ManifestOutputFormat::Toml => toml::to_string_pretty(manifest)?,
     };
-    fs::write(path, content).map_err(|e| e.to_string())
+    Ok(fs::write(path, content)?)
+}
+
+/// Async save: snapshot `world` now, then serialize and write the manifest to `path` on
+/// tokio's blocking pool.
+///
+/// The world is only borrowed for the snapshot, so the returned future can be spawned
+/// and awaited while the next frames run. Must be polled inside a tokio runtime.
+#[cfg(feature = "tokio")]
+pub fn save_world_manifest_async(
+    world: &World,
+    registry: &SnapshotRegistry,
+    path: impl Into<PathBuf>,
+    format: ManifestOutputFormat,
+) -> impl Future<Output = Result<(), ArchiveError>> + Send + 'static {
+    let manifest = save_world_manifest(world, registry);
+    let path = path.into();
+    async move {
+        let manifest = manifest?;
+        tokio::task::spawn_blocking(move || write_manifest_to_file(&manifest, path, format))
+            .await
+            .map_err(io::Error::other)?
+    }
 }
 
 /// Load a manifest from a file on disk and parse it.
@@ -851,7 +2207,7 @@ pub fn write_manifest_to_file<P: AsRef<Path>>(
 /// - `format_hint`: Optional explicit format. If not provided, guesses from extension.
 ///
 /// # Returns
-/// A parsed `AuroraWorldManifest`, or an error message.
+/// A parsed `AuroraWorldManifest`, or the [`ArchiveError`] that stopped the read.
 ///
 /// # Supported Extensions
 /// - `.toml` → `TOML`
@@ -859,14 +2215,20 @@ pub fn write_manifest_to_file<P: AsRef<Path>>(
 pub fn read_manifest_from_file<P: AsRef<Path>>(
     path: P,
     format_hint: Option<ManifestOutputFormat>,
-) -> Result<AuroraWorldManifest, String> {
-    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+) -> Result<AuroraWorldManifest, ArchiveError> {
+    let content = fs::read_to_string(&path)?;
+    parse_manifest(&content, path.as_ref(), format_hint)
+}
 
+fn parse_manifest(
+    content: &str,
+    path: &Path,
+    format_hint: Option<ManifestOutputFormat>,
+) -> Result<AuroraWorldManifest, ArchiveError> {
     let format = match format_hint {
         Some(f) => f,
         None => {
             let ext = path
-                .as_ref()
                 .extension()
                 .and_then(|e| e.to_str())
                 .unwrap_or("")
@@ -874,26 +2236,94 @@ pub fn read_manifest_from_file<P: AsRef<Path>>(
             match ext.as_str() {
                 "json" => ManifestOutputFormat::Json,
                 "toml" => ManifestOutputFormat::Toml,
-                _ => return Err(format!("Cannot guess format from extension: {}", ext)),
+                _ => {
+                    return Err(ArchiveError::Parse(format!(
+                        "Cannot guess format from extension: {}",
+                        ext
+                    )));
+                }
             }
         }
     };
 
     match format {
-        ManifestOutputFormat::Json => serde_json::from_str(&content).map_err(|e| e.to_string()),
-        ManifestOutputFormat::Toml => toml::from_str(&content).map_err(|e| e.to_string()),
+        ManifestOutputFormat::Json => Ok(serde_json::from_str(content)?),
+        ManifestOutputFormat::Toml => Ok(toml::from_str(content)?),
+    }
+}
+
+/// Read the manifest stored at `manifest_path` inside `container`.
+pub fn read_manifest_from_container<C: SnapshotContainer>(
+    container: &mut C,
+    manifest_path: &str,
+) -> Result<AuroraWorldManifest, ArchiveError> {
+    let bytes = container.read_entry(manifest_path)?;
+    let content = String::from_utf8(bytes).map_err(ArchiveError::parse)?;
+    parse_manifest(&content, Path::new(manifest_path), None)
+}
+
+/// Save the world into `container`: every `OutputStrategy::File` or `Return` payload
+/// becomes an entry under its path, and the manifest itself is written to `manifest_path`
+/// (`.json` or `.toml`). Nothing is written to the local filesystem.
+pub fn save_world_manifest_to_container<C: SnapshotContainer>(
+    world: &World,
+    registry: &SnapshotRegistry,
+    guidance: &ExportGuidance,
+    container: &mut C,
+    manifest_path: &str,
+) -> Result<AuroraWorldManifest, ArchiveError> {
+    let guidance = guidance.files_as_entries();
+    let manifest = save_world_manifest_with_guidance(world, registry, &guidance)?;
+    for (path, bytes) in &manifest.world.external_payloads {
+        container.write_entry(path, bytes)?;
     }
+    let content = if manifest_path.ends_with(".json") {
+        serde_json::to_string_pretty(&manifest)?
+    } else {
+        toml::to_string_pretty(&manifest)?
+    };
+    container.write_entry(manifest_path, content.as_bytes())?;
+    Ok(manifest)
+}
+
+/// Load a world saved with [`save_world_manifest_to_container`].
+pub fn load_world_manifest_from_container<C: SnapshotContainer>(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    container: &mut C,
+    manifest_path: &str,
+) -> Result<LoadReport, AuroraLoadError> {
+    let manifest =
+        read_manifest_from_container(container, manifest_path).map_err(AuroraLoadError::Manifest)?;
+    load_world_manifest_with_loader(world, &manifest, registry, container)
 }
 
 pub fn save_world_manifest_with_guidance(
     world: &World,
     registry: &SnapshotRegistry,
     guidance: &ExportGuidance,
-) -> Result<AuroraWorldManifest, String> {
-    let mut world_with_aurora = WorldWithAurora::from_guided(world, registry, guidance);
+) -> Result<AuroraWorldManifest, ArchiveError> {
+    let (mut world_with_aurora, export) =
+        WorldWithAurora::from_guided_indexed(world, registry, guidance)?;
     world_with_aurora.resources = save_world_resource(world, registry);
+    let mut metadata = index_metadata(&export.index);
+    if !export.spilled.is_empty() {
+        metadata.insert(EMBED_SPILLED_KEY.to_string(), Value::from(export.spilled));
+    }
+    insert_blob_checksums(&mut metadata, export.checksums);
+    insert_component_versions(&mut metadata, registry);
+    insert_schema_hashes(&mut metadata, registry);
+    if let Ok(schema) = serde_json::to_value(RegistrySchema::of(registry)) {
+        metadata.insert(REGISTRY_SCHEMA_KEY.to_string(), schema);
+    }
+    if guidance.csv != CsvOptions::default() {
+        metadata.insert(
+            CSV_NULL_TOKEN_KEY.to_string(),
+            Value::String(guidance.csv.null_token.clone()),
+        );
+    }
     Ok(AuroraWorldManifest {
-        metadata: None,
+        metadata: Some(metadata),
         world: world_with_aurora,
     })
 }
@@ -988,6 +2418,262 @@ mod tests {
         (world, registry)
     }
 
+    struct MemBlobLoader(HashMap<String, Vec<u8>>);
+    impl BlobLoader for MemBlobLoader {
+        fn load_blob(&mut self, path: &str) -> Result<Vec<u8>, ArchiveError> {
+            self.0.get(path).cloned().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("no blob at {}", path)).into()
+            })
+        }
+    }
+
+    fn manifest_with_source(name: &str, source: &str) -> AuroraWorldManifest {
+        AuroraWorldManifest {
+            metadata: None,
+            world: WorldWithAurora {
+                version: "0.1".to_string(),
+                name: None,
+                archetypes: vec![ArchetypeSpec {
+                    name: Some(name.to_string()),
+                    components: vec!["TestComponentA".to_string()],
+                    storage: None,
+                    source: Url(source.to_string()),
+                }],
+                embed: BTreeMap::new(),
+                external_payloads: BTreeMap::new(),
+                resources: BTreeMap::new(),
+                names: BTreeMap::new(),
+            },
+        }
+    }
+
+    fn load_malformed(name: &str, path: &str, bytes: &[u8]) -> AuroraLoadError {
+        let (_, registry) = init_world();
+        let manifest = manifest_with_source(name, &format!("file://{}", path));
+        let mut loader = MemBlobLoader(HashMap::from([(path.to_string(), bytes.to_vec())]));
+        let mut world = World::new();
+        let err = load_world_manifest_with_loader(&mut world, &manifest, &registry, &mut loader)
+            .unwrap_err();
+        assert!(err.to_string().contains(name), "missing archetype name: {err}");
+        assert!(err.to_string().contains(path), "missing source url: {err}");
+        err
+    }
+
+    #[test]
+    fn test_malformed_csv_blob() {
+        let err = load_malformed("bad_csv", "a.csv", b"TestComponentA\n1\n");
+        assert!(matches!(err, AuroraLoadError::Parse { .. }));
+        let err = load_malformed("bad_id", "b.csv", b"id,TestComponentA\nnope,1\n");
+        assert!(matches!(err, AuroraLoadError::Parse { .. }));
+    }
+
+    #[test]
+    fn test_malformed_msgpack_blob() {
+        let err = load_malformed("bad_msgpack", "a.msgpack", &[0xc1, 0xff, 0x00]);
+        assert!(matches!(err, AuroraLoadError::Parse { .. }));
+    }
+
+    #[cfg(feature = "arrow_rs")]
+    #[test]
+    fn test_malformed_parquet_blob() {
+        let err = load_malformed("bad_parquet", "a.parquet", b"PAR1 not really parquet");
+        assert!(matches!(err, AuroraLoadError::Parse { .. }));
+    }
+
+    #[test]
+    fn test_manifest_blob_limit() {
+        let (_, mut registry) = init_world();
+        registry.limits.max_blob_bytes = 4;
+        let manifest = manifest_with_source("big", "file://big.csv");
+        let mut loader = MemBlobLoader(HashMap::from([(
+            "big.csv".to_string(),
+            b"id,TestComponentA\n0,{\"value\":1}\n".to_vec(),
+        )]));
+        let mut world = World::new();
+        let err = load_world_manifest_with_loader(&mut world, &manifest, &registry, &mut loader)
+            .unwrap_err();
+        assert!(matches!(err, AuroraLoadError::Resolve { .. }));
+        assert!(err.to_string().contains("exceeds limit"));
+    }
+
+    #[test]
+    fn test_fs_loader_jail() {
+        let dir = "test_fs_loader_jail";
+        fs::create_dir_all(format!("{dir}/inner")).unwrap();
+        fs::write(format!("{dir}/inner/blob.csv"), b"id\n").unwrap();
+        fs::write(format!("{dir}/outside.csv"), b"id\n").unwrap();
+
+        let mut jailed = FsBlobLoader::new(format!("{dir}/inner"));
+        assert!(jailed.load_blob("blob.csv").is_ok());
+        assert!(jailed.load_blob("./blob.csv").is_ok());
+        assert!(jailed.load_blob("../outside.csv").is_err());
+        let abs = fs::canonicalize(format!("{dir}/outside.csv")).unwrap();
+        assert!(jailed.load_blob(abs.to_str().unwrap()).is_err());
+
+        let mut open = FsBlobLoader::unjailed(format!("{dir}/inner"));
+        assert!(open.load_blob("../outside.csv").is_ok());
+        assert!(open.load_blob(abs.to_str().unwrap()).is_ok());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fs_loader_write_jail() {
+        use std::os::unix::fs::symlink;
+        let dir = "test_fs_loader_write_jail";
+        fs::create_dir_all(format!("{dir}/inner")).unwrap();
+        fs::create_dir_all(format!("{dir}/outside")).unwrap();
+        let outside = fs::canonicalize(format!("{dir}/outside")).unwrap();
+        symlink(&outside, format!("{dir}/inner/link_dir")).unwrap();
+        symlink(outside.join("target.bin"), format!("{dir}/inner/link_file")).unwrap();
+
+        let mut jailed = FsBlobLoader::new(format!("{dir}/inner"));
+        assert!(jailed.write_entry("nested/ok.bin", b"ok").is_ok());
+        let err = jailed.write_entry("link_dir/sub/blob.bin", b"x").unwrap_err();
+        assert!(matches!(err, ContainerError::Escape(_)));
+        assert!(!outside.join("sub").exists());
+        assert!(jailed.write_entry("link_file", b"x").is_err());
+        assert!(!outside.join("target.bin").exists());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_try_from_jails_file_blobs() {
+        let dir = "test_try_from_jails_file_blobs";
+        fs::create_dir_all(dir).unwrap();
+        fs::write(format!("{dir}/blob.csv"), b"id\n").unwrap();
+        let abs = fs::canonicalize(format!("{dir}/blob.csv")).unwrap();
+
+        let manifest = manifest_with_source("abs", &format!("file://{}", abs.display()));
+        let err = WorldArchSnapshot::try_from(&manifest).unwrap_err();
+        assert!(matches!(err, ArchiveError::Io(ref e) if e.kind() == io::ErrorKind::PermissionDenied));
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn test_zip_blob_loader_csv() {
+        use std::io::Write;
+        let (world, registry) = init_world();
+        let mut guide = ExportGuidance::embed_all(ExportFormat::Csv);
+        for (i, arch) in world.archetypes().iter().enumerate() {
+            if !arch.is_empty() {
+                guide.set_strategy_for(
+                    i,
+                    OutputStrategy::Return(ExportFormat::Csv, format!("data/arch_{i}.csv")),
+                );
+            }
+        }
+        let manifest = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        for (path, bytes) in &manifest.world.external_payloads {
+            writer.start_file(path.as_str(), options).unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+        let cursor = writer.finish().unwrap();
+
+        let archive = zip::ZipArchive::new(std::io::Cursor::new(cursor.into_inner())).unwrap();
+        let mut loader = ZipBlobLoader { archive };
+        let mut world2 = World::new();
+        load_world_manifest_with_loader(&mut world2, &manifest, &registry, &mut loader).unwrap();
+        let mut q = world2.query::<&TestComponentA>();
+        assert_eq!(q.iter(&world2).count(), 30);
+    }
+
+    fn container_roundtrip<C: SnapshotContainer>(container: &mut C) {
+        let (world, registry) = init_world();
+        let mut guide = ExportGuidance::embed_all(ExportFormat::Csv);
+        // The first archetype keeps the default `File` strategy, which must land in the
+        // container as well.
+        guide.default = OutputStrategy::File(ExportFormat::Csv, "files".into());
+        let saved = world
+            .archetypes()
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| !a.is_empty() && !a.contains(bevy_ecs::resource::IS_RESOURCE));
+        for (i, _) in saved.skip(1) {
+            guide.set_strategy_for(
+                i,
+                OutputStrategy::Return(ExportFormat::MsgPack, "data".to_string()),
+            );
+        }
+        save_world_manifest_to_container(&world, &registry, &guide, container, "manifest.toml")
+            .unwrap();
+        let entries = container.list_entries().unwrap();
+        assert!(entries.iter().any(|n| n == "manifest.toml"));
+        assert!(entries.iter().any(|n| n.starts_with("files/")));
+        assert!(!Path::new("files").exists());
+
+        let mut world2 = World::new();
+        load_world_manifest_from_container(&mut world2, &registry, container, "manifest.toml")
+            .unwrap();
+        let mut q = world2.query::<&TestComponentA>();
+        assert_eq!(q.iter(&world2).count(), 30);
+    }
+
+    #[test]
+    fn test_manifest_containers() {
+        container_roundtrip(&mut std::collections::BTreeMap::new());
+
+        let dir = "test_manifest_containers";
+        fs::create_dir_all(dir).unwrap();
+        container_roundtrip(&mut FsBlobLoader::new(dir));
+        fs::remove_dir_all(dir).ok();
+
+        #[cfg(feature = "object_store")]
+        {
+            let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+            let mut bucket = crate::container::ObjectStoreContainer::new(store, "worlds/1").unwrap();
+            container_roundtrip(&mut bucket);
+            assert!(bucket.list_entries().unwrap().iter().any(|n| n.starts_with("data/")));
+        }
+
+        #[cfg(feature = "tar")]
+        {
+            let mut tar = crate::container::TarContainer::new();
+            container_roundtrip(&mut tar);
+            let bytes = tar.write_to(Vec::new()).unwrap();
+            let mut back = crate::container::TarContainer::from_reader(bytes.as_slice()).unwrap();
+            assert!(read_manifest_from_container(&mut back, "manifest.toml").is_ok());
+        }
+    }
+
+    #[test]
+    fn test_missing_blob_reports_archetype() {
+        let (_, registry) = init_world();
+        let manifest = manifest_with_source("gone", "embed://missing");
+        let mut loader = MemBlobLoader(HashMap::new());
+        let mut world = World::new();
+        let err = load_world_manifest_with_loader(&mut world, &manifest, &registry, &mut loader)
+            .unwrap_err();
+        assert!(matches!(err, AuroraLoadError::Resolve { ref archetype, .. } if archetype == "gone"));
+    }
+
+    #[test]
+    fn test_corrupt_manifest_converts_to_error() {
+        let missing = manifest_with_source("gone", "embed://missing");
+        let err = WorldArchSnapshot::try_from(&missing).unwrap_err();
+        assert!(matches!(err, ArchiveError::Io(ref e) if e.kind() == io::ErrorKind::NotFound));
+
+        let mut corrupt = manifest_with_source("bad", "embed://bad");
+        corrupt.world.embed.insert(
+            "bad".to_string(),
+            EmbeddedBlob {
+                format: "msgpack".to_string(),
+                compression: None,
+                data: "not base64!".to_string(),
+            },
+        );
+        let err = WorldArchSnapshot::try_from(&corrupt).unwrap_err();
+        assert!(matches!(err, ArchiveError::Parse(_)));
+        assert!(corrupt.get_entities().is_empty());
+    }
+
     #[test]
     fn test_aurora_snapshot_roundtrip() {
         let (world, registry) = init_world();
@@ -1000,7 +2686,8 @@ mod tests {
         let deserialized: WorldWithAurora = toml::from_str(&data).unwrap();
 
         let mut world2 = World::new();
-        load_world_arch_snapshot(&mut world2, &((&deserialized).into()), &registry);
+        let snapshot = WorldArchSnapshot::try_from(&deserialized).unwrap();
+        load_world_arch_snapshot(&mut world2, &snapshot, &registry).unwrap();
     }
 
     #[test]
@@ -1016,6 +2703,143 @@ mod tests {
         load_world_manifest(&mut world2, &deserialized, &registry).unwrap();
     }
 
+    #[test]
+    fn test_manifest_component_rename() {
+        let (world, registry) = init_world();
+        let mut old_registry = SnapshotRegistry::default();
+        old_registry.register_named::<TestComponentA>("LegacyA");
+        let expected = world
+            .iter_entities()
+            .filter(|e| e.contains::<TestComponentA>())
+            .count();
+
+        let formats = [
+            ExportFormat::Csv,
+            ExportFormat::MsgPack,
+            #[cfg(feature = "arrow_rs")]
+            ExportFormat::Parquet,
+        ];
+        for format in formats {
+            let guide = ExportGuidance::embed_all(format);
+            let mut manifest =
+                save_world_manifest_with_guidance(&world, &old_registry, &guide).unwrap();
+            manifest.add_component_rename("LegacyA", "TestComponentA");
+            let toml = toml::to_string_pretty(&manifest).unwrap();
+            let patched: AuroraWorldManifest = toml::from_str(&toml).unwrap();
+            assert_eq!(
+                patched
+                    .component_renames()
+                    .get("LegacyA")
+                    .map(String::as_str),
+                Some("TestComponentA")
+            );
+
+            let mut loaded = World::new();
+            load_world_manifest(&mut loaded, &patched, &registry).unwrap();
+            let count = loaded.query::<&TestComponentA>().iter(&loaded).count();
+            assert_eq!(count, expected);
+        }
+    }
+
+    #[test]
+    fn test_component_migration() {
+        let (world, old_registry) = init_world();
+        let mut registry = old_registry.clone();
+        // v1 doubled TestComponentA, v2 negated it.
+        registry.register_migration::<TestComponentA>(2, |from, mut value| {
+            let v = value["value"].as_i64().unwrap();
+            value["value"] = match from {
+                0 => (v * 2).into(),
+                _ => (-v).into(),
+            };
+            value
+        });
+        let mut expected: Vec<i32> = world
+            .iter_entities()
+            .filter_map(|e| e.get::<TestComponentA>().map(|a| -a.value * 2))
+            .collect();
+        expected.sort();
+        let values = |world: &mut World| {
+            let mut out: Vec<i32> = world
+                .query::<&TestComponentA>()
+                .iter(world)
+                .map(|a| a.value)
+                .collect();
+            out.sort();
+            out
+        };
+
+        let old = save_world_manifest_with_guidance(
+            &world,
+            &old_registry,
+            &ExportGuidance::embed_all(ExportFormat::MsgPack),
+        )
+        .unwrap();
+        assert!(old.component_versions().is_empty());
+        let mut loaded = World::new();
+        load_world_manifest(&mut loaded, &old, &registry).unwrap();
+        assert_eq!(values(&mut loaded), expected);
+
+        // Manifests saved at the current version load as they are.
+        let current = save_world_manifest(&loaded, &registry).unwrap();
+        assert_eq!(current.component_versions().get("TestComponentA"), Some(&2));
+        let mut again = World::new();
+        load_world_manifest(&mut again, &current, &registry).unwrap();
+        assert_eq!(values(&mut again), expected);
+        assert!(load_world_manifest(&mut World::new(), &current, &old_registry).is_err());
+    }
+
+    #[cfg(feature = "arrow_rs")]
+    #[test]
+    fn test_manifest_schema_hash_mismatch() {
+        let (world, registry) = init_world();
+        let guide = ExportGuidance::embed_all(ExportFormat::MsgPack);
+        let mut manifest = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+        let hashes = manifest.schema_hashes();
+        assert_eq!(
+            hashes.get("TestComponentA"),
+            registry.schema_hash("TestComponentA").as_ref()
+        );
+        load_world_manifest(&mut World::new(), &manifest, &registry).unwrap();
+
+        let table = manifest.metadata.as_mut().unwrap();
+        table.get_mut(SCHEMA_HASHES_KEY).unwrap()["TestComponentA"] = "0000".into();
+        let mut loaded = World::new();
+        let err = load_world_manifest(&mut loaded, &manifest, &registry);
+        assert!(matches!(
+            err,
+            Err(AuroraLoadError::Limit(LimitError::SchemaChanged(ref name)))
+                if name == "TestComponentA"
+        ));
+        assert_eq!(loaded.query::<&TestComponentA>().iter(&loaded).count(), 0);
+    }
+
+    #[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Gravity(f32);
+    #[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Seed(u64);
+    #[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Level(String);
+
+    #[test]
+    fn test_manifest_output_is_stable() {
+        let (mut world, mut registry) = init_world();
+        registry.resource_register::<Gravity>();
+        registry.resource_register::<Seed>();
+        registry.resource_register::<Level>();
+        world.insert_resource(Gravity(9.8));
+        world.insert_resource(Seed(7));
+        world.insert_resource(Level("intro".into()));
+
+        let guide = ExportGuidance::embed_all(ExportFormat::MsgPack);
+        let first = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+        let first = toml::to_string_pretty(&first).unwrap();
+        for _ in 0..4 {
+            let again = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+            assert_eq!(toml::to_string_pretty(&again).unwrap(), first);
+        }
+    }
+
     #[test]
     fn test_msgpack_manifest_snapshot_roundtrip() {
         let path = "test_msgpack.toml";
@@ -1079,7 +2903,17 @@ mod tests {
             toml::from_str(&toml).expect("Failed to deserialize TOML");
 
         let mut world2 = World::new();
-        load_world_manifest(&mut world2, &snapshot, &registry).unwrap();
+        let report = load_world_manifest(&mut world2, &snapshot, &registry).unwrap();
+        assert_eq!(
+            report.archetype_stats.len(),
+            snapshot.world.archetypes.len()
+        );
+        assert!(
+            report
+                .archetype_stats
+                .iter()
+                .all(|s| s.blob_bytes > Some(0))
+        );
         load_world_manifest(&mut world2, &deserialized, &registry).unwrap();
         fs::remove_file(path).ok();
     }
@@ -1128,4 +2962,232 @@ mod tests {
         load_world_manifest(&mut world2, &deserialized, &registry).unwrap();
         fs::remove_file(path).ok();
     }
+
+    #[test]
+    fn test_entity_names_roundtrip() {
+        let (_, registry) = init_world();
+        let mut world = World::new();
+        let hero = world.spawn(TestComponentA { value: 1 }).id();
+        let boss = world
+            .spawn((TestComponentA { value: 99 }, Name::new("boss")))
+            .id();
+        let decoy = world
+            .spawn((TestComponentA { value: 2 }, Name::new("boss")))
+            .id();
+
+        let manifest = save_world_manifest(&world, &registry).unwrap();
+        assert_eq!(manifest.entity_by_name("boss"), Some(boss.index_u32()));
+        assert_eq!(manifest.world.name_of(boss.index_u32()), Some("boss"));
+
+        let toml = toml::to_string_pretty(&manifest).unwrap();
+        let mut back: AuroraWorldManifest = toml::from_str(&toml).unwrap();
+        assert_eq!(back.entity_by_name("boss"), Some(boss.index_u32()));
+        assert_eq!(back.entity_by_name("missing"), None);
+
+        back.world.set_name("hero", hero.index_u32());
+        back.world.set_name("decoy", decoy.index_u32());
+        let mut target = World::new();
+        let targets: Vec<Entity> = (0..3).map(|_| target.spawn_empty().id()).collect();
+        let mapper = back
+            .remap_by_name([("decoy", targets[0]), ("boss", targets[1]), ("hero", targets[2])])
+            .unwrap();
+        back.apply_with_remap(&mut target, &registry, &IDRemapRegistry::default(), &mapper)
+            .unwrap();
+        assert_eq!(
+            target.get::<TestComponentA>(targets[1]),
+            Some(&TestComponentA { value: 99 })
+        );
+        assert!(back.remap_by_name([("ghost", hero)]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_zstd_embedded_blobs() {
+        let (world, registry) = init_world();
+        let mut guide = ExportGuidance::embed_all(ExportFormat::MsgPack);
+        let plain = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+        guide.zstd_embedded(3);
+        let packed = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+
+        assert!(packed.world.embed.values().all(|b| b.compression.as_deref() == Some("zstd")));
+        let size = |m: &AuroraWorldManifest| toml::to_string(m).unwrap().len();
+        assert!(size(&packed) < size(&plain));
+
+        let text = toml::to_string_pretty(&packed).unwrap();
+        let back: AuroraWorldManifest = toml::from_str(&text).unwrap();
+        let mut world2 = World::new();
+        load_world_manifest(&mut world2, &back, &registry).unwrap();
+        assert_eq!(world2.query::<&TestComponentA>().iter(&world2).count(), 30);
+
+        let blob = back.world.embed.values().next().unwrap();
+        assert!(blob.decode(4).is_err());
+    }
+
+    #[test]
+    fn test_embed_size_limit() {
+        let (world, registry) = init_world();
+        let mut guide = ExportGuidance::embed_all(ExportFormat::MsgPack);
+        let plain = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+        assert!(plain.spilled_embeds().is_empty());
+        let (largest, blob) = plain
+            .world
+            .embed
+            .iter()
+            .max_by_key(|(_, blob)| blob.data.len())
+            .unwrap();
+        let max = blob.data.len() as u64 - 1;
+
+        guide.max_embed_bytes = Some(max);
+        let err = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap_err();
+        assert!(matches!(err, ArchiveError::Io(e) if e.kind() == io::ErrorKind::FileTooLarge));
+
+        guide.spill_embeds_over(max, EmbedSpill::File("blobs".into()));
+        let mut container = std::collections::BTreeMap::new();
+        let manifest =
+            save_world_manifest_to_container(&world, &registry, &guide, &mut container, "w.toml")
+                .unwrap();
+        assert!(manifest.spilled_embeds().contains(largest));
+        assert!(!manifest.world.embed.contains_key(largest));
+        assert!(container.contains_key(&format!("blobs/{largest}.msgpack")));
+        assert!(!Path::new("blobs").exists());
+
+        let mut world2 = World::new();
+        load_world_manifest_from_container(&mut world2, &registry, &mut container, "w.toml")
+            .unwrap();
+        assert_eq!(world2.query::<&TestComponentA>().iter(&world2).count(), 30);
+    }
+
+    #[test]
+    fn test_load_single_entity_from_manifest() {
+        let (mut world, registry) = init_world();
+        let target = world
+            .query_filtered::<Entity, With<TestComponentF>>()
+            .iter(&world)
+            .nth(3)
+            .unwrap();
+        let mut manifest = save_world_manifest(&world, &registry).unwrap();
+        assert!(manifest.entity_index().is_some());
+
+        for _ in 0..2 {
+            let mut loaded = World::new();
+            let mut loader = MemBlobLoader(HashMap::new());
+            let id = target.index_u32();
+            assert!(
+                load_entity_from_manifest_with_loader(
+                    &mut loaded,
+                    &manifest,
+                    &registry,
+                    &mut loader,
+                    id
+                )
+                .unwrap()
+            );
+            let e = loaded.entities().resolve_from_index(target.index());
+            assert_eq!(loaded.get::<TestComponentF>(e), world.get::<TestComponentF>(target));
+            assert_eq!(loaded.query::<&TestComponentA>().iter(&loaded).count(), 1);
+            assert!(
+                !load_entity_from_manifest_with_loader(
+                    &mut loaded,
+                    &manifest,
+                    &registry,
+                    &mut loader,
+                    9999
+                )
+                .unwrap()
+            );
+            // Manifests written before the index existed are scanned instead.
+            manifest.metadata = None;
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn test_manifest_async_save_load() {
+        let (mut world, registry) = init_world();
+        let path = std::env::temp_dir().join(format!("bevy_archive_async_{}.toml", std::process::id()));
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        let save = save_world_manifest_async(&world, &registry, &path, ManifestOutputFormat::Toml);
+        rt.block_on(save).unwrap();
+        let manifest = read_manifest_from_file(&path, None).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let loader = MemBlobLoader(HashMap::new());
+        let prepared = rt
+            .block_on(load_world_manifest_with_loader_async(manifest, registry.limits, loader))
+            .unwrap();
+        let mut loaded = World::new();
+        prepared.apply(&mut loaded, &registry).unwrap();
+        assert_eq!(
+            loaded.query::<&TestComponentA>().iter(&loaded).count(),
+            world.query::<&TestComponentA>().iter(&world).count()
+        );
+    }
+
+    #[test]
+    fn test_http_location_parsing() {
+        assert_eq!(
+            AuroraLocation::from("https://cdn.example.com/a.parquet"),
+            AuroraLocation::Http("https://cdn.example.com/a.parquet".to_string())
+        );
+        assert_eq!(url_path("http://h/a.msgpack?v=2#x"), "http://h/a.msgpack");
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_http_blob_loader() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let (mut world, registry) = init_world();
+        let guide = ExportGuidance::embed_all(ExportFormat::MsgPack);
+        let mut manifest = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut served = HashMap::new();
+        for (i, arch) in manifest.world.archetypes.iter_mut().enumerate() {
+            let name = arch.source.0.strip_prefix("embed://").unwrap().to_string();
+            let bytes = manifest.world.embed[&name].decode(u64::MAX).unwrap();
+            served.insert(format!("/arch_{i}.msgpack"), bytes);
+            arch.source = Url(format!("http://{addr}/arch_{i}.msgpack?rev=1"));
+        }
+        manifest.world.embed.clear();
+
+        let requests = served.len();
+        let server = std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut line).unwrap();
+                while reader.read_line(&mut String::new()).unwrap() > 2 {}
+                let path = line.split(' ').nth(1).unwrap().split('?').next().unwrap();
+                let body = &served[path];
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+
+        let mut loaded = World::new();
+        load_world_manifest_with_loader(&mut loaded, &manifest, &registry, &mut HttpBlobLoader::new())
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(
+            loaded.query::<&TestComponentF>().iter(&loaded).count(),
+            world.query::<&TestComponentF>().iter(&world).count()
+        );
+
+        let err = HttpBlobLoader::new().load_blob("arch_0.msgpack").unwrap_err();
+        assert!(matches!(&err, ArchiveError::Io(e) if e.kind() == io::ErrorKind::InvalidInput));
+        assert!(err.to_string().contains("no base URL"));
+        assert_eq!(
+            HttpBlobLoader::with_base_url("http://h/assets/").resolve("/a.csv").unwrap(),
+            "http://h/assets/a.csv"
+        );
+    }
 }
@@ -0,0 +1,108 @@
+//! Registration without `register::<T>()` calls (`derive` feature).
+//!
+//! `#[derive(Snapshot)]` submits a component to a link-time collector, and
+//! [`SnapshotRegistry::with_auto_registered`] registers every component submitted anywhere
+//! in the final binary:
+//!
+//! ```
+//! use bevy_archive::prelude::*;
+//! use bevy_ecs::prelude::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Component, Serialize, Deserialize, Snapshot)]
+//! struct Health(f32);
+//!
+//! #[derive(Component, Serialize, Deserialize, Snapshot)]
+//! #[snapshot(mode = "EmplaceIfNotExists", name = "Team")]
+//! struct TeamId(u8);
+//!
+//! let registry = SnapshotRegistry::with_auto_registered();
+//! assert!(registry.get_factory("Health").is_some());
+//! assert!(registry.get_factory("Team").is_some());
+//! ```
+//!
+//! Typed names follow the [`naming`](SnapshotRegistry::naming) policy of the registry they
+//! are added to. Generic components cannot derive `Snapshot`.
+
+use bevy_ecs::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub use bevy_archive_derive::Snapshot;
+#[doc(hidden)]
+pub use inventory;
+
+use crate::bevy_registry::{SnapshotMode, SnapshotRegistry};
+
+/// A registration submitted by `#[derive(Snapshot)]`.
+pub struct AutoRegistration(pub fn(&mut SnapshotRegistry));
+
+inventory::collect!(AutoRegistration);
+
+/// Register `T` as `#[derive(Snapshot)]` asked for.
+#[doc(hidden)]
+pub fn register_derived<T>(
+    registry: &mut SnapshotRegistry,
+    name: Option<&'static str>,
+    mode: SnapshotMode,
+) where
+    T: Component + Serialize + DeserializeOwned,
+{
+    let name = name.unwrap_or_else(|| registry.naming.name_of::<T>());
+    registry.register_named::<T>(name);
+    if let Some(factory) = registry.get_factory_mut(name) {
+        factory.mode = mode;
+    }
+}
+
+impl SnapshotRegistry {
+    /// A default registry with every `#[derive(Snapshot)]` component registered.
+    pub fn with_auto_registered() -> Self {
+        let mut registry = Self::default();
+        registry.register_auto_registered();
+        registry
+    }
+
+    /// Register every `#[derive(Snapshot)]` component, replacing registrations under the
+    /// same names.
+    pub fn register_auto_registered(&mut self) {
+        for registration in inventory::iter::<AutoRegistration> {
+            (registration.0)(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::archetype_archive::{load_world_arch_snapshot, save_world_arch_snapshot};
+
+    #[derive(Component, Serialize, Deserialize, Snapshot, Debug, PartialEq)]
+    struct Mana(u32);
+
+    #[derive(Component, Serialize, Deserialize, Snapshot, Debug, PartialEq)]
+    #[snapshot(mode = "EmplaceIfNotExists", name = "Faction")]
+    struct FactionId(u8);
+
+    #[test]
+    fn test_auto_registered_components() {
+        let registry = SnapshotRegistry::with_auto_registered();
+        assert!(registry.get_factory("Mana").is_some());
+        assert!(registry.get_factory("FactionId").is_none());
+        let faction = registry.get_factory("Faction").unwrap();
+        assert!(matches!(faction.mode, SnapshotMode::EmplaceIfNotExists));
+
+        let mut world = World::new();
+        world.spawn((Mana(3), FactionId(2)));
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        let mut loaded = World::new();
+        load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        let (mana, faction) = loaded
+            .query::<(&Mana, &FactionId)>()
+            .single(&loaded)
+            .unwrap();
+        assert_eq!((mana, faction), (&Mana(3), &FactionId(2)));
+    }
+}
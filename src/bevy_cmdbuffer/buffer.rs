@@ -1,13 +1,51 @@
 use bevy_ecs::prelude::*;
 use bevy_ecs::ptr::{Aligned, OwningPtr};
+use bevy_ecs::change_detection::MaybeLocation;
 use bevy_ecs::component::ComponentId;
 use bumpalo::Bump;
+use std::alloc::Layout;
 use std::ptr::NonNull;
 use crate::prelude::ArenaBox;
 
 // Safety: Must be called with a pointer to the correct type T.
 pub type DropFn = unsafe fn(OwningPtr<'_, Aligned>);
 
+#[derive(Debug, thiserror::Error)]
+pub enum BufferError {
+    #[error("component id {0:?} is not registered in the target world")]
+    UnknownComponent(ComponentId),
+    #[error("payload type does not match component {name}")]
+    TypeMismatch { name: String },
+    #[error("payload layout {found:?} does not match component {name} ({expected:?})")]
+    LayoutMismatch {
+        name: String,
+        expected: Layout,
+        found: Layout,
+    },
+}
+
+/// Check that `payload` holds a value of the component `comp_id` in `world`. Payloads of
+/// runtime-defined components, which have no Rust type, are checked by layout.
+fn check_payload(world: &World, comp_id: ComponentId, payload: &ArenaBox) -> Result<(), BufferError> {
+    let info = world
+        .components()
+        .get_info(comp_id)
+        .ok_or(BufferError::UnknownComponent(comp_id))?;
+    if info.type_id() != payload.type_id() {
+        return Err(BufferError::TypeMismatch {
+            name: info.name().to_string(),
+        });
+    }
+    if info.layout() != payload.layout() {
+        return Err(BufferError::LayoutMismatch {
+            name: info.name().to_string(),
+            expected: info.layout(),
+            found: payload.layout(),
+        });
+    }
+    Ok(())
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct ArgMeta {
     pub comp_id: ComponentId,
@@ -36,26 +74,288 @@ pub enum OpHead {
         count: u16,
     },
     Despawn(Entity),
+    InsertResource {
+        comp_id: ComponentId,
+        payload_ptr: NonNull<u8>,
+        drop_fn: Option<DropFn>,
+    },
+    RemoveResource(ComponentId),
 }
 
-pub struct HarvardCommandBuffer {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OpKind {
+    ModifyEntity,
+    BatchInsert,
+    RemoveComponents,
+    Despawn,
+    InsertResource,
+    RemoveResource,
+}
+
+/// Number of recorded ops per [`OpKind`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    pub modify_entity: usize,
+    pub batch_insert: usize,
+    pub remove_components: usize,
+    pub despawn: usize,
+    pub insert_resource: usize,
+    pub remove_resource: usize,
+}
+
+impl OpCounts {
+    pub fn total(&self) -> usize {
+        self.modify_entity
+            + self.batch_insert
+            + self.remove_components
+            + self.despawn
+            + self.insert_resource
+            + self.remove_resource
+    }
+}
+
+/// Read-only view of a recorded op, yielded by [`HarvardCommandBuffer::iter_ops`].
+#[derive(Clone, Copy, Debug)]
+pub enum RecordedOp<'a> {
+    ModifyEntity {
+        entity: Entity,
+        args: &'a [ArgMeta],
+    },
+    BatchInsert {
+        entities: &'a [Entity],
+        comp_id: ComponentId,
+    },
+    RemoveComponents {
+        entity: Entity,
+        ids: &'a [ComponentId],
+    },
+    Despawn(Entity),
+    InsertResource(ComponentId),
+    RemoveResource(ComponentId),
+}
+
+impl<'a> RecordedOp<'a> {
+    fn from_head(op: &'a OpHead) -> Self {
+        // SAFETY: every pointer/count pair in an `OpHead` points into `meta_bump`,
+        // which lives at least as long as the borrow of the buffer.
+        match op {
+            OpHead::ModifyEntity { entity, args_ptr, count } => RecordedOp::ModifyEntity {
+                entity: *entity,
+                args: unsafe { std::slice::from_raw_parts(args_ptr.as_ptr(), *count as usize) },
+            },
+            OpHead::BatchInsert { entities_ptr, count, comp_id, .. } => RecordedOp::BatchInsert {
+                entities: unsafe {
+                    std::slice::from_raw_parts(entities_ptr.as_ptr(), *count as usize)
+                },
+                comp_id: *comp_id,
+            },
+            OpHead::RemoveComponents { entity, ids_ptr, count } => RecordedOp::RemoveComponents {
+                entity: *entity,
+                ids: unsafe { std::slice::from_raw_parts(ids_ptr.as_ptr(), *count as usize) },
+            },
+            OpHead::Despawn(entity) => RecordedOp::Despawn(*entity),
+            OpHead::InsertResource { comp_id, .. } => RecordedOp::InsertResource(*comp_id),
+            OpHead::RemoveResource(comp_id) => RecordedOp::RemoveResource(*comp_id),
+        }
+    }
+
+    pub fn kind(&self) -> OpKind {
+        match self {
+            RecordedOp::ModifyEntity { .. } => OpKind::ModifyEntity,
+            RecordedOp::BatchInsert { .. } => OpKind::BatchInsert,
+            RecordedOp::RemoveComponents { .. } => OpKind::RemoveComponents,
+            RecordedOp::Despawn(_) => OpKind::Despawn,
+            RecordedOp::InsertResource(_) => OpKind::InsertResource,
+            RecordedOp::RemoveResource(_) => OpKind::RemoveResource,
+        }
+    }
+
+    /// Entities touched by this op. Empty for resource ops.
+    pub fn entities(&self) -> &[Entity] {
+        match self {
+            RecordedOp::ModifyEntity { entity, .. }
+            | RecordedOp::RemoveComponents { entity, .. }
+            | RecordedOp::Despawn(entity) => std::slice::from_ref(entity),
+            RecordedOp::BatchInsert { entities, .. } => entities,
+            RecordedOp::InsertResource(_) | RecordedOp::RemoveResource(_) => &[],
+        }
+    }
+
+    /// Components (or resources) inserted or removed by this op. Empty for `Despawn`.
+    pub fn component_ids(&self) -> Vec<ComponentId> {
+        match self {
+            RecordedOp::ModifyEntity { args, .. } => args.iter().map(|a| a.comp_id).collect(),
+            RecordedOp::BatchInsert { comp_id, .. } => vec![*comp_id],
+            RecordedOp::RemoveComponents { ids, .. } => ids.to_vec(),
+            RecordedOp::Despawn(_) => Vec::new(),
+            RecordedOp::InsertResource(id) | RecordedOp::RemoveResource(id) => vec![*id],
+        }
+    }
+}
+
+/// Peak usage observed across `apply`/`reset` cycles, for sizing
+/// [`HarvardCommandBuffer::with_capacity`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HighWaterMarks {
+    pub ops: usize,
+    pub meta_bytes: usize,
+    pub data_bytes: usize,
+}
+
+/// Recorded ops and the write-combining state, apart from the data arena so a
+/// [`ArenaRecorder`] can record while the arena is lent out.
+#[derive(Default)]
+struct OpLog {
     ops: Vec<OpHead>,
     meta_bump: Bump,
-    data_bump: Bump,
-    
+
     // Staging for ModifyEntity Write Combining
     pending_entity: Option<Entity>,
     pending_args: Vec<ArgMeta>,
 }
 
+impl OpLog {
+    fn flush(&mut self) {
+        if let Some(entity) = self.pending_entity.take()
+            && !self.pending_args.is_empty()
+        {
+            // Deduplicate pending_args to support "Write Combining" where a later insert overwrites an earlier one.
+            // We must drop the payloads of the overwritten components.
+            let mut i = 0;
+            while i < self.pending_args.len() {
+                let id = self.pending_args[i].comp_id;
+                let mut overwritten = false;
+                // Check if this ID appears later in the list
+                for j in (i + 1)..self.pending_args.len() {
+                    if self.pending_args[j].comp_id == id {
+                        overwritten = true;
+                        break;
+                    }
+                }
+
+                if overwritten {
+                    // This arg is overwritten by a later one. Drop its payload.
+                    if let Some(drop_fn) = self.pending_args[i].drop_fn {
+                        let ptr = unsafe { OwningPtr::new(self.pending_args[i].payload_ptr) };
+                        unsafe { drop_fn(ptr) };
+                    }
+                    // Remove from list. swap_remove is efficient and order doesn't strictly matter for insert_by_ids.
+                    self.pending_args.swap_remove(i);
+                    // Do not increment i, as we swapped in a new element to check.
+                } else {
+                    i += 1;
+                }
+            }
+
+            if !self.pending_args.is_empty() {
+                let slice = self.meta_bump.alloc_slice_copy(&self.pending_args);
+                let count = slice.len() as u16;
+                let args_ptr = unsafe { NonNull::new_unchecked(slice.as_mut_ptr()) };
+                self.ops.push(OpHead::ModifyEntity {
+                    entity,
+                    args_ptr,
+                    count,
+                });
+            }
+            self.pending_args.clear();
+        }
+    }
+
+    fn insert_raw(&mut self, entity: Entity, comp_id: ComponentId, payload_ptr: NonNull<u8>, drop_fn: Option<DropFn>) {
+        if self.pending_entity != Some(entity) {
+            self.flush();
+            self.pending_entity = Some(entity);
+        }
+        self.pending_args.push(ArgMeta {
+            comp_id,
+            payload_ptr,
+            drop_fn,
+        });
+    }
+}
+
+/// Records into a [`HarvardCommandBuffer`] while its data arena is lent out by
+/// [`HarvardCommandBuffer::record`]. Only boxes allocated in that arena can be inserted:
+///
+/// ```compile_fail
+/// # use bevy_archive::bevy_cmdbuffer::HarvardCommandBuffer;
+/// # use bevy_archive::prelude::ArenaBox;
+/// # use bevy_ecs::prelude::*;
+/// # #[derive(Component)]
+/// # struct Health(f32);
+/// # let mut world = World::new();
+/// # let id = world.register_component::<Health>();
+/// # let e = world.spawn_empty().id();
+/// let mut buffer = HarvardCommandBuffer::new();
+/// let other = bumpalo::Bump::new();
+/// let result = buffer.record(|rec| {
+///     rec.insert_box_checked(&world, e, id, ArenaBox::alloc(&other, Health(1.0)))
+/// });
+/// ```
+pub struct ArenaRecorder<'b> {
+    bump: &'b Bump,
+    log: &'b mut OpLog,
+}
+
+impl<'b> ArenaRecorder<'b> {
+    /// The buffer's data arena, for type-erased constructors such as registry factories.
+    pub fn bump(&self) -> &'b Bump {
+        self.bump
+    }
+
+    /// Move `value` into the buffer's data arena.
+    pub fn alloc_component<T: Component>(&mut self, value: T) -> ArenaBox<'b> {
+        ArenaBox::alloc(self.bump, value)
+    }
+
+    /// Record an insert of `payload` without checking it against `comp_id`.
+    ///
+    /// # Safety
+    ///
+    /// `payload` must hold a value of the component `comp_id` of the world this buffer is
+    /// applied to. Use [`insert_box_checked`](Self::insert_box_checked) otherwise.
+    pub unsafe fn insert_box(&mut self, entity: Entity, comp_id: ComponentId, payload: ArenaBox<'b>) {
+        let (ptr, drop_fn) = payload.into_raw();
+        let payload_ptr = NonNull::new(ptr.as_ptr()).expect("ArenaBox ptr is null");
+        self.log.insert_raw(entity, comp_id, payload_ptr, Some(drop_fn));
+    }
+
+    /// Like [`insert_box`](Self::insert_box), but checks that `comp_id` is known to `world`
+    /// and that the payload holds a value of its type. On failure the payload is dropped.
+    pub fn insert_box_checked(
+        &mut self,
+        world: &World,
+        entity: Entity,
+        comp_id: ComponentId,
+        payload: ArenaBox<'b>,
+    ) -> Result<(), BufferError> {
+        if let Err(e) = check_payload(world, comp_id, &payload) {
+            payload.manual_drop();
+            return Err(e);
+        }
+        // SAFETY: checked above.
+        unsafe { self.insert_box(entity, comp_id, payload) };
+        Ok(())
+    }
+}
+
+pub struct HarvardCommandBuffer {
+    log: OpLog,
+    data_bump: Bump,
+
+    // Arenas taken over from appended buffers; their ops point into them until reset.
+    retired: Vec<Bump>,
+
+    high_water: HighWaterMarks,
+}
+
 impl Default for HarvardCommandBuffer {
     fn default() -> Self {
         Self {
-            ops: Vec::new(),
-            meta_bump: Bump::new(),
+            log: OpLog::default(),
             data_bump: Bump::new(),
-            pending_entity: None,
-            pending_args: Vec::new(),
+            retired: Vec::new(),
+            high_water: HighWaterMarks::default(),
         }
     }
 }
@@ -66,7 +366,7 @@ impl Drop for HarvardCommandBuffer {
         // we must run the destructors for the payloads.
         
         // 1. Pending args
-        for arg in &self.pending_args {
+        for arg in &self.log.pending_args {
             if let Some(drop_fn) = arg.drop_fn {
                 // Safety: We still own the data in data_bump (it hasn't been dropped yet)
                 // and we haven't applied it to the world.
@@ -76,7 +376,7 @@ impl Drop for HarvardCommandBuffer {
         }
         
         // 2. Ops
-        for op in &self.ops {
+        for op in &self.log.ops {
             match op {
                 OpHead::ModifyEntity { args_ptr, count, .. } => {
                     let args = unsafe { std::slice::from_raw_parts(args_ptr.as_ptr(), *count as usize) };
@@ -97,7 +397,10 @@ impl Drop for HarvardCommandBuffer {
                         }
                     }
                 }
-                // RemoveComponents and Despawn don't hold owned payloads that need dropping.
+                OpHead::InsertResource { payload_ptr, drop_fn: Some(drop_fn), .. } => {
+                    unsafe { drop_fn(OwningPtr::new(*payload_ptr)) };
+                }
+                // RemoveComponents, Despawn and RemoveResource don't hold owned payloads.
                 _ => {}
             }
         }
@@ -109,54 +412,115 @@ impl HarvardCommandBuffer {
         Self::default()
     }
 
+    /// Pre-size the instruction vector and both arenas.
+    pub fn with_capacity(ops: usize, meta_bytes: usize, data_bytes: usize) -> Self {
+        let mut buffer = Self::default();
+        buffer.log.ops = Vec::with_capacity(ops);
+        buffer.log.meta_bump = Bump::with_capacity(meta_bytes);
+        buffer.data_bump = Bump::with_capacity(data_bytes);
+        buffer
+    }
+
+    /// Make room for at least `ops` more ops and the given number of arena bytes
+    /// without growing mid-frame.
+    ///
+    /// An arena whose current chunk is too small is swapped for a fresh one; the old arena
+    /// is kept alive until [`reset`](Self::reset) if recorded ops still point into it.
+    pub fn reserve(&mut self, ops: usize, meta_bytes: usize, data_bytes: usize) {
+        self.log.ops.reserve(ops);
+        let idle = self.is_empty();
+        for (bump, bytes) in [
+            (&mut self.log.meta_bump, meta_bytes),
+            (&mut self.data_bump, data_bytes),
+        ] {
+            if bump.chunk_capacity() < bytes {
+                let old = std::mem::replace(bump, Bump::with_capacity(bytes));
+                if !idle {
+                    self.retired.push(old);
+                }
+            }
+        }
+    }
+
+    /// Peak op count and arena usage seen so far, including the current contents.
+    pub fn high_water_marks(&self) -> HighWaterMarks {
+        let current = self.current_usage();
+        HighWaterMarks {
+            ops: self.high_water.ops.max(current.ops),
+            meta_bytes: self.high_water.meta_bytes.max(current.meta_bytes),
+            data_bytes: self.high_water.data_bytes.max(current.data_bytes),
+        }
+    }
+
+    fn current_usage(&self) -> HighWaterMarks {
+        HighWaterMarks {
+            ops: self.len(),
+            meta_bytes: self.log.meta_bump.allocated_bytes() - self.log.meta_bump.chunk_capacity(),
+            data_bytes: self.data_bump.allocated_bytes() - self.data_bump.chunk_capacity(),
+        }
+    }
+
+    fn record_high_water(&mut self) {
+        self.high_water = self.high_water_marks();
+    }
+
     pub fn data_bump(&self) -> &Bump {
         &self.data_bump
     }
 
-    fn flush(&mut self) {
-        if let Some(entity) = self.pending_entity.take() {
-            if !self.pending_args.is_empty() {
-                // Deduplicate pending_args to support "Write Combining" where a later insert overwrites an earlier one.
-                // We must drop the payloads of the overwritten components.
-                let mut i = 0;
-                while i < self.pending_args.len() {
-                    let id = self.pending_args[i].comp_id;
-                    let mut overwritten = false;
-                    // Check if this ID appears later in the list
-                    for j in (i + 1)..self.pending_args.len() {
-                        if self.pending_args[j].comp_id == id {
-                            overwritten = true;
-                            break;
-                        }
-                    }
+    /// Number of recorded ops, counting a not-yet-flushed write-combined insert as one.
+    pub fn len(&self) -> usize {
+        self.log.ops.len() + usize::from(self.has_pending())
+    }
 
-                    if overwritten {
-                        // This arg is overwritten by a later one. Drop its payload.
-                        if let Some(drop_fn) = self.pending_args[i].drop_fn {
-                            let ptr = unsafe { OwningPtr::new(self.pending_args[i].payload_ptr) };
-                            unsafe { drop_fn(ptr) };
-                        }
-                        // Remove from list. swap_remove is efficient and order doesn't strictly matter for insert_by_ids.
-                        self.pending_args.swap_remove(i);
-                        // Do not increment i, as we swapped in a new element to check.
-                    } else {
-                        i += 1;
-                    }
-                }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-                if !self.pending_args.is_empty() {
-                    let slice = self.meta_bump.alloc_slice_copy(&self.pending_args);
-                    let count = slice.len() as u16;
-                    let args_ptr = unsafe { NonNull::new_unchecked(slice.as_mut_ptr()) };
-                    self.ops.push(OpHead::ModifyEntity {
-                        entity,
-                        args_ptr,
-                        count,
-                    });
-                }
-                self.pending_args.clear();
+    pub fn op_counts(&self) -> OpCounts {
+        let mut counts = OpCounts::default();
+        for op in self.iter_ops() {
+            match op.kind() {
+                OpKind::ModifyEntity => counts.modify_entity += 1,
+                OpKind::BatchInsert => counts.batch_insert += 1,
+                OpKind::RemoveComponents => counts.remove_components += 1,
+                OpKind::Despawn => counts.despawn += 1,
+                OpKind::InsertResource => counts.insert_resource += 1,
+                OpKind::RemoveResource => counts.remove_resource += 1,
             }
         }
+        counts
+    }
+
+    /// Bytes currently held by both arenas and the instruction vector.
+    pub fn bytes_allocated(&self) -> usize {
+        self.log.meta_bump.allocated_bytes()
+            + self.data_bump.allocated_bytes()
+            + self.retired.iter().map(Bump::allocated_bytes).sum::<usize>()
+            + self.log.ops.capacity() * std::mem::size_of::<OpHead>()
+            + self.log.pending_args.capacity() * std::mem::size_of::<ArgMeta>()
+    }
+
+    /// Iterate over recorded ops in application order, including the pending
+    /// write-combined insert (before deduplication).
+    pub fn iter_ops(&self) -> impl Iterator<Item = RecordedOp<'_>> + '_ {
+        let pending = self
+            .log
+            .pending_entity
+            .filter(|_| self.has_pending())
+            .map(|entity| RecordedOp::ModifyEntity {
+                entity,
+                args: &self.log.pending_args,
+            });
+        self.log.ops.iter().map(RecordedOp::from_head).chain(pending)
+    }
+
+    fn has_pending(&self) -> bool {
+        self.log.pending_entity.is_some() && !self.log.pending_args.is_empty()
+    }
+
+    fn flush(&mut self) {
+        self.log.flush();
     }
 
     pub fn insert_generic<T: Component>(&mut self, world: &World, entity: Entity, component: T) {
@@ -230,10 +594,10 @@ impl HarvardCommandBuffer {
         let drop_fn: DropFn = |ptr| unsafe { ptr.drop_as::<T>() };
         
         // Copy entities to meta_bump
-        let entities_slice = self.meta_bump.alloc_slice_copy(entities);
+        let entities_slice = self.log.meta_bump.alloc_slice_copy(entities);
         let entities_ptr = unsafe { NonNull::new_unchecked(entities_slice.as_mut_ptr()) };
 
-        self.ops.push(OpHead::BatchInsert {
+        self.log.ops.push(OpHead::BatchInsert {
             entities_ptr,
             payload_ptr,
             count: count as u32,
@@ -250,24 +614,100 @@ impl HarvardCommandBuffer {
     // User asked for `insert<T>`.
     // I'll rename the old `insert` to `insert_box`.
     
+    /// Record an insert of `payload`, which may live in any arena. That arena must outlive
+    /// the recorded op, and the payload is not checked against `comp_id`; prefer
+    /// [`record`](Self::record) with [`ArenaRecorder::insert_box_checked`], which checks
+    /// both.
     pub fn insert_box(&mut self, entity: Entity, comp_id: ComponentId, payload: ArenaBox<'_>) {
-        let ptr = payload.ptr;
-        let drop_fn = unsafe { std::mem::transmute::<_, DropFn>(payload.drop_fn) };
-        let payload_ptr = NonNull::new(ptr.as_ptr() as *mut u8).expect("ArenaBox ptr is null");
+        let (ptr, drop_fn) = payload.into_raw();
+        let payload_ptr = NonNull::new(ptr.as_ptr()).expect("ArenaBox ptr is null");
         self.insert_raw(entity, comp_id, payload_ptr, Some(drop_fn));
     }
 
+    /// Like [`insert_box`](Self::insert_box), but checks that `comp_id` is known to `world`
+    /// and that the payload holds a value of its type. On failure the payload is dropped.
+    pub fn insert_box_checked(
+        &mut self,
+        world: &World,
+        entity: Entity,
+        comp_id: ComponentId,
+        payload: ArenaBox<'_>,
+    ) -> Result<(), BufferError> {
+        if let Err(e) = check_payload(world, comp_id, &payload) {
+            payload.manual_drop();
+            return Err(e);
+        }
+        self.insert_box(entity, comp_id, payload);
+        Ok(())
+    }
+
+    /// Lend the data arena to `f` along with an [`ArenaRecorder`] recording into this
+    /// buffer, so payloads can be allocated and inserted with a type check and without
+    /// `unsafe`.
+    ///
+    /// ```
+    /// # use bevy_archive::bevy_cmdbuffer::HarvardCommandBuffer;
+    /// # use bevy_ecs::prelude::*;
+    /// # #[derive(Component)]
+    /// # struct Health(f32);
+    /// let mut world = World::new();
+    /// let id = world.register_component::<Health>();
+    /// let e = world.spawn_empty().id();
+    ///
+    /// let mut buffer = HarvardCommandBuffer::new();
+    /// buffer.record(|rec| {
+    ///     let payload = rec.alloc_component(Health(1.0));
+    ///     rec.insert_box_checked(&world, e, id, payload)
+    /// })
+    /// .unwrap();
+    /// buffer.apply(&mut world);
+    /// assert_eq!(world.get::<Health>(e).unwrap().0, 1.0);
+    /// ```
+    pub fn record<R>(&mut self, f: impl for<'b> FnOnce(&mut ArenaRecorder<'b>) -> R) -> R {
+        f(&mut ArenaRecorder {
+            bump: &self.data_bump,
+            log: &mut self.log,
+        })
+    }
+
+    /// Build a payload directly in the data arena and record it as a checked insert.
+    ///
+    /// ```
+    /// # use bevy_archive::bevy_cmdbuffer::HarvardCommandBuffer;
+    /// # use bevy_archive::prelude::ArenaBox;
+    /// # use bevy_ecs::prelude::*;
+    /// # #[derive(Component)]
+    /// # struct Health(f32);
+    /// let mut world = World::new();
+    /// let id = world.register_component::<Health>();
+    /// let e = world.spawn_empty().id();
+    ///
+    /// let mut buffer = HarvardCommandBuffer::new();
+    /// buffer
+    ///     .insert_with(&world, e, id, |bump| ArenaBox::alloc(bump, Health(1.0)))
+    ///     .unwrap();
+    /// buffer.apply(&mut world);
+    /// assert_eq!(world.get::<Health>(e).unwrap().0, 1.0);
+    /// ```
+    pub fn insert_with<F>(
+        &mut self,
+        world: &World,
+        entity: Entity,
+        comp_id: ComponentId,
+        ctor: F,
+    ) -> Result<(), BufferError>
+    where
+        F: for<'b> FnOnce(&'b Bump) -> ArenaBox<'b>,
+    {
+        self.record(|rec| {
+            let payload = ctor(rec.bump());
+            rec.insert_box_checked(world, entity, comp_id, payload)
+        })
+    }
+
     // Helper for low-level insert
     fn insert_raw(&mut self, entity: Entity, comp_id: ComponentId, payload_ptr: NonNull<u8>, drop_fn: Option<DropFn>) {
-        if self.pending_entity != Some(entity) {
-            self.flush();
-            self.pending_entity = Some(entity);
-        }
-        self.pending_args.push(ArgMeta {
-            comp_id,
-            payload_ptr,
-            drop_fn,
-        });
+        self.log.insert_raw(entity, comp_id, payload_ptr, drop_fn);
     }
 
     // Renamed remove to remove_raw
@@ -276,24 +716,78 @@ impl HarvardCommandBuffer {
         if components.is_empty() {
             return;
         }
-        let slice = self.meta_bump.alloc_slice_copy(components);
+        let slice = self.log.meta_bump.alloc_slice_copy(components);
         let ids_ptr = unsafe { NonNull::new_unchecked(slice.as_mut_ptr()) };
-        self.ops.push(OpHead::RemoveComponents {
+        self.log.ops.push(OpHead::RemoveComponents {
             entity,
             ids_ptr,
             count: slice.len() as u16,
         });
     }
 
+    /// Move all ops recorded in `other` to the end of this buffer, leaving `other` empty.
+    ///
+    /// Payloads are not copied: `other`'s arenas are taken over and kept alive until the
+    /// next [`reset`](Self::reset), so per-system buffers can be coalesced and applied in
+    /// one pass.
+    pub fn append(&mut self, other: &mut HarvardCommandBuffer) {
+        self.flush();
+        other.flush();
+        if other.log.ops.is_empty() {
+            return;
+        }
+        self.log.ops.append(&mut other.log.ops);
+        self.retired.push(std::mem::take(&mut other.log.meta_bump));
+        self.retired.push(std::mem::take(&mut other.data_bump));
+        self.retired.append(&mut other.retired);
+    }
+
     pub fn despawn(&mut self, entity: Entity) {
         self.flush();
-        self.ops.push(OpHead::Despawn(entity));
+        self.log.ops.push(OpHead::Despawn(entity));
+    }
+
+    pub fn insert_resource<R: Resource>(&mut self, world: &World, value: R) {
+        let comp_id = world.component_id::<R>().expect("Resource not registered");
+        self.flush();
+        let ptr = self.data_bump.alloc(value) as *mut R;
+        let payload_ptr = unsafe { NonNull::new_unchecked(ptr as *mut u8) };
+        let drop_fn: DropFn = |ptr| unsafe { ptr.drop_as::<R>() };
+        self.log.ops.push(OpHead::InsertResource {
+            comp_id,
+            payload_ptr,
+            drop_fn: Some(drop_fn),
+        });
+    }
+
+    /// Record a type-erased resource insert. `comp_id` must be the resource's id in the
+    /// world this buffer is applied to.
+    pub fn insert_resource_box(&mut self, comp_id: ComponentId, payload: ArenaBox<'_>) {
+        self.flush();
+        let (ptr, drop_fn) = payload.into_raw();
+        let payload_ptr = NonNull::new(ptr.as_ptr()).expect("ArenaBox ptr is null");
+        self.log.ops.push(OpHead::InsertResource {
+            comp_id,
+            payload_ptr,
+            drop_fn: Some(drop_fn),
+        });
+    }
+
+    pub fn remove_resource<R: Resource>(&mut self, world: &World) {
+        let comp_id = world.component_id::<R>().expect("Resource not registered");
+        self.remove_resource_by_id(comp_id);
+    }
+
+    pub fn remove_resource_by_id(&mut self, comp_id: ComponentId) {
+        self.flush();
+        self.log.ops.push(OpHead::RemoveResource(comp_id));
     }
 
     pub fn apply(&mut self, world: &mut World) {
         self.flush();
+        self.record_high_water();
 
-        for op in &self.ops {
+        for op in &self.log.ops {
             match op {
                 OpHead::ModifyEntity { entity, args_ptr, count } => {
                     let args = unsafe { std::slice::from_raw_parts(args_ptr.as_ptr(), *count as usize) };
@@ -302,11 +796,17 @@ impl HarvardCommandBuffer {
                     let ptrs = args.iter().map(|a| unsafe { OwningPtr::new(a.payload_ptr) });
                     
                     // Ensure entity is spawned (alloc_many only allocates indices, meta not extended)
-                    let _ = world.spawn_empty_at(*entity);
-                    let mut entity_mut = world.entity_mut(*entity);
+                    let Some(mut entity_mut) = spawned_entity_mut(world, *entity) else {
+                        for arg in args {
+                            if let Some(drop_fn) = arg.drop_fn {
+                                unsafe { drop_fn(OwningPtr::new(arg.payload_ptr)) };
+                            }
+                        }
+                        continue;
+                    };
                     unsafe { entity_mut.insert_by_ids(&ids, ptrs) };
                 }
-                OpHead::BatchInsert { entities_ptr, payload_ptr, count, comp_id, stride, .. } => {
+                OpHead::BatchInsert { entities_ptr, payload_ptr, count, comp_id, stride, drop_fn } => {
                     let entities = unsafe { std::slice::from_raw_parts(entities_ptr.as_ptr(), *count as usize) };
                     // We need to iterate entities and payload simultaneously.
                     // world.insert_batch expects IntoIterator<Item=(Entity, Bundle)>.
@@ -341,10 +841,17 @@ impl HarvardCommandBuffer {
                     
                     let mut ptr = payload_ptr.as_ptr();
                     for &entity in entities {
-                        let _ = world.spawn_empty_at(entity);
                         let owning_ptr = unsafe { OwningPtr::new(NonNull::new_unchecked(ptr)) };
-                        let mut entity_mut = world.entity_mut(entity);
-                        unsafe { entity_mut.insert_by_id(*comp_id, owning_ptr) };
+                        match spawned_entity_mut(world, entity) {
+                            Some(mut entity_mut) => {
+                                unsafe { entity_mut.insert_by_id(*comp_id, owning_ptr) };
+                            }
+                            None => {
+                                if let Some(drop_fn) = drop_fn {
+                                    unsafe { drop_fn(owning_ptr) };
+                                }
+                            }
+                        }
                         ptr = unsafe { ptr.add(*stride) };
                     }
                 }
@@ -357,24 +864,32 @@ impl HarvardCommandBuffer {
                 OpHead::Despawn(entity) => {
                      world.despawn(*entity);
                 }
+                OpHead::InsertResource { comp_id, payload_ptr, .. } => {
+                    let ptr = unsafe { OwningPtr::new(*payload_ptr) };
+                    unsafe { world.insert_resource_by_id(*comp_id, ptr, MaybeLocation::caller()) };
+                }
+                OpHead::RemoveResource(comp_id) => {
+                    world.remove_resource_by_id(*comp_id);
+                }
             }
         }
 
-        self.ops.clear();
-        self.pending_args.clear();
-        self.pending_entity = None;
+        self.log.ops.clear();
+        self.log.pending_args.clear();
+        self.log.pending_entity = None;
     }
 
     pub fn reset(&mut self) {
+        self.record_high_water();
         // Drop unapplied data
-        for arg in &self.pending_args {
+        for arg in &self.log.pending_args {
             if let Some(drop_fn) = arg.drop_fn {
                 let ptr = unsafe { OwningPtr::new(arg.payload_ptr) };
                 unsafe { drop_fn(ptr) };
             }
         }
         
-        for op in &self.ops {
+        for op in &self.log.ops {
             match op {
                 OpHead::ModifyEntity { args_ptr, count, .. } => {
                     let args = unsafe { std::slice::from_raw_parts(args_ptr.as_ptr(), *count as usize) };
@@ -395,16 +910,32 @@ impl HarvardCommandBuffer {
                         }
                     }
                 }
+                OpHead::InsertResource { payload_ptr, drop_fn: Some(drop_fn), .. } => {
+                    unsafe { drop_fn(OwningPtr::new(*payload_ptr)) };
+                }
                 _ => {}
             }
         }
 
-        self.ops.clear();
-        self.pending_args.clear();
-        self.pending_entity = None;
-        self.meta_bump.reset();
+        self.log.ops.clear();
+        self.log.pending_args.clear();
+        self.log.pending_entity = None;
+        self.log.meta_bump.reset();
         self.data_bump.reset();
+        self.retired.clear();
+    }
+}
+
+/// `entity`, spawning it first when its slot is allocated but not alive yet. Reports and
+/// returns `None` when the slot cannot be spawned, e.g. `entity` has a stale generation.
+fn spawned_entity_mut(world: &mut World, entity: Entity) -> Option<EntityWorldMut<'_>> {
+    if world.get_entity(entity).is_err()
+        && let Err(err) = world.spawn_empty_at(entity)
+    {
+        archive_warn!("skipping buffered insert into {entity}: {err}");
+        return None;
     }
+    world.get_entity_mut(entity).ok()
 }
 
 #[cfg(test)]
@@ -417,6 +948,11 @@ mod tests {
     #[derive(Component)]
     struct B(String);
 
+    // Same layout as `A`.
+    #[derive(Component)]
+    #[allow(dead_code)]
+    struct C(u32);
+
     #[test]
     fn test_reset_and_reuse() {
         let mut world = World::new();
@@ -486,6 +1022,163 @@ mod tests {
         assert!(world.entity(e1).get::<B>().is_some());
     }
 
+    #[test]
+    fn test_checked_insert() {
+        let mut world = World::new();
+        let id_a = world.register_component::<A>();
+        let id_b = world.register_component::<B>();
+        let id_c = world.register_component::<C>();
+        let e1 = world.spawn_empty().id();
+
+        let mut buffer = HarvardCommandBuffer::new();
+        buffer
+            .insert_with(&world, e1, id_a, |bump| ArenaBox::alloc(bump, A(7)))
+            .unwrap();
+
+        // `A` is not `B`; the payload is dropped and nothing is recorded.
+        let err = buffer.insert_with(&world, e1, id_b, |bump| ArenaBox::alloc(bump, A(8)));
+        assert!(matches!(err, Err(BufferError::TypeMismatch { .. })));
+
+        // Same layout, different type.
+        let err = buffer.insert_with(&world, e1, id_c, |bump| ArenaBox::alloc(bump, A(9)));
+        assert!(matches!(err, Err(BufferError::TypeMismatch { .. })));
+
+        let unknown = ComponentId::new(10_000);
+        let err = buffer.record(|rec| {
+            let payload = rec.alloc_component(B("x".into()));
+            rec.insert_box_checked(&world, e1, unknown, payload)
+        });
+        assert!(matches!(err, Err(BufferError::UnknownComponent(_))));
+
+        buffer.apply(&mut world);
+        assert_eq!(world.entity(e1).get::<A>().unwrap().0, 7);
+        assert!(world.entity(e1).get::<B>().is_none());
+    }
+
+    #[test]
+    fn test_introspection() {
+        let mut world = World::new();
+        let id_a = world.register_component::<A>();
+        let id_b = world.register_component::<B>();
+        let e1 = world.spawn_empty().id();
+        let e2 = world.spawn_empty().id();
+
+        let mut buffer = HarvardCommandBuffer::new();
+        assert!(buffer.is_empty());
+
+        buffer.insert(&world, e1, A(1));
+        buffer.insert(&world, e1, B("b".into()));
+        assert_eq!(buffer.len(), 1);
+
+        buffer.insert_batch(&world, &[e1, e2], vec![A(2), A(3)]);
+        buffer.remove::<B>(&world, e1);
+        buffer.despawn(e2);
+
+        let counts = buffer.op_counts();
+        assert_eq!(
+            counts,
+            OpCounts {
+                modify_entity: 1,
+                batch_insert: 1,
+                remove_components: 1,
+                despawn: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(counts.total(), buffer.len());
+        assert!(buffer.bytes_allocated() > 0);
+
+        let ops: Vec<_> = buffer.iter_ops().collect();
+        assert_eq!(ops[0].kind(), OpKind::ModifyEntity);
+        assert_eq!(ops[0].component_ids(), vec![id_a, id_b]);
+        assert_eq!(ops[1].entities(), &[e1, e2]);
+        assert_eq!(ops[2].component_ids(), vec![id_b]);
+        assert_eq!(ops[3].entities(), &[e2]);
+
+        buffer.apply(&mut world);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_append() {
+        let mut world = World::new();
+        world.register_component::<A>();
+        world.register_component::<B>();
+        let e1 = world.spawn_empty().id();
+        let e2 = world.spawn_empty().id();
+
+        let mut main = HarvardCommandBuffer::new();
+        main.insert(&world, e1, A(1));
+
+        let mut system = HarvardCommandBuffer::new();
+        system.insert(&world, e1, A(2));
+        system.insert(&world, e2, B("appended".into()));
+
+        main.append(&mut system);
+        assert!(system.is_empty());
+        assert_eq!(main.len(), 3);
+
+        // The donor buffer stays usable after being drained.
+        system.insert(&world, e2, A(3));
+        system.apply(&mut world);
+        drop(system);
+
+        main.apply(&mut world);
+        assert_eq!(world.entity(e1).get::<A>().unwrap().0, 2);
+        assert_eq!(world.entity(e2).get::<B>().unwrap().0, "appended");
+        assert_eq!(world.entity(e2).get::<A>().unwrap().0, 3);
+        main.reset();
+    }
+
+    #[test]
+    fn test_capacity_and_high_water() {
+        let mut world = World::new();
+        world.register_component::<A>();
+        let entities: Vec<Entity> = (0..16).map(|_| world.spawn_empty().id()).collect();
+
+        let mut buffer = HarvardCommandBuffer::with_capacity(32, 1024, 4096);
+        assert!(buffer.bytes_allocated() >= 1024 + 4096);
+
+        for (i, &e) in entities.iter().enumerate() {
+            buffer.insert(&world, e, A(i as u32));
+        }
+        buffer.apply(&mut world);
+        buffer.reset();
+
+        let marks = buffer.high_water_marks();
+        assert_eq!(marks.ops, entities.len());
+        assert!(marks.data_bytes >= entities.len() * std::mem::size_of::<A>());
+        assert!(marks.meta_bytes > 0);
+
+        // Growing while ops are pending must keep the recorded payloads alive.
+        buffer.insert(&world, entities[0], A(100));
+        buffer.reserve(8, 1 << 16, 1 << 16);
+        buffer.apply(&mut world);
+        assert_eq!(world.entity(entities[0]).get::<A>().unwrap().0, 100);
+    }
+
+    #[derive(Resource, Debug, PartialEq)]
+    struct Score(u32);
+
+    #[test]
+    fn test_resource_ops() {
+        let mut world = World::new();
+        world.register_component::<Score>();
+
+        let mut buffer = HarvardCommandBuffer::new();
+        buffer.insert_resource(&world, Score(1));
+        buffer.insert_resource(&world, Score(2));
+        assert_eq!(buffer.op_counts().insert_resource, 2);
+        buffer.apply(&mut world);
+        assert_eq!(world.resource::<Score>(), &Score(2));
+
+        buffer.reset();
+        buffer.remove_resource::<Score>(&world);
+        assert_eq!(buffer.iter_ops().next().unwrap().kind(), OpKind::RemoveResource);
+        buffer.apply(&mut world);
+        assert!(!world.contains_resource::<Score>());
+    }
+
     #[test]
     fn test_drop_safety() {
         use std::sync::atomic::{AtomicU32, Ordering};
@@ -520,4 +1213,29 @@ mod tests {
 
         assert_eq!(DROP_COUNT.load(Ordering::SeqCst), 1);
     }
+
+    #[test]
+    fn test_apply_skips_despawned_entity() {
+        use std::sync::Arc;
+
+        #[derive(Component)]
+        struct Shared(#[allow(dead_code)] Arc<()>);
+
+        let mut world = World::new();
+        world.register_component::<Shared>();
+        let alive = world.spawn_empty().id();
+        let missing = world.spawn_empty().id();
+        world.despawn(missing);
+        let value = Arc::new(());
+
+        let mut buffer = HarvardCommandBuffer::new();
+        buffer.insert(&world, missing, Shared(value.clone()));
+        buffer.insert_batch(&world, &[missing, alive], [Shared(value.clone()), Shared(value.clone())]);
+        buffer.apply(&mut world);
+
+        assert!(world.get_entity(missing).is_err());
+        assert!(world.entity(alive).contains::<Shared>());
+        // Only the payload inserted into `alive` is still held.
+        assert_eq!(Arc::strong_count(&value), 2);
+    }
 }
\ No newline at end of file
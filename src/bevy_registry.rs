@@ -1,33 +1,79 @@
 use bevy_ecs::ptr::{Aligned, OwningPtr, PtrMut};
-use bevy_ecs::{component::ComponentId, prelude::*};
+use bevy_ecs::component::{ComponentDescriptor, ComponentId};
+use bevy_ecs::prelude::*;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use bumpalo::Bump;
+use std::alloc::Layout;
 use std::any::TypeId;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ptr::NonNull;
+use std::sync::Arc;
 mod snapshot_factory;
 #[cfg(feature = "arrow_rs")]
 pub mod vec_snapshot_factory;
 pub use snapshot_factory::*;
 
+use crate::error::ArchiveError;
 use crate::prelude::codec::JsonValueCodec;
+use crate::prelude::dynamic::{DynamicCodec, DynamicComponent, DynamicComponentIds};
 
 //this is a workaround
 //it allows to have a type erased box that can drop the inner type correctly
 //it must be dropped manually or it will leak memory.
 pub struct ArenaBox<'a> {
-    pub ptr: OwningPtr<'a, Aligned>,
-    pub drop_fn: unsafe fn(OwningPtr<'a, Aligned>),
+    ptr: OwningPtr<'a, Aligned>,
+    drop_fn: unsafe fn(OwningPtr<'_, Aligned>),
+    /// Layout of the erased value, used to validate the payload against a `ComponentId`.
+    layout: Layout,
+    /// Type of the erased value; `None` for runtime-defined components without one.
+    type_id: Option<TypeId>,
 }
 impl<'a> ArenaBox<'a> {
-    pub fn new<T>(ptr: OwningPtr<'a, Aligned>) -> Self {
+    pub fn new<T: 'static>(ptr: OwningPtr<'a, Aligned>) -> Self {
         Self {
             ptr,
             drop_fn: |ptr| unsafe {
                 ptr.drop_as::<T>();
             },
+            layout: Layout::new::<T>(),
+            type_id: Some(TypeId::of::<T>()),
         }
     }
+    /// Move `value` into `bump` and wrap it, without any unsafe code at the call site.
+    pub fn alloc<T: 'static>(bump: &'a Bump, value: T) -> Self {
+        let ptr = bump.alloc(value) as *mut T;
+        // SAFETY: the pointer comes from a live bump allocation of a `T`.
+        Self::new::<T>(unsafe { OwningPtr::new(NonNull::new_unchecked(ptr.cast())) })
+    }
+    /// Wrap a value of a runtime-defined component that has no Rust type.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid value of `layout` that `drop_fn` can drop.
+    pub(crate) unsafe fn from_raw_parts(
+        ptr: OwningPtr<'a, Aligned>,
+        drop_fn: unsafe fn(OwningPtr<'_, Aligned>),
+        layout: Layout,
+        type_id: Option<TypeId>,
+    ) -> Self {
+        Self {
+            ptr,
+            drop_fn,
+            layout,
+            type_id,
+        }
+    }
+    /// Give up ownership of the value; the caller becomes responsible for dropping it.
+    pub(crate) fn into_raw(self) -> (OwningPtr<'a, Aligned>, unsafe fn(OwningPtr<'_, Aligned>)) {
+        (self.ptr, self.drop_fn)
+    }
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+    pub fn type_id(&self) -> Option<TypeId> {
+        self.type_id
+    }
     pub fn manual_drop(self) {
         unsafe { (self.drop_fn)(self.ptr) }
     }
@@ -85,6 +131,7 @@ impl EntityRemapper for HashMap<u32, Entity> {
     }
 }
 
+use crate::archetype_archive::StorageTypeFlag;
 use crate::bevy_cmdbuffer::HarvardCommandBuffer;
 
 /// Emulate the old `Entities::reserve_entities(n)` from Bevy 0.17.
@@ -110,14 +157,218 @@ pub fn reserve_entity_slots(world: &mut World, max_index: u32) {
     }
 }
 
+/// Caps applied by every loader before it reserves entities or decodes blobs, so a corrupted
+/// or hostile snapshot fails fast instead of exhausting memory.
+///
+/// Loaders read the limits from [`SnapshotRegistry::limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadLimits {
+    /// Largest number of entity slots a load may reserve (highest index + 1).
+    pub max_entities: u32,
+    /// Largest single blob (archetype table, resource, zip entry) in bytes.
+    pub max_blob_bytes: u64,
+    /// Largest number of archetypes in one snapshot.
+    pub max_archetypes: usize,
+}
+
+impl Default for LoadLimits {
+    fn default() -> Self {
+        Self {
+            max_entities: 1 << 24,
+            max_blob_bytes: 1 << 30,
+            max_archetypes: 1 << 16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LimitError {
+    #[error("snapshot needs {requested} entity slots, limit is {max}")]
+    TooManyEntities { requested: u64, max: u32 },
+    #[error("blob of {size} bytes exceeds limit of {max} bytes")]
+    BlobTooLarge { size: u64, max: u64 },
+    #[error("snapshot has {count} archetypes, limit is {max}")]
+    TooManyArchetypes { count: usize, max: usize },
+    /// A [strict](LoadOptions::strict) load met a type with no registered factory.
+    #[error("no factory registered for '{0}'")]
+    UnknownType(String),
+    /// The saved schema hash of a component differs from its registered type, see
+    /// [`SnapshotRegistry::check_schema_hashes`].
+    #[error("component '{0}' schema changed since this save was written")]
+    SchemaChanged(String),
+    /// The [registry schema](crate::registry_schema) saved with a snapshot does not match
+    /// the registry loading it.
+    #[error("snapshot does not match the registry: {0}")]
+    IncompatibleSchema(crate::registry_schema::SchemaMismatch),
+    /// An archetype has two columns of one component under [`DuplicatePolicy::Error`].
+    #[error("component '{0}' appears twice in one archetype")]
+    DuplicateComponent(String),
+}
+
+impl LoadLimits {
+    /// No limits at all; only use with trusted snapshots.
+    pub fn unlimited() -> Self {
+        Self {
+            max_entities: u32::MAX,
+            max_blob_bytes: u64::MAX,
+            max_archetypes: usize::MAX,
+        }
+    }
+
+    /// Check that `count` entity slots may be reserved.
+    pub fn check_entities(&self, count: u64) -> Result<(), LimitError> {
+        if count > self.max_entities as u64 {
+            return Err(LimitError::TooManyEntities {
+                requested: count,
+                max: self.max_entities,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that a blob of `size` bytes may be read.
+    pub fn check_blob(&self, size: u64) -> Result<(), LimitError> {
+        if size > self.max_blob_bytes {
+            return Err(LimitError::BlobTooLarge {
+                size,
+                max: self.max_blob_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that `count` archetypes may be loaded.
+    pub fn check_archetypes(&self, count: usize) -> Result<(), LimitError> {
+        if count > self.max_archetypes {
+            return Err(LimitError::TooManyArchetypes {
+                count,
+                max: self.max_archetypes,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// What a loader does with a row whose component value fails to deserialize.
+///
+/// Every failure is recorded in the world's
+/// [`LoadReport`](crate::archetype_archive::LoadReport) either way.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RowErrorPolicy {
+    /// Use the component's [row default](SnapshotRegistry::register_row_default); skip the
+    /// entity when none is registered.
+    #[default]
+    DefaultOrSkip,
+    /// Skip the entity: none of its components from that snapshot row are inserted.
+    SkipEntity,
+}
+
+/// What happens when the same component is given twice for one entity, e.g. by a
+/// malformed snapshot with two columns of one component.
+///
+/// Set it on a [`DeferredEntityBuilder`] with
+/// [`with_duplicate_policy`](DeferredEntityBuilder::with_duplicate_policy); loaders read it
+/// from [`SnapshotRegistry::duplicate_components`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// The last value wins, as with write-combined inserts.
+    #[default]
+    ReplaceLast,
+    /// The first value wins; later ones are dropped.
+    KeepFirst,
+    /// Reject the entity. Loaders fail with [`LimitError::DuplicateComponent`] before
+    /// writing anything.
+    Error,
+}
+
+impl DuplicatePolicy {
+    /// Whether the value at `idx` of `names`, the components given for one entity, is the
+    /// one kept under this policy.
+    pub(crate) fn keeps(self, names: &[&str], idx: usize) -> bool {
+        match self {
+            Self::ReplaceLast => !names[idx + 1..].contains(&names[idx]),
+            Self::KeepFirst | Self::Error => !names[..idx].contains(&names[idx]),
+        }
+    }
+}
+
+/// How loaders treat snapshot data the registry does not know.
+///
+/// Loaders read the options from [`SnapshotRegistry::load_options`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Fail with [`LimitError::UnknownType`] before writing anything when the snapshot has
+    /// a component or resource without a factory, instead of skipping it and listing it in
+    /// the [`LoadReport`](crate::archetype_archive::LoadReport).
+    pub strict: bool,
+}
+
+/// [`reserve_entity_slots`] guarded by `limits`.
+pub fn try_reserve_entity_slots(
+    world: &mut World,
+    max_index: u32,
+    limits: &LoadLimits,
+) -> Result<(), LimitError> {
+    limits.check_entities(max_index as u64 + 1)?;
+    reserve_entity_slots(world, max_index);
+    Ok(())
+}
+
+/// Stable-sort `items` so every component comes after the components it requires.
+///
+/// A component's transitive required set always contains the required set of each of its
+/// requirements, so ordering by its size is a valid topological order. Inserting one at a
+/// time in this order lets values from a snapshot win over `#[require]` defaults, which
+/// then only fill components the snapshot does not carry.
+pub fn sort_by_required_components<T>(
+    world: &World,
+    items: &mut [T],
+    comp_id: impl Fn(&T) -> Option<ComponentId>,
+) {
+    items.sort_by_cached_key(|item| {
+        comp_id(item)
+            .and_then(|id| world.components().get_info(id))
+            .map_or(0, |info| info.required_components().iter_ids().count())
+    });
+}
+
+struct StagedArg {
+    id: ComponentId,
+    ptr: NonNull<u8>,
+    drop_fn: unsafe fn(OwningPtr<'_, Aligned>),
+    layout: Layout,
+    type_id: Option<TypeId>,
+    storage: Option<StorageTypeFlag>,
+}
+
+/// Stages the components of a single entity before handing them to a
+/// [`HarvardCommandBuffer`] as one write-combined insert.
+///
+/// A component staged twice is resolved per the builder's [`DuplicatePolicy`].
 pub struct DeferredEntityBuilder<'w> {
     buffer: &'w mut HarvardCommandBuffer,
     entity: Entity,
+    staged: Vec<StagedArg>,
+    duplicates: DuplicatePolicy,
+    /// First component staged twice under [`DuplicatePolicy::Error`].
+    duplicate: Option<ComponentId>,
 }
 
 impl<'w> DeferredEntityBuilder<'w> {
     pub fn new(buffer: &'w mut HarvardCommandBuffer, entity: Entity) -> Self {
-        Self { buffer, entity }
+        Self {
+            buffer,
+            entity,
+            staged: Vec::new(),
+            duplicates: DuplicatePolicy::default(),
+            duplicate: None,
+        }
+    }
+
+    /// Resolve components staged twice per `policy` instead of keeping the last value.
+    pub fn with_duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicates = policy;
+        self
     }
     
     pub fn insert<T: Component>(&mut self, _world: &mut World, _value: T) {
@@ -128,8 +379,21 @@ impl<'w> DeferredEntityBuilder<'w> {
          unimplemented!("Use insert_by_id with ArenaBox");
     }
     
+    /// Stage a component. As with [`HarvardCommandBuffer::insert_box`], the arena holding
+    /// `ptr` must outlive the buffer's ops.
     pub fn insert_by_id(&mut self, id: ComponentId, ptr: ArenaBox<'_>) {
-        self.buffer.insert_box(self.entity, id, ptr);
+        self.stage(id, ptr, None);
+    }
+
+    /// Stage a component together with the storage type it had in the source world.
+    /// The hint is checked by [`commit_with_storage`](Self::commit_with_storage).
+    pub fn insert_by_id_with_storage(
+        &mut self,
+        id: ComponentId,
+        ptr: ArenaBox<'_>,
+        storage: StorageTypeFlag,
+    ) {
+        self.stage(id, ptr, Some(storage));
     }
     
     pub fn insert_if_new_by_id(&mut self, world: &World, id: ComponentId, ptr: ArenaBox<'_>) {
@@ -139,9 +403,175 @@ impl<'w> DeferredEntityBuilder<'w> {
         }
         self.insert_by_id(id, ptr);
     }
-    
-    pub fn commit(self) {
-        // No-op, buffer handles it on flush/apply
+
+    fn stage(&mut self, id: ComponentId, ptr: ArenaBox<'_>, storage: Option<StorageTypeFlag>) {
+        if let Some(pos) = self.staged.iter().position(|arg| arg.id == id) {
+            match self.duplicates {
+                DuplicatePolicy::ReplaceLast => drop_staged(vec![self.staged.remove(pos)]),
+                DuplicatePolicy::KeepFirst => {
+                    ptr.manual_drop();
+                    return;
+                }
+                DuplicatePolicy::Error => {
+                    ptr.manual_drop();
+                    self.duplicate.get_or_insert(id);
+                    return;
+                }
+            }
+        }
+        let (layout, type_id) = (ptr.layout(), ptr.type_id());
+        let (ptr, drop_fn) = ptr.into_raw();
+        let ptr = NonNull::new(ptr.as_ptr()).expect("ArenaBox ptr is null");
+        self.staged.push(StagedArg {
+            id,
+            ptr,
+            drop_fn,
+            layout,
+            type_id,
+            storage,
+        });
+    }
+
+    fn record(&mut self, staged: Vec<StagedArg>) {
+        for arg in staged {
+            // SAFETY: the payload was produced by an `ArenaBox` and is still owned by us.
+            let payload = unsafe {
+                ArenaBox::from_raw_parts(
+                    OwningPtr::new(arg.ptr),
+                    arg.drop_fn,
+                    arg.layout,
+                    arg.type_id,
+                )
+            };
+            self.buffer.insert_box(self.entity, arg.id, payload);
+        }
+    }
+
+    /// Record the staged components. Under [`DuplicatePolicy::Error`], if a component was
+    /// staged twice, nothing is committed and all staged payloads are dropped.
+    pub fn commit(mut self) -> Result<(), String> {
+        let staged = std::mem::take(&mut self.staged);
+        if let Some(id) = self.duplicate {
+            drop_staged(staged);
+            return Err(format!("component id {:?} was staged twice", id));
+        }
+        self.record(staged);
+        Ok(())
+    }
+
+    /// Commit using the storage layout of `world`.
+    ///
+    /// Table components are recorded before sparse-set components, still as a single
+    /// write-combined insert so the entity moves archetype once. If a component was
+    /// staged with a storage hint that differs from how `world` registered it, nothing
+    /// is committed and all staged payloads are dropped, as with a duplicate component
+    /// under [`DuplicatePolicy::Error`].
+    pub fn commit_with_storage(mut self, world: &World) -> Result<(), String> {
+        let mut staged = std::mem::take(&mut self.staged);
+        if let Some(id) = self.duplicate {
+            drop_staged(staged);
+            return Err(format!("component id {:?} was staged twice", id));
+        }
+        let storages: Result<Vec<_>, String> = staged
+            .iter()
+            .map(|arg| {
+                let info = world
+                    .components()
+                    .get_info(arg.id)
+                    .ok_or_else(|| format!("component id {:?} is not registered", arg.id))?;
+                let actual = StorageTypeFlag::from(info.storage_type());
+                match &arg.storage {
+                    Some(expected) if *expected != actual => Err(format!(
+                        "component {} is stored as {:?} in the world but {:?} in the snapshot",
+                        info.name(),
+                        actual,
+                        expected
+                    )),
+                    _ => Ok(actual),
+                }
+            })
+            .collect();
+        let storages = match storages {
+            Ok(storages) => storages,
+            Err(e) => {
+                drop_staged(staged);
+                return Err(e);
+            }
+        };
+        let mut order: Vec<usize> = (0..staged.len()).collect();
+        order.sort_by_key(|&i| matches!(storages[i], StorageTypeFlag::SparseSet));
+        let mut slots: Vec<Option<StagedArg>> = staged.drain(..).map(Some).collect();
+        let ordered = order.into_iter().filter_map(|i| slots[i].take()).collect();
+        self.record(ordered);
+        Ok(())
+    }
+}
+
+fn drop_staged(staged: Vec<StagedArg>) {
+    for arg in staged {
+        // SAFETY: staged payloads are owned by the builder until recorded.
+        unsafe { (arg.drop_fn)(OwningPtr::new(arg.ptr)) };
+    }
+}
+
+impl Drop for DeferredEntityBuilder<'_> {
+    fn drop(&mut self) {
+        drop_staged(std::mem::take(&mut self.staged));
+    }
+}
+
+/// A tuple of serde components registered together with
+/// [`SnapshotRegistry::register_bundle`].
+pub trait SnapshotBundle {
+    fn register_all(registry: &mut SnapshotRegistry);
+    /// Names of the components under `naming`, in tuple order.
+    fn names(naming: NamingPolicy) -> Vec<&'static str>;
+}
+
+macro_rules! impl_snapshot_bundle {
+    ($($t:ident),+) => {
+        impl<$($t),+> SnapshotBundle for ($($t,)+)
+        where
+            $($t: Component + Serialize + DeserializeOwned),+
+        {
+            fn register_all(registry: &mut SnapshotRegistry) {
+                $(registry.register::<$t>();)+
+            }
+            fn names(naming: NamingPolicy) -> Vec<&'static str> {
+                vec![$(naming.name_of::<$t>()),+]
+            }
+        }
+    };
+}
+
+impl_snapshot_bundle!(A);
+impl_snapshot_bundle!(A, B);
+impl_snapshot_bundle!(A, B, C);
+impl_snapshot_bundle!(A, B, C, D);
+impl_snapshot_bundle!(A, B, C, D, E);
+impl_snapshot_bundle!(A, B, C, D, E, F);
+impl_snapshot_bundle!(A, B, C, D, E, F, G);
+impl_snapshot_bundle!(A, B, C, D, E, F, G, H);
+
+/// How [`SnapshotRegistry::register`] and the other typed registrations name a type in
+/// snapshots. Registrations taking an explicit name ignore it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NamingPolicy {
+    /// The last path segment, e.g. `Position` for `game::physics::Position`.
+    #[default]
+    ShortName,
+    /// The full path, e.g. `game::physics::Position`, for projects where two types share a
+    /// short name. The path comes from [`std::any::type_name`], whose output may change
+    /// between compiler versions.
+    FullPath,
+}
+
+impl NamingPolicy {
+    pub fn name_of<T>(self) -> &'static str {
+        match self {
+            NamingPolicy::ShortName => short_type_name::<T>(),
+            NamingPolicy::FullPath => std::any::type_name::<T>(),
+        }
     }
 }
 
@@ -154,7 +584,97 @@ pub struct SnapshotRegistry {
     pub type_registry: HashMap<&'static str, TypeId>,
     pub entries: HashMap<&'static str, SnapshotFactory>,
     pub resource_entries: HashMap<&'static str, SnapshotFactory>,
+    /// Rust types of the registered resources, by name.
+    pub resource_types: HashMap<&'static str, TypeId>,
+    /// Limits enforced by loaders using this registry.
+    pub limits: LoadLimits,
+    /// How loaders recover from component values that fail to deserialize.
+    pub row_errors: RowErrorPolicy,
+    /// How loaders treat an archetype with two columns of one component.
+    pub duplicate_components: DuplicatePolicy,
+    /// Whether loaders reject snapshots with unregistered types.
+    pub load_options: LoadOptions,
+    /// Keep columns of unregistered components in
+    /// [`UnknownComponents`](crate::unknown_components::UnknownComponents) instead of
+    /// dropping them on load.
+    pub keep_unknown: bool,
+    /// Trigger [`load_events`](crate::load_events) observer events after every load.
+    pub emit_load_events: bool,
+    /// Insert a [`SnapshotOrigin`](crate::snapshot_origin::SnapshotOrigin) with this source
+    /// on every loaded entity.
+    pub origin: Option<String>,
+    /// Run [`repair_hierarchy`](crate::hierarchy::repair_hierarchy) after every load.
+    pub repair_hierarchy: bool,
+    /// Rows the Arrow loaders decode and insert at a time. `None` imports each archetype
+    /// whole, which keeps every decoded value of a column in memory until it is inserted.
+    pub import_batch_rows: Option<usize>,
+    /// Named component groups, for registering and excluding related components together.
+    pub groups: BTreeMap<&'static str, Vec<&'static str>>,
+    /// Old component names mapped to the registered names they load as.
+    pub aliases: HashMap<String, &'static str>,
+    /// How typed registrations name their type. Set it before registering.
+    pub naming: NamingPolicy,
+}
+/// Named [`SnapshotRegistry`] instances, e.g. `"persist"`, `"replicate"` and `"debug"`, so
+/// subsystems can keep independent component sets in one resource.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct SnapshotRegistries {
+    registries: BTreeMap<String, SnapshotRegistry>,
 }
+
+impl SnapshotRegistries {
+    /// Add or replace the registry `name`, returning the previous one.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        registry: SnapshotRegistry,
+    ) -> Option<SnapshotRegistry> {
+        self.registries.insert(name.into(), registry)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SnapshotRegistry> {
+        self.registries.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut SnapshotRegistry> {
+        self.registries.get_mut(name)
+    }
+
+    /// The registry `name`, created empty if missing.
+    pub fn entry(&mut self, name: &str) -> &mut SnapshotRegistry {
+        self.registries.entry(name.to_string()).or_default()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SnapshotRegistry> {
+        self.registries.remove(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.registries.contains_key(name)
+    }
+
+    /// Registry names, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.registries.keys().map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SnapshotRegistry)> {
+        self.registries.iter().map(|(name, reg)| (name.as_str(), reg))
+    }
+
+    /// Run `f` with `world` and the registry `name` of its [`SnapshotRegistries`] resource,
+    /// for loaders that need `&mut World`. Returns `None` if there is no such registry.
+    pub fn scope<R>(
+        world: &mut World,
+        name: &str,
+        f: impl FnOnce(&mut World, &SnapshotRegistry) -> R,
+    ) -> Option<R> {
+        world.resource_scope(|world, registries: Mut<SnapshotRegistries>| {
+            registries.get(name).map(|registry| f(world, registry))
+        })
+    }
+}
+
 impl SnapshotMerge for SnapshotRegistry {
     fn merge_only_new(&mut self, other: &Self) {
         for (name, type_id) in &other.type_registry {
@@ -168,6 +688,15 @@ impl SnapshotMerge for SnapshotRegistry {
                 .entry(*name)
                 .or_insert_with(|| factory.clone());
         }
+        for (name, type_id) in &other.resource_types {
+            self.resource_types.entry(*name).or_insert(*type_id);
+        }
+        for (group, names) in &other.groups {
+            self.groups.entry(group).or_insert_with(|| names.clone());
+        }
+        for (old, name) in &other.aliases {
+            self.aliases.entry(old.clone()).or_insert(name);
+        }
     }
 
     fn merge(&mut self, other: &Self) {
@@ -180,19 +709,81 @@ impl SnapshotMerge for SnapshotRegistry {
         for (name, factory) in &other.resource_entries {
             self.resource_entries.insert(*name, factory.clone());
         }
+        for (name, type_id) in &other.resource_types {
+            self.resource_types.insert(*name, *type_id);
+        }
+        for (group, names) in &other.groups {
+            self.groups.insert(group, names.clone());
+        }
+        for (old, name) in &other.aliases {
+            self.aliases.insert(old.clone(), name);
+        }
+    }
+}
+
+/// A [`SnapshotRegistry`] behind an [`Arc`], cloned by bumping a count, e.g. to hand to
+/// every background save task.
+///
+/// Reads go through [`Deref`](std::ops::Deref). [`make_mut`](Self::make_mut) registers more
+/// types copy-on-write: the registry is copied only while another clone still shares it, so
+/// tasks holding older clones keep the registry they started with.
+#[derive(Clone, Default, Debug)]
+pub struct SnapshotRegistryRef(Arc<SnapshotRegistry>);
+
+impl SnapshotRegistryRef {
+    pub fn new(registry: SnapshotRegistry) -> Self {
+        Self(Arc::new(registry))
+    }
+
+    /// The registry, for mutation, copied first if other clones share it.
+    pub fn make_mut(&mut self) -> &mut SnapshotRegistry {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Whether both refer to the same registry, i.e. neither was mutated since they were
+    /// cloned from one another.
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Arc::ptr_eq(&a.0, &b.0)
+    }
+}
+
+impl std::ops::Deref for SnapshotRegistryRef {
+    type Target = SnapshotRegistry;
+
+    fn deref(&self) -> &SnapshotRegistry {
+        &self.0
+    }
+}
+
+impl From<SnapshotRegistry> for SnapshotRegistryRef {
+    fn from(registry: SnapshotRegistry) -> Self {
+        Self::new(registry)
     }
 }
 
 impl SnapshotRegistry {
+    /// Move the registry behind a [`SnapshotRegistryRef`].
+    pub fn freeze(self) -> SnapshotRegistryRef {
+        SnapshotRegistryRef::new(self)
+    }
+
     pub fn register<T>(&mut self)
     where
         T: Serialize + DeserializeOwned + Component + 'static,
     {
-        let name = short_type_name::<T>();
+        let name = self.naming.name_of::<T>();
         self.type_registry.insert(name, TypeId::of::<T>());
         self.entries
             .insert(name, SnapshotFactory::new::<T>(SnapshotMode::Full));
     }
+    /// Register `T` under its full type path whatever the [`naming`](Self::naming) policy,
+    /// e.g. when only a few types collide.
+    pub fn register_full_path<T>(&mut self)
+    where
+        T: Serialize + DeserializeOwned + Component,
+    {
+        self.register_named::<T>(NamingPolicy::FullPath.name_of::<T>());
+    }
     pub fn register_with_name<T, T1>(&mut self, name: &'static str)
     where
         T: Component + From<T1>,
@@ -226,7 +817,7 @@ impl SnapshotRegistry {
         T: Component + From<T1>,
         T1: Serialize + DeserializeOwned + for<'a> From<&'a T> + Into<T>,
     {
-        let name = short_type_name::<T>();
+        let name = self.naming.name_of::<T>();
         self.type_registry.insert(name, TypeId::of::<T>());
         self.entries.insert(
             name,
@@ -237,57 +828,513 @@ impl SnapshotRegistry {
     where
         T: Serialize + DeserializeOwned + Component + Default + 'static,
     {
-        let name = short_type_name::<T>();
+        let name = self.naming.name_of::<T>();
         self.type_registry.insert(name, TypeId::of::<T>());
         self.entries.insert(name, SnapshotFactory::new::<T>(mode));
     }
 
+    /// Register `T` like [`register`](Self::register), but also load values saved in the
+    /// older shape `Old`: each value is decoded as `T` first and otherwise as `Old`
+    /// upgraded with `T::from`. See [`Versioned`](crate::versioned::Versioned).
+    pub fn register_versioned<T, Old>(&mut self)
+    where
+        T: Serialize + DeserializeOwned + Component + From<Old>,
+        Old: DeserializeOwned,
+    {
+        let name = self.naming.name_of::<T>();
+        self.type_registry.insert(name, TypeId::of::<T>());
+        self.entries.insert(
+            name,
+            SnapshotFactory::new_versioned::<T, Old>(SnapshotMode::Full),
+        );
+    }
+
+    /// Register `T` through its [`Reflect`](bevy_reflect::Reflect) impl instead of serde,
+    /// see [`reflect`](crate::prelude::reflect). Arrow and Parquet skip it.
+    #[cfg(feature = "reflect")]
+    pub fn register_reflect<T>(&mut self)
+    where
+        T: Component + bevy_reflect::FromReflect + bevy_reflect::TypePath,
+        T: bevy_reflect::GetTypeRegistration,
+    {
+        let name = self.naming.name_of::<T>();
+        self.type_registry.insert(name, TypeId::of::<T>());
+        self.entries
+            .insert(name, SnapshotFactory::new_reflect::<T>(SnapshotMode::Full));
+    }
+
+    /// Register a component without a Rust type under `name`, e.g. one a script defines at
+    /// runtime. `codec` converts its memory to and from JSON; see [`dynamic`] for how its
+    /// component id is found in each world.
+    pub fn register_dynamic(
+        &mut self,
+        name: &'static str,
+        descriptor: ComponentDescriptor,
+        codec: DynamicCodec,
+    ) {
+        self.type_registry
+            .insert(name, TypeId::of::<DynamicComponent>());
+        self.entries.insert(
+            name,
+            SnapshotFactory::new_dynamic(name, descriptor, codec, SnapshotMode::Full),
+        );
+    }
+
+    /// Use `id` as the component registered with [`register_dynamic`](Self::register_dynamic)
+    /// as `name` in `world`, for components the application registered itself. `id` must
+    /// come from the same descriptor.
+    pub fn bind_dynamic(
+        &self,
+        world: &mut World,
+        name: &str,
+        id: ComponentId,
+    ) -> Result<(), ArchiveError> {
+        let (&name, &type_id) = self
+            .type_registry
+            .get_key_value(name)
+            .ok_or_else(|| ArchiveError::MissingFactory(name.to_string()))?;
+        if type_id != TypeId::of::<DynamicComponent>() {
+            return Err(ArchiveError::schema(format!(
+                "'{name}' is not a dynamic component"
+            )));
+        }
+        world
+            .get_resource_or_init::<DynamicComponentIds>()
+            .insert(name, id);
+        Ok(())
+    }
+
+    /// Register `T` like [`register`](Self::register), but with the raw-bytes Arrow codec of
+    /// [`ArrowSnapshotFactory::new_pod`](crate::prelude::vec_snapshot_factory::ArrowSnapshotFactory::new_pod).
+    ///
+    /// Arrow and Parquet columns of `T` skip serde entirely; JSON-based formats still use
+    /// its serde impls.
+    #[cfg(feature = "arrow_rs")]
+    pub fn register_pod<T>(&mut self)
+    where
+        T: Serialize + DeserializeOwned + Component + bytemuck::Pod,
+    {
+        let name = self.naming.name_of::<T>();
+        let mut factory = SnapshotFactory::new::<T>(SnapshotMode::Full);
+        factory.arrow = Some(
+            crate::prelude::vec_snapshot_factory::ArrowSnapshotFactory::new_pod::<T>(),
+        );
+        self.type_registry.insert(name, TypeId::of::<T>());
+        self.entries.insert(name, factory);
+    }
+
+    /// Substitute `T::default()` for rows whose `T` value fails to deserialize, under every
+    /// name `T` is registered with.
+    pub fn register_row_default<T: Component + Default>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        for (name, _) in self.type_registry.iter().filter(|(_, id)| **id == type_id) {
+            if let Some(factory) = self.entries.get_mut(name) {
+                factory.row_default = Some(|bump| ArenaBox::alloc(bump, T::default()));
+            }
+        }
+    }
+
+    /// Fill fields missing from saved `T` values with those of `prototype`, under every name
+    /// `T` is registered with, so components without [`Default`] and snapshots written
+    /// before a field existed still load. Call it after registering `T`.
+    ///
+    /// Saved fields win; nested objects are merged field by field. Only the JSON-based
+    /// formats are filled; Arrow columns must match the schema of `T`.
+    pub fn set_prototype<T: Component + Serialize>(&mut self, prototype: T) {
+        let prototype = serde_json::to_value(&prototype).expect("prototype must serialize to JSON");
+        let type_id = TypeId::of::<T>();
+        for (name, _) in self.type_registry.iter().filter(|(_, id)| **id == type_id) {
+            if let Some(factory) = self.entries.get_mut(name) {
+                factory.prototype = Some(prototype.clone());
+            }
+        }
+    }
+
+    /// Copy `T` with [`Clone`] instead of a JSON round trip in
+    /// [`clone_registered`](crate::world_clone::clone_registered), under every name `T` is
+    /// registered with.
+    pub fn register_clone<T: Component + Clone>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        for (name, _) in self.type_registry.iter().filter(|(_, id)| **id == type_id) {
+            if let Some(factory) = self.entries.get_mut(name) {
+                factory.clone_value = Some(|world, entity, bump| {
+                    let value = world.get::<T>(entity)?.clone();
+                    Some(ArenaBox::alloc(bump, value))
+                });
+            }
+        }
+    }
+
+    /// Register every component of the tuple `B`, e.g. `(Position, Velocity, Tag)`.
+    pub fn register_bundle<B: SnapshotBundle>(&mut self) {
+        B::register_all(self);
+    }
+
+    /// Register the components of `B` and add them to the group `group`.
+    pub fn register_group<B: SnapshotBundle>(&mut self, group: &'static str) {
+        B::register_all(self);
+        let members = self.groups.entry(group).or_default();
+        for name in B::names(self.naming) {
+            if !members.contains(&name) {
+                members.push(name);
+            }
+        }
+    }
+
+    /// Component names in the group `group`.
+    pub fn group(&self, group: &str) -> Option<&[&'static str]> {
+        self.groups.get(group).map(Vec::as_slice)
+    }
+
+    /// Unregister every component of the group `group`, and the group itself. Like
+    /// [`unregister`](Self::unregister), this also drops the members from other groups and
+    /// the aliases pointing at them.
+    ///
+    /// Returns `false` if there is no such group.
+    pub fn remove_group(&mut self, group: &str) -> bool {
+        let Some(names) = self.groups.remove(group) else {
+            return false;
+        };
+        for name in names {
+            self.remove_component(name);
+        }
+        true
+    }
+
+    /// Unregister the component or resource `name`, returning its factory. The component is
+    /// also dropped from its groups and from the aliases pointing at it.
+    pub fn unregister(&mut self, name: &str) -> Option<SnapshotFactory> {
+        if let Some(factory) = self.remove_component(name) {
+            return Some(factory);
+        }
+        self.resource_types.remove(name);
+        self.resource_entries.remove(name)
+    }
+
+    fn remove_component(&mut self, name: &str) -> Option<SnapshotFactory> {
+        let factory = self.entries.remove(name)?;
+        self.type_registry.remove(name);
+        for members in self.groups.values_mut() {
+            members.retain(|member| *member != name);
+        }
+        self.aliases.retain(|_, target| *target != name);
+        Some(factory)
+    }
+
+    /// Swap the factory of the registered component or resource `name` for `factory`,
+    /// returning the old one, e.g. to save a component through another wrapper type. `T`
+    /// is the component or resource `factory` builds and becomes the registered type of
+    /// `name`. Versions and migrations come from the new factory.
+    ///
+    /// Returns `None` and leaves the registry untouched if `name` is not registered.
+    pub fn replace<T: 'static>(
+        &mut self,
+        name: &str,
+        factory: SnapshotFactory,
+    ) -> Option<SnapshotFactory> {
+        let type_id = TypeId::of::<T>();
+        if let Some((&key, _)) = self.entries.get_key_value(name) {
+            self.type_registry.insert(key, type_id);
+            return self.entries.insert(key, factory);
+        }
+        let (&key, _) = self.resource_entries.get_key_value(name)?;
+        self.resource_types.insert(key, type_id);
+        self.resource_entries.insert(key, factory)
+    }
+
+    /// A copy of this registry without the components of `groups`, e.g. a profile that
+    /// leaves out `"render"`.
+    pub fn without_groups(&self, groups: &[&str]) -> Self {
+        let mut registry = self.clone();
+        for group in groups {
+            registry.remove_group(group);
+        }
+        registry
+    }
+
+    /// Load the component saved as `old` as the registered component `name`, so snapshots
+    /// written before a rename still load. Name lookups consult the alias table; a name
+    /// that is registered itself is never aliased.
+    pub fn alias(&mut self, old: impl Into<String>, name: &'static str) {
+        self.aliases.insert(old.into(), name);
+    }
+
+    /// The registered name `name` stands for: itself, or the target of its alias.
+    pub fn resolve_name<'a>(&'a self, name: &'a str) -> &'a str {
+        if self.entries.contains_key(name) {
+            return name;
+        }
+        self.aliases.get(name).copied().unwrap_or(name)
+    }
+
+    pub fn type_id_by_name(&self, name: &str) -> Option<TypeId> {
+        self.type_registry.get(self.resolve_name(name)).copied()
+    }
+
+    /// Declare `version` as the current schema of `T`, under every name `T` is registered
+    /// with. Loaders call `migrate(v, value)` for each `v` from the version a value was
+    /// saved with up to `version - 1`.
+    pub fn register_migration<T: Component>(&mut self, version: u32, migrate: MigrateFn) {
+        let type_id = TypeId::of::<T>();
+        for (name, _) in self.type_registry.iter().filter(|(_, id)| **id == type_id) {
+            if let Some(factory) = self.entries.get_mut(name) {
+                factory.version = version;
+                factory.migrate = Some(migrate);
+            }
+        }
+    }
+
+    /// Current schema version of component `name`; `0` if it has none or is unknown.
+    pub fn component_version(&self, name: &str) -> u32 {
+        self.get_factory(name).map_or(0, |f| f.version)
+    }
+
+    /// Upgrade `value` of component `name` from schema version `from` to the current one.
+    pub fn migrate_value(
+        &self,
+        name: &str,
+        from: u32,
+        mut value: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let Some(factory) = self.get_factory(name) else {
+            return Ok(value);
+        };
+        if from > factory.version {
+            return Err(format!(
+                "Component '{}' was saved with schema version {} but this build knows {}",
+                name, from, factory.version
+            ));
+        }
+        if from < factory.version {
+            let migrate = factory
+                .migrate
+                .ok_or_else(|| format!("Component '{}' has no migration", name))?;
+            for v in from..factory.version {
+                value = migrate(v, value);
+            }
+        }
+        Ok(value)
+    }
+
     pub fn get_factory(&self, name: &str) -> Option<&SnapshotFactory> {
-        self.entries.get(name)
+        self.entries.get(self.resolve_name(name))
     }
     pub fn get_factory_mut(&mut self, name: &str) -> Option<&mut SnapshotFactory> {
+        let name = match self.aliases.get(name) {
+            Some(&target) if !self.entries.contains_key(name) => target,
+            _ => name,
+        };
         self.entries.get_mut(name)
     }
     pub fn comp_id_by_name(&self, name: &str, world: &World) -> Option<ComponentId> {
-        self.entries
-            .get(name)
-            .and_then(|entry| (entry.comp_id)(world))
+        self.get_factory(name).and_then(|entry| (entry.comp_id)(world))
     }
 
     pub fn reg_by_name(&self, name: &str, world: &mut World) -> ComponentId {
-        (self.entries.get(name).unwrap().register)(world)
+        (self.get_factory(name).unwrap().register)(world)
+    }
+
+    /// Resolve (registering on first use) the component `name` and check that the world
+    /// stores it the way the snapshot recorded it.
+    ///
+    /// Storage is fixed by the Rust type at registration, so a mismatch means the snapshot
+    /// came from a world with a different definition of the component.
+    pub fn reg_by_name_with_storage(
+        &self,
+        name: &str,
+        world: &mut World,
+        expected: &StorageTypeFlag,
+    ) -> Result<ComponentId, String> {
+        let entry = self
+            .get_factory(name)
+            .ok_or_else(|| format!("Component '{}' is not registered", name))?;
+        let comp_id = (entry.comp_id)(world).unwrap_or_else(|| (entry.register)(world));
+        let actual = world
+            .components()
+            .get_info(comp_id)
+            .map(|info| StorageTypeFlag::from(info.storage_type()))
+            .unwrap_or_default();
+        if actual != *expected {
+            return Err(format!(
+                "Component '{}' is stored as {:?} but the snapshot expects {:?}",
+                name, actual, expected
+            ));
+        }
+        Ok(comp_id)
     }
 
     pub fn comp_id<T>(&self, world: &World) -> Option<ComponentId> {
-        let name = short_type_name::<T>();
+        let name = self.naming.name_of::<T>();
         self.entries
             .get(name)
             .and_then(|entry| (entry.comp_id)(world))
     }
 }
 
+/// A registered component or resource, as listed by [`SnapshotRegistry::components`] and
+/// [`SnapshotRegistry::resources`], e.g. for debug UIs and save inspectors.
+#[derive(Debug, Clone, Copy)]
+pub struct RegistryEntry<'a> {
+    pub name: &'static str,
+    /// `None` for [dynamic](SnapshotRegistry::register_dynamic) components, which have no
+    /// Rust type.
+    pub type_id: Option<TypeId>,
+    pub factory: &'a SnapshotFactory,
+}
+
+impl<'a> RegistryEntry<'a> {
+    pub fn mode(&self) -> SnapshotMode {
+        self.factory.mode
+    }
+
+    /// Current schema version, see
+    /// [`register_migration`](SnapshotRegistry::register_migration).
+    pub fn version(&self) -> u32 {
+        self.factory.version
+    }
+
+    /// Arrow fields of the saved column; `None` when Arrow and Parquet skip it.
+    #[cfg(feature = "arrow_rs")]
+    pub fn arrow_schema(&self) -> Option<&'a [arrow::datatypes::FieldRef]> {
+        self.factory
+            .arrow
+            .as_ref()
+            .map(|arrow| arrow.schema.as_slice())
+    }
+}
+
 impl SnapshotRegistry {
+    /// Registered components, sorted by name.
+    pub fn components(&self) -> Vec<RegistryEntry<'_>> {
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(&name, factory)| RegistryEntry {
+                name,
+                type_id: self
+                    .type_registry
+                    .get(name)
+                    .copied()
+                    .filter(|id| *id != TypeId::of::<DynamicComponent>()),
+                factory,
+            })
+            .collect();
+        entries.sort_by_key(|e| e.name);
+        entries
+    }
+
+    /// Registered resources, sorted by name.
+    pub fn resources(&self) -> Vec<RegistryEntry<'_>> {
+        let mut entries: Vec<_> = self
+            .resource_entries
+            .iter()
+            .map(|(&name, factory)| RegistryEntry {
+                name,
+                type_id: self.resource_types.get(name).copied(),
+                factory,
+            })
+            .collect();
+        entries.sort_by_key(|e| e.name);
+        entries
+    }
+
+    /// Registered component names, sorted.
+    pub fn component_names(&self) -> Vec<&'static str> {
+        self.components().into_iter().map(|e| e.name).collect()
+    }
+
+    /// Registered resource names, sorted.
+    pub fn resource_names(&self) -> Vec<&'static str> {
+        self.resources().into_iter().map(|e| e.name).collect()
+    }
+
     pub fn get_res_factory(&self, name: &str) -> Option<&SnapshotFactory> {
         self.resource_entries.get(name)
     }
 
+    /// Under [strict](LoadOptions::strict) loading, fail on the first of `names` that is
+    /// neither a registered component nor a registered resource.
+    pub fn check_known<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), LimitError> {
+        if !self.load_options.strict {
+            return Ok(());
+        }
+        match names
+            .into_iter()
+            .find(|name| self.get_factory(name).is_none() && self.get_res_factory(name).is_none())
+        {
+            Some(name) => Err(LimitError::UnknownType(name.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Under [`DuplicatePolicy::Error`], fail on the first of `names`, the components of
+    /// one archetype, that appears twice.
+    pub fn check_duplicates<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), LimitError> {
+        if self.duplicate_components != DuplicatePolicy::Error {
+            return Ok(());
+        }
+        let mut seen = HashSet::new();
+        match names.into_iter().find(|name| !seen.insert(*name)) {
+            Some(name) => Err(LimitError::DuplicateComponent(name.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Hash of the saved shape of component `name`, recorded by saves so loads can tell a
+    /// changed type up front. `None` without the `arrow_rs` feature, which derives it from
+    /// the Arrow schema, and for components Arrow skips.
+    pub fn schema_hash(&self, name: &str) -> Option<String> {
+        #[cfg(feature = "arrow_rs")]
+        {
+            self.get_factory(name)?
+                .arrow
+                .as_ref()
+                .map(|a| a.schema_hash())
+        }
+        #[cfg(not(feature = "arrow_rs"))]
+        {
+            let _ = name;
+            None
+        }
+    }
+
+    /// Fail on the first saved `(name, hash)` pair whose hash differs from the
+    /// [`schema_hash`](Self::schema_hash) of the registered component.
+    pub fn check_schema_hashes<'a>(
+        &self,
+        saved: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<(), LimitError> {
+        for (name, hash) in saved {
+            if self.schema_hash(name).is_some_and(|h| h != hash) {
+                return Err(LimitError::SchemaChanged(name.to_string()));
+            }
+        }
+        Ok(())
+    }
+
     pub fn resource_register<T: Resource + Serialize + DeserializeOwned>(&mut self) {
         let mode = SnapshotMode::Full;
         let factory = SnapshotFactory {
-            js_value: JsonValueCodec {
-                export: |world, _| {
+            js_value: JsonValueCodec::from_fns(
+                |world, _| {
                     world
                         .get_resource::<T>()
                         .map(|r| serde_json::to_value(r).unwrap())
                 },
-                import: |value, world, _| match serde_json::from_value::<T>(value.clone()) {
+                |value, world, _| match serde_json::from_value::<T>(value.clone()) {
                     Ok(resource) => {
                         world.insert_resource(resource);
                         Ok(())
                     }
                     Err(e) => Err(format!("Deserialization error: {}", e)),
                 },
-                dyn_ctor: |val, bump| {
+                |val, bump| {
                     let name = short_type_name::<T>();
                     let component: T = serde_json::from_value(val.clone())
                         .map_err(|e| format!("Deserialization error for {}:{}", name, e))?;
@@ -296,15 +1343,383 @@ impl SnapshotRegistry {
                         OwningPtr::new(NonNull::new_unchecked(ptr.cast()))
                     }))
                 },
-            },
+            ),
 
-            comp_id: |world| world.component_id::<T>(),
-            register: |world| world.register_component::<T>(),
+            comp_id: Arc::new(|world: &World| world.component_id::<T>()),
+            register: Arc::new(|world: &mut World| world.register_component::<T>()),
             mode,
             #[cfg(feature = "arrow_rs")]
             arrow: None,
+            row_default: None,
+            clone_value: None,
+            version: 0,
+            migrate: None,
+            prototype: None,
+        };
+        let name = self.naming.name_of::<T>();
+        self.resource_types.insert(name, TypeId::of::<T>());
+        self.resource_entries.insert(name, factory);
+    }
+
+    /// Register the message queue `Messages<M>` as a resource named after `M`.
+    ///
+    /// Pending messages are saved as a JSON array in send order, without draining the
+    /// queue. Loading replaces the queue with the saved messages as the current update, so
+    /// readers in the loaded world see them once, like a mid-frame checkpoint would.
+    pub fn message_register<M: Message + Serialize + DeserializeOwned>(&mut self) {
+        fn decode<M: Message + DeserializeOwned>(
+            value: &serde_json::Value,
+        ) -> Result<Vec<M>, String> {
+            serde_json::from_value::<Vec<M>>(value.clone())
+                .map_err(|e| format!("Deserialization error for {}:{}", short_type_name::<M>(), e))
+        }
+        let factory = SnapshotFactory {
+            js_value: JsonValueCodec::from_fns(
+                |world, _| {
+                    let messages = world.get_resource::<Messages<M>>()?;
+                    let mut cursor = messages.get_cursor();
+                    let pending: Vec<&M> = cursor.read(messages).collect();
+                    Some(serde_json::to_value(pending).unwrap())
+                },
+                |value, world, _| {
+                    let pending = decode::<M>(value)?;
+                    let mut messages = world.get_resource_or_init::<Messages<M>>();
+                    messages.clear();
+                    messages.write_batch(pending);
+                    Ok(())
+                },
+                |val, bump| {
+                    let mut messages = Messages::<M>::default();
+                    messages.write_batch(decode::<M>(val)?);
+                    let ptr = bump.alloc(messages) as *mut Messages<M>;
+                    Ok(ArenaBox::new::<Messages<M>>(unsafe {
+                        OwningPtr::new(NonNull::new_unchecked(ptr.cast()))
+                    }))
+                },
+            ),
+
+            comp_id: Arc::new(|world: &World| world.component_id::<Messages<M>>()),
+            register: Arc::new(|world: &mut World| world.register_component::<Messages<M>>()),
+            mode: SnapshotMode::Full,
+            #[cfg(feature = "arrow_rs")]
+            arrow: None,
+            row_default: None,
+            clone_value: None,
+            version: 0,
+            migrate: None,
+            prototype: None,
+        };
+        let name = self.naming.name_of::<M>();
+        self.resource_types
+            .insert(name, TypeId::of::<Messages<M>>());
+        self.resource_entries.insert(name, factory);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct Dense(u32);
+
+    #[derive(Component)]
+    #[component(storage = "SparseSet")]
+    struct Marker(u32);
+
+    #[test]
+    fn test_builder_commit_with_storage() {
+        let mut world = World::new();
+        let dense = world.register_component::<Dense>();
+        let marker = world.register_component::<Marker>();
+        let e = world.spawn_empty().id();
+        let bump = Bump::new();
+
+        let mut buffer = HarvardCommandBuffer::new();
+        let mut builder = DeferredEntityBuilder::new(&mut buffer, e);
+        builder.insert_by_id_with_storage(marker, ArenaBox::alloc(&bump, Marker(2)), StorageTypeFlag::SparseSet);
+        builder.insert_by_id(dense, ArenaBox::alloc(&bump, Dense(1)));
+        builder.commit_with_storage(&world).unwrap();
+
+        let ops: Vec<_> = buffer.iter_ops().collect();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].component_ids(), vec![dense, marker]);
+        buffer.apply(&mut world);
+        assert_eq!(world.get::<Dense>(e).unwrap().0, 1);
+        assert_eq!(world.get::<Marker>(e).unwrap().0, 2);
+
+        let mut builder = DeferredEntityBuilder::new(&mut buffer, e);
+        builder.insert_by_id_with_storage(dense, ArenaBox::alloc(&bump, Dense(3)), StorageTypeFlag::SparseSet);
+        assert!(builder.commit_with_storage(&world).is_err());
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_builder_duplicate_policy() {
+        let mut world = World::new();
+        let dense = world.register_component::<Dense>();
+        let e = world.spawn_empty().id();
+        let bump = Bump::new();
+        let mut buffer = HarvardCommandBuffer::new();
+
+        for (policy, expected) in [(DuplicatePolicy::ReplaceLast, 2), (DuplicatePolicy::KeepFirst, 1)] {
+            let mut builder =
+                DeferredEntityBuilder::new(&mut buffer, e).with_duplicate_policy(policy);
+            builder.insert_by_id(dense, ArenaBox::alloc(&bump, Dense(1)));
+            builder.insert_by_id(dense, ArenaBox::alloc(&bump, Dense(2)));
+            builder.commit().unwrap();
+            buffer.apply(&mut world);
+            buffer.reset();
+            assert_eq!(world.get::<Dense>(e).unwrap().0, expected);
+        }
+
+        let mut builder = DeferredEntityBuilder::new(&mut buffer, e)
+            .with_duplicate_policy(DuplicatePolicy::Error);
+        builder.insert_by_id(dense, ArenaBox::alloc(&bump, Dense(3)));
+        builder.insert_by_id(dense, ArenaBox::alloc(&bump, Dense(4)));
+        assert!(builder.commit_with_storage(&world).is_err());
+        assert!(buffer.is_empty());
+    }
+
+    #[derive(Component, Serialize, serde::Deserialize)]
+    struct Position(f32);
+    #[derive(Component, Serialize, serde::Deserialize)]
+    struct Velocity(f32);
+    #[derive(Component, Serialize, serde::Deserialize)]
+    struct Sprite(String);
+
+    #[test]
+    fn test_component_groups() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register_bundle::<(Position,)>();
+        assert!(registry.get_factory("Position").is_some());
+
+        registry.register_group::<(Position, Velocity)>("physics");
+        registry.register_group::<(Sprite,)>("render");
+        registry.alias("Pos", "Position");
+        assert_eq!(registry.group("physics"), Some(&["Position", "Velocity"][..]));
+
+        let headless = registry.without_groups(&["render"]);
+        assert!(headless.get_factory("Sprite").is_none());
+        assert!(headless.get_factory("Velocity").is_some());
+        assert!(registry.get_factory("Sprite").is_some());
+
+        assert!(registry.remove_group("physics"));
+        assert!(!registry.remove_group("physics"));
+        assert!(registry.get_factory("Position").is_none());
+        assert!(!registry.type_registry.contains_key("Velocity"));
+        assert!(registry.aliases.is_empty());
+    }
+
+    #[test]
+    fn test_component_alias() {
+        use crate::archetype_archive::{load_world_arch_snapshot, save_world_arch_snapshot};
+
+        let mut old = SnapshotRegistry::default();
+        old.register_named::<Position>("Pos");
+        let mut world = World::new();
+        world.spawn(Position(4.0));
+        let snapshot = save_world_arch_snapshot(&world, &old);
+
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Position>();
+        registry.alias("Pos", "Position");
+        assert_eq!(registry.resolve_name("Pos"), "Position");
+        assert!(registry.get_factory("Pos").is_some());
+
+        let mut loaded = World::new();
+        load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        let values: Vec<f32> = loaded.query::<&Position>().iter(&loaded).map(|p| p.0).collect();
+        assert_eq!(values, [4.0]);
+
+        #[cfg(feature = "arrow_rs")]
+        {
+            use crate::binary_archive::WorldArrowSnapshot;
+            let arrow = WorldArrowSnapshot::from_world_reg(&world, &old).unwrap();
+            let mut loaded = World::new();
+            arrow.to_world_reg(&mut loaded, &registry).unwrap();
+            assert_eq!(loaded.query::<&Position>().iter(&loaded).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_unregister_and_replace() {
+        use crate::archetype_archive::save_world_arch_snapshot;
+
+        #[derive(Serialize, serde::Deserialize)]
+        struct Meters {
+            meters: f32,
+        }
+        impl From<&Position> for Meters {
+            fn from(p: &Position) -> Self {
+                Meters { meters: p.0 }
+            }
+        }
+        impl From<Meters> for Position {
+            fn from(m: Meters) -> Self {
+                Position(m.meters)
+            }
+        }
+
+        let mut registry = SnapshotRegistry::default();
+        registry.register_group::<(Position, Velocity)>("physics");
+        registry.alias("Pos", "Position");
+        registry.message_register::<Damage>();
+        let mut world = World::new();
+        world.spawn(Position(2.0));
+
+        let wrapped = SnapshotFactory::new_with_wrapper::<Position, Meters>(SnapshotMode::Full);
+        assert!(registry.replace::<Position>("Position", wrapped).is_some());
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        assert_eq!(
+            snapshot.archetypes[0].get_column("Position").unwrap(),
+            &vec![serde_json::json!({"meters": 2.0})]
+        );
+        let spare = SnapshotFactory::new::<Sprite>(SnapshotMode::Full);
+        assert!(registry.replace::<Sprite>("Sprite", spare.clone()).is_none());
+        assert!(registry.replace::<Sprite>("Velocity", spare).is_some());
+        assert_eq!(registry.type_id_by_name("Velocity"), Some(TypeId::of::<Sprite>()));
+
+        assert!(registry.unregister("Position").is_some());
+        assert!(registry.unregister("Position").is_none());
+        assert_eq!(registry.type_id_by_name("Position"), None);
+        assert_eq!(registry.group("physics"), Some(&["Velocity"][..]));
+        assert!(registry.aliases.is_empty());
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        assert!(snapshot.archetypes.is_empty());
+
+        assert!(registry.unregister("Damage").is_some());
+        assert!(registry.resource_names().is_empty());
+        assert!(registry.resources().is_empty());
+    }
+
+    #[test]
+    fn test_registry_ref_copy_on_write() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Position>();
+        let mut shared = registry.freeze();
+        let task = shared.clone();
+        assert!(SnapshotRegistryRef::ptr_eq(&shared, &task));
+
+        let handle = std::thread::spawn(move || task.component_names());
+        shared.make_mut().register::<Velocity>();
+        assert_eq!(handle.join().unwrap(), ["Position"]);
+        assert_eq!(shared.component_names(), ["Position", "Velocity"]);
+
+        let before = shared.clone();
+        shared.make_mut().register::<Sprite>();
+        assert!(!SnapshotRegistryRef::ptr_eq(&shared, &before));
+        assert_eq!(before.component_names().len(), 2);
+    }
+
+    mod ui {
+        #[derive(bevy_ecs::component::Component, serde::Serialize, serde::Deserialize)]
+        pub struct Position(pub f32);
+    }
+
+    #[test]
+    fn test_full_path_naming() {
+        use crate::archetype_archive::{load_world_arch_snapshot, save_world_arch_snapshot};
+
+        let mut registry = SnapshotRegistry {
+            naming: NamingPolicy::FullPath,
+            ..Default::default()
         };
-        self.resource_entries
-            .insert(short_type_name::<T>(), factory);
+        registry.register_group::<(Position,)>("physics");
+        registry.register::<ui::Position>();
+        let full = std::any::type_name::<Position>();
+        assert_eq!(registry.group("physics"), Some(&[full][..]));
+        assert_eq!(registry.entries.len(), 2);
+
+        let mut world = World::new();
+        world.spawn((Position(1.0), ui::Position(2.0)));
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        let mut loaded = World::new();
+        load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        let (p, q) = loaded
+            .query::<(&Position, &ui::Position)>()
+            .single(&loaded)
+            .unwrap();
+        assert_eq!((p.0, q.0), (1.0, 2.0));
+
+        let mut mixed = SnapshotRegistry::default();
+        mixed.register::<Position>();
+        mixed.register_full_path::<ui::Position>();
+        assert!(mixed.get_factory("Position").is_some());
+        let ui_name = std::any::type_name::<ui::Position>();
+        assert!(mixed.get_factory(ui_name).is_some());
+    }
+
+    #[test]
+    fn test_registry_introspection() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Velocity>();
+        registry.register::<Position>();
+        registry.register::<Sprite>();
+        registry.get_factory_mut("Sprite").unwrap().mode = SnapshotMode::EmplaceIfNotExists;
+        registry.message_register::<Damage>();
+        assert_eq!(
+            registry.component_names(),
+            ["Position", "Sprite", "Velocity"]
+        );
+        assert_eq!(registry.resource_names(), ["Damage"]);
+
+        let components = registry.components();
+        assert_eq!(components[0].type_id, Some(TypeId::of::<Position>()));
+        assert!(matches!(
+            components[1].mode(),
+            SnapshotMode::EmplaceIfNotExists
+        ));
+        let resources = registry.resources();
+        assert_eq!(resources[0].type_id, Some(TypeId::of::<Messages<Damage>>()));
+        #[cfg(feature = "arrow_rs")]
+        assert_eq!(components[0].arrow_schema().map(<[_]>::len), Some(1));
+    }
+
+    #[test]
+    fn test_named_registries() {
+        let mut registries = SnapshotRegistries::default();
+        registries.entry("persist").register::<Position>();
+        registries.entry("persist").register::<Sprite>();
+        registries.entry("replicate").register::<Velocity>();
+        assert_eq!(registries.names().collect::<Vec<_>>(), ["persist", "replicate"]);
+        assert!(registries.get("replicate").unwrap().get_factory("Position").is_none());
+
+        let mut world = World::new();
+        world.spawn((Position(1.0), Velocity(2.0)));
+        world.insert_resource(registries);
+        let columns = SnapshotRegistries::scope(&mut world, "replicate", |world, registry| {
+            let snapshot = crate::archetype_archive::save_world_arch_snapshot(world, registry);
+            snapshot.archetypes[0].component_types.clone()
+        });
+        assert_eq!(columns, Some(vec!["Velocity".to_string()]));
+        assert!(SnapshotRegistries::scope(&mut world, "debug", |_, _| ()).is_none());
+    }
+
+    #[derive(Message, Clone, Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Damage(u32);
+
+    #[test]
+    fn test_message_snapshot() {
+        let mut registry = SnapshotRegistry::default();
+        registry.message_register::<Damage>();
+        let mut world = World::new();
+        world.init_resource::<Messages<Damage>>();
+        world.write_message(Damage(1));
+        world.resource_mut::<Messages<Damage>>().update();
+        world.write_message(Damage(2));
+
+        let resources = crate::archetype_archive::save_world_resource(&world, &registry);
+        assert_eq!(resources["Damage"], serde_json::json!([1, 2]));
+        // Saving does not consume the queue.
+        assert_eq!(world.resource::<Messages<Damage>>().len(), 2);
+
+        let mut loaded = World::new();
+        loaded.write_message(Damage(9));
+        crate::archetype_archive::load_world_resource(&resources, &mut loaded, &registry).unwrap();
+        let messages = loaded.resource::<Messages<Damage>>();
+        let mut cursor = messages.get_cursor();
+        let pending: Vec<_> = cursor.read(messages).cloned().collect();
+        assert_eq!(pending, [Damage(1), Damage(2)]);
     }
 }
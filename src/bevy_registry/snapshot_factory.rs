@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
 use bevy_ecs::{component::ComponentId, prelude::*};
 
 use serde::de::DeserializeOwned;
@@ -7,9 +11,17 @@ use crate::prelude::codec::JsonValueCodec;
 #[cfg(feature = "arrow_rs")]
 use crate::prelude::vec_snapshot_factory::ArrowSnapshotFactory;
 pub mod codec;
+pub mod dynamic;
+#[cfg(feature = "reflect")]
+pub mod reflect;
 
-pub type CompIdFn = fn(&World) -> Option<ComponentId>;
-pub type CompRegFn = fn(&mut World) -> ComponentId;
+pub type CompIdFn = Arc<dyn Fn(&World) -> Option<ComponentId> + Send + Sync>;
+pub type CompRegFn = Arc<dyn Fn(&mut World) -> ComponentId + Send + Sync>;
+pub type RowDefaultFn = for<'a> fn(&'a bumpalo::Bump) -> crate::prelude::ArenaBox<'a>;
+pub type CloneFn =
+    for<'a> fn(&World, Entity, &'a bumpalo::Bump) -> Option<crate::prelude::ArenaBox<'a>>;
+/// Upgrades a value saved at schema version `from` to version `from + 1`.
+pub type MigrateFn = fn(from: u32, serde_json::Value) -> serde_json::Value;
 
 pub fn short_type_name<T>() -> &'static str {
     std::any::type_name::<T>()
@@ -25,7 +37,7 @@ pub enum SnapshotMode {
     EmplaceIfNotExists,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct SnapshotFactory {
     pub js_value: JsonValueCodec,
     #[cfg(feature = "arrow_rs")]
@@ -33,6 +45,33 @@ pub struct SnapshotFactory {
     pub comp_id: CompIdFn,
     pub register: CompRegFn,
     pub mode: SnapshotMode,
+    /// Substituted for rows whose value fails to deserialize, see
+    /// [`SnapshotRegistry::register_row_default`](crate::prelude::SnapshotRegistry::register_row_default).
+    pub row_default: Option<RowDefaultFn>,
+    /// Copies the component without serde when cloning worlds, see
+    /// [`SnapshotRegistry::register_clone`](crate::prelude::SnapshotRegistry::register_clone).
+    pub clone_value: Option<CloneFn>,
+    /// Current schema version, recorded in manifests; `0` for components never migrated.
+    pub version: u32,
+    /// Brings older saved values up to `version`, see
+    /// [`SnapshotRegistry::register_migration`](crate::prelude::SnapshotRegistry::register_migration).
+    pub migrate: Option<MigrateFn>,
+    /// Supplies fields missing from saved values, see
+    /// [`SnapshotRegistry::set_prototype`](crate::prelude::SnapshotRegistry::set_prototype).
+    pub prototype: Option<serde_json::Value>,
+}
+
+impl fmt::Debug for SnapshotFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("SnapshotFactory");
+        s.field("js_value", &self.js_value);
+        #[cfg(feature = "arrow_rs")]
+        s.field("arrow", &self.arrow);
+        s.field("mode", &self.mode)
+            .field("version", &self.version)
+            .field("prototype", &self.prototype)
+            .finish_non_exhaustive()
+    }
 }
 
 #[cfg(feature = "arrow_rs")]
@@ -79,15 +118,54 @@ impl SnapshotFactory {
             mode,
             comp_id,
             register,
+            row_default: None,
+            clone_value: None,
+            version: 0,
+            migrate: None,
+            prototype: None,
+        }
+    }
+
+    /// `value` with the fields it lacks taken from [`prototype`](Self::prototype).
+    pub fn fill_from_prototype<'a>(
+        &self,
+        value: &'a serde_json::Value,
+    ) -> Cow<'a, serde_json::Value> {
+        match &self.prototype {
+            Some(prototype) => Cow::Owned(merge_prototype(prototype, value)),
+            None => Cow::Borrowed(value),
         }
     }
 }
 
+/// Overlay `value` on `prototype`, recursing into objects present in both. Values that are
+/// not objects replace the prototype whole.
+pub fn merge_prototype(
+    prototype: &serde_json::Value,
+    value: &serde_json::Value,
+) -> serde_json::Value {
+    match (prototype, value) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(fields)) => {
+            let mut merged = base.clone();
+            for (key, field) in fields {
+                let field = match base.get(key) {
+                    Some(base_field) => merge_prototype(base_field, field),
+                    None => field.clone(),
+                };
+                merged.insert(key.clone(), field);
+            }
+            serde_json::Value::Object(merged)
+        }
+        _ => value.clone(),
+    }
+}
+
 macro_rules! build_common {
     ($t:ty ) => {
-        (SnapshotFactory::component_id::<$t>, |world| {
-            world.register_component::<$t>()
-        })
+        (
+            Arc::new(SnapshotFactory::component_id::<$t>),
+            Arc::new(|world: &mut World| world.register_component::<$t>()),
+        )
     };
 }
 
@@ -117,4 +195,16 @@ impl SnapshotFactory {
         let arrow = feature_expr!("arrow_rs", Some(ArrowSnapshotFactory::new_with::<T, T1>()));
         return SnapshotFactory::from_mode_tuple(mode, comp_id, register, (js, arrow));
     }
+
+    /// Like [`new`](Self::new), but JSON-based formats also load values saved as `Old`,
+    /// see [`Versioned`](crate::versioned::Versioned). Arrow columns use the schema of `T`.
+    pub fn new_versioned<T, Old>(mode: SnapshotMode) -> Self
+    where
+        T: Serialize + DeserializeOwned + Component + From<Old>,
+        Old: DeserializeOwned,
+    {
+        let mut factory = SnapshotFactory::new::<T>(mode);
+        factory.js_value = JsonValueCodec::new_versioned::<T, Old>();
+        factory
+    }
 }
@@ -1,4 +1,6 @@
+use std::fmt;
 use std::ptr::NonNull;
+use std::sync::Arc;
 
 use bevy_ecs::prelude::*;
 use bevy_ecs::ptr::OwningPtr;
@@ -6,10 +8,23 @@ use bevy_ecs::ptr::OwningPtr;
 use serde::{Deserialize, Serialize};
 
 use crate::prelude::ArenaBox;
-pub type ExportFn = fn(&World, Entity) -> Option<serde_json::Value>;
-pub type ImportFn = fn(&serde_json::Value, &mut World, Entity) -> Result<(), String>;
-pub type DynBuilderFn =
-    for<'a> fn(&serde_json::Value, &'a bumpalo::Bump) -> Result<ArenaBox<'a>, String>;
+use crate::versioned::Versioned;
+pub type ExportFn = Arc<dyn Fn(&World, Entity) -> Option<serde_json::Value> + Send + Sync>;
+pub type ImportFn =
+    Arc<dyn Fn(&serde_json::Value, &mut World, Entity) -> Result<(), String> + Send + Sync>;
+pub type DynBuilderFn = Arc<
+    dyn for<'a> Fn(&serde_json::Value, &'a bumpalo::Bump) -> Result<ArenaBox<'a>, String>
+        + Send
+        + Sync,
+>;
+
+/// Forces the higher-ranked signature of [`DynBuilderFn`] onto a closure.
+pub(super) fn builder<F>(f: F) -> F
+where
+    F: for<'a> Fn(&serde_json::Value, &'a bumpalo::Bump) -> Result<ArenaBox<'a>, String>,
+{
+    f
+}
 
 fn short_type_name<T>() -> &'static str {
     std::any::type_name::<T>()
@@ -18,13 +33,19 @@ fn short_type_name<T>() -> &'static str {
         .unwrap_or("unknown")
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct JsonValueCodec {
     pub export: ExportFn,
     pub import: ImportFn,
     pub dyn_ctor: DynBuilderFn,
 }
 
+impl fmt::Debug for JsonValueCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonValueCodec").finish_non_exhaustive()
+    }
+}
+
 fn export<T>(world: &World, entity: Entity) -> Option<serde_json::Value>
 where
     T: Serialize + Component,
@@ -100,16 +121,63 @@ where
     Ok(unsafe { ArenaBox::new::<T>(OwningPtr::new(NonNull::new_unchecked(ptr.cast()))) })
 }
 
+fn decode_versioned<T, Old>(val: &serde_json::Value) -> Result<T, String>
+where
+    T: for<'a> Deserialize<'a> + From<Old>,
+    Old: for<'a> Deserialize<'a>,
+{
+    let name = short_type_name::<T>();
+    serde_json::from_value::<Versioned<Old, T>>(val.clone())
+        .map(Versioned::into_current)
+        .map_err(|e| format!("Deserialization error for {}:{}", name, e))
+}
+
+fn import_versioned<T, Old>(
+    val: &serde_json::Value,
+    world: &mut World,
+    entity: Entity,
+) -> Result<(), String>
+where
+    T: for<'a> Deserialize<'a> + Component + From<Old>,
+    Old: for<'a> Deserialize<'a>,
+{
+    let value = decode_versioned::<T, Old>(val)?;
+    world.entity_mut(entity).insert(value);
+    Ok(())
+}
+
+fn dyn_ctor_versioned<'a, T, Old>(
+    val: &serde_json::Value,
+    bump: &'a bumpalo::Bump,
+) -> Result<ArenaBox<'a>, String>
+where
+    T: for<'de> Deserialize<'de> + Component + From<Old>,
+    Old: for<'de> Deserialize<'de>,
+{
+    let component = decode_versioned::<T, Old>(val)?;
+    let ptr = bump.alloc(component) as *mut T;
+    Ok(unsafe { ArenaBox::new::<T>(OwningPtr::new(NonNull::new_unchecked(ptr.cast()))) })
+}
+
 impl JsonValueCodec {
+    /// Build a codec from plain functions or non-capturing closures.
+    pub fn from_fns(
+        export: fn(&World, Entity) -> Option<serde_json::Value>,
+        import: fn(&serde_json::Value, &mut World, Entity) -> Result<(), String>,
+        dyn_ctor: for<'a> fn(&serde_json::Value, &'a bumpalo::Bump) -> Result<ArenaBox<'a>, String>,
+    ) -> Self {
+        Self {
+            export: Arc::new(export),
+            import: Arc::new(import),
+            dyn_ctor: Arc::new(dyn_ctor),
+        }
+    }
+
     pub fn new<T>() -> Self
     where
         T: Serialize + for<'a> Deserialize<'a> + Component,
     {
-        Self {
-            export: export::<T>,
-            import: import::<T>,
-            dyn_ctor: dyn_ctor::<T>,
-        }
+        Self::from_fns(export::<T>, import::<T>, dyn_ctor::<T>)
     }
 
     pub fn new_with<T, T1>() -> Self
@@ -117,11 +185,25 @@ impl JsonValueCodec {
         T: Component + From<T1>,
         T1: Serialize + for<'a> Deserialize<'a> + for<'a> From<&'a T>,
     {
-        Self {
-            export: export_wrapper::<T, T1>,
-            import: import_wrapper::<T, T1>,
-            dyn_ctor: dyn_ctor_wrapper::<T, T1>,
-        }
+        Self::from_fns(
+            export_wrapper::<T, T1>,
+            import_wrapper::<T, T1>,
+            dyn_ctor_wrapper::<T, T1>,
+        )
+    }
+
+    /// Saves `T` as is, and loads values saved as either `T` or `Old`, see
+    /// [`Versioned`].
+    pub fn new_versioned<T, Old>() -> Self
+    where
+        T: Serialize + for<'a> Deserialize<'a> + Component + From<Old>,
+        Old: for<'a> Deserialize<'a>,
+    {
+        Self::from_fns(
+            export::<T>,
+            import_versioned::<T, Old>,
+            dyn_ctor_versioned::<T, Old>,
+        )
     }
 }
 
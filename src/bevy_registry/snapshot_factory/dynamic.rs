@@ -0,0 +1,269 @@
+//! Snapshots of components registered at runtime with
+//! [`World::register_component_with_descriptor`], e.g. components defined by scripts or
+//! mods, which have no Rust type to take serde impls from.
+//!
+//! Register them with
+//! [`SnapshotRegistry::register_dynamic`](crate::prelude::SnapshotRegistry::register_dynamic)
+//! and a [`DynamicCodec`] converting between the component memory and JSON. The JSON-based
+//! formats then save and load them like typed components; Arrow and Parquet skip them.
+//! Registry names are `&'static str`, so names built at runtime are leaked with
+//! [`String::leak`].
+//!
+//! Component ids are resolved per world through the [`DynamicComponentIds`] resource.
+//! Loading registers the descriptor on first use, without its relationship accessor. A component the application registered
+//! itself must be bound with
+//! [`SnapshotRegistry::bind_dynamic`](crate::prelude::SnapshotRegistry::bind_dynamic)
+//! before saving, or it is skipped and a load registers a second component.
+
+use std::alloc::Layout;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use bevy_ecs::component::{ComponentCloneBehavior, ComponentDescriptor, ComponentId, StorageType};
+use bevy_ecs::prelude::*;
+use bevy_ecs::ptr::{OwningPtr, Ptr, PtrMut};
+
+use super::codec::{DynBuilderFn, JsonValueCodec, builder};
+use super::{CompIdFn, CompRegFn, SnapshotFactory, SnapshotMode};
+use crate::prelude::ArenaBox;
+
+pub type DynSerializeFn = Arc<dyn Fn(Ptr<'_>) -> Option<serde_json::Value> + Send + Sync>;
+pub type DynDeserializeFn =
+    Arc<dyn Fn(&serde_json::Value, PtrMut<'_>) -> Result<(), String> + Send + Sync>;
+
+/// Type id recorded in [`SnapshotRegistry::type_registry`](crate::prelude::SnapshotRegistry::type_registry)
+/// for dynamic components.
+pub(crate) struct DynamicComponent;
+
+/// Converts a dynamic component between its memory and JSON.
+#[derive(Clone)]
+pub struct DynamicCodec {
+    /// Reads the component behind the pointer.
+    pub serialize: DynSerializeFn,
+    /// Writes a value into zeroed memory with the layout of the component. Memory left by
+    /// a failed call is discarded without running the component's drop.
+    pub deserialize: DynDeserializeFn,
+}
+
+impl DynamicCodec {
+    pub fn new(
+        serialize: impl Fn(Ptr<'_>) -> Option<serde_json::Value> + Send + Sync + 'static,
+        deserialize: impl Fn(&serde_json::Value, PtrMut<'_>) -> Result<(), String>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        Self {
+            serialize: Arc::new(serialize),
+            deserialize: Arc::new(deserialize),
+        }
+    }
+}
+
+impl fmt::Debug for DynamicCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicCodec").finish_non_exhaustive()
+    }
+}
+
+/// Component ids of the dynamic components of a world, by registry name.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct DynamicComponentIds(HashMap<&'static str, ComponentId>);
+
+impl DynamicComponentIds {
+    pub fn get(&self, name: &str) -> Option<ComponentId> {
+        self.0.get(name).copied()
+    }
+
+    pub fn insert(&mut self, name: &'static str, id: ComponentId) -> Option<ComponentId> {
+        self.0.insert(name, id)
+    }
+}
+
+unsafe fn no_drop(_: OwningPtr<'_>) {}
+
+/// The parts of a [`ComponentDescriptor`] needed to register it again. Descriptors are
+/// neither `Send` nor expose their layout, so the parts are read back from a scratch world.
+#[derive(Clone)]
+struct DescriptorParts {
+    storage_type: StorageType,
+    layout: Layout,
+    drop: Option<unsafe fn(OwningPtr<'_>)>,
+    mutable: bool,
+    clone_behavior: ComponentCloneBehavior,
+}
+
+impl DescriptorParts {
+    fn of(descriptor: ComponentDescriptor) -> Self {
+        let mut scratch = World::new();
+        let id = scratch.register_component_with_descriptor(descriptor);
+        let info = scratch.components().get_info(id).unwrap();
+        Self {
+            storage_type: info.storage_type(),
+            layout: info.layout(),
+            drop: info.drop(),
+            mutable: info.mutable(),
+            clone_behavior: info.clone_behavior().clone(),
+        }
+    }
+
+    fn descriptor(&self, name: &'static str) -> ComponentDescriptor {
+        // SAFETY: the parts come from a valid descriptor.
+        unsafe {
+            ComponentDescriptor::new_with_layout(
+                name,
+                self.storage_type,
+                self.layout,
+                self.drop,
+                self.mutable,
+                self.clone_behavior.clone(),
+                None,
+            )
+        }
+    }
+}
+
+impl SnapshotFactory {
+    /// Factory for the component `name` described by `descriptor`, see the
+    /// [module docs](self).
+    pub fn new_dynamic(
+        name: &'static str,
+        descriptor: ComponentDescriptor,
+        codec: DynamicCodec,
+        mode: SnapshotMode,
+    ) -> Self {
+        let parts = DescriptorParts::of(descriptor);
+        let (layout, drop) = (parts.layout, parts.drop);
+        let comp_id: CompIdFn =
+            Arc::new(move |world: &World| world.get_resource::<DynamicComponentIds>()?.get(name));
+        let lookup = comp_id.clone();
+        let register: CompRegFn = Arc::new(move |world: &mut World| {
+            if let Some(id) = lookup(world) {
+                return id;
+            }
+            let id = world.register_component_with_descriptor(parts.descriptor(name));
+            world
+                .get_resource_or_init::<DynamicComponentIds>()
+                .insert(name, id);
+            id
+        });
+        let deserialize = codec.deserialize;
+        let dyn_ctor: DynBuilderFn = Arc::new(builder(move |value, bump| {
+            let ptr = bump.alloc_layout(layout);
+            // SAFETY: `ptr` is a fresh allocation of `layout`, zeroed before the codec writes
+            // the value and handed out only once it succeeded.
+            unsafe {
+                ptr.as_ptr().write_bytes(0, layout.size());
+                deserialize(value, PtrMut::new(ptr))?;
+                Ok(ArenaBox::from_raw_parts(
+                    OwningPtr::new(ptr),
+                    drop.unwrap_or(no_drop),
+                    layout,
+                    None,
+                ))
+            }
+        }));
+        let serialize = codec.serialize;
+        let export_id = comp_id.clone();
+        let import_register = register.clone();
+        let import_ctor = dyn_ctor.clone();
+        let js_value = JsonValueCodec {
+            export: Arc::new(move |world: &World, entity: Entity| {
+                serialize(world.get_by_id(entity, export_id(world)?)?)
+            }),
+            import: Arc::new(
+                move |value: &serde_json::Value, world: &mut World, entity: Entity| {
+                    let id = import_register(world);
+                    let bump = bumpalo::Bump::new();
+                    let component = import_ctor(value, &bump)?;
+                    // SAFETY: `component` has the layout of the descriptor registered as `id`.
+                    unsafe { world.entity_mut(entity).insert_by_id(id, component.into_raw().0) };
+                    Ok(())
+                },
+            ),
+            dyn_ctor,
+        };
+        SnapshotFactory {
+            js_value,
+            #[cfg(feature = "arrow_rs")]
+            arrow: None,
+            comp_id,
+            register,
+            mode,
+            row_default: None,
+            clone_value: None,
+            version: 0,
+            migrate: None,
+            prototype: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::archetype_archive::{load_world_arch_snapshot, save_world_arch_snapshot};
+    use crate::prelude::SnapshotRegistry;
+
+    fn script_pos() -> ComponentDescriptor {
+        // SAFETY: `[f32; 2]` needs no drop and is Send + Sync.
+        unsafe {
+            ComponentDescriptor::new_with_layout(
+                "ScriptPos",
+                StorageType::Table,
+                Layout::new::<[f32; 2]>(),
+                None,
+                true,
+                ComponentCloneBehavior::Default,
+                None,
+            )
+        }
+    }
+
+    fn script_pos_codec() -> DynamicCodec {
+        DynamicCodec::new(
+            // SAFETY: `ScriptPos` components are `[f32; 2]`.
+            |ptr| serde_json::to_value(unsafe { ptr.deref::<[f32; 2]>() }).ok(),
+            |value, ptr| {
+                let pos: [f32; 2] =
+                    serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+                unsafe { *ptr.deref_mut::<[f32; 2]>() = pos };
+                Ok(())
+            },
+        )
+    }
+
+    #[test]
+    fn test_dynamic_component_roundtrip() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register_dynamic("ScriptPos", script_pos(), script_pos_codec());
+
+        let mut world = World::new();
+        let id = world.register_component_with_descriptor(script_pos());
+        let mut entity = world.spawn_empty();
+        // SAFETY: `ScriptPos` components are `[f32; 2]`.
+        OwningPtr::make([1.0f32, 2.0], |ptr| unsafe {
+            entity.insert_by_id(id, ptr);
+        });
+        registry.bind_dynamic(&mut world, "ScriptPos", id).unwrap();
+
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        let column = snapshot.archetypes[0].get_column("ScriptPos").unwrap();
+        assert_eq!(column, &vec![json!([1.0, 2.0])]);
+
+        let mut loaded = World::new();
+        load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        let id = registry.comp_id_by_name("ScriptPos", &loaded).unwrap();
+        let entity = loaded
+            .query::<EntityRef>()
+            .iter(&loaded)
+            .find(|e| e.contains_id(id))
+            .unwrap()
+            .id();
+        let ptr = loaded.get_by_id(entity, id).unwrap();
+        assert_eq!(unsafe { *ptr.deref::<[f32; 2]>() }, [1.0, 2.0]);
+    }
+}
@@ -0,0 +1,128 @@
+//! Snapshots of components that derive [`Reflect`](bevy_reflect::Reflect) instead of serde's
+//! traits (`reflect` feature).
+//!
+//! Register them with
+//! [`SnapshotRegistry::register_reflect`](crate::prelude::SnapshotRegistry::register_reflect).
+//! Values go through bevy_reflect's [`TypedReflectSerializer`] and
+//! [`TypedReflectDeserializer`], which use the `ReflectSerialize` and `ReflectDeserialize`
+//! type data where a type registers them, e.g. primitives, `String` or types with
+//! `#[reflect(Serialize, Deserialize)]`, and walk the reflected fields otherwise. Each factory
+//! keeps a [`TypeRegistry`] holding the component and the types of its fields.
+//!
+//! The JSON-based formats save and load them like serde components; Arrow and Parquet skip
+//! them.
+
+use std::sync::Arc;
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer};
+use bevy_reflect::{FromReflect, GetTypeRegistration, TypePath, TypeRegistry};
+use serde::de::DeserializeSeed;
+
+use super::codec::{JsonValueCodec, builder};
+use super::{CompIdFn, CompRegFn, SnapshotFactory, SnapshotMode, short_type_name};
+use crate::prelude::ArenaBox;
+
+fn decode<T>(registry: &TypeRegistry, val: &serde_json::Value) -> Result<T, String>
+where
+    T: FromReflect + TypePath,
+{
+    let name = short_type_name::<T>();
+    let reflected = TypedReflectDeserializer::of::<T>(registry)
+        .deserialize(val)
+        .map_err(|e| format!("Deserialization error for {}:{}", name, e))?;
+    T::from_reflect(reflected.as_ref()).ok_or_else(|| {
+        format!(
+            "Deserialization error for {}:value does not match the type",
+            name
+        )
+    })
+}
+
+impl SnapshotFactory {
+    /// Factory for a component serialized through reflection, see the [module docs](self).
+    pub fn new_reflect<T>(mode: SnapshotMode) -> Self
+    where
+        T: Component + FromReflect + TypePath + GetTypeRegistration,
+    {
+        let mut registry = TypeRegistry::new();
+        registry.register::<T>();
+        let registry = Arc::new(registry);
+        let (export_registry, import_registry) = (registry.clone(), registry.clone());
+        let js_value = JsonValueCodec {
+            export: Arc::new(move |world: &World, entity: Entity| {
+                let value = world.entity(entity).get::<T>()?;
+                serde_json::to_value(TypedReflectSerializer::new(value, &export_registry)).ok()
+            }),
+            import: Arc::new(
+                move |val: &serde_json::Value, world: &mut World, entity: Entity| {
+                    let value = decode::<T>(&import_registry, val)?;
+                    world.entity_mut(entity).insert(value);
+                    Ok(())
+                },
+            ),
+            dyn_ctor: Arc::new(builder(move |val, bump| {
+                Ok(ArenaBox::alloc(bump, decode::<T>(&registry, val)?))
+            })),
+        };
+        let comp_id: CompIdFn = Arc::new(|world: &World| world.component_id::<T>());
+        let register: CompRegFn = Arc::new(|world: &mut World| world.register_component::<T>());
+        SnapshotFactory {
+            js_value,
+            #[cfg(feature = "arrow_rs")]
+            arrow: None,
+            comp_id,
+            register,
+            mode,
+            row_default: None,
+            clone_value: None,
+            version: 0,
+            migrate: None,
+            prototype: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::Reflect;
+    use serde_json::json;
+
+    use crate::archetype_archive::{load_world_arch_snapshot, save_world_arch_snapshot};
+    use crate::prelude::SnapshotRegistry;
+
+    use super::*;
+
+    #[derive(Component, Reflect, Debug, Clone, PartialEq)]
+    struct Health {
+        current: f32,
+        max: f32,
+    }
+
+    #[derive(Component, Reflect, Debug, Clone, PartialEq)]
+    struct Tag(String);
+
+    #[test]
+    fn test_reflect_component_roundtrip() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register_reflect::<Health>();
+        registry.register_reflect::<Tag>();
+
+        let mut world = World::new();
+        let health = Health {
+            current: 7.5,
+            max: 10.0,
+        };
+        world.spawn((health.clone(), Tag("orc".to_string())));
+
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        let column = snapshot.archetypes[0].get_column("Health").unwrap();
+        assert_eq!(column, &vec![json!({"current": 7.5, "max": 10.0})]);
+
+        let mut loaded = World::new();
+        load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        let (h, t) = loaded.query::<(&Health, &Tag)>().single(&loaded).unwrap();
+        assert_eq!(h, &health);
+        assert_eq!(t.0, "orc");
+    }
+}
@@ -1,7 +1,15 @@
-use std::{marker::PhantomData, ptr::NonNull};
+use std::{cell::UnsafeCell, marker::PhantomData, ptr::NonNull};
 
+use bevy_ecs::storage::Table;
+
+use crate::binary_archive::arrow_column::row_batches;
 use crate::prelude::{ArenaBox, vec_snapshot_factory::*};
-use arrow::{array::Array, datatypes::FieldRef};
+use arrow::{
+    array::{Array, FixedSizeBinaryArray},
+    buffer::Buffer,
+    datatypes::FieldRef,
+};
+use bytemuck::Pod;
 use serde::de::DeserializeOwned;
 use serde_arrow::marrow;
 #[derive(Debug, thiserror::Error)]
@@ -18,6 +26,12 @@ pub enum SnapshotError {
     InvalidEntityID(u32),
     #[error("unexpected null component {0}")]
     MissingComponent(String),
+    #[error(transparent)]
+    Limit(#[from] crate::bevy_registry::LimitError),
+    #[error(transparent)]
+    Container(#[from] crate::container::ContainerError),
+    #[error(transparent)]
+    Checksum(#[from] crate::checksum::ChecksumMismatch),
     #[error("generic error: {0}")]
     Generic(String),
     #[error("generic error: {0}")]
@@ -25,6 +39,11 @@ pub enum SnapshotError {
 }
 
 pub type ArrExportFn = fn(&[FieldRef], &World, &[Entity]) -> Result<ArrowColumn, SnapshotError>;
+/// Export the whole column of a table, in table row order, from its dense slice.
+///
+/// Returns `None` when the component is not stored in `table`.
+pub type ArrTableExportFn =
+    fn(&[FieldRef], &World, &Table) -> Option<Result<ArrowColumn, SnapshotError>>;
 pub type ArrImportFn = fn(&ArrowColumn, &mut World, &[Entity]) -> Result<(), SnapshotError>;
 pub type ArrDynFn =
     for<'a> fn(&ArrowColumn, &'a bumpalo::Bump) -> Result<Vec<ArenaBox<'a>>, SnapshotError>;
@@ -33,6 +52,8 @@ impl DefaultSchema for Vec<FieldRef> {}
 #[derive(Clone, Debug)]
 pub struct ArrowSnapshotFactory {
     pub arr_export: ArrExportFn,
+    /// Bulk export for [`Table`](bevy_ecs::component::StorageType::Table) components.
+    pub arr_export_table: Option<ArrTableExportFn>,
     pub arr_import: ArrImportFn,
     pub arr_dyn: ArrDynFn,
     pub schema: Vec<FieldRef>,
@@ -59,6 +80,27 @@ where
     };
     arr_export
 }
+/// The dense column of `T` in `table`.
+fn table_slice<'w, T: Component>(world: &World, table: &'w Table) -> Option<&'w [UnsafeCell<T>]> {
+    let cid = world.component_id::<T>()?;
+    // SAFETY: `cid` is the id of `T`, and the slice is bounded by the table's entity count.
+    unsafe { table.get_data_slice_for::<T>(cid) }
+}
+fn export_table_full<T>() -> ArrTableExportFn
+where
+    T: Serialize + for<'a> Deserialize<'a> + Component,
+{
+    let arr_export: ArrTableExportFn = |fields, world, table| {
+        let slice = table_slice::<T>(world, table)?;
+        // SAFETY: `world` is borrowed immutably, so nothing mutates the column meanwhile.
+        let v: Vec<&T> = slice.iter().map(|cell| unsafe { &*cell.get() }).collect();
+        Some(serialize_data(fields, v).map(|data| ArrowColumn {
+            fields: fields.to_vec(),
+            data,
+        }))
+    };
+    arr_export
+}
 fn import_full<T>() -> ArrImportFn
 where
     T: Serialize + DeserializeOwned + Component,
@@ -126,6 +168,30 @@ where
     arr_export
 }
 
+fn export_table_wrapper<T, T1>() -> ArrTableExportFn
+where
+    T: Component,
+    T1: Serialize + DeserializeOwned + for<'a> From<&'a T>,
+{
+    let arr_export: ArrTableExportFn = |fields, world, table| {
+        let slice = table_slice::<T>(world, table)?;
+        // SAFETY: `world` is borrowed immutably, so nothing mutates the column meanwhile.
+        let v: Vec<T1> = slice
+            .iter()
+            .map(|cell| T1::from(unsafe { &*cell.get() }))
+            .collect();
+        Some(
+            serailize_data_owned(fields, v)
+                .map(|data| ArrowColumn {
+                    fields: fields.to_vec(),
+                    data,
+                })
+                .map_err(SnapshotError::GenericBox),
+        )
+    };
+    arr_export
+}
+
 fn dyn_wrapper<T, T1>() -> ArrDynFn
 where
     T: Component + From<T1>,
@@ -153,6 +219,7 @@ impl ArrowSnapshotFactory {
             <Vec<FieldRef> as DefaultSchema>::default_schema::<T>().to_vec();
         Self {
             arr_export: export_full::<T>(),
+            arr_export_table: Some(export_table_full::<T>()),
             arr_import: import_full::<T>(),
             arr_dyn: dyn_ctor_full::<T>(),
             schema,
@@ -168,11 +235,165 @@ impl ArrowSnapshotFactory {
             <Vec<FieldRef> as DefaultSchema>::default_schema::<T1>().to_vec();
         Self {
             arr_export: export_wrapper::<T, T1>(),
+            arr_export_table: Some(export_table_wrapper::<T, T1>()),
             arr_import: import_wrapper::<T, T1>(),
             arr_dyn: dyn_wrapper::<T, T1>(),
             schema,
         }
     }
+
+    /// Run [`arr_import`](Self::arr_import) on `batch_rows` rows at a time, so only one
+    /// batch of decoded values is alive at once. `None` imports the column whole.
+    pub fn import_batched(
+        &self,
+        column: &ArrowColumn,
+        world: &mut World,
+        entities: &[Entity],
+        batch_rows: Option<usize>,
+    ) -> Result<(), SnapshotError> {
+        for (start, len) in row_batches(entities.len(), batch_rows) {
+            (self.arr_import)(
+                &column.slice(start, len),
+                world,
+                &entities[start..start + len],
+            )?;
+        }
+        Ok(())
+    }
+}
+/// Column of a [`new_pod`](ArrowSnapshotFactory::new_pod) factory: one fixed-size binary
+/// value per row holding the raw bytes of the component.
+fn pod_schema<T: Pod>() -> Vec<FieldRef> {
+    let width = size_of::<T>() as i32;
+    vec![Arc::new(Field::new(
+        "item",
+        DataType::FixedSizeBinary(width),
+        false,
+    ))]
+}
+fn pod_column<T: Pod>(fields: &[FieldRef], bytes: Vec<u8>) -> Result<ArrowColumn, SnapshotError> {
+    let array = FixedSizeBinaryArray::try_new(size_of::<T>() as i32, Buffer::from_vec(bytes), None)
+        .map_err(|e| SnapshotError::Generic(e.to_string()))?;
+    Ok(ArrowColumn {
+        fields: fields.to_vec(),
+        data: vec![Arc::new(array)],
+    })
+}
+/// Copy the rows of a pod column back into values of `T`.
+fn pod_values<T: Pod>(arrow: &ArrowColumn) -> Result<Vec<T>, SnapshotError> {
+    let name = std::any::type_name::<T>();
+    let array = arrow
+        .data
+        .first()
+        .and_then(|a| a.as_any().downcast_ref::<FixedSizeBinaryArray>())
+        .filter(|a| a.value_length() as usize == size_of::<T>())
+        .ok_or_else(|| {
+            SnapshotError::Generic(format!(
+                "{name}: expected a FixedSizeBinary({}) column",
+                size_of::<T>()
+            ))
+        })?;
+    if array.null_count() > 0 {
+        return Err(SnapshotError::MissingComponent(name.to_string()));
+    }
+    // `value_data` need not be aligned for `T`, so this copies instead of casting.
+    let mut out = vec![T::zeroed(); array.len()];
+    let bytes = &array.value_data()[..out.len() * size_of::<T>()];
+    bytemuck::cast_slice_mut::<T, u8>(&mut out).copy_from_slice(bytes);
+    Ok(out)
+}
+fn export_pod<T: Component + Pod>() -> ArrExportFn {
+    let arr_export: ArrExportFn = |fields, world, entities| {
+        let mut bytes = Vec::with_capacity(entities.len() * size_of::<T>());
+        for entity in entities {
+            let value = world.get::<T>(*entity).ok_or_else(|| {
+                SnapshotError::MissingComponent(std::any::type_name::<T>().to_string())
+            })?;
+            bytes.extend_from_slice(bytemuck::bytes_of(value));
+        }
+        pod_column::<T>(fields, bytes)
+    };
+    arr_export
+}
+fn export_table_pod<T: Component + Pod>() -> ArrTableExportFn {
+    let arr_export: ArrTableExportFn = |fields, world, table| {
+        let slice = table_slice::<T>(world, table)?;
+        // SAFETY: `UnsafeCell<T>` has the layout of `T`, and `world` is borrowed immutably,
+        // so nothing mutates the column meanwhile.
+        let values = unsafe { &*(slice as *const [UnsafeCell<T>] as *const [T]) };
+        Some(pod_column::<T>(
+            fields,
+            bytemuck::cast_slice(values).to_vec(),
+        ))
+    };
+    arr_export
+}
+fn import_pod<T: Component + Pod>() -> ArrImportFn {
+    let arr_import: ArrImportFn = |arrow, world, entities| {
+        let data = pod_values::<T>(arrow)?;
+        world.insert_batch(entities.iter().copied().zip(data));
+        Ok(())
+    };
+    arr_import
+}
+fn dyn_pod<T: Component + Pod>() -> ArrDynFn {
+    let arr_dyn_ctor: ArrDynFn = |arrow, bump| {
+        let data = pod_values::<T>(arrow)?
+            .into_iter()
+            .map(|component| ArenaBox::alloc(bump, component))
+            .collect();
+        Ok(data)
+    };
+    arr_dyn_ctor
+}
+impl ArrowSnapshotFactory {
+    /// Hash of [`schema`](Self::schema) over the field names, types and nullability, as
+    /// 16 hex digits. It only changes when the saved shape of the type does.
+    pub fn schema_hash(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        for field in &self.schema {
+            hasher.update(
+                format!(
+                    "{}:{}:{};",
+                    field.name(),
+                    field.data_type(),
+                    field.is_nullable()
+                )
+                .as_bytes(),
+            );
+        }
+        hasher.finalize().as_bytes()[..8]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+impl ArrowSnapshotFactory {
+    /// Arrow codec that stores `T` as its raw bytes, without going through serde.
+    ///
+    /// Each row is a `FixedSizeBinary(size_of::<T>())` value in the in-memory layout of
+    /// `T`, which is little-endian on every target Bevy supports. Table columns are exported
+    /// with a single copy of the dense slice, and loads copy the bytes straight back, so
+    /// this suits large plain-data columns such as particles or voxels. The files are only
+    /// readable by a build with the same layout of `T`.
+    ///
+    /// # Panics
+    /// If `T` is zero-sized.
+    pub fn new_pod<T: Component + Pod>() -> Self {
+        assert!(
+            size_of::<T>() > 0,
+            "zero-sized component {} cannot use the pod codec",
+            std::any::type_name::<T>()
+        );
+        Self {
+            arr_export: export_pod::<T>(),
+            arr_export_table: Some(export_table_pod::<T>()),
+            arr_import: import_pod::<T>(),
+            arr_dyn: dyn_pod::<T>(),
+            schema: pod_schema::<T>(),
+        }
+    }
 }
 #[allow(dead_code)]
 #[derive(Serialize, Deserialize)]
@@ -25,6 +25,18 @@ pub struct RawTData<'a> {
     pub data: Vec<ArenaBox<'a>>,
 }
 
+/// `(start, len)` row ranges covering `rows` rows, `batch_rows` at a time (all at once for
+/// `None`).
+pub(crate) fn row_batches(
+    rows: usize,
+    batch_rows: Option<usize>,
+) -> impl Iterator<Item = (usize, usize)> {
+    let step = batch_rows.unwrap_or(rows).max(1);
+    (0..rows)
+        .step_by(step)
+        .map(move |start| (start, step.min(rows - start)))
+}
+
 pub fn short_type_name<T>() -> &'static str {
     std::any::type_name::<T>()
         .rsplit("::")
@@ -76,6 +88,22 @@ impl ArrowColumn {
     //     Ok(d)
     // }
 
+    /// Zero-copy view of `len` rows starting at `offset`.
+    pub fn slice(&self, offset: usize, len: usize) -> Self {
+        Self {
+            fields: self.fields.clone(),
+            data: self.data.iter().map(|a| a.slice(offset, len)).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.first().map_or(0, |a| a.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn to_vec<T>(&self) -> Result<Vec<T>, Box<dyn std::error::Error>>
     where
         T: for<'de> Deserialize<'de>,
@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+#[cfg(any(feature = "lz4", feature = "deflate"))]
+use std::io::Write;
+use std::io::{self, Read};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SparseSegment {
@@ -40,6 +44,21 @@ impl SparseU32List {
         Self { segments }
     }
 
+    /// Number of ids in the list, computed without decompressing.
+    pub fn len(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|seg| match *seg {
+                SparseSegment::Single(_) => 1,
+                SparseSegment::Range(start, end) => (end as u64).saturating_sub(start as u64) + 1,
+            })
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
     /// Decompress to Vec<u32>
     pub fn to_vec(&self) -> Vec<u32> {
         let mut out = Vec::new();
@@ -58,6 +77,99 @@ impl SparseU32List {
 #[derive(Serialize, Clone, Debug, Default, Deserialize)]
 pub struct BinBlob(#[serde(with = "serde_bytes")] pub Vec<u8>);
 
+/// Compression of the blobs of a [`WorldBinArchSnapshot`]. Each codec but `None` needs
+/// its feature: `zstd`, `lz4` or `deflate`.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
+pub enum BlobCodec {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+    Deflate,
+}
+
+/// How blobs are encoded when a [`WorldBinArchSnapshot`] is saved.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BinSaveOptions {
+    pub codec: BlobCodec,
+    /// Level for zstd and deflate; `None` uses the codec's default.
+    pub level: Option<i32>,
+}
+
+impl BinSaveOptions {
+    pub fn with_codec(codec: BlobCodec) -> Self {
+        Self { codec, level: None }
+    }
+}
+
+fn unsupported(codec: BlobCodec) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{codec:?} blobs need the matching cargo feature"),
+    )
+}
+
+fn decoder(codec: BlobCodec, bytes: &[u8]) -> io::Result<Box<dyn Read + '_>> {
+    match codec {
+        BlobCodec::None => Ok(Box::new(bytes)),
+        #[cfg(feature = "zstd")]
+        BlobCodec::Zstd => Ok(Box::new(zstd::stream::read::Decoder::new(bytes)?)),
+        #[cfg(feature = "lz4")]
+        BlobCodec::Lz4 => Ok(Box::new(lz4_flex::frame::FrameDecoder::new(bytes))),
+        #[cfg(feature = "deflate")]
+        BlobCodec::Deflate => Ok(Box::new(flate2::read::DeflateDecoder::new(bytes))),
+        #[allow(unreachable_patterns)]
+        codec => Err(unsupported(codec)),
+    }
+}
+
+impl BinBlob {
+    /// Compress `bytes` as `options` says.
+    pub fn encode(bytes: Vec<u8>, options: &BinSaveOptions) -> io::Result<Self> {
+        let packed = match options.codec {
+            BlobCodec::None => bytes,
+            #[cfg(feature = "zstd")]
+            BlobCodec::Zstd => zstd::encode_all(&bytes[..], options.level.unwrap_or(0))?,
+            #[cfg(feature = "lz4")]
+            BlobCodec::Lz4 => {
+                let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+                encoder.write_all(&bytes)?;
+                encoder.finish().map_err(io::Error::other)?
+            }
+            #[cfg(feature = "deflate")]
+            BlobCodec::Deflate => {
+                let level = options.level.map_or(flate2::Compression::default(), |l| {
+                    flate2::Compression::new(l.clamp(0, 9) as u32)
+                });
+                let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), level);
+                encoder.write_all(&bytes)?;
+                encoder.finish()?
+            }
+            #[allow(unreachable_patterns)]
+            codec => return Err(unsupported(codec)),
+        };
+        Ok(Self(packed))
+    }
+
+    /// The bytes of a blob compressed with `codec`, failing once they exceed `max_bytes`.
+    pub fn decode(&self, codec: BlobCodec, max_bytes: u64) -> io::Result<Cow<'_, [u8]>> {
+        if codec == BlobCodec::None {
+            return Ok(Cow::Borrowed(&self.0));
+        }
+        let mut out = Vec::new();
+        decoder(codec, &self.0)?
+            .take(max_bytes.saturating_add(1))
+            .read_to_end(&mut out)?;
+        if out.len() as u64 > max_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("decompressed blob exceeds {max_bytes} bytes"),
+            ));
+        }
+        Ok(Cow::Owned(out))
+    }
+}
+
 #[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
 pub enum BinFormat {
     #[default]
@@ -69,9 +181,13 @@ pub enum BinFormat {
 pub struct WorldBinArchSnapshot {
     pub entities: SparseU32List,
     pub archetypes: Vec<BinBlob>,
-    pub resources: HashMap<String, BinBlob>,
+    pub resources: BTreeMap<String, BinBlob>,
     pub format: BinFormat,
-    pub meta: HashMap<String, String>,
+    pub meta: BTreeMap<String, String>,
+    /// Compression of the archetype and resource blobs. Archives written before it existed
+    /// read as `None`.
+    #[serde(default)]
+    pub codec: BlobCodec,
 }
 
 impl WorldBinArchSnapshot {
@@ -1,18 +1,48 @@
 use crate::archetype_archive::{
     load_world_arch_snapshot_defragment, load_world_arch_snapshot_with_remap, save_single_archetype_snapshot, ArchetypeSnapshot,
-    WorldArchSnapshot, WorldExt,
+    LoadReport, WorldArchSnapshot, WorldExt,
+};
+use crate::bevy_registry::{EntityRemapper, IDRemapRegistry, LimitError, LoadLimits, SnapshotRegistry};
+use crate::binary_archive::common::{
+    BinBlob, BinFormat, BinSaveOptions, BlobCodec, SparseU32List, WorldBinArchSnapshot,
 };
-use crate::bevy_registry::{SnapshotRegistry, IDRemapRegistry, EntityRemapper};
-use crate::binary_archive::common::{BinBlob, BinFormat, SparseU32List, WorldBinArchSnapshot};
 use crate::traits::Archive;
+use crate::unknown_components::attach_unknown_components;
 use bevy_ecs::prelude::*;
-use std::collections::HashMap;
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
-use std::io::{self};
+use std::io::{self, BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
 pub struct MsgPackArchive(pub WorldBinArchSnapshot);
 
+/// What a save browser lists about a [`MsgPackArchive`], read by
+/// [`MsgPackArchive::read_header`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveHeader {
+    pub format: BinFormat,
+    pub entity_count: u64,
+    pub archetype_count: usize,
+    /// Names of the saved resources, sorted.
+    pub resources: Vec<String>,
+    pub meta: BTreeMap<String, String>,
+    pub codec: BlobCodec,
+}
+
+/// [`WorldBinArchSnapshot`] with the blobs skipped instead of copied.
+#[derive(Deserialize)]
+struct HeaderRepr {
+    entities: SparseU32List,
+    archetypes: Vec<IgnoredAny>,
+    resources: BTreeMap<String, IgnoredAny>,
+    format: BinFormat,
+    meta: BTreeMap<String, String>,
+    #[serde(default)]
+    codec: BlobCodec,
+}
+
 impl Archive for MsgPackArchive {
     fn create(
         world: &World,
@@ -26,7 +56,8 @@ impl Archive for MsgPackArchive {
         world: &mut World,
         registry: &SnapshotRegistry,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.to_world(world, registry).map_err(|e| e.into())
+        self.to_world(world, registry)?;
+        Ok(())
     }
 
     fn apply_with_remap(
@@ -36,8 +67,9 @@ impl Archive for MsgPackArchive {
         id_registry: &IDRemapRegistry,
         mapper: &dyn EntityRemapper,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.check_limits(&registry.limits)?;
         let snap = self.decode_snapshot()?;
-        load_world_arch_snapshot_with_remap(world, &snap, registry, id_registry, mapper);
+        load_world_arch_snapshot_with_remap(world, &snap, registry, id_registry, mapper)?;
         self.load_resources(world, registry).map_err(|e| e.into())
     }
 
@@ -65,13 +97,31 @@ impl Archive for MsgPackArchive {
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         Self::from_file(path).map_err(|e| e.into())
     }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(rmp_serde::to_vec(&self.0)?)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(rmp_serde::from_slice(bytes)?))
+    }
 }
 
 impl MsgPackArchive {
     /// Save the world to an in-memory MsgPackArchive
     pub fn from_world(world: &World, reg: &SnapshotRegistry) -> Result<Self, io::Error> {
+        Self::from_world_with(world, reg, &BinSaveOptions::default())
+    }
+
+    /// [`from_world`](Self::from_world), compressing the blobs as `options` says.
+    pub fn from_world_with(
+        world: &World,
+        reg: &SnapshotRegistry,
+        options: &BinSaveOptions,
+    ) -> Result<Self, io::Error> {
         let mut snapshot = WorldBinArchSnapshot::default();
         snapshot.format = BinFormat::MsgPack;
+        snapshot.codec = options.codec;
 
         // 1. Entities
         let entities: Vec<u32> = WorldExt::iter_entities(world).map(|e| e.index_u32()).collect();
@@ -91,14 +141,15 @@ impl MsgPackArchive {
             .iter()
             .filter(|x| !x.is_empty() && !x.contains(bevy_ecs::resource::IS_RESOURCE));
 
-        for arch in archetypes {
-            let arch_snap = save_single_archetype_snapshot(world, arch, reg, &reg_comp_ids);
-            if !arch_snap.entities.is_empty() {
-                // Serialize ArchetypeSnapshot to MsgPack bytes
-                let bytes = rmp_serde::to_vec(&arch_snap)
-                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-                snapshot.archetypes.push(BinBlob(bytes));
-            }
+        let mut arch_snaps: Vec<ArchetypeSnapshot> = archetypes
+            .map(|arch| save_single_archetype_snapshot(world, arch, reg, &reg_comp_ids))
+            .filter(|arch_snap| !arch_snap.entities.is_empty())
+            .collect();
+        attach_unknown_components(world, &mut arch_snaps);
+        for arch_snap in &arch_snaps {
+            // Serialize ArchetypeSnapshot to MsgPack bytes
+            let bytes = rmp_serde::to_vec(arch_snap).map_err(io::Error::other)?;
+            snapshot.archetypes.push(BinBlob::encode(bytes, options)?);
         }
 
         // 3. Resources
@@ -107,13 +158,26 @@ impl MsgPackArchive {
             {
                 let bytes = rmp_serde::to_vec(&value)
                     .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-                snapshot.resources.insert(name.to_string(), BinBlob(bytes));
+                snapshot
+                    .resources
+                    .insert(name.to_string(), BinBlob::encode(bytes, options)?);
             }
         }
 
         Ok(Self(snapshot))
     }
     
+    /// Check entity count, archetype count and blob sizes before anything is decoded.
+    pub fn check_limits(&self, limits: &LoadLimits) -> Result<(), io::Error> {
+        let invalid = |e: LimitError| io::Error::new(io::ErrorKind::InvalidData, e);
+        limits.check_entities(self.0.entities.len()).map_err(invalid)?;
+        limits.check_archetypes(self.0.archetypes.len()).map_err(invalid)?;
+        for blob in self.0.archetypes.iter().chain(self.0.resources.values()) {
+            limits.check_blob(blob.0.len() as u64).map_err(invalid)?;
+        }
+        Ok(())
+    }
+
     pub fn decode_snapshot(&self) -> Result<WorldArchSnapshot, io::Error> {
          if self.0.format != BinFormat::MsgPack {
             return Err(io::Error::new(
@@ -125,8 +189,10 @@ impl MsgPackArchive {
         let mut world_arch_snap = WorldArchSnapshot::default();
         world_arch_snap.entities = self.0.entities.to_vec();
 
+        let max = LoadLimits::default().max_blob_bytes;
         for blob in &self.0.archetypes {
-            let arch_snap: ArchetypeSnapshot = rmp_serde::from_slice(&blob.0)
+            let bytes = blob.decode(self.0.codec, max)?;
+            let arch_snap: ArchetypeSnapshot = rmp_serde::from_slice(&bytes)
                 .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
             world_arch_snap.archetypes.push(arch_snap);
         }
@@ -135,9 +201,12 @@ impl MsgPackArchive {
     }
 
     pub fn load_resources(&self, world: &mut World, reg: &SnapshotRegistry) -> Result<(), io::Error> {
+        reg.check_known(self.0.resources.keys().map(String::as_str))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
          for (name, blob) in &self.0.resources {
             if let Some(factory) = reg.get_res_factory(name) {
-                let value: serde_json::Value = rmp_serde::from_slice(&blob.0)
+                let bytes = blob.decode(self.0.codec, reg.limits.max_blob_bytes)?;
+                let value: serde_json::Value = rmp_serde::from_slice(&bytes)
                     .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
                 
                 (factory.js_value.import)(&value, world, Entity::from_raw_u32(0).unwrap())
@@ -148,7 +217,7 @@ impl MsgPackArchive {
     }
 
     /// Load the archive into the world
-    pub fn to_world(&self, world: &mut World, reg: &SnapshotRegistry) -> Result<(), io::Error> {
+    pub fn to_world(&self, world: &mut World, reg: &SnapshotRegistry) -> Result<LoadReport, io::Error> {
         if self.0.format != BinFormat::MsgPack {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
@@ -156,24 +225,32 @@ impl MsgPackArchive {
             ));
         }
 
+        self.check_limits(&reg.limits)?;
+        reg.check_known(self.0.resources.keys().map(String::as_str))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
         // 1. Entities & Archetypes
         // Reconstruct WorldArchSnapshot (the structure used by archetype_archive loader)
         let mut world_arch_snap = WorldArchSnapshot::default();
         world_arch_snap.entities = self.0.entities.to_vec();
 
+        let max = reg.limits.max_blob_bytes;
         for blob in &self.0.archetypes {
-            let arch_snap: ArchetypeSnapshot = rmp_serde::from_slice(&blob.0)
+            let bytes = blob.decode(self.0.codec, max)?;
+            let arch_snap: ArchetypeSnapshot = rmp_serde::from_slice(&bytes)
                 .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
             world_arch_snap.archetypes.push(arch_snap);
         }
 
         // Use the existing defragmenting loader
-        load_world_arch_snapshot_defragment(world, &world_arch_snap, reg);
+        let report = load_world_arch_snapshot_defragment(world, &world_arch_snap, reg)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
         // 2. Resources
         for (name, blob) in &self.0.resources {
             if let Some(factory) = reg.get_res_factory(name) {
-                let value: serde_json::Value = rmp_serde::from_slice(&blob.0)
+                let bytes = blob.decode(self.0.codec, reg.limits.max_blob_bytes)?;
+                let value: serde_json::Value = rmp_serde::from_slice(&bytes)
                     .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
                 
                 (factory.js_value.import)(&value, world, Entity::from_raw_u32(0).unwrap())
@@ -181,21 +258,77 @@ impl MsgPackArchive {
             }
         }
 
-        Ok(())
+        Ok(report)
     }
 
     pub fn to_file(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
-        let mut file = File::create(path)?;
-        rmp_serde::encode::write(&mut file, &self.0)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+        self.write_to(BufWriter::new(File::create(path)?))
     }
 
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self, io::Error> {
-        let file = File::open(path)?;
-        let snapshot: WorldBinArchSnapshot = rmp_serde::decode::from_read(file)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Encode the archive into `writer`, e.g. a socket, a compression stream or a buffer.
+    /// The writer is flushed once the archive is written.
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), io::Error> {
+        rmp_serde::encode::write(&mut writer, &self.0).map_err(io::Error::other)?;
+        writer.flush()
+    }
+
+    /// Decode an archive written by [`write_to`](Self::write_to) from `reader`. Reads one
+    /// archive and no further, so several can follow each other on one stream.
+    pub fn read_from(reader: impl Read) -> Result<Self, io::Error> {
+        let snapshot: WorldBinArchSnapshot =
+            rmp_serde::decode::from_read(reader).map_err(io::Error::other)?;
         Ok(Self(snapshot))
     }
+
+    /// Read the [`ArchiveHeader`] of the archive at `path`. Archetype and resource blobs
+    /// are read past without being kept or decoded, so listing many saves stays cheap.
+    pub fn read_header(path: impl AsRef<Path>) -> Result<ArchiveHeader, io::Error> {
+        Self::read_header_from(BufReader::new(File::open(path)?))
+    }
+
+    /// [`read_header`](Self::read_header) from any reader, e.g. an entry of a container.
+    pub fn read_header_from(reader: impl Read) -> Result<ArchiveHeader, io::Error> {
+        let repr: HeaderRepr = rmp_serde::decode::from_read(reader).map_err(io::Error::other)?;
+        Ok(ArchiveHeader {
+            format: repr.format,
+            entity_count: repr.entities.len(),
+            archetype_count: repr.archetypes.len(),
+            resources: repr.resources.into_keys().collect(),
+            meta: repr.meta,
+            codec: repr.codec,
+        })
+    }
+
+    /// Async [`to_file`](Self::to_file): encodes now, writes through `tokio::fs`.
+    ///
+    /// The returned future does not borrow `self`. Must be polled inside a tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn to_file_async(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> impl std::future::Future<Output = Result<(), io::Error>> + Send + 'static {
+        let bytes = rmp_serde::to_vec(&self.0).map_err(io::Error::other);
+        let path = path.as_ref().to_path_buf();
+        async move { tokio::fs::write(path, bytes?).await }
+    }
+
+    /// Async [`from_file`](Self::from_file): reads through `tokio::fs`, decodes on the
+    /// blocking pool.
+    #[cfg(feature = "tokio")]
+    pub async fn from_file_async(path: impl AsRef<Path>) -> Result<Self, io::Error> {
+        let bytes = tokio::fs::read(path).await?;
+        tokio::task::spawn_blocking(move || {
+            rmp_serde::from_slice::<WorldBinArchSnapshot>(&bytes)
+                .map(Self)
+                .map_err(io::Error::other)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
 }
 
 #[cfg(test)]
@@ -260,6 +393,48 @@ mod tests {
         assert_eq!(config.mode, "Hardcore");
     }
 
+    #[test]
+    fn test_blob_codecs() {
+        let mut world = World::new();
+        let registry = setup_registry();
+        for i in 0..64 {
+            world.spawn(Position {
+                x: i as f32,
+                y: 0.0,
+            });
+        }
+        world.insert_resource(GameConfig {
+            difficulty: 1,
+            mode: "Story".to_string(),
+        });
+        let plain = MsgPackArchive::from_world(&world, &registry).unwrap();
+
+        let codecs = [BlobCodec::Zstd, BlobCodec::Lz4, BlobCodec::Deflate];
+        for codec in codecs {
+            let options = BinSaveOptions::with_codec(codec);
+            let Ok(archive) = MsgPackArchive::from_world_with(&world, &registry, &options) else {
+                continue;
+            };
+            assert!(archive.0.archetypes[0].0.len() < plain.0.archetypes[0].0.len());
+            let bytes = archive.to_bytes().unwrap();
+            let header = MsgPackArchive::read_header_from(&bytes[..]).unwrap();
+            assert_eq!(header.codec, codec);
+
+            let mut loaded = World::new();
+            let archive = MsgPackArchive::from_bytes(&bytes).unwrap();
+            archive.to_world(&mut loaded, &registry).unwrap();
+            assert_eq!(loaded.query::<&Position>().iter(&loaded).count(), 64);
+            assert_eq!(loaded.resource::<GameConfig>().mode, "Story");
+        }
+
+        #[cfg(not(feature = "lz4"))]
+        {
+            let options = BinSaveOptions::with_codec(BlobCodec::Lz4);
+            let err = MsgPackArchive::from_world_with(&world, &registry, &options).err();
+            assert_eq!(err.map(|e| e.kind()), Some(io::ErrorKind::Unsupported));
+        }
+    }
+
     #[test]
     fn test_file_io() {
          let mut world = World::new();
@@ -282,4 +457,76 @@ mod tests {
         
         std::fs::remove_file(path).unwrap();
     }
+
+    #[test]
+    fn test_stream_io() {
+        let mut world = World::new();
+        let registry = setup_registry();
+        world.spawn(Position { x: 1.0, y: 2.0 });
+        world.spawn(Position { x: 3.0, y: 4.0 });
+
+        let archive = MsgPackArchive::from_world(&world, &registry).unwrap();
+        let mut stream = Vec::new();
+        archive.write_to(&mut stream).unwrap();
+        archive.write_to(&mut stream).unwrap();
+
+        let mut reader = std::io::Cursor::new(stream);
+        for _ in 0..2 {
+            let mut new_world = World::new();
+            MsgPackArchive::read_from(&mut reader)
+                .unwrap()
+                .to_world(&mut new_world, &registry)
+                .unwrap();
+            assert_eq!(new_world.query::<&Position>().iter(&new_world).count(), 2);
+        }
+        assert!(MsgPackArchive::read_from(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_read_header() {
+        let mut world = World::new();
+        let registry = setup_registry();
+        world.spawn(Position { x: 1.0, y: 2.0 });
+        world.spawn(Position { x: 3.0, y: 4.0 });
+        world.insert_resource(GameConfig {
+            difficulty: 1,
+            mode: "Story".to_string(),
+        });
+        let path = "test_msgpack_header.bin";
+
+        let mut archive = MsgPackArchive::from_world(&world, &registry).unwrap();
+        archive.0.meta.insert("slot".to_string(), "2".to_string());
+        archive.to_file(path).unwrap();
+        let header = MsgPackArchive::read_header(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(header.format, BinFormat::MsgPack);
+        assert_eq!(header.entity_count, archive.0.entities.len());
+        assert_eq!(header.archetype_count, archive.0.archetypes.len());
+        assert_eq!(header.resources, ["GameConfig"]);
+        assert_eq!(header.meta["slot"], "2");
+    }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn test_file_io_async() {
+        let mut world = World::new();
+        let registry = setup_registry();
+        world.spawn(Position { x: 1.0, y: 2.0 });
+        let path = "test_msgpack_archive_async.bin";
+
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let archive = MsgPackArchive::from_world(&world, &registry).unwrap();
+        let write = archive.to_file_async(path);
+        drop(archive);
+        rt.block_on(write).unwrap();
+        let loaded_archive = rt.block_on(MsgPackArchive::from_file_async(path)).unwrap();
+
+        let mut new_world = World::new();
+        loaded_archive.to_world(&mut new_world, &registry).unwrap();
+        let pos = new_world.query::<&Position>().single(&new_world).unwrap();
+        assert_eq!(pos.x, 1.0);
+
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -6,6 +6,7 @@ use crate::{
 };
 use bevy_ecs::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // === Test Components ===
 #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -217,3 +218,669 @@ fn test_snapshot_zip_roundtrip() {
 
     std::fs::remove_file("snapshot.zip").unwrap();
 }
+
+#[test]
+fn test_single_archetype_parquet_file() {
+    use crate::binary_archive::{load_arrow_archetype_from_file, save_arrow_archetype_to_file};
+
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+    let pos = world.component_id::<Position>().unwrap();
+    let vel = world.component_id::<Velocity>().unwrap();
+
+    let path = "single_archetype.parquet";
+    save_arrow_archetype_to_file(&world, &registry, |a| a.contains(pos) && a.contains(vel), path)
+        .unwrap();
+    // Position alone appears in several archetypes.
+    assert!(save_arrow_archetype_to_file(&world, &registry, |a| a.contains(pos), path).is_err());
+
+    let mut new_world = World::new();
+    load_arrow_archetype_from_file(&mut new_world, &registry, path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let mut q = new_world.query::<(&Position, &Velocity)>();
+    let loaded: Vec<_> = q.iter(&new_world).collect();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].0, &Position { x: 1.0, y: 2.0 });
+    assert_eq!(new_world.query::<&Inventory>().iter(&new_world).count(), 0);
+}
+
+#[test]
+fn test_snapshot_container_roundtrip() {
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let mut container = std::collections::BTreeMap::new();
+    snapshot.write_to_container(&mut container).unwrap();
+
+    let limits = LoadLimits::default();
+    let back = WorldArrowSnapshot::read_from_container(&mut container, &limits).unwrap();
+    assert_eq!(back.archetypes.len(), snapshot.archetypes.len());
+    assert_eq!(back.resources.len(), snapshot.resources.len());
+
+    let tight = LoadLimits {
+        max_blob_bytes: 8,
+        ..LoadLimits::default()
+    };
+    assert!(WorldArrowSnapshot::read_from_container(&mut container, &tight).is_err());
+}
+
+#[test]
+fn test_update_zip_entry() {
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+
+    let path = "update_entry.zip";
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    std::fs::write(path, snapshot.to_zip(None).unwrap()).unwrap();
+    let idx = snapshot
+        .archetypes
+        .iter()
+        .position(|t| t.column_names().eq(["Position", "Velocity"]))
+        .unwrap();
+
+    // Change one entity in the archetype and add another to it.
+    let mut q = world.query::<(&mut Position, &Velocity)>();
+    for (mut p, _) in q.iter_mut(&mut world) {
+        p.x = 42.0;
+    }
+    let added = world
+        .spawn((Position { x: 7.0, y: 7.0 }, Velocity { dx: 0.0, dy: 0.0 }))
+        .id();
+
+    WorldArrowSnapshot::update_zip_entry(path, &format!("arch_{idx}"), &world, &registry).unwrap();
+    assert!(WorldArrowSnapshot::update_zip_entry(path, "arch_999", &world, &registry).is_err());
+    let updated = WorldArrowSnapshot::from_zip(&std::fs::read(path).unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(updated.archetypes.len(), snapshot.archetypes.len());
+    assert_eq!(updated.archetypes[idx].entities.len(), 2);
+    assert!(updated.entities.contains(&added.index_u32()));
+
+    let mut new_world = World::new();
+    updated.to_world_reg(&mut new_world, &registry).unwrap();
+    let mut q = new_world.query::<(&Position, &Velocity)>();
+    let mut xs: Vec<_> = q.iter(&new_world).map(|(p, _)| p.x).collect();
+    xs.sort_by(f32::total_cmp);
+    assert_eq!(xs, vec![7.0, 42.0]);
+    assert_eq!(new_world.query::<&Inventory>().iter(&new_world).count(), 3);
+}
+
+#[test]
+fn test_zip_diff_and_patch() {
+    use crate::binary_archive::{apply_zip_patch, diff_zip};
+
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let a = snapshot.to_zip(None).unwrap();
+    assert!(diff_zip(&a, &snapshot.to_zip(Some(9)).unwrap()).unwrap().is_empty());
+
+    let mut q = world.query::<(&mut Position, &Velocity)>();
+    for (mut p, _) in q.iter_mut(&mut world) {
+        p.x = 42.0;
+    }
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let b = snapshot.to_zip(None).unwrap();
+    let idx = snapshot
+        .archetypes
+        .iter()
+        .position(|t| t.column_names().eq(["Position", "Velocity"]))
+        .unwrap();
+    let diff = diff_zip(&a, &b).unwrap();
+    // `meta.toml` changes with it, as it holds the checksum of the entry.
+    let changed = vec![format!("archetypes/arch_{idx}.parquet"), "meta.toml".to_string()];
+    assert_eq!(diff.changed, changed);
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+
+    let patch = diff.to_patch(&b).unwrap();
+    assert!(patch.len() < b.len());
+    let patched = apply_zip_patch(&a, &patch).unwrap();
+    assert!(diff_zip(&patched, &b).unwrap().is_empty());
+    assert!(apply_zip_patch(&a, &b).is_err());
+}
+
+#[test]
+fn test_zip_patch_after_codec_change() {
+    use crate::arrow_snapshot::ParquetCompression;
+    use crate::binary_archive::{apply_zip_patch, diff_zip};
+
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+    let mut snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let a = snapshot.to_zip(None).unwrap();
+    snapshot.set_default_compression(ParquetCompression::Snappy);
+    let b = snapshot.to_zip(None).unwrap();
+
+    let diff = diff_zip(&a, &b).unwrap();
+    // Only `meta.toml` changes; the re-encoded tables are carried by the patch.
+    assert_eq!(diff.changed, vec!["meta.toml".to_string()]);
+    assert_eq!(diff.recoded.len(), snapshot.archetypes.len());
+    let patched = apply_zip_patch(&a, &diff.to_patch(&b).unwrap()).unwrap();
+    assert!(diff_zip(&patched, &b).unwrap().is_empty());
+    let back = WorldArrowSnapshot::from_zip(&patched).unwrap();
+    assert_eq!(back.archetypes.len(), snapshot.archetypes.len());
+}
+
+#[test]
+fn test_schema_hash_checked_before_load() {
+    use crate::bevy_registry::{LimitError, vec_snapshot_factory::SnapshotError};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Position3 {
+        x: f32,
+        y: f32,
+        z: f32,
+    }
+
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let hash = registry.schema_hash("Position").unwrap();
+    assert_eq!(snapshot.meta.get("schema_hash.Position"), Some(&hash));
+    let mut snapshot = WorldArrowSnapshot::from_zip(&snapshot.to_zip(None).unwrap()).unwrap();
+
+    let mut changed = setup_registry();
+    changed.register_named::<Position3>("Position");
+    changed.unregister("Velocity");
+    changed.load_options.strict = true;
+    assert_ne!(changed.schema_hash("Position").unwrap(), hash);
+    let mut new_world = World::new();
+    let err = snapshot.to_world_reg(&mut new_world, &changed);
+    let Err(SnapshotError::Limit(LimitError::IncompatibleSchema(mismatch))) = err else {
+        panic!("expected a schema mismatch, got {err:?}");
+    };
+    assert_eq!(mismatch.missing, ["Velocity"]);
+    assert_eq!(mismatch.changed, ["Position"]);
+
+    // Saves without a registry schema fall back to the per-component hashes.
+    snapshot
+        .meta
+        .remove(crate::registry_schema::REGISTRY_SCHEMA_KEY);
+    changed.register::<Velocity>();
+    let err = snapshot.to_world_reg(&mut new_world, &changed);
+    assert!(matches!(
+        err,
+        Err(SnapshotError::Limit(LimitError::SchemaChanged(ref name))) if name == "Position"
+    ));
+    assert_eq!(new_world.query::<&Position>().iter(&new_world).count(), 0);
+
+    snapshot.to_world_reg(&mut new_world, &registry).unwrap();
+}
+
+#[test]
+fn test_per_archetype_parquet_compression() {
+    use crate::arrow_snapshot::ParquetCompression;
+    use parquet::basic::Compression;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+
+    let mut snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let inv = snapshot
+        .archetypes
+        .iter()
+        .position(|t| t.get_column("Inventory").is_some())
+        .unwrap();
+    snapshot
+        .set_default_compression(ParquetCompression::Snappy)
+        .set_archetype_compression(inv, ParquetCompression::Zstd(3));
+
+    let mut container = std::collections::BTreeMap::new();
+    snapshot.write_to_container(&mut container).unwrap();
+    let back = WorldArrowSnapshot::read_from_container(&mut container, &LoadLimits::default())
+        .unwrap();
+    assert_eq!(back.archetype_compression(inv).unwrap(), ParquetCompression::Zstd(3));
+
+    let codec = |idx: usize| {
+        let bytes = container[&format!("archetypes/arch_{idx}.parquet")].clone();
+        let reader = SerializedFileReader::new(bytes::Bytes::from(bytes)).unwrap();
+        reader.metadata().row_group(0).column(0).compression()
+    };
+    assert!(matches!(codec(inv), Compression::ZSTD(_)));
+    let other = (0..snapshot.archetypes.len()).find(|&i| i != inv).unwrap();
+    assert_eq!(codec(other), Compression::SNAPPY);
+
+    assert_eq!("zstd(7)".parse(), Ok(ParquetCompression::Zstd(7)));
+    assert_eq!("none".parse(), Ok(ParquetCompression::None));
+    assert!("zstd(x)".parse::<ParquetCompression>().is_err());
+}
+
+#[test]
+fn test_arrow_ipc_roundtrip() {
+    use crate::arrow_snapshot::ComponentTable;
+
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    for table in &snapshot.archetypes {
+        let file = ComponentTable::from_ipc(&table.to_ipc().unwrap()).unwrap();
+        let stream = ComponentTable::from_ipc(&table.to_ipc_stream().unwrap()).unwrap();
+        assert_eq!(file.entities.len(), table.entities.len());
+        assert!(stream.column_names().eq(table.column_names()));
+    }
+
+    let back = WorldArrowSnapshot::from_ipc(&snapshot.to_ipc().unwrap()).unwrap();
+    assert_eq!(back.archetypes.len(), snapshot.archetypes.len());
+    let mut new_world = World::new();
+    back.to_world_reg(&mut new_world, &registry).unwrap();
+    assert_eq!(new_world.query::<&Position>().iter(&new_world).count(), 5);
+    assert_eq!(new_world.query::<&Inventory>().iter(&new_world).count(), 3);
+
+    // Aurora manifests can embed IPC blobs as well.
+    let guide = ExportGuidance::embed_all(ExportFormat::ArrowIpc);
+    let manifest = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+    assert!(manifest.world.embed.values().all(|b| b.format == "arrow"));
+    let text = toml::to_string(&manifest).unwrap();
+    let manifest: AuroraWorldManifest = toml::from_str(&text).unwrap();
+    let mut aurora_world = World::new();
+    load_world_manifest(&mut aurora_world, &manifest, &registry).unwrap();
+    assert_eq!(aurora_world.query::<&Position>().iter(&aurora_world).count(), 5);
+}
+
+#[test]
+fn test_extract_entity_with_index() {
+    let mut world = World::new();
+    let registry = setup_registry();
+    let boss = build_sample_world(&mut world);
+
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    for data in [snapshot.to_zip(None).unwrap(), snapshot.to_ipc().unwrap()] {
+        let row = WorldArrowSnapshot::extract_entity(&data, boss.index_u32())
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.entities.len(), 1);
+        assert_eq!(row.entities[0].id, boss.index_u32());
+        assert!(row.get_column("NestedComponent").is_some());
+        assert!(WorldArrowSnapshot::extract_entity(&data, 9999).unwrap().is_none());
+    }
+
+    let index = snapshot.entity_index();
+    for (idx, table) in snapshot.archetypes.iter().enumerate() {
+        for (row, e) in table.entities.iter().enumerate() {
+            assert_eq!(index.locate(e.id), Some((idx, row)));
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[component(storage = "SparseSet")]
+struct Marker(u32);
+
+#[test]
+fn test_table_slice_export() {
+    let mut registry = SnapshotRegistry::default();
+    registry.register::<Position>();
+    registry.register_with::<Vector2, Vector2Wrapper>();
+    registry.register::<Marker>();
+    let mut world = World::new();
+    let mut spawned: Vec<Entity> = (0..64)
+        .map(|i| {
+            let pos = Position {
+                x: i as f32,
+                y: -(i as f32),
+            };
+            let vector = Vector2([i as f32, 1.0]);
+            match i % 3 {
+                // These two archetypes share one table and take the per-entity path.
+                0 => world.spawn((pos, vector, Marker(i))).id(),
+                1 => world.spawn((pos, vector)).id(),
+                _ => world.spawn((pos, vector, Tag)).id(),
+            }
+        })
+        .collect();
+    // Swap-removes reorder the table rows.
+    for e in spawned.drain(10..20).collect::<Vec<_>>() {
+        world.despawn(e);
+    }
+
+    let reg_comp_ids: HashMap<_, _> = registry
+        .type_registry
+        .keys()
+        .filter_map(|&name| registry.comp_id_by_name(name, &world).map(|cid| (cid, name)))
+        .collect();
+    let pos_factory = registry.get_factory("Position").unwrap().arrow.as_ref().unwrap();
+    let pos_id = world.component_id::<Position>().unwrap();
+    for arch in world.archetypes().iter().filter(|a| a.contains(pos_id)) {
+        let table = crate::binary_archive::save_arrow_archetype_from_world(
+            &world,
+            &registry,
+            arch,
+            &reg_comp_ids,
+        )
+        .unwrap();
+        let entities: Vec<Entity> = table
+            .entities
+            .iter()
+            .map(|id| crate::serde_utils::entity_from_index(id.id))
+            .map(|e| world.entities().resolve_from_index(e.index()))
+            .collect();
+        let per_entity = (pos_factory.arr_export)(&pos_factory.schema, &world, &entities).unwrap();
+        let sliced = table.get_column("Position").unwrap();
+        assert_eq!(sliced.data.len(), per_entity.data.len());
+        for (a, b) in sliced.data.iter().zip(&per_entity.data) {
+            assert_eq!(a.to_data(), b.to_data());
+        }
+    }
+
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let mut restored = World::new();
+    snapshot.to_world_reg(&mut restored, &registry).unwrap();
+    for e in spawned {
+        let at = restored.entities().resolve_from_index(e.index());
+        assert_eq!(restored.get::<Position>(at), world.get::<Position>(e));
+        assert_eq!(restored.get::<Marker>(at), world.get::<Marker>(e));
+        assert_eq!(
+            restored.get::<Vector2>(at).map(|v| v.0),
+            world.get::<Vector2>(e).map(|v| v.0)
+        );
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+struct Particle {
+    pos: [f32; 3],
+    vel: [f32; 3],
+    id: u32,
+}
+// SAFETY: `repr(C)` plain data without padding.
+unsafe impl bytemuck::Zeroable for Particle {}
+unsafe impl bytemuck::Pod for Particle {}
+
+#[test]
+fn test_pod_column_roundtrip() {
+    let mut registry = SnapshotRegistry::default();
+    registry.register_pod::<Particle>();
+    registry.register::<Position>();
+    let mut world = World::new();
+    let particle = |i: u32| Particle {
+        pos: [i as f32, 0.5, -1.0],
+        vel: [0.0, i as f32 * 0.25, 2.0],
+        id: i,
+    };
+    let spawned: Vec<(Entity, Particle)> = (0..500)
+        .map(|i| {
+            let p = particle(i);
+            let e = if i % 2 == 0 {
+                world.spawn(p).id()
+            } else {
+                world.spawn((p, Position { x: 1.0, y: 2.0 })).id()
+            };
+            (e, p)
+        })
+        .collect();
+
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let column = snapshot.archetypes[0].get_column("Particle").unwrap();
+    assert_eq!(
+        column.data[0].data_type(),
+        &arrow::datatypes::DataType::FixedSizeBinary(size_of::<Particle>() as i32)
+    );
+
+    let zip = snapshot.to_zip(None).unwrap();
+    let snapshot = WorldArrowSnapshot::from_zip(&zip).unwrap();
+    let mut restored = World::new();
+    snapshot.to_world_reg(&mut restored, &registry).unwrap();
+    for (e, p) in spawned {
+        let at = restored.entities().resolve_from_index(e.index());
+        assert_eq!(restored.get::<Particle>(at), Some(&p));
+    }
+    assert_eq!(restored.query::<&Position>().iter(&restored).count(), 250);
+}
+
+#[test]
+fn test_batched_import() {
+    let mut registry = SnapshotRegistry::default();
+    registry.register::<Position>();
+    registry.register::<Inventory>();
+    let mut world = World::new();
+    let spawned: Vec<Entity> = (0..100)
+        .map(|i| {
+            let pos = Position {
+                x: i as f32,
+                y: 0.0,
+            };
+            world.spawn((pos, Inventory(vec![format!("item{i}")]))).id()
+        })
+        .collect();
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+
+    for batch in [None, Some(1), Some(7), Some(100), Some(1000)] {
+        registry.import_batch_rows = batch;
+        let mut restored = World::new();
+        snapshot.to_world_reg(&mut restored, &registry).unwrap();
+        let mut remapped = World::new();
+        let mapper: HashMap<u32, Entity> = spawned
+            .iter()
+            .map(|e| (e.index_u32(), remapped.spawn_empty().id()))
+            .collect();
+        snapshot
+            .apply_with_remap(
+                &mut remapped,
+                &registry,
+                &IDRemapRegistry::default(),
+                &mapper,
+            )
+            .unwrap();
+        for &e in &spawned {
+            let at = restored.entities().resolve_from_index(e.index());
+            assert_eq!(restored.get::<Position>(at), world.get::<Position>(e));
+            assert_eq!(restored.get::<Inventory>(at), world.get::<Inventory>(e));
+            let to = mapper[&e.index_u32()];
+            assert_eq!(remapped.get::<Inventory>(to), world.get::<Inventory>(e));
+        }
+    }
+
+    let column = snapshot.archetypes[0].get_column("Inventory").unwrap();
+    let factory = registry
+        .get_factory("Inventory")
+        .unwrap()
+        .arrow
+        .as_ref()
+        .unwrap();
+    let mut direct = World::new();
+    let targets: Vec<Entity> = spawned.iter().map(|_| direct.spawn_empty().id()).collect();
+    factory
+        .import_batched(column, &mut direct, &targets, Some(9))
+        .unwrap();
+    let items: Vec<_> = targets
+        .iter()
+        .map(|&e| direct.get::<Inventory>(e).unwrap().0[0].clone())
+        .collect();
+    assert_eq!(items[0], "item0");
+    assert_eq!(items[99], "item99");
+}
+
+#[test]
+fn test_record_batch_layout() {
+    use crate::arrow_snapshot::{
+        ComponentTable, IndexColumn, ParquetCompression, RecordBatchLayout,
+    };
+
+    let mut world = World::new();
+    let mut registry = SnapshotRegistry::default();
+    registry.register::<Position>();
+    world.spawn(Position { x: 40.0, y: 0.0 });
+    world.spawn(Position { x: -3.0, y: 1.0 });
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let table = &snapshot.archetypes[0];
+
+    let chunk_x = |p: &Position| (p.x / 16.0).floor() as i64;
+    let team = |t: &u8| *t as i64;
+    let layout = RecordBatchLayout::default()
+        .with_id_column("entity")
+        .with_index_column(IndexColumn::from_component("chunk_x", "Position", chunk_x))
+        .with_index_column(IndexColumn::from_component("team", "Team", team));
+    let batch = table.to_record_batch_with(&layout).unwrap();
+    let schema = batch.schema();
+    let names: Vec<_> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names[..3], ["entity", "chunk_x", "team"]);
+    let chunks = batch
+        .column_by_name("chunk_x")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::Int64Array>()
+        .unwrap();
+    assert_eq!(chunks.values(), &[2, -1]);
+    assert_eq!(batch.column_by_name("team").unwrap().null_count(), 2);
+
+    let back = ComponentTable::from_record_batch(&batch).unwrap();
+    assert_eq!(back.entities.len(), 2);
+    assert!(back.column_names().eq(table.column_names()));
+    let parquet = table
+        .to_parquet_with_layout(ParquetCompression::None, &layout)
+        .unwrap();
+    let back = ComponentTable::from_parquet_u8(&parquet).unwrap();
+    assert_eq!(back.entities[1].id, table.entities[1].id);
+}
+
+#[test]
+fn test_partitioned_dataset() {
+    use crate::arrow_snapshot::IndexColumn;
+    use crate::binary_archive::{DATASET_METADATA, DatasetPartition};
+
+    let mut world = World::new();
+    let mut registry = SnapshotRegistry::default();
+    registry.register::<Position>();
+    registry.register::<Velocity>();
+    world.spawn(Position { x: 40.0, y: 0.0 });
+    world.spawn(Position { x: -3.0, y: 1.0 });
+    world.spawn(Position { x: 1.0, y: 1.0 });
+    world.spawn((Position { x: 0.0, y: 0.0 }, Velocity { dx: 1.0, dy: 0.0 }));
+    world.spawn(Velocity { dx: 0.0, dy: 2.0 });
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+
+    let mut container = std::collections::BTreeMap::new();
+    let summary = snapshot
+        .write_dataset(&mut container, &DatasetPartition::Archetype)
+        .unwrap();
+    assert_eq!(summary.files.len(), snapshot.archetypes.len());
+    assert!(container.contains_key(DATASET_METADATA));
+    let back = WorldArrowSnapshot::read_dataset(&mut container, &LoadLimits::default()).unwrap();
+    assert_eq!(back.entities, snapshot.entities);
+    assert_eq!(back.archetypes.len(), snapshot.archetypes.len());
+
+    let chunk_x = |p: &Position| (p.x / 16.0).floor() as i64;
+    let key = IndexColumn::from_component("chunk_x", "Position", chunk_x);
+    let dir = std::env::temp_dir().join(format!("bevy_archive_dataset_{}", std::process::id()));
+    let summary = snapshot
+        .to_dataset_dir(&dir, &DatasetPartition::Key(key))
+        .unwrap();
+    let has = |prefix: &str| summary.files.iter().any(|f| f.path.starts_with(prefix));
+    assert!(has("chunk_x=2/archetype="));
+    assert!(has("chunk_x=-1/archetype="));
+    assert!(has("chunk_x=__HIVE_DEFAULT_PARTITION__/archetype="));
+    assert!(dir.join(&summary.files[0].path).is_file());
+    assert_eq!(summary.files.iter().map(|f| f.rows).sum::<usize>(), 5);
+
+    let back = WorldArrowSnapshot::from_dataset_dir(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let mut loaded = World::new();
+    back.to_world_reg(&mut loaded, &registry).unwrap();
+    let mut xs: Vec<f32> = loaded
+        .query::<&Position>()
+        .iter(&loaded)
+        .map(|p| p.x)
+        .collect();
+    xs.sort_by(f32::total_cmp);
+    assert_eq!(xs, [-3.0, 0.0, 1.0, 40.0]);
+    assert_eq!(loaded.query::<&Velocity>().iter(&loaded).count(), 2);
+}
+
+#[test]
+fn test_msgpack_bin_snapshot_to_arrow() {
+    let mut world = World::new();
+    let registry = setup_registry();
+    let boss = build_sample_world(&mut world);
+
+    let msgpack = crate::binary_archive::MsgPackArchive::from_world(&world, &registry).unwrap();
+    let arrow = WorldArrowSnapshot::from(msgpack.0);
+    let parquet = WorldBinArchSnapshot::from(arrow);
+    let mut loaded = World::new();
+    WorldArrowSnapshot::from(parquet)
+        .to_world_reg(&mut loaded, &registry)
+        .unwrap();
+
+    let mut positions: Vec<_> = loaded
+        .query::<&Position>()
+        .iter(&loaded)
+        .map(|p| (p.x, p.y))
+        .collect();
+    positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(
+        positions,
+        [(-1.0, 0.0), (0.0, 0.0), (1.0, 0.0), (1.0, 2.0), (9.0, 3.5)]
+    );
+    assert_eq!(loaded.query::<&Tag>().iter(&loaded).count(), 2);
+    let inventory = loaded.query::<&Inventory>().iter(&loaded).count();
+    assert_eq!(inventory, 3);
+    assert_eq!(loaded.get::<Children>(boss).map(|c| c.len()), Some(2));
+    let nested = loaded.get::<NestedComponent>(boss).unwrap();
+    assert_eq!(nested.name, "Boss");
+}
+
+#[test]
+fn test_zip_entry_checksums() {
+    use crate::bevy_registry::vec_snapshot_factory::SnapshotError;
+    use crate::checksum::ENTRY_CHECKSUM_PREFIX;
+
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+    let mut container = std::collections::BTreeMap::new();
+    snapshot.write_to_container(&mut container).unwrap();
+
+    let limits = LoadLimits::default();
+    let back = WorldArrowSnapshot::read_from_container(&mut container.clone(), &limits).unwrap();
+    assert!(!back.meta.keys().any(|k| k.starts_with(ENTRY_CHECKSUM_PREFIX)));
+    let zip = back.to_zip(None).unwrap();
+    let diff = crate::binary_archive::diff_zip(&zip, &snapshot.to_zip(None).unwrap()).unwrap();
+    assert!(diff.is_empty());
+
+    let entry = container
+        .keys()
+        .find(|name| name.starts_with("resources/"))
+        .unwrap()
+        .clone();
+    container.get_mut(&entry).unwrap().push(0);
+    let err = WorldArrowSnapshot::read_from_container(&mut container, &limits).unwrap_err();
+    assert!(matches!(err, SnapshotError::Checksum(e) if e.entry == entry));
+}
+
+#[test]
+fn test_zip_writer_streams_same_entries() {
+    let mut world = World::new();
+    let registry = setup_registry();
+    build_sample_world(&mut world);
+    let snapshot = WorldArrowSnapshot::from_world_reg(&world, &registry).unwrap();
+
+    let path = std::env::temp_dir().join(format!("bevy_archive_stream_{}.zip", std::process::id()));
+    let file = std::fs::File::create(&path).unwrap();
+    snapshot.to_zip_writer(file, Some(6)).unwrap();
+    let streamed = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let buffered = snapshot.to_zip(Some(6)).unwrap();
+    let diff = crate::binary_archive::diff_zip(&buffered, &streamed).unwrap();
+    assert!(diff.is_empty());
+    let back = WorldArrowSnapshot::from_zip(&streamed).unwrap();
+    let mut loaded = World::new();
+    back.to_world_reg(&mut loaded, &registry).unwrap();
+    assert_eq!(
+        loaded.query::<&Position>().iter(&loaded).count(),
+        world.query::<&Position>().iter(&world).count()
+    );
+}
@@ -1,34 +1,46 @@
-use crate::binary_archive::arrow_column::RawTData;
+use crate::binary_archive::arrow_column::{RawTData, row_batches};
 use bevy_ecs::{component::ComponentId, entity::EntityIndex, prelude::*};
  
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::io::{Read, Write};
 use std::path::Path;
 use std::fs::File;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "flecs")]
 pub mod flecs;
+mod dataset;
+mod zip_diff;
 mod zip_snapshot;
+pub use dataset::*;
+pub use zip_diff::*;
 
 use crate::{
     serde_utils::entity_to_index,
-    archetype_archive::WorldExt,
+    archetype_archive::{ArchetypeLoadStats, LoadReport, WorldExt},
     arrow_snapshot::{ComponentTable, EntityID},
     binary_archive::common::*, // Import common types
     prelude::{
-        SnapshotMode, SnapshotRegistry, reserve_entity_slots, vec_snapshot_factory::SnapshotError,
+        SnapshotMode, SnapshotRegistry, try_reserve_entity_slots, vec_snapshot_factory::SnapshotError,
     },
     bevy_registry::{IDRemapRegistry, EntityRemapper},
+    save_profile::SaveProfile,
     traits::Archive,
     bevy_cmdbuffer::HarvardCommandBuffer,
+    load_events::trigger_load_events,
+    registry_schema::{REGISTRY_SCHEMA_KEY, RegistrySchema},
 };
 
+/// Prefix of the [`WorldArrowSnapshot::meta`] keys holding the
+/// [schema hash](SnapshotRegistry::schema_hash) of each saved component.
+pub const SCHEMA_HASH_PREFIX: &str = "schema_hash.";
+
 #[derive(Debug, Clone, Default)]
 pub struct WorldArrowSnapshot {
     pub entities: Vec<u32>,
     pub archetypes: Vec<ComponentTable>,
-    pub resources: HashMap<String, BinBlob>,
-    pub meta: HashMap<String, String>,
+    pub resources: BTreeMap<String, BinBlob>,
+    pub meta: BTreeMap<String, String>,
 }
 
 impl Archive for WorldArrowSnapshot {
@@ -54,13 +66,20 @@ impl Archive for WorldArrowSnapshot {
         id_registry: &IDRemapRegistry,
         mapper: &dyn EntityRemapper,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        registry.limits.check_archetypes(self.archetypes.len())?;
+        registry.limits.check_entities(self.entities.len() as u64)?;
         Self::load_world_resource(&self.resources, world, registry).map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(format!("{:?}", e)))?;
         let mut buffer = HarvardCommandBuffer::new();
+        let mut loaded = Vec::new();
         for archetype in &self.archetypes {
             load_arrow_archetype_with_remap(world, registry, id_registry, archetype, &mut buffer, mapper).map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(format!("{:?}", e)))?;
             buffer.apply(world);
             buffer.reset();
+            if registry.tracks_loaded() {
+                loaded.extend(archetype.entities.iter().map(|id| (id.id, mapper.map(id.id))));
+            }
         }
+        trigger_load_events(world, registry, &loaded);
         Ok(())
     }
 
@@ -94,14 +113,27 @@ impl Archive for WorldArrowSnapshot {
         file.read_to_end(&mut bytes)?;
         Self::from_zip(&bytes).map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(format!("{:?}", e)))
     }
+
+    /// A zip, like [`save_to`](Archive::save_to).
+    fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        self.to_zip(None)
+            .map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(format!("{:?}", e)))
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::from_zip(bytes)
+            .map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(format!("{:?}", e)))
+    }
 }
 
 impl WorldArrowSnapshot {
     pub fn load_world_resource(
-        data: &HashMap<String, BinBlob>,
+        data: &BTreeMap<String, BinBlob>,
         world: &mut World,
         reg: &SnapshotRegistry,
     ) -> Result<(), SnapshotError> {
+        reg.check_known(data.keys().map(String::as_str))?;
+        let mut report = LoadReport::default();
         for res in data.keys() {
             match reg.get_res_factory(res) {
                 Some(factory) => {
@@ -120,11 +152,10 @@ impl WorldArrowSnapshot {
                             ))
                         })?;
                 }
-                None => {
-                    println!("No factory found for resource `{res}`, skipping.");
-                }
+                None => report.skip(res, 1, true),
             }
         }
+        report.record(world);
         Ok(())
     }
 
@@ -145,8 +176,8 @@ impl WorldArrowSnapshot {
     pub fn save_world_resource(
         world: &World,
         reg: &SnapshotRegistry,
-    ) -> Result<HashMap<String, BinBlob>, SnapshotError> {
-        let mut map = HashMap::new();
+    ) -> Result<BTreeMap<String, BinBlob>, SnapshotError> {
+        let mut map = BTreeMap::new();
 
         for res in reg.resource_entries.keys() {
             let factory = reg
@@ -173,6 +204,7 @@ pub fn save_arrow_archetype_from_world<'a>(
     archetype: &'a Archetype,
     reg_comp_ids: &HashMap<ComponentId, &'a str>,
 ) -> Result<ComponentTable, SnapshotError> {
+    let _span = archive_span!("save_arrow_archetype", rows = archetype.len());
     let can_be_stored = archetype
         .components()
         .iter()
@@ -183,7 +215,17 @@ pub fn save_arrow_archetype_from_world<'a>(
     }
 
     let mut archetype_snapshot = ComponentTable::default();
-    let entities: Vec<_> = archetype.entities().iter().map(|x| x.id()).collect();
+    // When the archetype owns its table, rows are written in table order so Table
+    // components can be exported straight from their dense column slices.
+    let table = world
+        .storages()
+        .tables
+        .get(archetype.table_id())
+        .filter(|table| table.entity_count() == archetype.len());
+    let entities: Vec<_> = match table {
+        Some(table) => table.entities().to_vec(),
+        None => archetype.entities().iter().map(|x| x.id()).collect(),
+    };
 
     let entities_ids: Vec<_> = entities
         .iter()
@@ -198,7 +240,13 @@ pub fn save_arrow_archetype_from_world<'a>(
                 .and_then(|f| f.arrow.as_ref())
                 .ok_or_else(|| SnapshotError::MissingFactory(type_name.to_string()))?;
 
-            let column = (arrow.arr_export)(&arrow.schema, world, &entities)?;
+            let sliced = table
+                .zip(arrow.arr_export_table)
+                .and_then(|(table, export)| export(&arrow.schema, world, table));
+            let column = match sliced {
+                Some(column) => column?,
+                None => (arrow.arr_export)(&arrow.schema, world, &entities)?,
+            };
             archetype_snapshot.insert_column(type_name, column);
         }
     }
@@ -206,6 +254,88 @@ pub fn save_arrow_archetype_from_world<'a>(
     Ok(archetype_snapshot)
 }
 
+fn registered_comp_ids<'a>(
+    world: &World,
+    registry: &'a SnapshotRegistry,
+) -> HashMap<ComponentId, &'a str> {
+    registry
+        .type_registry
+        .keys()
+        .filter_map(|&name| registry.comp_id_by_name(name, world).map(|cid| (cid, name)))
+        .collect()
+}
+
+/// Save the single archetype selected by `arch_filter` as a standalone parquet file.
+///
+/// Resource and empty archetypes are never considered. It is an error if no archetype
+/// or more than one archetype matches the filter.
+pub fn save_arrow_archetype_to_file(
+    world: &World,
+    registry: &SnapshotRegistry,
+    arch_filter: impl Fn(&Archetype) -> bool,
+    path: impl AsRef<Path>,
+) -> Result<(), SnapshotError> {
+    let mut matches = world
+        .archetypes()
+        .iter()
+        .filter(|x| !x.is_empty() && !x.contains(bevy_ecs::resource::IS_RESOURCE))
+        .filter(|x| arch_filter(x));
+    let archetype = matches
+        .next()
+        .ok_or_else(|| SnapshotError::Generic("no archetype matches the filter".to_string()))?;
+    if matches.next().is_some() {
+        return Err(SnapshotError::Generic(
+            "more than one archetype matches the filter".to_string(),
+        ));
+    }
+
+    let reg_comp_ids = registered_comp_ids(world, registry);
+    let table = save_arrow_archetype_from_world(world, registry, archetype, &reg_comp_ids)?;
+    let bytes = table.to_parquet()?;
+    std::fs::write(path, bytes)
+        .map_err(|e| SnapshotError::Generic(format!("write parquet failed: {e}")))
+}
+
+/// Load a parquet file written by [`save_arrow_archetype_to_file`] into `world`,
+/// keeping the entity indices stored in the file.
+pub fn load_arrow_archetype_from_file(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    path: impl AsRef<Path>,
+) -> Result<(), SnapshotError> {
+    let path = path.as_ref();
+    let size = std::fs::metadata(path)
+        .map_err(|e| SnapshotError::Generic(format!("read parquet failed: {e}")))?
+        .len();
+    registry.limits.check_blob(size)?;
+    let bytes = std::fs::read(path)
+        .map_err(|e| SnapshotError::Generic(format!("read parquet failed: {e}")))?;
+    load_arrow_archetype_from_parquet(world, registry, &bytes)
+}
+
+/// [`load_arrow_archetype_from_file`] for parquet bytes already in memory.
+pub fn load_arrow_archetype_from_parquet(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    bytes: &[u8],
+) -> Result<(), SnapshotError> {
+    let table = ComponentTable::from_parquet_u8(bytes)?;
+    let max_id = table.entities.iter().map(|e| e.id).max().unwrap_or(0);
+    try_reserve_entity_slots(world, max_id.saturating_add(1), &registry.limits)?;
+    world.flush();
+
+    let mut buffer = HarvardCommandBuffer::new();
+    load_arrow_archetype_to_world(world, registry, &table, &mut buffer)?;
+    buffer.apply(world);
+    let loaded: Vec<_> = if registry.tracks_loaded() {
+        arrow_loaded_entities(world, &table).collect()
+    } else {
+        Vec::new()
+    };
+    trigger_load_events(world, registry, &loaded);
+    Ok(())
+}
+
 impl WorldArrowSnapshot {
     pub fn from_world(world: &World) -> Self {
         let reg = world.resource::<SnapshotRegistry>();
@@ -221,23 +351,51 @@ impl WorldArrowSnapshot {
             .iter()
             .filter(|x| !x.is_empty() && !x.contains(bevy_ecs::resource::IS_RESOURCE));
 
-        let reg_comp_ids: HashMap<ComponentId, &str> = registry
-            .type_registry
-            .keys()
-            .filter_map(|&name| registry.comp_id_by_name(name, world).map(|cid| (cid, name)))
-            .collect();
+        let reg_comp_ids = registered_comp_ids(world, registry);
 
         let mut world_snapshot = WorldArrowSnapshot::default();
         world_snapshot.entities = WorldExt::iter_entities(world).map(|x| entity_to_index(&x)).collect();
 
         let snap = Self::save_archetypes(world, registry, archetypes, reg_comp_ids);
         world_snapshot.archetypes = snap.collect::<Result<_, _>>()?;
+        let saved: BTreeSet<&str> = world_snapshot
+            .archetypes
+            .iter()
+            .flat_map(|table| table.columns().map(|(name, _)| name.as_str()))
+            .collect();
+        let schema = serde_json::to_string(&RegistrySchema::of(registry))
+            .map_err(|e| SnapshotError::Generic(format!("json encode error: {e}")))?;
+        world_snapshot
+            .meta
+            .insert(REGISTRY_SCHEMA_KEY.to_string(), schema);
+        for name in saved {
+            if let Some(hash) = registry.schema_hash(name) {
+                world_snapshot
+                    .meta
+                    .insert(format!("{SCHEMA_HASH_PREFIX}{name}"), hash);
+            }
+        }
 
         world_snapshot.resources = Self::save_world_resource(world, registry)?;
 
         Ok(world_snapshot)
     }
 
+    /// Like [`from_world_reg`](Self::from_world_reg), with only the components and
+    /// resources `profile` allows.
+    pub fn from_world_profile(
+        world: &World,
+        registry: &SnapshotRegistry,
+        profile: &SaveProfile,
+    ) -> Result<Self, SnapshotError> {
+        Self::from_world_reg(world, &profile.apply(registry))
+    }
+
+    /// The [registry schema](crate::registry_schema) stored in `meta` by the save, if any.
+    pub fn registry_schema(&self) -> Option<RegistrySchema> {
+        serde_json::from_str(self.meta.get(REGISTRY_SCHEMA_KEY)?).ok()
+    }
+
     pub fn to_world(&self, world: &mut World) -> Result<(), SnapshotError> {
         world.resource_scope(|world, reg: Mut<SnapshotRegistry>| self.to_world_reg(world, &reg))
     }
@@ -246,71 +404,145 @@ impl WorldArrowSnapshot {
         world: &mut World,
         reg: &SnapshotRegistry,
     ) -> Result<(), SnapshotError> {
-        reserve_entity_slots(world, *self.entities.iter().max().unwrap_or(&0) + 1);
+        reg.limits.check_archetypes(self.archetypes.len())?;
+        if let Some(schema) = self.registry_schema() {
+            schema.check(
+                reg,
+                self.archetypes
+                    .iter()
+                    .flat_map(|table| table.columns().map(|(name, _)| name.as_str())),
+            )?;
+        }
+        reg.check_known(
+            self.archetypes
+                .iter()
+                .flat_map(|table| table.columns().map(|(name, _)| name.as_str()))
+                .chain(self.resources.keys().map(String::as_str)),
+        )?;
+        reg.check_schema_hashes(self.meta.iter().filter_map(|(key, hash)| {
+            Some((key.strip_prefix(SCHEMA_HASH_PREFIX)?, hash.as_str()))
+        }))?;
+        let max_id = self.entities.iter().max().copied().unwrap_or(0);
+        try_reserve_entity_slots(world, max_id.saturating_add(1), &reg.limits)?;
         world.flush();
         Self::load_world_resource(&self.resources, world, reg)?;
         let mut buffer = HarvardCommandBuffer::new();
+        let mut loaded = Vec::new();
         for archetype in &self.archetypes {
             load_arrow_archetype_to_world(world, reg, archetype, &mut buffer)?;
             buffer.apply(world);
             buffer.reset();
+            if reg.tracks_loaded() {
+                loaded.extend(arrow_loaded_entities(world, archetype));
+            }
         }
+        trigger_load_events(world, reg, &loaded);
         Ok(())
     }
 }
 
+/// (snapshot id, entity) pairs that [`load_arrow_archetype_to_world`] writes `table` to.
+pub(crate) fn arrow_loaded_entities<'a>(
+    world: &'a World,
+    table: &'a ComponentTable,
+) -> impl Iterator<Item = (u32, Entity)> + 'a {
+    table.entities.iter().map(|id| {
+        let entity = world
+            .entities()
+            .resolve_from_index(EntityIndex::from_raw_u32(id.id).unwrap());
+        (id.id, entity)
+    })
+}
+
+impl ComponentTable {
+    fn load_stats(&self, decode: Duration, insert: Duration) -> ArchetypeLoadStats {
+        ArchetypeLoadStats {
+            components: self.columns.keys().cloned().collect(),
+            rows: self.entities.len(),
+            blob_bytes: None,
+            decode,
+            insert,
+        }
+    }
+}
+
+/// Stage the rows of `archetype` into `buffer`, applying it between
+/// [batches](SnapshotRegistry::import_batch_rows); the caller applies the last batch.
 pub fn load_arrow_archetype_to_world(
     world: &mut World,
     reg: &SnapshotRegistry,
     archetype: &ComponentTable,
     buffer: &mut HarvardCommandBuffer,
-) -> Result<(), SnapshotError> {
-    let mut columns = Vec::new();
-    let types = archetype.columns();
-    let bump_ptr = buffer.data_bump() as *const bumpalo::Bump;
-
-    for (type_name, data) in types {
+) -> Result<LoadReport, SnapshotError> {
+    reg.check_known(archetype.columns().map(|(name, _)| name.as_str()))?;
+    let rows = archetype.entities.len();
+    let _span = archive_span!("load_arrow_archetype", rows = rows);
+    let mut report = LoadReport::default();
+    let mut factories = Vec::new();
+    for (type_name, data) in archetype.columns() {
         if let Some(arrow) = reg.get_factory(type_name).and_then(|x| x.arrow.as_ref()) {
             let comp_id = reg
                 .comp_id_by_name(type_name.as_str(), world)
                 .or_else(|| Some(reg.reg_by_name(type_name, world)))
                 .unwrap();
             let mode = unsafe { reg.get_factory(type_name).unwrap_unchecked().mode };
-            // Note: arr_dyn now expects (ArrowColumn, &Bump). 
-            // In factory.rs it was: `fn(&ArrowColumn, &'a bumpalo::Bump) -> ...`
-            // But we modified factory.rs back to original state?
-            // Wait, I might have messed up `factory.rs` state in the confusion about reverting.
-            // Let's assume `arr_dyn` takes `&Bump`.
-            let data = (arrow.arr_dyn)(data, unsafe { &*bump_ptr })?;
-            let raw_vec = RawTData { comp_id, data };
-            columns.push((mode, raw_vec));
+            factories.push((mode, comp_id, arrow.arr_dyn, data));
         } else {
-            println!("warning type {} cannot be converted", type_name);
+            report.skip(type_name, rows, reg.get_factory(type_name).is_none());
         }
     }
-    for id in archetype.entities.iter().rev() {
-        let entity = world
-            .entities()
-            .resolve_from_index(EntityIndex::from_raw_u32(id.id).unwrap());
-        
-        for (mode, raw) in &mut columns {
-            let ptr = raw.data.pop().unwrap();
-            match mode {
-                SnapshotMode::Full => {
-                    buffer.insert_box(entity, raw.comp_id, ptr);
-                }
-                crate::prelude::SnapshotMode::EmplaceIfNotExists => {
-                     if !world.entity(entity).contains_id(raw.comp_id) {
-                        buffer.insert_box(entity, raw.comp_id, ptr);
-                    } else {
-                        ptr.manual_drop();
+    if !factories.is_empty() {
+        report.entities_loaded = rows;
+        report.components_loaded = rows * factories.len();
+    }
+    let load_start = Instant::now();
+    let mut decode = Duration::ZERO;
+
+    for (start, len) in row_batches(archetype.entities.len(), reg.import_batch_rows) {
+        if start > 0 {
+            // Insert the previous batch so its values are freed before decoding the next.
+            buffer.apply(world);
+            buffer.reset();
+        }
+        buffer.record(|rec| {
+            let mut columns = Vec::with_capacity(factories.len());
+            let decode_start = Instant::now();
+            for (mode, comp_id, arr_dyn, data) in &factories {
+                let data = arr_dyn(&data.slice(start, len), rec.bump())?;
+                columns.push((*mode, RawTData { comp_id: *comp_id, data }));
+            }
+            decode += decode_start.elapsed();
+            for id in archetype.entities[start..start + len].iter().rev() {
+                let entity = world
+                    .entities()
+                    .resolve_from_index(EntityIndex::from_raw_u32(id.id).unwrap());
+
+                for (mode, raw) in &mut columns {
+                    let ptr = raw.data.pop().unwrap();
+                    // SAFETY: `ptr` was built by the factory registered for `raw.comp_id`.
+                    match mode {
+                        SnapshotMode::Full => {
+                            unsafe { rec.insert_box(entity, raw.comp_id, ptr) };
+                        }
+                        crate::prelude::SnapshotMode::EmplaceIfNotExists => {
+                             if !world.entity(entity).contains_id(raw.comp_id) {
+                                unsafe { rec.insert_box(entity, raw.comp_id, ptr) };
+                            } else {
+                                ptr.manual_drop();
+                            }
+                        }
                     }
                 }
             }
-        }
+            Ok::<_, SnapshotError>(())
+        })?;
     }
+    report
+        .archetype_stats
+        .push(archetype.load_stats(decode, load_start.elapsed().saturating_sub(decode)));
 
-    Ok(())
+    report.record(world);
+    Ok(report)
 }
 
 pub fn load_arrow_archetype_with_remap(
@@ -320,12 +552,13 @@ pub fn load_arrow_archetype_with_remap(
     archetype: &ComponentTable,
     buffer: &mut HarvardCommandBuffer,
     mapper: &dyn EntityRemapper,
-) -> Result<(), SnapshotError> {
-    let mut columns = Vec::new();
-    let types = archetype.columns();
-    let bump_ptr = buffer.data_bump() as *const bumpalo::Bump;
-
-    for (type_name, data) in types {
+) -> Result<LoadReport, SnapshotError> {
+    reg.check_known(archetype.columns().map(|(name, _)| name.as_str()))?;
+    let rows = archetype.entities.len();
+    let _span = archive_span!("load_arrow_archetype", rows = rows);
+    let mut report = LoadReport::default();
+    let mut factories = Vec::new();
+    for (type_name, data) in archetype.columns() {
         if let Some(factory) = reg.get_factory(type_name) {
             if let Some(arrow) = factory.arrow.as_ref() {
                 let comp_id = reg
@@ -333,88 +566,136 @@ pub fn load_arrow_archetype_with_remap(
                     .or_else(|| Some(reg.reg_by_name(type_name, world)))
                     .unwrap();
                 let mode = factory.mode;
-                let data = (arrow.arr_dyn)(data, unsafe { &*bump_ptr })?;
-                let type_id = reg.type_registry.get(type_name.as_str()).cloned();
+                let type_id = reg.type_id_by_name(type_name);
                 let hook = type_id.and_then(|tid| id_reg.get_hook(tid));
-                
-                let raw_vec = RawTData { comp_id, data };
-                columns.push((mode, raw_vec, hook));
+                factories.push((mode, comp_id, arrow.arr_dyn, data, hook));
+            } else {
+                report.skip(type_name, rows, false);
             }
         } else {
-            println!("warning type {} cannot be converted", type_name);
+            report.skip(type_name, rows, true);
         }
     }
+    if !factories.is_empty() {
+        report.entities_loaded = rows;
+        report.components_loaded = rows * factories.len();
+    }
+    let load_start = Instant::now();
+    let mut decode = Duration::ZERO;
 
-    for id in archetype.entities.iter().rev() {
-        let current_entity = mapper.map(id.id as u32);
-        if current_entity == Entity::PLACEHOLDER {
-             panic!("Entity mapping failure: Old ID {} mapped to PLACEHOLDER", id.id);
+    for (start, len) in row_batches(archetype.entities.len(), reg.import_batch_rows) {
+        if start > 0 {
+            buffer.apply(world);
+            buffer.reset();
         }
-
-        for (mode, raw, hook) in &mut columns {
-            let mut comp_ptr = raw.data.pop().unwrap();
-            
-            // Apply hook if present
-            if let Some(h) = hook {
-                let ptr_mut = comp_ptr.get_ptr_mut();
-                h(ptr_mut, mapper);
+        buffer.record(|rec| {
+            let mut columns = Vec::with_capacity(factories.len());
+            let decode_start = Instant::now();
+            for (mode, comp_id, arr_dyn, data, hook) in &factories {
+                let data = arr_dyn(&data.slice(start, len), rec.bump())?;
+                columns.push((*mode, RawTData { comp_id: *comp_id, data }, *hook));
             }
-
-            match mode {
-                SnapshotMode::Full => {
-                     buffer.insert_box(current_entity, raw.comp_id, comp_ptr);
+            decode += decode_start.elapsed();
+            for id in archetype.entities[start..start + len].iter().rev() {
+                let current_entity = mapper.map(id.id);
+                if current_entity == Entity::PLACEHOLDER {
+                     panic!("Entity mapping failure: Old ID {} mapped to PLACEHOLDER", id.id);
                 }
-                crate::prelude::SnapshotMode::EmplaceIfNotExists => {
-                    if !world.entity(current_entity).contains_id(raw.comp_id) {
-                         buffer.insert_box(current_entity, raw.comp_id, comp_ptr);
-                    } else {
-                        comp_ptr.manual_drop();
+
+                for (mode, raw, hook) in &mut columns {
+                    let mut comp_ptr = raw.data.pop().unwrap();
+                
+                    // Apply hook if present
+                    if let Some(h) = hook {
+                        let ptr_mut = comp_ptr.get_ptr_mut();
+                        h(ptr_mut, mapper);
+                    }
+
+                    // SAFETY: `comp_ptr` was built by the factory registered for `raw.comp_id`.
+                    match mode {
+                        SnapshotMode::Full => {
+                             unsafe { rec.insert_box(current_entity, raw.comp_id, comp_ptr) };
+                        }
+                        crate::prelude::SnapshotMode::EmplaceIfNotExists => {
+                            if !world.entity(current_entity).contains_id(raw.comp_id) {
+                                 unsafe { rec.insert_box(current_entity, raw.comp_id, comp_ptr) };
+                            } else {
+                                comp_ptr.manual_drop();
+                            }
+                        }
                     }
                 }
             }
-        }
+            Ok::<_, SnapshotError>(())
+        })?;
     }
+    report
+        .archetype_stats
+        .push(archetype.load_stats(decode, load_start.elapsed().saturating_sub(decode)));
 
-    Ok(())
+    report.record(world);
+    Ok(report)
 }
 
 use bevy_ecs::archetype::Archetype;
 
-impl From<WorldArrowSnapshot> for WorldBinArchSnapshot {
-    fn from(value: WorldArrowSnapshot) -> Self {
-        let archetypes = value
-            .archetypes
-            .iter()
-            .map(|x| BinBlob(x.to_parquet().unwrap()))
-            .collect();
-        let entities = SparseU32List::from_unsorted(value.entities);
-        Self {
-            entities,
+impl WorldBinArchSnapshot {
+    /// Parquet blobs of `value`, compressed as `options` says.
+    pub fn from_arrow_with(
+        value: WorldArrowSnapshot,
+        options: &BinSaveOptions,
+    ) -> Result<Self, SnapshotError> {
+        let mut archetypes = Vec::with_capacity(value.archetypes.len());
+        for table in &value.archetypes {
+            archetypes.push(BinBlob::encode(table.to_parquet()?, options).map_err(io_err)?);
+        }
+        let mut resources = BTreeMap::new();
+        for (name, blob) in value.resources {
+            let blob = BinBlob::encode(blob.0, options).map_err(io_err)?;
+            resources.insert(name, blob);
+        }
+        Ok(Self {
+            entities: SparseU32List::from_unsorted(value.entities),
             archetypes,
-            resources: value.resources,
+            resources,
             format: BinFormat::Parquet,
             meta: value.meta,
-        }
+            codec: options.codec,
+        })
+    }
+}
+
+fn io_err(e: std::io::Error) -> SnapshotError {
+    SnapshotError::Generic(e.to_string())
+}
+
+impl From<WorldArrowSnapshot> for WorldBinArchSnapshot {
+    fn from(value: WorldArrowSnapshot) -> Self {
+        Self::from_arrow_with(value, &BinSaveOptions::default()).unwrap()
     }
 }
 impl From<WorldBinArchSnapshot> for WorldArrowSnapshot {
     fn from(value: WorldBinArchSnapshot) -> Self {
-        if value.format != BinFormat::Parquet {
-            panic!(
-                "mismatched format: desired {:?} got {:?}",
-                BinFormat::Parquet,
-                value.format
-            );
-        }
-        let archetypes = value
-            .archetypes
+        let max = crate::bevy_registry::LoadLimits::default().max_blob_bytes;
+        let unpack = |blob: &BinBlob| blob.decode(value.codec, max).unwrap().into_owned();
+        let decode = |blob: &BinBlob| match value.format {
+            BinFormat::Parquet => ComponentTable::from_parquet_u8(&unpack(blob)).unwrap(),
+            BinFormat::MsgPack => {
+                let arch: crate::archetype_archive::ArchetypeSnapshot =
+                    rmp_serde::from_slice(&unpack(blob)).unwrap();
+                ComponentTable::try_from(&arch).unwrap()
+            }
+        };
+        let archetypes = value.archetypes.iter().map(decode).collect();
+        let resources = value
+            .resources
             .iter()
-            .map(|x| ComponentTable::from_parquet_u8(&x.0).unwrap())
+            .map(|(name, blob)| (name.clone(), BinBlob(unpack(blob))))
             .collect();
         Self {
             entities: value.entities.to_vec(),
             archetypes,
-            resources: value.resources,
+            resources,
             meta: value.meta,
         }
     }
@@ -0,0 +1,228 @@
+//! Partitioned Parquet datasets: a [`WorldArrowSnapshot`] written as a Hive-style directory
+//! tree that Spark, DuckDB or Polars can scan directly, e.g.
+//! `read_parquet('world/**/*.parquet', hive_partitioning = true)`.
+//!
+//! Each archetype table goes to `archetype=N/part-0.parquet`, or, partitioned by a
+//! [`DatasetPartition::Key`], one file per key value under `key=value/archetype=N/`. The
+//! key column is not stored in the files, as Hive readers restore it from the path. Files
+//! whose names start with `_` are skipped by those readers and hold the rest of the
+//! snapshot: `_metadata` is the [`DatasetSummary`] as TOML, `_entities.msgpack` the entity
+//! list and `_resources/<name>.msgpack` the resources.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use arrow::array::{ArrayRef, RecordBatch, UInt32Array};
+use arrow::compute::{concat_batches, take_record_batch};
+use arrow::datatypes::SchemaRef;
+use arrow::util::display::array_value_to_string;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::arrow_snapshot::{ComponentTable, IndexColumn, record_batch_to_parquet};
+use crate::aurora_archive::FsBlobLoader;
+use crate::bevy_registry::LoadLimits;
+use crate::binary_archive::common::SparseU32List;
+use crate::binary_archive::{BinBlob, WorldArrowSnapshot};
+use crate::container::SnapshotContainer;
+use crate::prelude::vec_snapshot_factory::SnapshotError;
+
+/// Entry holding the [`DatasetSummary`].
+pub const DATASET_METADATA: &str = "_metadata";
+const ENTITIES_MSGPACK: &str = "_entities.msgpack";
+const RESOURCES_PREFIX: &str = "_resources/";
+const RESOURCES_SUFFIX: &str = ".msgpack";
+/// Hive's directory name for rows whose key is null.
+const DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// How [`WorldArrowSnapshot::write_dataset`] splits the archetype tables into files.
+#[derive(Debug, Clone, Default)]
+pub enum DatasetPartition {
+    /// One file per archetype.
+    #[default]
+    Archetype,
+    /// One file per archetype and value of the column, e.g. the chunk of a position.
+    Key(IndexColumn),
+}
+
+/// Contents of a dataset, written to [`DATASET_METADATA`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DatasetSummary {
+    /// `archetype`, or the name of the key column.
+    pub partition_by: String,
+    /// [`WorldArrowSnapshot::meta`] of the snapshot.
+    #[serde(default)]
+    pub meta: BTreeMap<String, String>,
+    #[serde(default)]
+    pub files: Vec<DatasetFile>,
+}
+
+/// One Parquet file of a dataset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DatasetFile {
+    pub path: String,
+    /// Index of the archetype table the rows belong to.
+    pub archetype: usize,
+    pub rows: usize,
+}
+
+/// Escape a partition value for a path segment, as `%XX` for anything but ASCII letters,
+/// digits, `-` and `_`.
+fn escape_partition_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || b == b'-' || b == b'_' {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// Rows of `keys` grouped by their escaped partition value.
+fn partition_rows(keys: &ArrayRef) -> Result<BTreeMap<String, Vec<u32>>, SnapshotError> {
+    let mut groups: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    for row in 0..keys.len() {
+        let value = if keys.is_null(row) {
+            DEFAULT_PARTITION.to_string()
+        } else {
+            let value = array_value_to_string(keys, row)
+                .map_err(|e| SnapshotError::Generic(format!("partition key error: {e}")))?;
+            escape_partition_value(&value)
+        };
+        groups.entry(value).or_default().push(row as u32);
+    }
+    Ok(groups)
+}
+
+impl WorldArrowSnapshot {
+    /// Write the snapshot to `container` as a partitioned Parquet dataset, returning the
+    /// summary written to [`DATASET_METADATA`].
+    ///
+    /// Under a [`DatasetPartition::Key`], archetypes without rows are left out.
+    pub fn write_dataset<C: SnapshotContainer>(
+        &self,
+        container: &mut C,
+        partition: &DatasetPartition,
+    ) -> Result<DatasetSummary, SnapshotError> {
+        let mut summary = DatasetSummary {
+            partition_by: match partition {
+                DatasetPartition::Archetype => "archetype".to_string(),
+                DatasetPartition::Key(key) => key.name.clone(),
+            },
+            meta: self.meta.clone(),
+            files: Vec::new(),
+        };
+        for (idx, table) in self.archetypes.iter().enumerate() {
+            let compression = self.archetype_compression(idx)?;
+            let batch = table.to_record_batch()?;
+            let parts = match partition {
+                DatasetPartition::Archetype => vec![(String::new(), batch)],
+                DatasetPartition::Key(key) => {
+                    let groups = partition_rows(&key.values(table)?)?;
+                    let mut parts = Vec::with_capacity(groups.len());
+                    for (value, rows) in groups {
+                        let rows = UInt32Array::from(rows);
+                        let part = take_record_batch(&batch, &rows)
+                            .map_err(|e| SnapshotError::Generic(e.to_string()))?;
+                        parts.push((format!("{}={value}/", key.name), part));
+                    }
+                    parts
+                }
+            };
+            for (prefix, part) in parts {
+                let path = format!("{prefix}archetype={idx}/part-0.parquet");
+                container.write_entry(&path, &record_batch_to_parquet(&part, compression)?)?;
+                summary.files.push(DatasetFile {
+                    path,
+                    archetype: idx,
+                    rows: part.num_rows(),
+                });
+            }
+        }
+
+        let entities = SparseU32List::from_unsorted(self.entities.clone());
+        let entities = rmp_serde::to_vec(&entities)
+            .map_err(|e| SnapshotError::Generic(format!("msgpack encode error: {e}")))?;
+        container.write_entry(ENTITIES_MSGPACK, &entities)?;
+        for (key, blob) in &self.resources {
+            let path = format!("{RESOURCES_PREFIX}{key}{RESOURCES_SUFFIX}");
+            container.write_entry(&path, &blob.0)?;
+        }
+        // Written last, so a dataset with a summary is complete.
+        let toml = toml::to_string(&summary)
+            .map_err(|e| SnapshotError::Generic(format!("toml encode error: {e}")))?;
+        container.write_entry(DATASET_METADATA, toml.as_bytes())?;
+        Ok(summary)
+    }
+
+    /// Read a dataset written by [`write_dataset`](Self::write_dataset), joining the files
+    /// of each archetype back into one table.
+    pub fn read_dataset<C: SnapshotContainer>(
+        container: &mut C,
+        limits: &LoadLimits,
+    ) -> Result<Self, SnapshotError> {
+        let raw = container.read_entry_limited(DATASET_METADATA, limits.max_blob_bytes)?;
+        let text = std::str::from_utf8(&raw)
+            .map_err(|e| SnapshotError::Generic(format!("toml decode error: {e}")))?;
+        let summary: DatasetSummary = toml::from_str(text)
+            .map_err(|e| SnapshotError::Generic(format!("toml decode error: {e}")))?;
+        limits.check_archetypes(summary.files.len())?;
+
+        let mut batches: BTreeMap<usize, (SchemaRef, Vec<RecordBatch>)> = BTreeMap::new();
+        for file in &summary.files {
+            let buf = container.read_entry_limited(&file.path, limits.max_blob_bytes)?;
+            let builder = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(buf))?;
+            let schema = builder.schema().clone();
+            let reader = builder.with_batch_size(8192).build()?;
+            let (_, parts) = batches
+                .entry(file.archetype)
+                .or_insert_with(|| (schema, Vec::new()));
+            for batch in reader {
+                parts.push(batch.map_err(|e| SnapshotError::Generic(e.to_string()))?);
+            }
+        }
+        let mut archetypes = Vec::with_capacity(batches.len());
+        for (schema, parts) in batches.into_values() {
+            let batch = concat_batches(&schema, &parts)
+                .map_err(|e| SnapshotError::Generic(e.to_string()))?;
+            archetypes.push(ComponentTable::from_record_batch(&batch)?);
+        }
+
+        let raw = container.read_entry_limited(ENTITIES_MSGPACK, limits.max_blob_bytes)?;
+        let entities: SparseU32List = rmp_serde::from_slice(&raw)
+            .map_err(|e| SnapshotError::Generic(format!("msgpack decode error: {e}")))?;
+        limits.check_entities(entities.len())?;
+        let mut resources = BTreeMap::new();
+        for name in container.list_entries()? {
+            let key = name
+                .strip_prefix(RESOURCES_PREFIX)
+                .and_then(|n| n.strip_suffix(RESOURCES_SUFFIX));
+            if let Some(key) = key {
+                let blob = container.read_entry_limited(&name, limits.max_blob_bytes)?;
+                resources.insert(key.to_string(), BinBlob(blob));
+            }
+        }
+        Ok(WorldArrowSnapshot {
+            entities: entities.to_vec(),
+            archetypes,
+            resources,
+            meta: summary.meta,
+        })
+    }
+
+    /// [`write_dataset`](Self::write_dataset) into the directory `dir`, created if missing.
+    pub fn to_dataset_dir(
+        &self,
+        dir: impl AsRef<Path>,
+        partition: &DatasetPartition,
+    ) -> Result<DatasetSummary, SnapshotError> {
+        self.write_dataset(&mut FsBlobLoader::new(dir.as_ref()), partition)
+    }
+
+    /// [`read_dataset`](Self::read_dataset) from the directory `dir`.
+    pub fn from_dataset_dir(dir: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Self::read_dataset(&mut FsBlobLoader::new(dir.as_ref()), &LoadLimits::default())
+    }
+}
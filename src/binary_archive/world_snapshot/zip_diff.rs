@@ -0,0 +1,174 @@
+//! Entry-level diffs between two snapshot zips written by
+//! [`WorldArrowSnapshot::to_zip`](crate::binary_archive::WorldArrowSnapshot::to_zip), for
+//! syncing large saves by uploading only what changed.
+//!
+//! Archetype tables are compared by content, so a table re-encoded with another Parquet
+//! codec is not a change. Other entries (meta, entities, resources) are compared byte for
+//! byte after decompression. A patch zip holds the changed and added entries of the newer
+//! zip, copied without recompressing, plus [`PATCH_TOML`] listing the removed ones.
+//! Re-encoded tables are copied too, as the checksums in the newer `meta.toml` are those
+//! of their new bytes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Cursor, Write};
+
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use super::zip_snapshot::{
+    META_TOML, checksum_key, parse_archetype_idx, parse_archetype_ipc_idx, zip_err,
+};
+use crate::arrow_snapshot::ComponentTable;
+use crate::bevy_registry::LoadLimits;
+use crate::container::{ContainerError, read_limited};
+use crate::prelude::vec_snapshot_factory::SnapshotError;
+
+/// Entry of a patch zip listing the entries it removes.
+pub const PATCH_TOML: &str = "patch.toml";
+
+/// Entries that differ between two snapshot zips, by entry name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZipDiff {
+    /// Entries only in the newer zip.
+    pub added: Vec<String>,
+    /// Entries only in the older zip.
+    pub removed: Vec<String>,
+    /// Entries in both whose content differs.
+    pub changed: Vec<String>,
+    /// Entries in both with the same content but another recorded checksum, e.g. tables
+    /// re-encoded with another codec. Not a change, but patches carry them.
+    pub recoded: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct PatchIndex {
+    #[serde(default)]
+    removed: Vec<String>,
+}
+
+type Zip<'a> = ZipArchive<Cursor<&'a [u8]>>;
+
+fn open(data: &[u8]) -> Result<Zip<'_>, SnapshotError> {
+    ZipArchive::new(Cursor::new(data)).map_err(zip_err)
+}
+
+fn read(archive: &mut Zip<'_>, name: &str) -> Result<Vec<u8>, SnapshotError> {
+    let file = archive.by_name(name).map_err(zip_err)?;
+    Ok(read_limited(
+        file,
+        name,
+        LoadLimits::default().max_blob_bytes,
+    )?)
+}
+
+/// The `meta.toml` of `archive`, empty if it has none.
+fn recorded_meta(archive: &mut Zip<'_>) -> BTreeMap<String, String> {
+    read(archive, META_TOML)
+        .ok()
+        .and_then(|raw| toml::from_str(std::str::from_utf8(&raw).ok()?).ok())
+        .unwrap_or_default()
+}
+
+/// Whether two versions of entry `name` hold the same snapshot data.
+fn same_content(name: &str, a: &[u8], b: &[u8]) -> Result<bool, SnapshotError> {
+    if a == b {
+        return Ok(true);
+    }
+    let decode: fn(&[u8]) -> _ = if parse_archetype_idx(name).is_some() {
+        ComponentTable::from_parquet_u8
+    } else if parse_archetype_ipc_idx(name).is_some() {
+        ComponentTable::from_ipc
+    } else {
+        return Ok(false);
+    };
+    Ok(decode(a)?.to_record_batch()? == decode(b)?.to_record_batch()?)
+}
+
+/// Compare the snapshot zip `a` with the newer snapshot zip `b`.
+pub fn diff_zip(a: &[u8], b: &[u8]) -> Result<ZipDiff, SnapshotError> {
+    let (mut old, mut new) = (open(a)?, open(b)?);
+    let old_names: BTreeSet<String> = old.file_names().map(str::to_string).collect();
+    let new_names: BTreeSet<String> = new.file_names().map(str::to_string).collect();
+    let mut diff = ZipDiff {
+        added: new_names.difference(&old_names).cloned().collect(),
+        removed: old_names.difference(&new_names).cloned().collect(),
+        changed: vec![],
+        recoded: vec![],
+    };
+    let (old_meta, new_meta) = (recorded_meta(&mut old), recorded_meta(&mut new));
+    for name in old_names.intersection(&new_names) {
+        let (a, b) = (read(&mut old, name)?, read(&mut new, name)?);
+        if !same_content(name, &a, &b)? {
+            diff.changed.push(name.clone());
+        } else if a != b && old_meta.get(&checksum_key(name)) != new_meta.get(&checksum_key(name)) {
+            diff.recoded.push(name.clone());
+        }
+    }
+    Ok(diff)
+}
+
+impl ZipDiff {
+    /// Whether the zips hold the same snapshot data; [`recoded`](Self::recoded) entries
+    /// do not count.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Patch zip turning the older zip into `b`, the newer zip this diff was computed
+    /// against. Apply it with [`apply_zip_patch`].
+    pub fn to_patch(&self, b: &[u8]) -> Result<Vec<u8>, SnapshotError> {
+        let mut source = open(b)?;
+        let mut out = ZipWriter::new(Cursor::new(Vec::new()));
+        let index = PatchIndex {
+            removed: self.removed.clone(),
+        };
+        let content = toml::to_string(&index)
+            .map_err(|e| SnapshotError::Generic(format!("toml encode error: {e}")))?;
+        out.start_file(PATCH_TOML, SimpleFileOptions::default())
+            .map_err(zip_err)?;
+        out.write_all(content.as_bytes())
+            .map_err(ContainerError::from)?;
+        for name in self.added.iter().chain(&self.changed).chain(&self.recoded) {
+            let idx = source
+                .index_for_name(name)
+                .ok_or_else(|| ContainerError::NotFound(name.clone()))?;
+            let file = source.by_index_raw(idx).map_err(zip_err)?;
+            out.raw_copy_file(file).map_err(zip_err)?;
+        }
+        Ok(out.finish().map_err(zip_err)?.into_inner())
+    }
+}
+
+/// Apply a patch zip made by [`ZipDiff::to_patch`] to the snapshot zip `base`. Entries are
+/// copied without recompressing.
+pub fn apply_zip_patch(base: &[u8], patch: &[u8]) -> Result<Vec<u8>, SnapshotError> {
+    let (mut base, mut patch) = (open(base)?, open(patch)?);
+    let index = read(&mut patch, PATCH_TOML).map_err(|_| {
+        SnapshotError::Generic(format!("not a snapshot patch: missing {PATCH_TOML}"))
+    })?;
+    let index: PatchIndex = std::str::from_utf8(&index)
+        .ok()
+        .and_then(|text| toml::from_str(text).ok())
+        .ok_or_else(|| SnapshotError::Generic(format!("toml decode error in {PATCH_TOML}")))?;
+    let replaced: BTreeSet<String> = patch
+        .file_names()
+        .map(str::to_string)
+        .chain(index.removed)
+        .collect();
+
+    let mut out = ZipWriter::new(Cursor::new(Vec::new()));
+    for i in 0..base.len() {
+        let file = base.by_index_raw(i).map_err(zip_err)?;
+        if !replaced.contains(file.name()) {
+            out.raw_copy_file(file).map_err(zip_err)?;
+        }
+    }
+    for i in 0..patch.len() {
+        let file = patch.by_index_raw(i).map_err(zip_err)?;
+        if file.name() != PATCH_TOML {
+            out.raw_copy_file(file).map_err(zip_err)?;
+        }
+    }
+    Ok(out.finish().map_err(zip_err)?.into_inner())
+}
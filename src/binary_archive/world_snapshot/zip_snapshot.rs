@@ -1,23 +1,31 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
-use std::io::Cursor;
-use std::io::Read;
-use std::io::Write;
-use zip::ZipArchive;
-use zip::{ZipWriter, write::SimpleFileOptions};
+use std::fs::File;
+use std::io::{Cursor, Seek, Write};
+use std::path::Path;
+use bevy_ecs::prelude::World;
+use zip::{ZipArchive, ZipWriter};
+use zip::write::SimpleFileOptions;
 
-use crate::arrow_snapshot::ComponentTable;
+use crate::arrow_snapshot::{ComponentTable, ParquetCompression};
 use crate::binary_archive::BinBlob;
 use crate::binary_archive::WorldArrowSnapshot;
 use crate::binary_archive::common::SparseU32List;
+use crate::aurora_archive::ZipBlobLoader;
+use crate::bevy_registry::{LoadLimits, SnapshotRegistry};
+use crate::checksum::{ENTRY_CHECKSUM_PREFIX, blob_checksum, compare_checksum, verify_checksum};
+use crate::entity_index::{ENTITY_INDEX_KEY, EntityLocationIndex};
+use crate::container::{ContainerError, SnapshotContainer, ZipWriterContainer, read_limited};
 use crate::prelude::vec_snapshot_factory::SnapshotError;
 // === Magic string 常量区（全局唯一入口） ===
-const META_TOML: &str = "meta.toml";
+pub(super) const META_TOML: &str = "meta.toml";
 const ENTITIES_MSGPACK: &str = "entities.msgpack";
 const RESOURCES_PREFIX: &str = "resources/";
 const RESOURCES_SUFFIX: &str = ".msgpack";
 const ARCHETYPES_PREFIX: &str = "archetypes/";
 const ARCHETYPES_SUFFIX: &str = ".parquet";
+const ARCHETYPES_IPC_SUFFIX: &str = ".arrow";
+const COMPRESSION_KEY: &str = "parquet_compression";
 
 // 工具函数，读写都调它，不再拼字符串
 #[inline]
@@ -34,102 +42,545 @@ fn parse_resource_key(path: &str) -> Option<&str> {
         .strip_suffix(RESOURCES_SUFFIX)
 }
 #[inline]
-fn parse_archetype_idx(path: &str) -> Option<usize> {
+pub(super) fn parse_archetype_idx(path: &str) -> Option<usize> {
     path.strip_prefix(ARCHETYPES_PREFIX)?
         .strip_prefix("arch_")?
         .strip_suffix(ARCHETYPES_SUFFIX)?
         .parse()
         .ok()
 }
+#[inline]
+fn archetype_ipc_path(idx: usize) -> String {
+    format!("{ARCHETYPES_PREFIX}arch_{idx}{ARCHETYPES_IPC_SUFFIX}")
+}
+#[inline]
+pub(super) fn parse_archetype_ipc_idx(path: &str) -> Option<usize> {
+    path.strip_prefix(ARCHETYPES_PREFIX)?
+        .strip_prefix("arch_")?
+        .strip_suffix(ARCHETYPES_IPC_SUFFIX)?
+        .parse()
+        .ok()
+}
+/// Accept either `arch_N` or the full `archetypes/arch_N.parquet` entry path.
+fn archetype_entry(name: &str) -> Option<String> {
+    parse_archetype_idx(name)
+        .or_else(|| parse_archetype_idx(&format!("{ARCHETYPES_PREFIX}{name}{ARCHETYPES_SUFFIX}")))
+        .map(archetype_path)
+}
+#[inline]
+pub(super) fn checksum_key(name: &str) -> String {
+    format!("{ENTRY_CHECKSUM_PREFIX}{name}")
+}
+/// Check `bytes`, the content of entry `name`, against the checksum `meta` records for it.
+fn verify_entry(
+    meta: &BTreeMap<String, String>,
+    name: &str,
+    bytes: &[u8],
+) -> Result<(), SnapshotError> {
+    if let Some(expected) = meta.get(&checksum_key(name)) {
+        verify_checksum(name, bytes, expected)?;
+    }
+    Ok(())
+}
+/// Hashes what passes through it, to checksum zip entries written as a stream.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: blake3::Hasher,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: blake3::Hasher::new(),
+        }
+    }
+
+    /// Checksum of everything written so far, as [`blob_checksum`] computes it.
+    fn checksum(&self) -> String {
+        self.hasher.finalize().to_hex().to_string()
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Start zip entry `name`, returning a writer that checksums its content.
+fn start_entry<'a, W: Write + Seek>(
+    zip: &'a mut ZipWriter<W>,
+    name: &str,
+    options: SimpleFileOptions,
+) -> Result<HashingWriter<&'a mut ZipWriter<W>>, SnapshotError> {
+    zip.start_file(name, options).map_err(zip_err)?;
+    Ok(HashingWriter::new(zip))
+}
+
+#[inline]
+fn compression_key(idx: usize) -> String {
+    format!("{COMPRESSION_KEY}.arch_{idx}")
+}
+/// Codec recorded in `meta` for archetype `idx`, falling back to the snapshot default.
+fn compression_from_meta(
+    meta: &BTreeMap<String, String>,
+    idx: usize,
+) -> Result<ParquetCompression, SnapshotError> {
+    meta.get(&compression_key(idx))
+        .or_else(|| meta.get(COMPRESSION_KEY))
+        .map_or(Ok(ParquetCompression::None), |s| s.parse().map_err(SnapshotError::Generic))
+}
+pub(super) fn zip_err(e: zip::result::ZipError) -> SnapshotError {
+    ContainerError::Archive(e.to_string()).into()
+}
 
 impl WorldArrowSnapshot {
-    pub fn to_zip(&self, level: Option<i64>) -> Result<Vec<u8>, Box<dyn Error>> {
-        let mut buffer = Vec::new();
-        let cursor = Cursor::new(&mut buffer);
-        let mut zip = ZipWriter::new(cursor);
+    /// Compress every archetype table with `compression` unless overridden per archetype.
+    ///
+    /// The choice is stored in `meta`, so it is written to `meta.toml` with the snapshot.
+    pub fn set_default_compression(&mut self, compression: ParquetCompression) -> &mut Self {
+        self.meta
+            .insert(COMPRESSION_KEY.to_string(), compression.to_string());
+        self
+    }
+
+    /// Compress archetype table `idx` with `compression`, recorded in `meta`.
+    pub fn set_archetype_compression(
+        &mut self,
+        idx: usize,
+        compression: ParquetCompression,
+    ) -> &mut Self {
+        self.meta.insert(compression_key(idx), compression.to_string());
+        self
+    }
 
+    /// Codec archetype table `idx` is written with.
+    pub fn archetype_compression(&self, idx: usize) -> Result<ParquetCompression, SnapshotError> {
+        compression_from_meta(&self.meta, idx)
+    }
+
+    /// Locations of every archetype row, as written to `meta.toml` under `entity_index`.
+    pub fn entity_index(&self) -> EntityLocationIndex {
+        let ids: Vec<Vec<u32>> = self
+            .archetypes
+            .iter()
+            .map(|t| t.entities.iter().map(|e| e.id).collect())
+            .collect();
+        EntityLocationIndex::build(ids.iter().map(Vec::as_slice))
+    }
+
+    /// Write meta, entities, resources and archetype tables as entries of `container`.
+    pub fn write_to_container<C: SnapshotContainer>(
+        &self,
+        container: &mut C,
+    ) -> Result<(), SnapshotError> {
+        self.write_entries(container, false)
+    }
+
+    /// Like [`write_to_container`](Self::write_to_container), with archetype tables stored as
+    /// Arrow IPC files (`archetypes/arch_N.arrow`) instead of Parquet.
+    pub fn write_to_container_ipc<C: SnapshotContainer>(
+        &self,
+        container: &mut C,
+    ) -> Result<(), SnapshotError> {
+        self.write_entries(container, true)
+    }
+
+    fn write_entries<C: SnapshotContainer>(
+        &self,
+        container: &mut C,
+        ipc: bool,
+    ) -> Result<(), SnapshotError> {
+        let mut checksums = BTreeMap::new();
+        let mut write = |container: &mut C, name: &str, data: &[u8]| {
+            checksums.insert(checksum_key(name), blob_checksum(data));
+            container.write_entry(name, data)
+        };
+
+        // 1. entities
+        let entity_bytes = SparseU32List::from_unsorted(self.entities.clone());
+        let entity_bytes = rmp_serde::to_vec(&entity_bytes)
+            .map_err(|e| SnapshotError::Generic(format!("msgpack encode error: {e}")))?;
+        write(container, ENTITIES_MSGPACK, &entity_bytes)?;
+
+        // 2. resources
+        for (key, blob) in &self.resources {
+            write(container, &resource_path(key), &blob.0)?;
+        }
+
+        // 3. archetypes
+        for (idx, arch) in self.archetypes.iter().enumerate() {
+            if ipc {
+                write(container, &archetype_ipc_path(idx), &arch.to_ipc()?)?;
+            } else {
+                let parquet_data = arch.to_parquet_with(self.archetype_compression(idx)?)?;
+                write(container, &archetype_path(idx), &parquet_data)?;
+            }
+        }
+
+        // 4. meta, with a fresh entity index and the checksums of the entries above
+        container.write_entry(META_TOML, self.meta_toml(checksums)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// `meta.toml` with a fresh entity index and the entry `checksums`.
+    fn meta_toml(&self, checksums: BTreeMap<String, String>) -> Result<String, SnapshotError> {
+        let mut meta = self.meta.clone();
+        meta.retain(|key, _| !key.starts_with(ENTRY_CHECKSUM_PREFIX));
+        meta.insert(ENTITY_INDEX_KEY.to_string(), self.entity_index().to_meta_string());
+        meta.extend(checksums);
+        toml::to_string(&meta).map_err(|e| SnapshotError::Generic(format!("toml encode error: {e}")))
+    }
+
+    pub fn to_zip(&self, level: Option<i64>) -> Result<Vec<u8>, Box<dyn Error>> {
         let options = SimpleFileOptions::default()
             .compression_method(zip::CompressionMethod::Deflated)
             .compression_level(level);
+        let mut container = ZipWriterContainer::with_options(Cursor::new(Vec::new()), options);
+        self.write_to_container(&mut container)?;
+        Ok(container.finish()?.into_inner())
+    }
 
-        // 1. meta
-        let meta_toml = toml::to_string(&self.meta)
-            .map_err(|e| SnapshotError::Generic(format!("toml encode error: {e}")))?;
-        zip.start_file(META_TOML, options)?;
-        zip.write_all(meta_toml.as_bytes())?;
+    /// [`to_zip`](Self::to_zip) written to `writer` as it is encoded, e.g. a [`File`].
+    ///
+    /// Archetype tables are encoded as Parquet straight into their zip entries instead of
+    /// into buffers, so memory stays bounded by the largest table rather than the whole
+    /// archive. The entries and their checksums are the same as those of `to_zip`.
+    pub fn to_zip_writer<W: Write + Seek + Send>(
+        &self,
+        writer: W,
+        level: Option<i64>,
+    ) -> Result<W, SnapshotError> {
+        let options = SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .compression_level(level);
+        let mut zip = ZipWriter::new(writer);
+        let mut checksums = BTreeMap::new();
 
-        // 2. entities
         let entity_bytes = SparseU32List::from_unsorted(self.entities.clone());
-        zip.start_file(ENTITIES_MSGPACK, options)?;
-        zip.write_all(&rmp_serde::to_vec(&entity_bytes)?)?;
+        let entity_bytes = rmp_serde::to_vec(&entity_bytes)
+            .map_err(|e| SnapshotError::Generic(format!("msgpack encode error: {e}")))?;
+        let mut out = start_entry(&mut zip, ENTITIES_MSGPACK, options)?;
+        out.write_all(&entity_bytes).map_err(ContainerError::from)?;
+        checksums.insert(checksum_key(ENTITIES_MSGPACK), out.checksum());
 
-        // 3. resources
         for (key, blob) in &self.resources {
-            let path = resource_path(key);
-            zip.start_file(&path, options)?;
-            zip.write_all(&blob.0)?;
+            let name = resource_path(key);
+            let mut out = start_entry(&mut zip, &name, options)?;
+            out.write_all(&blob.0).map_err(ContainerError::from)?;
+            checksums.insert(checksum_key(&name), out.checksum());
         }
 
-        // 4. archetypes
         for (idx, arch) in self.archetypes.iter().enumerate() {
             let name = archetype_path(idx);
-            zip.start_file(&name, options)?;
-            let parquet_data = arch.to_parquet()?;
-            zip.write_all(&parquet_data)?;
+            let mut out = start_entry(&mut zip, &name, options)?;
+            arch.write_parquet_with(self.archetype_compression(idx)?, &mut out)?;
+            checksums.insert(checksum_key(&name), out.checksum());
         }
 
-        zip.finish()?; // flush everything
+        let meta_toml = self.meta_toml(checksums)?;
+        zip.start_file(META_TOML, options).map_err(zip_err)?;
+        zip.write_all(meta_toml.as_bytes()).map_err(ContainerError::from)?;
+        zip.finish().map_err(zip_err)
+    }
 
-        Ok(buffer)
+    /// Zip the snapshot with Arrow IPC archetype tables, left uncompressed so saving and
+    /// loading skip both Parquet encoding and deflate. Read it back with
+    /// [`from_ipc`](Self::from_ipc) or [`from_zip`](Self::from_zip).
+    pub fn to_ipc(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        let mut container = ZipWriterContainer::with_options(Cursor::new(Vec::new()), options);
+        self.write_to_container_ipc(&mut container)?;
+        Ok(container.finish()?.into_inner())
     }
 }
 
 impl WorldArrowSnapshot {
     pub fn from_zip(zip_data: &[u8]) -> Result<Self, SnapshotError> {
-        let cursor = Cursor::new(zip_data);
-        let mut zip = ZipArchive::new(cursor)
+        Self::from_zip_with_limits(zip_data, &LoadLimits::default())
+    }
+
+    /// Read a snapshot written by [`to_ipc`](Self::to_ipc).
+    pub fn from_ipc(zip_data: &[u8]) -> Result<Self, SnapshotError> {
+        Self::from_zip(zip_data)
+    }
+
+    /// Read the single row of entity `id` from a snapshot zip, as a one-row table.
+    ///
+    /// The `entity_index` in `meta.toml` points at the archetype entry and row, so only that
+    /// entry is decompressed and, for Parquet, only the row group holding the row is decoded.
+    /// Zips written without an index fall back to scanning every archetype.
+    pub fn extract_entity(
+        zip_data: &[u8],
+        id: u32,
+    ) -> Result<Option<ComponentTable>, SnapshotError> {
+        let max = LoadLimits::default().max_blob_bytes;
+        let mut archive = ZipArchive::new(Cursor::new(zip_data)).map_err(zip_err)?;
+        let meta: BTreeMap<String, String> = match archive.by_name(META_TOML) {
+            Ok(file) => {
+                let raw = read_limited(file, META_TOML, max)?;
+                std::str::from_utf8(&raw)
+                    .ok()
+                    .and_then(|text| toml::from_str(text).ok())
+                    .unwrap_or_default()
+            }
+            Err(_) => BTreeMap::new(),
+        };
+        let index = meta
+            .get(ENTITY_INDEX_KEY)
+            .map(|s| EntityLocationIndex::from_meta_str(s))
+            .transpose()
+            .map_err(SnapshotError::Generic)?;
+
+        type Row = Result<Option<ComponentTable>, SnapshotError>;
+        let mut read_row = |idx: usize, row: Option<usize>| -> Row {
+            if let Ok(file) = archive.by_name(&archetype_path(idx)) {
+                let buf = read_limited(file, &archetype_path(idx), max)?;
+                verify_entry(&meta, &archetype_path(idx), &buf)?;
+                return Ok(Some(match row {
+                    Some(row) => ComponentTable::from_parquet_row(&buf, row)?,
+                    None => ComponentTable::from_parquet_u8(&buf)?,
+                }));
+            }
+            if let Ok(file) = archive.by_name(&archetype_ipc_path(idx)) {
+                let buf = read_limited(file, &archetype_ipc_path(idx), max)?;
+                verify_entry(&meta, &archetype_ipc_path(idx), &buf)?;
+                let table = ComponentTable::from_ipc(&buf)?;
+                return Ok(Some(match row {
+                    Some(row) => table.slice(row, 1)?,
+                    None => table,
+                }));
+            }
+            Ok(None)
+        };
+
+        if let Some(index) = index {
+            let Some((idx, row)) = index.locate(id) else {
+                return Ok(None);
+            };
+            return read_row(idx, Some(row));
+        }
+        let mut idx = 0;
+        while let Some(table) = read_row(idx, None)? {
+            if let Some(row) = table.entities.iter().position(|e| e.id == id) {
+                return Ok(Some(table.slice(row, 1)?));
+            }
+            idx += 1;
+        }
+        Ok(None)
+    }
+
+    /// Like [`from_zip`](Self::from_zip), rejecting archives whose entries or archetype
+    /// count exceed `limits` before decompressing them.
+    pub fn from_zip_with_limits(
+        zip_data: &[u8],
+        limits: &LoadLimits,
+    ) -> Result<Self, SnapshotError> {
+        let archive = ZipArchive::new(Cursor::new(zip_data))
             .map_err(|e| SnapshotError::Generic(format!("zip decode error: {e}")))?;
+        Self::read_from_container(&mut ZipBlobLoader { archive }, limits)
+    }
+
+    /// Read a snapshot written by [`write_to_container`](Self::write_to_container) or
+    /// [`write_to_container_ipc`](Self::write_to_container_ipc).
+    pub fn read_from_container<C: SnapshotContainer>(
+        container: &mut C,
+        limits: &LoadLimits,
+    ) -> Result<Self, SnapshotError> {
+        let names = container.list_entries()?;
+        limits.check_archetypes(names.len())?;
 
         let mut meta = None;
         let mut entities: Option<Vec<u32>> = None;
-        let mut resources = HashMap::new();
+        let mut resources = BTreeMap::new();
         let mut archetypes = vec![];
+        let mut checksums = Vec::new();
 
-        for i in 0..zip.len() {
-            let mut file = zip
-                .by_index(i)
-                .map_err(|x| SnapshotError::Generic(x.to_string()))?;
-            let name = file.name().to_owned();
-
-            let mut buf = Vec::new();
-            file.read_to_end(&mut buf)
-                .map_err(|x| SnapshotError::Generic(x.to_string()))?;
+        for name in names {
+            let buf = container.read_entry_limited(&name, limits.max_blob_bytes)?;
+            if name != META_TOML {
+                checksums.push((name.clone(), blob_checksum(&buf)));
+            }
 
             if name == META_TOML {
+                let text = std::str::from_utf8(&buf)
+                    .map_err(|e| SnapshotError::Generic(format!("toml decode error: {e}")))?;
                 meta = Some(
-                    toml::from_str(std::str::from_utf8(&buf).unwrap())
+                    toml::from_str(text)
                         .map_err(|e| SnapshotError::Generic(format!("toml decode error: {e}")))?,
                 );
             } else if name == ENTITIES_MSGPACK {
                 let raw: &[u8] = &buf;
                 let ent: SparseU32List = rmp_serde::from_slice(raw)
                     .map_err(|x| SnapshotError::Generic(format!("msgpack decode error: {x}")))?;
+                limits.check_entities(ent.len())?;
                 entities = Some(ent.to_vec().iter().copied().collect());
             } else if let Some(key) = parse_resource_key(&name) {
                 resources.insert(key.to_string(), BinBlob(buf));
-            } else if let Some(_idx) = parse_archetype_idx(&name) {
+            } else if let Some(idx) = parse_archetype_idx(&name) {
                 let table = ComponentTable::from_parquet_u8(&buf)?;
-                archetypes.push(table);
+                archetypes.push((idx, table));
+            } else if let Some(idx) = parse_archetype_ipc_idx(&name) {
+                archetypes.push((idx, ComponentTable::from_ipc(&buf)?));
             } else {
-                println!("unrecognized file in snapshot zip: {name}");
+                archive_warn!("unrecognized file in snapshot zip: {name}");
             }
         }
+        // Containers may list entries in any order; keep archetypes in saved order.
+        archetypes.sort_by_key(|(idx, _)| *idx);
         let entities: Vec<u32> = entities.unwrap_or_default();
+        let mut meta: BTreeMap<String, String> = meta.unwrap_or_default();
+        for (name, actual) in checksums {
+            if let Some(expected) = meta.get(&checksum_key(&name)) {
+                compare_checksum(&name, actual, expected)?;
+            }
+        }
+        meta.retain(|key, _| !key.starts_with(ENTRY_CHECKSUM_PREFIX));
         Ok(WorldArrowSnapshot {
-            meta: meta.unwrap_or_default(),
+            meta,
             entities,
             resources,
-            archetypes,
+            archetypes: archetypes.into_iter().map(|(_, t)| t).collect(),
         })
     }
 }
+
+impl WorldArrowSnapshot {
+    /// Re-export a single archetype of `world` into the snapshot zip at `zip_path`.
+    ///
+    /// `archetype_name` is `arch_N` or the full `archetypes/arch_N.parquet` entry. The
+    /// world archetype is the one whose registered components match the columns stored in
+    /// that entry. Only this entry is re-encoded (plus `entities.msgpack` when new entities
+    /// appeared), with the codec `meta.toml` records for it; all other entries are copied
+    /// without decompressing. The new archive is
+    /// written next to `zip_path` and renamed over it, so a failure leaves the original intact.
+    pub fn update_zip_entry(
+        zip_path: impl AsRef<Path>,
+        archetype_name: &str,
+        world: &World,
+        registry: &SnapshotRegistry,
+    ) -> Result<(), SnapshotError> {
+        let zip_path = zip_path.as_ref();
+        let entry = archetype_entry(archetype_name).ok_or_else(|| {
+            SnapshotError::Generic(format!("not an archetype entry: {archetype_name}"))
+        })?;
+        let max = registry.limits.max_blob_bytes;
+        let mut archive = ZipArchive::new(File::open(zip_path).map_err(ContainerError::from)?)
+            .map_err(zip_err)?;
+
+        // Find the live archetype holding the same registered components as the entry.
+        let old = read_limited(archive.by_name(&entry).map_err(zip_err)?, &entry, max)?;
+        let old = ComponentTable::from_parquet_u8(&old)?;
+        let wanted: BTreeSet<&str> = old.column_names().map(String::as_str).collect();
+        let reg_comp_ids = super::registered_comp_ids(world, registry);
+        let matches: Vec<_> = world
+            .archetypes()
+            .iter()
+            .filter(|x| !x.contains(bevy_ecs::resource::IS_RESOURCE))
+            .filter(|x| {
+                let names: BTreeSet<&str> = x
+                    .components()
+                    .iter()
+                    .filter_map(|c| reg_comp_ids.get(c).copied())
+                    .collect();
+                names == wanted
+            })
+            .collect();
+        let mut live = matches.iter().filter(|x| !x.is_empty());
+        let archetype = match (live.next(), live.next()) {
+            (Some(_), Some(_)) => {
+                return Err(SnapshotError::Generic(format!(
+                    "more than one archetype matches {entry}"
+                )));
+            }
+            (Some(x), None) => *x,
+            (None, _) => *matches.first().ok_or_else(|| {
+                SnapshotError::Generic(format!("no archetype in the world matches {entry}"))
+            })?,
+        };
+        let table =
+            super::save_arrow_archetype_from_world(world, registry, archetype, &reg_comp_ids)?;
+        let mut meta: BTreeMap<String, String> = match archive.by_name(META_TOML) {
+            Ok(file) => {
+                let raw = read_limited(file, META_TOML, max)?;
+                let text = std::str::from_utf8(&raw)
+                    .map_err(|e| SnapshotError::Generic(format!("toml decode error: {e}")))?;
+                toml::from_str(text)
+                    .map_err(|e| SnapshotError::Generic(format!("toml decode error: {e}")))?
+            }
+            Err(_) => BTreeMap::new(),
+        };
+        let idx = parse_archetype_idx(&entry).unwrap_or_default();
+        let table_bytes = table.to_parquet_with(compression_from_meta(&meta, idx)?)?;
+
+        // Entities that joined the archetype must be in the entity list for loading.
+        let mut entity_bytes = None;
+        if let Ok(file) = archive.by_name(ENTITIES_MSGPACK) {
+            let raw = read_limited(file, ENTITIES_MSGPACK, max)?;
+            let list: SparseU32List = rmp_serde::from_slice(&raw)
+                .map_err(|x| SnapshotError::Generic(format!("msgpack decode error: {x}")))?;
+            let mut ids = list.to_vec();
+            let known: BTreeSet<u32> = ids.iter().copied().collect();
+            let before = ids.len();
+            ids.extend(table.entities.iter().map(|e| e.id).filter(|id| !known.contains(id)));
+            if ids.len() != before {
+                let list = SparseU32List::from_unsorted(ids);
+                entity_bytes = Some(rmp_serde::to_vec(&list).map_err(|e| {
+                    SnapshotError::Generic(format!("msgpack encode error: {e}"))
+                })?);
+            }
+        }
+        // Keep the checksums in `meta.toml` in step with the replaced entries.
+        meta.insert(checksum_key(&entry), blob_checksum(&table_bytes));
+        if let Some(bytes) = &entity_bytes {
+            meta.insert(checksum_key(ENTITIES_MSGPACK), blob_checksum(bytes));
+        }
+        let meta_bytes = toml::to_string(&meta)
+            .map_err(|e| SnapshotError::Generic(format!("toml encode error: {e}")))?;
+
+        let tmp_path = zip_path.with_extension("zip.tmp");
+        let written = (|| -> Result<(), SnapshotError> {
+            let options = SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated);
+            let mut out = ZipWriter::new(File::create(&tmp_path).map_err(ContainerError::from)?);
+            for i in 0..archive.len() {
+                let file = archive.by_index_raw(i).map_err(zip_err)?;
+                let replacement = if file.name() == entry {
+                    Some(table_bytes.as_slice())
+                } else if file.name() == ENTITIES_MSGPACK {
+                    entity_bytes.as_deref()
+                } else if file.name() == META_TOML {
+                    Some(meta_bytes.as_bytes())
+                } else {
+                    None
+                };
+                match replacement {
+                    Some(data) => {
+                        let name = file.name().to_string();
+                        drop(file);
+                        out.start_file(name, options).map_err(zip_err)?;
+                        out.write_all(data).map_err(ContainerError::from)?;
+                    }
+                    None => out.raw_copy_file(file).map_err(zip_err)?,
+                }
+            }
+            out.finish().map_err(zip_err)?;
+            Ok(())
+        })();
+        drop(archive);
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+        std::fs::rename(&tmp_path, zip_path).map_err(ContainerError::from)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+//! Portable save files: a world manifest bundled with the asset files it needs.
+//!
+//! [`save_bundle`] writes the world into a [`SnapshotContainer`] like
+//! [`save_world_manifest_to_container`] and adds a [`BundleIndex`] listing every required
+//! asset with its size and BLAKE3 hash. With [`RequiredAssets::embedded`] the asset bytes
+//! are copied into the bundle under [`BUNDLE_ASSET_PREFIX`] too.
+//!
+//! [`load_bundle`] checks every listed asset before touching the world: embedded assets
+//! against the bundle, the others against the local content install. A save made against
+//! different content fails with [`BundleError::MissingAsset`] or
+//! [`BundleError::HashMismatch`] instead of loading with broken references.
+
+use std::collections::BTreeSet;
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::archetype_archive::LoadReport;
+use crate::aurora_archive::{
+    AuroraLoadError, ExportGuidance, load_world_manifest_from_container,
+    save_world_manifest_to_container,
+};
+use crate::bevy_registry::SnapshotRegistry;
+use crate::container::{ContainerError, SnapshotContainer};
+use crate::error::ArchiveError;
+
+/// Entry holding the world manifest inside a bundle.
+pub const BUNDLE_MANIFEST: &str = "world.toml";
+/// Entry holding the [`BundleIndex`] inside a bundle.
+pub const BUNDLE_INDEX: &str = "bundle.toml";
+/// Prefix of the entries holding embedded asset files.
+pub const BUNDLE_ASSET_PREFIX: &str = "assets/";
+
+/// An asset file a bundled save depends on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AssetEntry {
+    /// Path relative to the asset root, e.g. `textures/grass.png`.
+    pub path: String,
+    pub size: u64,
+    /// Lowercase hex BLAKE3 hash of the file.
+    pub hash: String,
+    /// Whether the bytes are stored in the bundle under [`BUNDLE_ASSET_PREFIX`].
+    #[serde(default)]
+    pub embedded: bool,
+}
+
+/// Table of contents of a bundle, stored at [`BUNDLE_INDEX`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleIndex {
+    /// Entry of the world manifest.
+    pub manifest: String,
+    #[serde(default)]
+    pub assets: Vec<AssetEntry>,
+}
+
+impl BundleIndex {
+    pub fn asset(&self, path: &str) -> Option<&AssetEntry> {
+        self.assets.iter().find(|a| a.path == path)
+    }
+}
+
+/// Asset files to record in a bundle, read from the asset container passed to
+/// [`save_bundle`].
+#[derive(Debug, Clone, Default)]
+pub struct RequiredAssets {
+    pub paths: BTreeSet<String>,
+    /// Copy the asset bytes into the bundle instead of only recording their hashes.
+    pub embed: bool,
+}
+
+impl RequiredAssets {
+    pub fn new<S: Into<String>>(paths: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+            embed: false,
+        }
+    }
+
+    pub fn embedded(mut self) -> Self {
+        self.embed = true;
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error(transparent)]
+    Archive(#[from] ArchiveError),
+    /// Boxed, as it is much larger than the other variants.
+    #[error(transparent)]
+    Load(Box<AuroraLoadError>),
+    #[error("required asset '{0}' is missing")]
+    MissingAsset(String),
+    #[error("asset '{path}' does not match the save: expected blake3 {expected}, found {actual}")]
+    HashMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl From<AuroraLoadError> for BundleError {
+    fn from(e: AuroraLoadError) -> Self {
+        Self::Load(Box::new(e))
+    }
+}
+
+/// Lowercase hex BLAKE3 hash of `bytes`, as recorded in [`AssetEntry::hash`].
+pub fn asset_hash(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+/// Save `world` and the `required` assets, read from `assets`, into `bundle`.
+pub fn save_bundle<C: SnapshotContainer, A: SnapshotContainer>(
+    world: &World,
+    registry: &SnapshotRegistry,
+    guidance: &ExportGuidance,
+    required: &RequiredAssets,
+    assets: &mut A,
+    bundle: &mut C,
+) -> Result<BundleIndex, BundleError> {
+    let mut index = BundleIndex {
+        manifest: BUNDLE_MANIFEST.to_string(),
+        assets: Vec::with_capacity(required.paths.len()),
+    };
+    for path in &required.paths {
+        let bytes = read_asset(assets, path)?;
+        if required.embed {
+            bundle
+                .write_entry(&asset_entry_name(path), &bytes)
+                .map_err(ArchiveError::from)?;
+        }
+        index.assets.push(AssetEntry {
+            path: path.clone(),
+            size: bytes.len() as u64,
+            hash: asset_hash(&bytes),
+            embedded: required.embed,
+        });
+    }
+    save_world_manifest_to_container(world, registry, guidance, bundle, &index.manifest)?;
+    let content = toml::to_string_pretty(&index).map_err(ArchiveError::from)?;
+    bundle
+        .write_entry(BUNDLE_INDEX, content.as_bytes())
+        .map_err(ArchiveError::from)?;
+    Ok(index)
+}
+
+/// Read the [`BundleIndex`] of `bundle`.
+pub fn read_bundle_index<C: SnapshotContainer>(
+    bundle: &mut C,
+) -> Result<BundleIndex, ArchiveError> {
+    let bytes = bundle.read_entry(BUNDLE_INDEX)?;
+    let content = String::from_utf8(bytes).map_err(ArchiveError::parse)?;
+    Ok(toml::from_str(&content)?)
+}
+
+/// Check every asset listed in the index of `bundle`: embedded ones against the bundle,
+/// the others against `assets`.
+pub fn verify_bundle<C: SnapshotContainer, A: SnapshotContainer>(
+    bundle: &mut C,
+    assets: &mut A,
+) -> Result<BundleIndex, BundleError> {
+    let index = read_bundle_index(bundle)?;
+    for entry in &index.assets {
+        let bytes = if entry.embedded {
+            read_asset(bundle, &asset_entry_name(&entry.path))
+        } else {
+            read_asset(assets, &entry.path)
+        }
+        .map_err(|e| match e {
+            BundleError::MissingAsset(_) => BundleError::MissingAsset(entry.path.clone()),
+            e => e,
+        })?;
+        let actual = asset_hash(&bytes);
+        if actual != entry.hash {
+            return Err(BundleError::HashMismatch {
+                path: entry.path.clone(),
+                expected: entry.hash.clone(),
+                actual,
+            });
+        }
+    }
+    Ok(index)
+}
+
+/// [`verify_bundle`], then load the world of `bundle` into `world`.
+pub fn load_bundle<C: SnapshotContainer, A: SnapshotContainer>(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    bundle: &mut C,
+    assets: &mut A,
+) -> Result<LoadReport, BundleError> {
+    let index = verify_bundle(bundle, assets)?;
+    Ok(load_world_manifest_from_container(
+        world,
+        registry,
+        bundle,
+        &index.manifest,
+    )?)
+}
+
+/// Copy the embedded assets of a verified bundle into `assets`, e.g. to install them
+/// next to the local content.
+pub fn extract_bundle_assets<C: SnapshotContainer, A: SnapshotContainer>(
+    bundle: &mut C,
+    index: &BundleIndex,
+    assets: &mut A,
+) -> Result<(), BundleError> {
+    for entry in index.assets.iter().filter(|a| a.embedded) {
+        let bytes = read_asset(bundle, &asset_entry_name(&entry.path))?;
+        assets
+            .write_entry(&entry.path, &bytes)
+            .map_err(ArchiveError::from)?;
+    }
+    Ok(())
+}
+
+fn asset_entry_name(path: &str) -> String {
+    format!("{BUNDLE_ASSET_PREFIX}{path}")
+}
+
+fn read_asset<C: SnapshotContainer>(container: &mut C, path: &str) -> Result<Vec<u8>, BundleError> {
+    container.read_entry(path).map_err(|e| match e {
+        ContainerError::NotFound(_) => BundleError::MissingAsset(path.to_string()),
+        ContainerError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            BundleError::MissingAsset(path.to_string())
+        }
+        e => ArchiveError::from(e).into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::aurora_archive::ExportFormat;
+
+    #[derive(Component, Serialize, Deserialize, Debug, PartialEq)]
+    struct Sprite(String);
+
+    fn setup() -> (World, SnapshotRegistry, BTreeMap<String, Vec<u8>>) {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Sprite>();
+        let mut world = World::new();
+        world.spawn(Sprite("textures/grass.png".to_string()));
+        let assets = BTreeMap::from([("textures/grass.png".to_string(), b"grass".to_vec())]);
+        (world, registry, assets)
+    }
+
+    #[test]
+    fn test_bundle_verifies_assets() {
+        let (world, registry, mut assets) = setup();
+        let required = RequiredAssets::new(["textures/grass.png"]);
+        let mut bundle = BTreeMap::new();
+        let guidance = ExportGuidance::embed_all(ExportFormat::Csv);
+        let index = save_bundle(
+            &world,
+            &registry,
+            &guidance,
+            &required,
+            &mut assets,
+            &mut bundle,
+        )
+        .unwrap();
+        assert_eq!(index.asset("textures/grass.png").unwrap().size, 5);
+        assert!(!bundle.contains_key("assets/textures/grass.png"));
+
+        let mut loaded = World::new();
+        load_bundle(&mut loaded, &registry, &mut bundle, &mut assets).unwrap();
+        assert_eq!(loaded.query::<&Sprite>().iter(&loaded).count(), 1);
+
+        let mut modded = BTreeMap::from([("textures/grass.png".to_string(), b"snow".to_vec())]);
+        let err = load_bundle(&mut World::new(), &registry, &mut bundle, &mut modded).unwrap_err();
+        assert!(
+            matches!(err, BundleError::HashMismatch { ref path, .. } if path == "textures/grass.png")
+        );
+        let err = verify_bundle(&mut bundle, &mut BTreeMap::new()).unwrap_err();
+        assert!(matches!(err, BundleError::MissingAsset(ref path) if path == "textures/grass.png"));
+    }
+
+    #[test]
+    fn test_bundle_with_embedded_assets() {
+        let (world, registry, mut assets) = setup();
+        let required = RequiredAssets::new(["textures/grass.png"]).embedded();
+        let mut bundle = BTreeMap::new();
+        let guidance = ExportGuidance::embed_all(ExportFormat::Csv);
+        save_bundle(
+            &world,
+            &registry,
+            &guidance,
+            &required,
+            &mut assets,
+            &mut bundle,
+        )
+        .unwrap();
+
+        let mut empty_install = BTreeMap::new();
+        let index = verify_bundle(&mut bundle, &mut empty_install).unwrap();
+        extract_bundle_assets(&mut bundle, &index, &mut empty_install).unwrap();
+        assert_eq!(empty_install, assets);
+
+        bundle.insert(
+            "assets/textures/grass.png".to_string(),
+            b"tampered".to_vec(),
+        );
+        let err = verify_bundle(&mut bundle, &mut assets).unwrap_err();
+        assert!(matches!(err, BundleError::HashMismatch { .. }));
+    }
+}
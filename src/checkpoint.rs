@@ -0,0 +1,339 @@
+//! In-memory checkpoints for rewinding a simulation (`arrow_rs` feature).
+//!
+//! [`CheckpointManager`] keeps the last few [`WorldArrowSnapshot`]s in a ring buffer.
+//! Take one with [`checkpoint`](CheckpointManager::checkpoint) every frame (or every few)
+//! and [`rollback`](CheckpointManager::rollback) to go back without touching disk.
+//!
+//! Only what the [`SnapshotRegistry`] resource covers is rewound. On rollback, registered
+//! components are stripped from every entity and reloaded from the checkpoint, and entities
+//! that did not exist at the checkpoint are despawned if they carry a registered
+//! component. Entities with nothing registered are left alone, so observers and other
+//! bookkeeping entities survive.
+//!
+//! [`RollbackBuffer`] keeps the same snapshots keyed by simulation tick instead, for fixed
+//! tick loops such as rollback netcode: save every tick, then
+//! [`restore`](RollbackBuffer::restore) the tick a late input belongs to and resimulate.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::resource::IS_RESOURCE;
+
+use crate::archetype_archive::WorldExt;
+use crate::bevy_registry::SnapshotRegistry;
+use crate::binary_archive::WorldArrowSnapshot;
+use crate::prelude::vec_snapshot_factory::SnapshotError;
+
+/// Ring buffer of the most recent world checkpoints, oldest first.
+#[derive(Resource, Debug, Clone)]
+pub struct CheckpointManager {
+    capacity: usize,
+    checkpoints: VecDeque<WorldArrowSnapshot>,
+}
+
+impl Default for CheckpointManager {
+    fn default() -> Self {
+        Self::new(8)
+    }
+}
+
+impl CheckpointManager {
+    /// Keep at most `capacity` checkpoints (at least one).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            checkpoints: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.is_empty()
+    }
+
+    /// The checkpoint `n` steps back; `0` is the latest.
+    pub fn get(&self, n: usize) -> Option<&WorldArrowSnapshot> {
+        self.checkpoints.iter().rev().nth(n)
+    }
+
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+    }
+
+    /// Snapshot `world` with its [`SnapshotRegistry`] resource, evicting the oldest
+    /// checkpoint when full.
+    pub fn checkpoint(&mut self, world: &World) -> Result<(), SnapshotError> {
+        self.checkpoint_with(world, world.resource::<SnapshotRegistry>())
+    }
+
+    pub fn checkpoint_with(
+        &mut self,
+        world: &World,
+        registry: &SnapshotRegistry,
+    ) -> Result<(), SnapshotError> {
+        let snapshot = WorldArrowSnapshot::from_world_reg(world, registry)?;
+        if self.checkpoints.len() == self.capacity {
+            self.checkpoints.pop_front();
+        }
+        self.checkpoints.push_back(snapshot);
+        Ok(())
+    }
+
+    /// Restore the checkpoint `n` steps back (`0` is the latest) into `world`, using its
+    /// [`SnapshotRegistry`] resource.
+    ///
+    /// The checkpoints newer than the restored one are dropped; the restored one stays the
+    /// latest, so rolling back twice in a row lands on the same state.
+    pub fn rollback(&mut self, world: &mut World, n: usize) -> Result<(), SnapshotError> {
+        world.resource_scope(|world, registry: Mut<SnapshotRegistry>| {
+            self.rollback_with(world, &registry, n)
+        })
+    }
+
+    pub fn rollback_with(
+        &mut self,
+        world: &mut World,
+        registry: &SnapshotRegistry,
+        n: usize,
+    ) -> Result<(), SnapshotError> {
+        if n >= self.checkpoints.len() {
+            return Err(SnapshotError::Generic(format!(
+                "no checkpoint {n} steps back, {} stored",
+                self.checkpoints.len()
+            )));
+        }
+        let keep = self.checkpoints.len() - n;
+        self.checkpoints.truncate(keep);
+        restore_snapshot(world, registry, &self.checkpoints[keep - 1])
+    }
+}
+
+/// Snapshots keyed by simulation tick, keeping the `depth` most recent ticks.
+#[derive(Resource, Debug, Clone)]
+pub struct RollbackBuffer {
+    depth: usize,
+    ticks: BTreeMap<u64, WorldArrowSnapshot>,
+}
+
+impl Default for RollbackBuffer {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+impl RollbackBuffer {
+    /// Keep at most `depth` ticks (at least one).
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth: depth.max(1),
+            ticks: BTreeMap::new(),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    pub fn contains(&self, tick: u64) -> bool {
+        self.ticks.contains_key(&tick)
+    }
+
+    pub fn get(&self, tick: u64) -> Option<&WorldArrowSnapshot> {
+        self.ticks.get(&tick)
+    }
+
+    pub fn oldest_tick(&self) -> Option<u64> {
+        self.ticks.keys().next().copied()
+    }
+
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.ticks.keys().next_back().copied()
+    }
+
+    /// Stored ticks, oldest first.
+    pub fn ticks(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ticks.keys().copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.ticks.clear();
+    }
+
+    /// Snapshot `world` as the state at `tick`, using its [`SnapshotRegistry`] resource.
+    ///
+    /// Ticks at or after `tick` are dropped first: they belong to the timeline being
+    /// resimulated. The oldest ticks are evicted past the depth.
+    pub fn save(&mut self, world: &World, tick: u64) -> Result<(), SnapshotError> {
+        self.save_with(world, world.resource::<SnapshotRegistry>(), tick)
+    }
+
+    pub fn save_with(
+        &mut self,
+        world: &World,
+        registry: &SnapshotRegistry,
+        tick: u64,
+    ) -> Result<(), SnapshotError> {
+        let snapshot = WorldArrowSnapshot::from_world_reg(world, registry)?;
+        self.ticks.split_off(&tick);
+        self.ticks.insert(tick, snapshot);
+        while self.ticks.len() > self.depth {
+            self.ticks.pop_first();
+        }
+        Ok(())
+    }
+
+    /// Restore `world` to its state at `tick`, using its [`SnapshotRegistry`] resource.
+    ///
+    /// Newer ticks are kept until the next [`save`](Self::save) replaces them.
+    pub fn restore(&self, world: &mut World, tick: u64) -> Result<(), SnapshotError> {
+        world.resource_scope(|world, registry: Mut<SnapshotRegistry>| {
+            self.restore_with(world, &registry, tick)
+        })
+    }
+
+    pub fn restore_with(
+        &self,
+        world: &mut World,
+        registry: &SnapshotRegistry,
+        tick: u64,
+    ) -> Result<(), SnapshotError> {
+        let Some(snapshot) = self.ticks.get(&tick) else {
+            return Err(SnapshotError::Generic(format!(
+                "tick {tick} is not in the rollback buffer"
+            )));
+        };
+        restore_snapshot(world, registry, snapshot)
+    }
+}
+
+/// Strip registered state from `world` and load `snapshot` in its place.
+fn restore_snapshot(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    snapshot: &WorldArrowSnapshot,
+) -> Result<(), SnapshotError> {
+    let ids: Vec<_> = registry
+        .type_registry
+        .keys()
+        .filter_map(|name| registry.comp_id_by_name(name, world))
+        .collect();
+    let known: HashSet<u32> = snapshot.entities.iter().copied().collect();
+    let touched: Vec<Entity> = WorldExt::iter_entities(world)
+        .filter(|&e| {
+            let entity = world.entity(e);
+            !entity.contains_id(IS_RESOURCE) && ids.iter().any(|&id| entity.contains_id(id))
+        })
+        .collect();
+    for entity in touched {
+        if known.contains(&entity.index_u32()) {
+            world.entity_mut(entity).remove_by_ids(&ids);
+        } else {
+            world.despawn(entity);
+        }
+    }
+    snapshot.to_world_reg(world, registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hp(u32);
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Poisoned;
+
+    fn hp(world: &mut World) -> Vec<u32> {
+        let mut out: Vec<u32> = world.query::<&Hp>().iter(world).map(|h| h.0).collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn test_checkpoint_rollback() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        registry.register::<Poisoned>();
+        let mut world = World::new();
+        world.insert_resource(registry);
+        let mut manager = CheckpointManager::new(3);
+
+        let a = world.spawn(Hp(10)).id();
+        manager.checkpoint(&world).unwrap();
+        world.get_mut::<Hp>(a).unwrap().0 = 8;
+        world.entity_mut(a).insert(Poisoned);
+        manager.checkpoint(&world).unwrap();
+        let b = world.spawn(Hp(50)).id();
+        manager.checkpoint(&world).unwrap();
+        world.get_mut::<Hp>(b).unwrap().0 = 1;
+        world.entity_mut(a).remove::<Poisoned>();
+        let watcher = world.add_observer(|_: On<Add, Hp>| {}).id();
+
+        // Back to the second checkpoint: `b` did not exist yet and `a` was poisoned.
+        manager.rollback(&mut world, 1).unwrap();
+        assert_eq!(manager.len(), 2);
+        assert_eq!(hp(&mut world), vec![8]);
+        assert!(world.get_entity(b).is_err());
+        assert!(world.get::<Poisoned>(a).is_some());
+        assert!(world.get_entity(watcher).is_ok());
+
+        manager.rollback(&mut world, 1).unwrap();
+        assert_eq!(hp(&mut world), vec![10]);
+        assert!(world.get::<Poisoned>(a).is_none());
+        assert!(manager.rollback(&mut world, 1).is_err());
+
+        // The oldest checkpoint is evicted once the buffer is full.
+        for i in 0..5 {
+            world.get_mut::<Hp>(a).unwrap().0 = i;
+            manager.checkpoint(&world).unwrap();
+        }
+        assert_eq!(manager.len(), 3);
+        manager.rollback(&mut world, 2).unwrap();
+        assert_eq!(hp(&mut world), vec![2]);
+    }
+
+    #[test]
+    fn test_rollback_buffer() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        let mut world = World::new();
+        world.insert_resource(registry);
+        let mut buffer = RollbackBuffer::new(4);
+
+        let a = world.spawn(Hp(0)).id();
+        for tick in 0..6 {
+            world.get_mut::<Hp>(a).unwrap().0 = tick as u32;
+            buffer.save(&world, tick).unwrap();
+        }
+        assert_eq!(buffer.ticks().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+        assert!(buffer.restore(&mut world, 1).is_err());
+
+        world.spawn(Hp(99));
+        buffer.restore(&mut world, 3).unwrap();
+        assert_eq!(hp(&mut world), vec![3]);
+
+        // Resimulating from tick 3 replaces the stale future.
+        world.get_mut::<Hp>(a).unwrap().0 = 40;
+        buffer.save(&world, 4).unwrap();
+        assert_eq!(buffer.latest_tick(), Some(4));
+        buffer.restore(&mut world, 4).unwrap();
+        assert_eq!(hp(&mut world), vec![40]);
+    }
+}
@@ -0,0 +1,121 @@
+//! BLAKE3 checksums of snapshot blobs, recorded on save and checked on load so a corrupted
+//! save fails with a [`ChecksumMismatch`] instead of a confusing decode error, or silently
+//! wrong data.
+//!
+//! Aurora manifests keep one hash per archetype blob in `metadata` under
+//! [`BLOB_CHECKSUMS_KEY`], keyed by the archetype `source`; embedded blobs are hashed after
+//! decoding, files and container entries as stored. Snapshot zips keep one
+//! `blake3:<entry>` key per entry in `meta.toml`. Blobs without a recorded hash, e.g. from
+//! saves written before checksums existed, load unchecked.
+
+/// Manifest metadata key of the blob checksums, see
+/// [`AuroraWorldManifest::blob_checksum`](crate::aurora_archive::AuroraWorldManifest::blob_checksum).
+pub const BLOB_CHECKSUMS_KEY: &str = "blob_checksums";
+/// Prefix of the `meta.toml` keys holding the checksum of each snapshot zip entry.
+pub const ENTRY_CHECKSUM_PREFIX: &str = "blake3:";
+
+/// A blob that does not hash to the checksum recorded when it was saved.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("checksum mismatch for '{entry}': expected blake3 {expected}, found {actual}")]
+pub struct ChecksumMismatch {
+    pub entry: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// BLAKE3 hash of `bytes` as lowercase hex.
+pub fn blob_checksum(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+/// Check that `bytes`, the content of `entry`, hash to `expected`.
+pub fn verify_checksum(entry: &str, bytes: &[u8], expected: &str) -> Result<(), ChecksumMismatch> {
+    compare_checksum(entry, blob_checksum(bytes), expected)
+}
+
+/// Check the `actual` checksum of `entry`, hashed while it was read, against `expected`.
+pub(crate) fn compare_checksum(
+    entry: &str,
+    actual: String,
+    expected: &str,
+) -> Result<(), ChecksumMismatch> {
+    if actual.eq_ignore_ascii_case(expected) {
+        return Ok(());
+    }
+    Err(ChecksumMismatch {
+        entry: entry.to_string(),
+        expected: expected.to_string(),
+        actual,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bevy_ecs::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::aurora_archive::{
+        AuroraLoadError, ExportFormat, ExportGuidance, load_world_manifest,
+        load_world_manifest_from_container, save_world_manifest, save_world_manifest_to_container,
+    };
+    use crate::archetype_archive::WorldArchSnapshot;
+    use crate::bevy_registry::SnapshotRegistry;
+    use crate::error::ArchiveError;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hull(u32);
+
+    fn is_mismatch(result: Result<impl Sized, AuroraLoadError>) -> bool {
+        matches!(
+            result,
+            Err(AuroraLoadError::Resolve {
+                source: ArchiveError::Checksum(_),
+                ..
+            })
+        )
+    }
+
+    #[test]
+    fn test_blob_checksums() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hull>();
+        let mut world = World::new();
+        world.spawn(Hull(100));
+
+        let mut manifest = save_world_manifest(&world, &registry).unwrap();
+        let source = manifest.world.archetypes[0].source.0.clone();
+        assert!(manifest.blob_checksum(&source).is_some());
+        load_world_manifest(&mut World::new(), &manifest, &registry).unwrap();
+        let blob = manifest.world.embed.values_mut().next().unwrap();
+        blob.data = blob.data.replace("100", "999");
+        assert!(matches!(
+            WorldArchSnapshot::try_from(&manifest),
+            Err(ArchiveError::Checksum(_))
+        ));
+        assert!(is_mismatch(load_world_manifest(
+            &mut World::new(),
+            &manifest,
+            &registry
+        )));
+
+        let guidance = ExportGuidance::file_all(ExportFormat::MsgPack, "blobs");
+        let mut container: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+        save_world_manifest_to_container(&world, &registry, &guidance, &mut container, "w.toml")
+            .unwrap();
+        let entry = container
+            .keys()
+            .find(|name| name.starts_with("blobs"))
+            .unwrap()
+            .clone();
+        *container.get_mut(&entry).unwrap().last_mut().unwrap() ^= 1;
+        let loaded = load_world_manifest_from_container(
+            &mut World::new(),
+            &registry,
+            &mut container,
+            "w.toml",
+        );
+        assert!(is_mismatch(loaded));
+    }
+}
@@ -0,0 +1,420 @@
+//! Storage layouts that hold a snapshot as a set of named entries.
+//!
+//! A [`SnapshotContainer`] is anything that can list, read and write entries by path:
+//! a directory ([`FsBlobLoader`](crate::aurora_archive::FsBlobLoader)), an in-memory map,
+//! a zip archive (`zip` feature), a tar archive (`tar` feature) or a prefix in cloud object
+//! storage (`object_store` feature). The Aurora manifest
+//! loader/saver and `WorldArrowSnapshot` zip I/O are written against this trait, so a new
+//! layout only needs one implementation.
+
+use std::collections::{BTreeMap, HashMap};
+#[cfg(any(feature = "zip", feature = "tar"))]
+use std::io::Read;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ContainerError {
+    #[error("entry not found: {0}")]
+    NotFound(String),
+    #[error("entry path '{0}' escapes the container")]
+    Escape(String),
+    #[error("container does not support {0}")]
+    Unsupported(&'static str),
+    #[error("entry '{name}' is larger than {max} bytes")]
+    TooLarge { name: String, max: u64 },
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("archive error: {0}")]
+    Archive(String),
+}
+
+/// A named-entry store a snapshot can be read from or written to.
+pub trait SnapshotContainer {
+    /// All entry paths currently in the container.
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError>;
+    /// Read a whole entry.
+    fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, ContainerError>;
+    /// Create or replace an entry.
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> Result<(), ContainerError>;
+
+    /// Read an entry, failing instead of buffering more than `max` bytes.
+    ///
+    /// Compressed containers override this to stop decompressing at the limit.
+    fn read_entry_limited(&mut self, name: &str, max: u64) -> Result<Vec<u8>, ContainerError> {
+        let data = self.read_entry(name)?;
+        if data.len() as u64 > max {
+            return Err(ContainerError::TooLarge {
+                name: name.to_string(),
+                max,
+            });
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(feature = "zip")]
+pub(crate) fn read_limited(
+    mut reader: impl Read,
+    name: &str,
+    max: u64,
+) -> Result<Vec<u8>, ContainerError> {
+    let mut buf = Vec::new();
+    (&mut reader)
+        .take(max.saturating_add(1))
+        .read_to_end(&mut buf)?;
+    if buf.len() as u64 > max {
+        return Err(ContainerError::TooLarge {
+            name: name.to_string(),
+            max,
+        });
+    }
+    Ok(buf)
+}
+
+impl SnapshotContainer for BTreeMap<String, Vec<u8>> {
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError> {
+        Ok(self.keys().cloned().collect())
+    }
+    fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, ContainerError> {
+        self.get(name)
+            .cloned()
+            .ok_or_else(|| ContainerError::NotFound(name.to_string()))
+    }
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> Result<(), ContainerError> {
+        self.insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+}
+
+impl SnapshotContainer for HashMap<String, Vec<u8>> {
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError> {
+        let mut names: Vec<_> = self.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+    fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, ContainerError> {
+        self.get(name)
+            .cloned()
+            .ok_or_else(|| ContainerError::NotFound(name.to_string()))
+    }
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> Result<(), ContainerError> {
+        self.insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+}
+
+/// The entries of `inner` under `prefix`, with the prefix stripped, e.g. one section of a
+/// container shared by several saves.
+pub struct SubContainer<'a, C: ?Sized> {
+    inner: &'a mut C,
+    prefix: String,
+}
+
+impl<'a, C: SnapshotContainer + ?Sized> SubContainer<'a, C> {
+    /// View of the entries of `inner` starting with `prefix`, e.g. `"render/"`.
+    pub fn new(inner: &'a mut C, prefix: impl Into<String>) -> Self {
+        Self {
+            inner,
+            prefix: prefix.into(),
+        }
+    }
+
+    fn path(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+}
+
+impl<C: SnapshotContainer + ?Sized> SnapshotContainer for SubContainer<'_, C> {
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError> {
+        Ok(self
+            .inner
+            .list_entries()?
+            .into_iter()
+            .filter_map(|name| name.strip_prefix(&self.prefix).map(str::to_string))
+            .collect())
+    }
+    fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, ContainerError> {
+        let path = self.path(name);
+        self.inner.read_entry(&path)
+    }
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> Result<(), ContainerError> {
+        let path = self.path(name);
+        self.inner.write_entry(&path, data)
+    }
+    fn read_entry_limited(&mut self, name: &str, max: u64) -> Result<Vec<u8>, ContainerError> {
+        let path = self.path(name);
+        self.inner.read_entry_limited(&path, max)
+    }
+}
+
+/// Write-only zip container; call [`finish`](Self::finish) to get the archive back.
+#[cfg(feature = "zip")]
+pub struct ZipWriterContainer<W: std::io::Write + std::io::Seek> {
+    writer: zip::ZipWriter<W>,
+    options: zip::write::SimpleFileOptions,
+    names: Vec<String>,
+}
+
+#[cfg(feature = "zip")]
+impl<W: std::io::Write + std::io::Seek> ZipWriterContainer<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_options(inner, zip::write::SimpleFileOptions::default())
+    }
+    pub fn with_options(inner: W, options: zip::write::SimpleFileOptions) -> Self {
+        Self {
+            writer: zip::ZipWriter::new(inner),
+            options,
+            names: Vec::new(),
+        }
+    }
+    pub fn finish(self) -> Result<W, ContainerError> {
+        self.writer
+            .finish()
+            .map_err(|e| ContainerError::Archive(e.to_string()))
+    }
+}
+
+#[cfg(feature = "zip")]
+impl<W: std::io::Write + std::io::Seek> SnapshotContainer for ZipWriterContainer<W> {
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError> {
+        Ok(self.names.clone())
+    }
+    fn read_entry(&mut self, _name: &str) -> Result<Vec<u8>, ContainerError> {
+        Err(ContainerError::Unsupported("reading from a zip writer"))
+    }
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> Result<(), ContainerError> {
+        use std::io::Write;
+        if self.names.iter().any(|n| n == name) {
+            return Err(ContainerError::Unsupported("replacing a zip entry"));
+        }
+        self.writer
+            .start_file(name, self.options)
+            .map_err(|e| ContainerError::Archive(e.to_string()))?;
+        self.writer.write_all(data)?;
+        self.names.push(name.to_string());
+        Ok(())
+    }
+}
+
+/// Tar archive held in memory.
+///
+/// Tar has no index, so entries are read up front by [`from_reader`](Self::from_reader);
+/// [`write_to`](Self::write_to) serializes the current entries back to a tar stream.
+#[cfg(feature = "tar")]
+#[derive(Debug, Default, Clone)]
+pub struct TarContainer {
+    pub entries: BTreeMap<String, Vec<u8>>,
+}
+
+#[cfg(feature = "tar")]
+impl TarContainer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_reader(reader: impl Read) -> Result<Self, ContainerError> {
+        let mut archive = tar::Archive::new(reader);
+        let mut entries = BTreeMap::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            entries.insert(name, data);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn write_to<W: std::io::Write>(&self, writer: W) -> Result<W, ContainerError> {
+        let mut builder = tar::Builder::new(writer);
+        for (name, data) in &self.entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, data.as_slice())?;
+        }
+        Ok(builder.into_inner()?)
+    }
+}
+
+#[cfg(feature = "tar")]
+impl SnapshotContainer for TarContainer {
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError> {
+        self.entries.list_entries()
+    }
+    fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, ContainerError> {
+        self.entries.read_entry(name)
+    }
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> Result<(), ContainerError> {
+        self.entries.write_entry(name, data)
+    }
+}
+
+/// Entries stored under `prefix` in an [`object_store`] bucket (S3, GCS, Azure, ...).
+///
+/// Enable the cloud backends through `object_store`'s own features. The trait is
+/// synchronous, so every call blocks on a private current-thread tokio runtime; do not use
+/// the container from inside another tokio runtime, move it to a blocking task instead.
+/// Entry names must be plain relative paths.
+#[cfg(feature = "object_store")]
+pub struct ObjectStoreContainer {
+    store: std::sync::Arc<dyn object_store::ObjectStore>,
+    prefix: object_store::path::Path,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[cfg(feature = "object_store")]
+impl ObjectStoreContainer {
+    pub fn new(
+        store: std::sync::Arc<dyn object_store::ObjectStore>,
+        prefix: &str,
+    ) -> Result<Self, ContainerError> {
+        let prefix = object_store::path::Path::parse(prefix)
+            .map_err(|e| ContainerError::Archive(e.to_string()))?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            store,
+            prefix,
+            runtime,
+        })
+    }
+
+    pub fn prefix(&self) -> &object_store::path::Path {
+        &self.prefix
+    }
+
+    fn location(&self, name: &str) -> Result<object_store::path::Path, ContainerError> {
+        let relative = object_store::path::Path::parse(name)
+            .map_err(|_| ContainerError::Escape(name.to_string()))?;
+        Ok(self.prefix.parts().chain(relative.parts()).collect())
+    }
+
+    fn store_error(name: &str, e: object_store::Error) -> ContainerError {
+        match e {
+            object_store::Error::NotFound { .. } => ContainerError::NotFound(name.to_string()),
+            e => ContainerError::Archive(e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "object_store")]
+impl SnapshotContainer for ObjectStoreContainer {
+    fn list_entries(&mut self) -> Result<Vec<String>, ContainerError> {
+        use futures::TryStreamExt;
+        let metas: Vec<_> = self
+            .runtime
+            .block_on(self.store.list(Some(&self.prefix)).try_collect())
+            .map_err(|e| Self::store_error(self.prefix.as_ref(), e))?;
+        let mut names: Vec<String> = metas
+            .iter()
+            .filter_map(|meta| meta.location.prefix_match(&self.prefix))
+            .map(|parts| {
+                parts
+                    .map(|p| p.as_ref().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn read_entry(&mut self, name: &str) -> Result<Vec<u8>, ContainerError> {
+        self.read_entry_limited(name, u64::MAX)
+    }
+
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> Result<(), ContainerError> {
+        let location = self.location(name)?;
+        self.runtime
+            .block_on(self.store.put(&location, data.to_vec().into()))
+            .map_err(|e| Self::store_error(name, e))?;
+        Ok(())
+    }
+
+    fn read_entry_limited(&mut self, name: &str, max: u64) -> Result<Vec<u8>, ContainerError> {
+        let location = self.location(name)?;
+        self.runtime.block_on(async {
+            let object = self
+                .store
+                .get(&location)
+                .await
+                .map_err(|e| Self::store_error(name, e))?;
+            if object.meta.size > max {
+                return Err(ContainerError::TooLarge {
+                    name: name.to_string(),
+                    max,
+                });
+            }
+            let bytes = object
+                .bytes()
+                .await
+                .map_err(|e| Self::store_error(name, e))?;
+            Ok(bytes.to_vec())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(c: &mut impl SnapshotContainer) {
+        c.write_entry("a/b.csv", b"id\n").unwrap();
+        c.write_entry("c.msgpack", &[1, 2, 3]).unwrap();
+        assert_eq!(c.list_entries().unwrap(), vec!["a/b.csv", "c.msgpack"]);
+        assert_eq!(c.read_entry("c.msgpack").unwrap(), vec![1, 2, 3]);
+        assert!(matches!(
+            c.read_entry("missing"),
+            Err(ContainerError::NotFound(_))
+        ));
+        assert!(matches!(
+            c.read_entry_limited("c.msgpack", 2),
+            Err(ContainerError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_map_containers() {
+        roundtrip(&mut BTreeMap::new());
+        roundtrip(&mut HashMap::new());
+    }
+
+    #[test]
+    fn test_sub_container() {
+        let mut shared = BTreeMap::from([("app/world.toml".to_string(), Vec::new())]);
+        roundtrip(&mut SubContainer::new(&mut shared, "render/"));
+        assert!(shared.contains_key("render/a/b.csv"));
+        assert!(shared.contains_key("app/world.toml"));
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn test_tar_container() {
+        let mut tar = TarContainer::new();
+        roundtrip(&mut tar);
+        let bytes = tar.write_to(Vec::new()).unwrap();
+        let mut back = TarContainer::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(back.read_entry("a/b.csv").unwrap(), b"id\n");
+    }
+
+    #[cfg(feature = "object_store")]
+    #[test]
+    fn test_object_store_container() {
+        let store = std::sync::Arc::new(object_store::memory::InMemory::new());
+        let mut c = ObjectStoreContainer::new(store.clone(), "saves/slot1").unwrap();
+        roundtrip(&mut c);
+        assert!(matches!(
+            c.write_entry("../x", b""),
+            Err(ContainerError::Escape(_))
+        ));
+
+        // Entries of other prefixes are not visible.
+        let mut other = ObjectStoreContainer::new(store, "saves/slot2").unwrap();
+        assert!(other.list_entries().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+//! Opt-in emergency snapshot written when the app panics.
+//!
+//! Wrap the world in a [`CrashSnapshotGuard`] for the duration of a frame (or the whole
+//! run loop). If a panic unwinds through the guard, it serializes the world to
+//! `crash_snapshot_<unix_ms>.bin` with [`MsgPackArchive`] before the world is torn down.
+//! The guard owns a clone of the [`SnapshotRegistry`], so it still works if the panic hit
+//! while the registry resource was taken out of the world (e.g. inside `resource_scope`).
+//!
+//! [`install_panic_hook`] additionally records the panic message and location, which the
+//! guard stores in the snapshot's `meta` under `"panic"`.
+//!
+//! Nothing is written when the crate is built with `panic = "abort"`, since no unwinding
+//! takes place. A second panic while writing the snapshot aborts the process.
+
+use std::cell::RefCell;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy_ecs::prelude::*;
+
+use crate::bevy_registry::SnapshotRegistry;
+use crate::binary_archive::MsgPackArchive;
+
+thread_local! {
+    // The hook and the unwinding guard run on the panicking thread, so panics on other
+    // threads never reach a guard's snapshot.
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Chain a panic hook that remembers the last panic message of each thread for
+/// [`CrashSnapshotGuard`].
+///
+/// The previously installed hook still runs afterwards.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        LAST_PANIC.with(|last| *last.borrow_mut() = Some(info.to_string()));
+        previous(info);
+    }));
+}
+
+/// Serialize `world` to `dir/crash_snapshot_<unix_ms>.bin` and return the path.
+///
+/// `panic` is stored in the snapshot meta under `"panic"` when given.
+pub fn write_crash_snapshot(
+    world: &World,
+    registry: &SnapshotRegistry,
+    dir: impl AsRef<Path>,
+    panic: Option<&str>,
+) -> io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let mut archive = MsgPackArchive::from_world(world, registry)?;
+    archive
+        .0
+        .meta
+        .insert("timestamp_ms".to_string(), timestamp.to_string());
+    if let Some(panic) = panic {
+        archive
+            .0
+            .meta
+            .insert("panic".to_string(), panic.to_string());
+    }
+    let path = dir.as_ref().join(format!("crash_snapshot_{timestamp}.bin"));
+    archive.to_file(&path)?;
+    Ok(path)
+}
+
+/// Mutable access to a world that is dumped to disk if a panic unwinds past it.
+///
+/// Derefs to [`World`], so schedules can run on it directly.
+pub struct CrashSnapshotGuard<'w> {
+    world: &'w mut World,
+    registry: SnapshotRegistry,
+    dir: PathBuf,
+}
+
+impl<'w> CrashSnapshotGuard<'w> {
+    /// Guard `world`, writing crash snapshots into `dir`.
+    pub fn new(world: &'w mut World, registry: SnapshotRegistry, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            world,
+            registry,
+            dir: dir.into(),
+        }
+    }
+
+    /// Guard `world` using a clone of its [`SnapshotRegistry`] resource.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world has no `SnapshotRegistry` resource.
+    pub fn from_world(world: &'w mut World, dir: impl Into<PathBuf>) -> Self {
+        let registry = world.resource::<SnapshotRegistry>().clone();
+        Self::new(world, registry, dir)
+    }
+}
+
+impl Deref for CrashSnapshotGuard<'_> {
+    type Target = World;
+    fn deref(&self) -> &World {
+        self.world
+    }
+}
+
+impl DerefMut for CrashSnapshotGuard<'_> {
+    fn deref_mut(&mut self) -> &mut World {
+        self.world
+    }
+}
+
+impl Drop for CrashSnapshotGuard<'_> {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            return;
+        }
+        let panic = LAST_PANIC.with(|last| last.borrow_mut().take());
+        match write_crash_snapshot(self.world, &self.registry, &self.dir, panic.as_deref()) {
+            Ok(path) => archive_error!("crash snapshot written to {}", path.display()),
+            Err(e) => archive_error!("failed to write crash snapshot: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, PartialEq)]
+    struct Hp(u32);
+
+    #[test]
+    fn test_crash_snapshot_guard() {
+        let dir = std::env::temp_dir().join(format!("bevy_archive_crash_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        install_panic_hook();
+        // A panic on another thread is not attributed to this one.
+        let other = std::thread::spawn(|| panic!("elsewhere"));
+        assert!(other.join().is_err());
+        assert!(LAST_PANIC.with(|last| last.borrow().is_none()));
+
+        let mut world = World::new();
+        world.insert_resource(registry.clone());
+        {
+            let mut guard = CrashSnapshotGuard::from_world(&mut world, &dir);
+            guard.spawn(Hp(3));
+        }
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut guard = CrashSnapshotGuard::new(&mut world, registry.clone(), &dir);
+            guard.spawn(Hp(7));
+            panic!("simulation diverged");
+        }));
+        assert!(result.is_err());
+
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("crash_snapshot_") && name.ends_with(".bin"));
+
+        let archive = MsgPackArchive::from_file(&files[0]).unwrap();
+        assert!(archive.0.meta["panic"].contains("simulation diverged"));
+        let mut restored = World::new();
+        archive.to_world(&mut restored, &registry).unwrap();
+        let mut hp: Vec<_> = restored
+            .query::<&Hp>()
+            .iter(&restored)
+            .map(|h| h.0)
+            .collect();
+        hp.sort();
+        assert_eq!(hp, vec![3, 7]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
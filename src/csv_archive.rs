@@ -1,3 +1,27 @@
+//! Columnar CSV encoding of an [`ArchetypeSnapshot`].
+//!
+//! Every component becomes one column, or one column per field (`Component.field`) when
+//! all of its values are JSON objects. Cells hold the JSON text of the value.
+//!
+//! # Null and `Option<T>`
+//!
+//! `Option<T>` serializes as its inner value or as JSON `null`, and every format keeps that
+//! distinction:
+//!
+//! | Format | `None` | `Some(v)` |
+//! |---|---|---|
+//! | CSV | the [null token](CsvOptions::null_token) (an empty cell by default) | JSON text of `v` |
+//! | JSON / msgpack | `null` / `nil` | `v` |
+//! | Arrow / Parquet | a null slot in a nullable column | `v` |
+//!
+//! Strings are written quoted, so `Some(String::new())` is the cell `""` and never reads back
+//! as `None`. A component whose values mix objects with `null` (such as a newtype around
+//! `Option<Struct>`) is kept in a single column so that `None` and `Some` with all-`None`
+//! fields stay distinct. A field missing from an object, as written by
+//! `#[serde(skip_serializing_if = "Option::is_none")]`, becomes a null cell.
+//!
+//! On read, empty cells and cells equal to the null token are `null`.
+
 use csv::Reader;
 use csv::Writer;
 use serde::Deserialize;
@@ -9,6 +33,37 @@ use std::io::{Read, Result as IoResult, Write};
 
 use super::archetype_archive::ArchetypeSnapshot;
 use super::archetype_archive::StorageTypeFlag;
+use crate::error::ArchiveError;
+
+/// Key of the null token in Aurora manifest `metadata`.
+pub const CSV_NULL_TOKEN_KEY: &str = "csv_null_token";
+
+/// Text encoding options for [`ColumnarCsv`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Cell text written for `null` values. Empty cells always read as `null` as well.
+    pub null_token: String,
+}
+
+impl CsvOptions {
+    pub fn with_null_token(token: impl Into<String>) -> Self {
+        Self {
+            null_token: token.into(),
+        }
+    }
+
+    fn is_null(&self, cell: &str) -> bool {
+        cell.trim().is_empty() || (!self.null_token.is_empty() && cell == self.null_token)
+    }
+
+    fn parse_cell(&self, cell: &str) -> Value {
+        if self.is_null(cell) {
+            Value::Null
+        } else {
+            serde_json::from_str(cell).unwrap_or(Value::String(cell.to_string()))
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct ComponentColumnGroup {
@@ -35,9 +90,12 @@ impl ColumnarCsv {
         }
     }
 
-    fn add_column(&mut self, name: String) -> Result<(), String> {
+    fn add_column(&mut self, name: String) -> Result<(), ArchiveError> {
         if self.header_index_map.contains_key(&name) {
-            return Err(format!("Column '{}' already exists", name));
+            return Err(ArchiveError::Schema(format!(
+                "Column '{}' already exists",
+                name
+            )));
         }
         let idx = self.headers.len();
         self.headers.push(name.clone());
@@ -46,7 +104,10 @@ impl ColumnarCsv {
         Ok(())
     }
 
-    fn add_columns<I: IntoIterator<Item = String>>(&mut self, names: I) -> Result<(), String> {
+    fn add_columns<I: IntoIterator<Item = String>>(
+        &mut self,
+        names: I,
+    ) -> Result<(), ArchiveError> {
         for n in names {
             self.add_column(n)?;
         }
@@ -80,10 +141,14 @@ impl ColumnarCsv {
         writer.flush()
     }
 
-    pub fn from_csv<R: Read>(r: R) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn from_csv<R: Read>(r: R) -> Result<Self, ArchiveError> {
         let mut reader = csv::Reader::from_reader(r);
         let mut headers: Vec<String> = reader.headers()?.iter().map(|s| s.to_string()).collect();
-        assert_eq!(headers.first().map(String::as_str), Some("id"));
+        if headers.first().map(String::as_str) != Some("id") {
+            return Err(ArchiveError::schema(
+                "CSV header must start with an 'id' column",
+            ));
+        }
         headers.remove(0);
 
         let mut row_index = Vec::new();
@@ -91,7 +156,7 @@ impl ColumnarCsv {
 
         for rec in reader.records() {
             let rec = rec?;
-            row_index.push(rec[0].parse::<u32>()?);
+            row_index.push(rec[0].parse::<u32>().map_err(ArchiveError::parse)?);
             for (j, f) in rec.iter().skip(1).enumerate() {
                 let v = if f.trim().is_empty() {
                     Value::Null
@@ -130,11 +195,16 @@ fn build_schema(snapshot: &ArchetypeSnapshot, strict: bool) -> Vec<ComponentColu
         .map(|(col, comp)| {
             let fields: Vec<String> = if strict {
                 // 扫描所有行，收集完整字段集合
-                let mut set = HashSet::new();
-                for v in col {
-                    set.extend(infer_schema(comp, v).fields);
+                if col.iter().all(Value::is_object) {
+                    let mut set = HashSet::new();
+                    for v in col {
+                        set.extend(infer_schema(comp, v).fields);
+                    }
+                    set.into_iter().collect()
+                } else {
+                    // Mixed objects and scalars/nulls stay whole so `null` survives.
+                    vec![comp.clone()]
                 }
-                set.into_iter().collect()
             } else {
                 // 只看第一行，假定 schema 固定
                 infer_schema(comp, col.first().unwrap()).fields
@@ -166,15 +236,14 @@ fn columnar_core(snapshot: &ArchetypeSnapshot, strict: bool) -> ColumnarCsv {
     // 填充数据
     for (values, schema) in snapshot.columns.iter().zip(schemas) {
         for field in schema.fields {
-            let suffix = field
-                .strip_prefix(&format!("{}.", schema.component))
-                .unwrap_or("");
+            let suffix = field.strip_prefix(&format!("{}.", schema.component));
             let col = csv.get_column_mut(&field).unwrap();
             for (i, item) in values.iter().enumerate() {
-                col[i] = if let Value::Object(map) = item {
-                    map.get(suffix).cloned().unwrap_or(Value::Null)
-                } else {
-                    item.clone()
+                col[i] = match (item, suffix) {
+                    (Value::Object(map), Some(suffix)) => {
+                        map.get(suffix).cloned().unwrap_or(Value::Null)
+                    }
+                    _ => item.clone(),
                 };
             }
         }
@@ -207,6 +276,11 @@ pub fn infer_schema(component: &str, value: &Value) -> ComponentColumnGroup {
 
 impl ColumnarCsv {
     pub fn to_csv_writer<W: Write>(&self, w: W) -> IoResult<()> {
+        self.to_csv_writer_with(w, &CsvOptions::default())
+    }
+
+    /// [`to_csv_writer`](Self::to_csv_writer) writing `null` as `options.null_token`.
+    pub fn to_csv_writer_with<W: Write>(&self, w: W, options: &CsvOptions) -> IoResult<()> {
         let mut writer = Writer::from_writer(w);
 
         // 写入 header 行
@@ -220,7 +294,7 @@ impl ColumnarCsv {
             for col in &self.columns {
                 let value = &col[row];
                 record.push(match value {
-                    Value::Null => "".into(),
+                    Value::Null => options.null_token.clone(),
                     _ => value.to_string(),
                 });
             }
@@ -232,14 +306,23 @@ impl ColumnarCsv {
 }
 
 impl ColumnarCsv {
-    pub fn from_csv_reader<R: Read>(r: R) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn from_csv_reader<R: Read>(r: R) -> Result<Self, ArchiveError> {
+        Self::from_csv_reader_with(r, &CsvOptions::default())
+    }
+
+    /// [`from_csv_reader`](Self::from_csv_reader) reading `options.null_token` as `null`.
+    pub fn from_csv_reader_with<R: Read>(r: R, options: &CsvOptions) -> Result<Self, ArchiveError> {
         let mut reader = Reader::from_reader(r);
         let mut headers = reader
             .headers()?
             .iter()
             .map(|s| s.to_string())
             .collect::<Vec<_>>();
-        assert!(headers.first() == Some(&"id".to_string()));
+        if headers.first().map(String::as_str) != Some("id") {
+            return Err(ArchiveError::schema(
+                "CSV header must start with an 'id' column",
+            ));
+        }
 
         headers.remove(0); // remove id from header list
         let mut row_index = Vec::new();
@@ -247,15 +330,11 @@ impl ColumnarCsv {
 
         for result in reader.records() {
             let record = result?;
-            row_index.push(record.get(0).unwrap().parse::<u32>()?); // 👈 ID 列
+            let id = record.get(0).unwrap().parse::<u32>();
+            row_index.push(id.map_err(ArchiveError::parse)?); // 👈 ID 列
 
             for (j, field) in record.iter().skip(1).enumerate() {
-                let value = if field.trim().is_empty() {
-                    Value::Null
-                } else {
-                    serde_json::from_str(field).unwrap_or(Value::String(field.to_string()))
-                };
-                columns[j].push(value);
+                columns[j].push(options.parse_cell(field));
             }
         }
 
@@ -450,7 +529,7 @@ mod tests {
             snapshot.archetypes[0].entities.len()
         );
         snapshot.archetypes[0] = new_snap;
-        load_world_arch_snapshot(&mut world, &snapshot, &registry);
+        load_world_arch_snapshot(&mut world, &snapshot, &registry).unwrap();
     }
     #[test]
     fn test_csv_archetype_snapshot_roundtrip() {
@@ -469,4 +548,111 @@ mod tests {
         new_csv.to_csv_writer(&mut nv).unwrap();
         assert_eq!(nv, v);
     }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Component)]
+    struct Optional {
+        count: Option<i32>,
+        label: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        skipped: Option<i32>,
+        order: u32,
+    }
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Component)]
+    struct MaybeInner(Option<TestComponentA>);
+
+    #[test]
+    fn test_option_roundtrip_in_every_format() {
+        use crate::aurora_archive::{
+            ExportFormat, ExportGuidance, load_world_manifest, save_world_manifest_with_guidance,
+        };
+
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Optional>();
+        registry.register::<MaybeInner>();
+        let rows = vec![
+            (
+                Optional {
+                    count: None,
+                    label: None,
+                    skipped: None,
+                    order: 0,
+                },
+                MaybeInner(None),
+            ),
+            (
+                Optional {
+                    count: Some(0),
+                    label: Some(String::new()),
+                    skipped: Some(1),
+                    order: 1,
+                },
+                MaybeInner(Some(TestComponentA { value: 3 })),
+            ),
+            (
+                Optional {
+                    count: Some(-2),
+                    label: Some("NULL".into()),
+                    skipped: None,
+                    order: 2,
+                },
+                MaybeInner(None),
+            ),
+        ];
+        let mut world = World::new();
+        for row in &rows {
+            world.spawn(row.clone());
+        }
+
+        let mut guides = vec![
+            ExportGuidance::embed_all(ExportFormat::Csv),
+            ExportGuidance::embed_all(ExportFormat::Json),
+            ExportGuidance::embed_all(ExportFormat::MsgPack),
+            ExportGuidance::embed_all(ExportFormat::CsvMsgPack),
+        ];
+        let mut null_token = ExportGuidance::embed_all(ExportFormat::Csv);
+        null_token.csv = CsvOptions::with_null_token("NULL");
+        guides.push(null_token);
+        #[cfg(feature = "arrow_rs")]
+        guides.extend([
+            ExportGuidance::embed_all(ExportFormat::Parquet),
+            ExportGuidance::embed_all(ExportFormat::ArrowIpc),
+        ]);
+
+        for guide in guides {
+            let manifest = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+            let mut loaded = World::new();
+            load_world_manifest(&mut loaded, &manifest, &registry).unwrap();
+            let mut back: Vec<_> = loaded
+                .query::<(&Optional, &MaybeInner)>()
+                .iter(&loaded)
+                .map(|(o, m)| (o.clone(), m.clone()))
+                .collect();
+            back.sort_by_key(|(o, _)| o.order);
+            assert_eq!(back, rows);
+        }
+
+        // The token is written for nulls and only read back as null when it is configured.
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        let csv = columnar_from_snapshot(&snapshot.archetypes[0]);
+        let options = CsvOptions::with_null_token("NULL");
+        let mut bytes = Vec::new();
+        csv.to_csv_writer_with(&mut bytes, &options).unwrap();
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(text.contains(",NULL"));
+        assert!(text.contains("\"\"\"NULL\"\"\""));
+        let with = ColumnarCsv::from_csv_reader_with(bytes.as_slice(), &options).unwrap();
+        assert!(with.columns.iter().flatten().any(Value::is_null));
+        let without = ColumnarCsv::from_csv_reader(bytes.as_slice()).unwrap();
+        assert!(without.columns.iter().flatten().any(|v| v == "NULL"));
+    }
+
+    #[test]
+    fn test_csv_error_kinds() {
+        let err = ColumnarCsv::from_csv_reader("name,value\na,1\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, ArchiveError::Schema(_)));
+        let err = ColumnarCsv::from_csv_reader("id,value\nx,1\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, ArchiveError::Parse(_)));
+        let err = ColumnarCsv::from_csv("id,value\nx,1\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, ArchiveError::Parse(_)));
+    }
 }
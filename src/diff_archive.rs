@@ -0,0 +1,223 @@
+//! Structural diff between two [`WorldArchSnapshot`]s.
+//!
+//! [`diff_world_snapshots`] compares two snapshots entity by entity (matched by their stored id)
+//! and records which entities were added or removed and which component values changed.
+//! The resulting [`SnapshotDiff`] is plain serde data, so it can be written next to a full
+//! save as a small incremental file. [`apply_patch`] replays it onto a world restored from
+//! the older snapshot through the registry's JSON codecs, the same way
+//! [`replay_journal`] replays a journal frame.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::archetype_archive::{WorldArchSnapshot, save_world_arch_snapshot};
+use crate::bevy_registry::SnapshotRegistry;
+use crate::entity_archive::{ComponentSnapshot, EntitySnapshot};
+use crate::journal::{JournalError, JournalFrame, JournalOp, replay_journal};
+
+/// Component changes of an entity present in both snapshots.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityChange {
+    pub entity: u32,
+    /// Components that were added or whose value differs, with their new value.
+    pub set: Vec<ComponentSnapshot>,
+    /// Components the entity no longer has.
+    pub removed: Vec<String>,
+}
+
+/// Difference from an old snapshot to a new one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// Entities only in the new snapshot, with all their components.
+    pub added: Vec<EntitySnapshot>,
+    /// Ids of entities only in the old snapshot.
+    pub removed: Vec<u32>,
+    pub changed: Vec<EntityChange>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The diff as a single journal frame: despawns first, then changes, then new entities.
+    pub fn to_journal_frame(&self) -> JournalFrame {
+        let mut ops = Vec::new();
+        ops.extend(
+            self.removed
+                .iter()
+                .map(|&entity| JournalOp::Despawn { entity }),
+        );
+        for change in &self.changed {
+            if !change.removed.is_empty() {
+                ops.push(JournalOp::Remove {
+                    entity: change.entity,
+                    types: change.removed.clone(),
+                });
+            }
+            if !change.set.is_empty() {
+                ops.push(JournalOp::Insert {
+                    entity: change.entity,
+                    components: change.set.clone(),
+                });
+            }
+        }
+        ops.extend(self.added.iter().map(|e| JournalOp::Insert {
+            entity: e.id as u32,
+            components: e.components.clone(),
+        }));
+        JournalFrame {
+            frame: 0,
+            timestamp_ms: 0,
+            ops,
+        }
+    }
+}
+
+/// Components of every entity of `snapshot`, keyed by id and then by type name.
+fn entity_components(snapshot: &WorldArchSnapshot) -> BTreeMap<u32, BTreeMap<&str, &Value>> {
+    let mut out: BTreeMap<u32, BTreeMap<&str, &Value>> = snapshot
+        .entities
+        .iter()
+        .map(|&id| (id, BTreeMap::new()))
+        .collect();
+    for arch in &snapshot.archetypes {
+        for (row, &id) in arch.entities.iter().enumerate() {
+            let comps = out.entry(id).or_default();
+            for (name, col) in arch.component_types.iter().zip(&arch.columns) {
+                comps.insert(name, &col[row]);
+            }
+        }
+    }
+    out
+}
+
+fn component_snapshots(comps: &BTreeMap<&str, &Value>) -> Vec<ComponentSnapshot> {
+    comps
+        .iter()
+        .map(|(name, value)| ComponentSnapshot {
+            r#type: name.to_string(),
+            value: (*value).clone(),
+        })
+        .collect()
+}
+
+/// Compute the changes that turn `old` into `new`.
+pub fn diff_world_snapshots(old: &WorldArchSnapshot, new: &WorldArchSnapshot) -> SnapshotDiff {
+    let old = entity_components(old);
+    let new = entity_components(new);
+    let mut diff = SnapshotDiff {
+        removed: old
+            .keys()
+            .filter(|id| !new.contains_key(id))
+            .copied()
+            .collect(),
+        ..Default::default()
+    };
+    for (&id, comps) in &new {
+        let Some(before) = old.get(&id) else {
+            diff.added.push(EntitySnapshot {
+                id: id as u64,
+                components: component_snapshots(comps),
+            });
+            continue;
+        };
+        let set: BTreeMap<&str, &Value> = comps
+            .iter()
+            .filter(|(name, value)| before.get(*name) != Some(*value))
+            .map(|(name, value)| (*name, *value))
+            .collect();
+        let removed: Vec<String> = before
+            .keys()
+            .filter(|name| !comps.contains_key(*name))
+            .map(|name| name.to_string())
+            .collect();
+        if !set.is_empty() || !removed.is_empty() {
+            diff.changed.push(EntityChange {
+                entity: id,
+                set: component_snapshots(&set),
+                removed,
+            });
+        }
+    }
+    diff
+}
+
+/// Diff `base` against the current state of `world`.
+pub fn diff_world(
+    world: &World,
+    registry: &SnapshotRegistry,
+    base: &WorldArchSnapshot,
+) -> SnapshotDiff {
+    diff_world_snapshots(base, &save_world_arch_snapshot(world, registry))
+}
+
+/// Replay `diff` onto a world restored from the snapshot it was computed against.
+///
+/// Entity ids are resolved by index, like every other loader. Removed entities that are
+/// already gone are skipped; types that are not registered fail the patch.
+pub fn apply_patch(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    diff: &SnapshotDiff,
+) -> Result<(), JournalError> {
+    replay_journal(world, registry, &[diff.to_journal_frame()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetype_archive::load_world_arch_snapshot;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Pos(i32, i32);
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hp(u32);
+
+    #[test]
+    fn test_diff_and_patch() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Pos>();
+        registry.register::<Hp>();
+
+        let mut world = World::new();
+        let still = world.spawn((Pos(0, 0), Hp(10))).id();
+        let moved = world.spawn((Pos(1, 1), Hp(10))).id();
+        let gone = world.spawn(Pos(2, 2)).id();
+        let base = save_world_arch_snapshot(&world, &registry);
+        assert!(diff_world(&world, &registry, &base).is_empty());
+
+        // Spawn before despawning so the new entity does not reuse the freed index.
+        let born = world.spawn((Pos(9, 9), Hp(1))).id();
+        world.entity_mut(moved).insert(Pos(5, 5)).remove::<Hp>();
+        world.entity_mut(still).insert(Hp(10));
+        world.despawn(gone);
+
+        let diff = diff_world(&world, &registry, &base);
+        assert_eq!(diff.removed, vec![gone.index_u32()]);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.changed.len(), 1);
+        let change = &diff.changed[0];
+        assert_eq!(change.entity, moved.index_u32());
+        assert_eq!(change.removed, vec!["Hp".to_string()]);
+        assert_eq!(change.set.len(), 1);
+
+        // The patch survives a serialization round trip.
+        let bytes = rmp_serde::to_vec_named(&diff).unwrap();
+        let diff: SnapshotDiff = rmp_serde::from_slice(&bytes).unwrap();
+
+        let mut restored = World::new();
+        load_world_arch_snapshot(&mut restored, &base, &registry).unwrap();
+        apply_patch(&mut restored, &registry, &diff).unwrap();
+
+        let after = save_world_arch_snapshot(&world, &registry);
+        let patched = save_world_arch_snapshot(&restored, &registry);
+        assert!(diff_world_snapshots(&after, &patched).is_empty());
+        let at = |w: &World, e: Entity| w.entities().resolve_from_index(e.index());
+        assert_eq!(restored.get::<Pos>(at(&restored, born)), Some(&Pos(9, 9)));
+        assert!(restored.get_entity(at(&restored, gone)).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Cheap "has anything changed since the last save?" check for autosave systems.
+//!
+//! Add [`track_snapshot_dirty`] to a schedule that runs every frame and call
+//! [`SnapshotDirtyExt::mark_snapshot_clean`] right after each save. An autosave system can
+//! then skip the save while [`SnapshotDirtyExt::is_snapshot_dirty`] is `false`.
+//!
+//! Only components registered in the [`SnapshotRegistry`] resource count: a component is
+//! dirty once it was added, mutated or removed on any entity after the last clean mark.
+//! Registered resources are not tracked.
+
+use std::collections::BTreeSet;
+
+use bevy_ecs::change_detection::Tick;
+use bevy_ecs::component::ComponentId;
+use bevy_ecs::prelude::*;
+
+use crate::bevy_registry::SnapshotRegistry;
+
+/// Which registered components changed since the last snapshot.
+///
+/// A world that was never marked clean is dirty.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct SnapshotDirty {
+    last_snapshot: Option<Tick>,
+    changed: BTreeSet<String>,
+}
+
+impl SnapshotDirty {
+    pub fn is_dirty(&self) -> bool {
+        self.last_snapshot.is_none() || !self.changed.is_empty()
+    }
+
+    /// Names of the registered components seen changing since the last snapshot.
+    pub fn changed_components(&self) -> impl Iterator<Item = &str> {
+        self.changed.iter().map(String::as_str)
+    }
+
+    /// Tick of the last snapshot, if any.
+    pub fn last_snapshot_tick(&self) -> Option<Tick> {
+        self.last_snapshot
+    }
+
+    /// Force the next check to report the world as dirty.
+    pub fn mark_dirty(&mut self) {
+        self.last_snapshot = None;
+    }
+}
+
+/// Record which registered components changed since the last snapshot tick.
+///
+/// Does nothing until both [`SnapshotDirty`] and [`SnapshotRegistry`] are resources of the
+/// world. Components that are already known to be dirty are not scanned again.
+pub fn track_snapshot_dirty(world: &mut World) {
+    let Some(since) = world
+        .get_resource::<SnapshotDirty>()
+        .and_then(|d| d.last_snapshot)
+    else {
+        return;
+    };
+    let Some(registry) = world.get_resource::<SnapshotRegistry>() else {
+        return;
+    };
+    let this_run = world.read_change_tick();
+    let mut changed = Vec::new();
+    for &name in registry.type_registry.keys() {
+        if world.resource::<SnapshotDirty>().changed.contains(name) {
+            continue;
+        }
+        let Some(cid) = registry.comp_id_by_name(name, world) else {
+            continue;
+        };
+        if component_changed_since(world, cid, since, this_run) {
+            changed.push(name.to_string());
+        }
+    }
+    world
+        .resource_mut::<SnapshotDirty>()
+        .changed
+        .extend(changed);
+}
+
+fn component_changed_since(world: &World, cid: ComponentId, since: Tick, this_run: Tick) -> bool {
+    let mutated = world
+        .archetypes()
+        .iter()
+        .filter(|arch| arch.contains(cid))
+        .flat_map(|arch| arch.entities())
+        .filter_map(|e| world.get_entity(e.id()).ok())
+        .filter_map(|e| e.get_change_ticks_by_id(cid))
+        .any(|ticks| ticks.is_changed(since, this_run));
+    mutated || world.removed_with_id(cid).next().is_some()
+}
+
+/// Whether any component registered in `registry` was added, mutated or removed after
+/// `since`.
+pub fn registered_changed_since(world: &World, registry: &SnapshotRegistry, since: Tick) -> bool {
+    let this_run = world.read_change_tick();
+    registry
+        .type_registry
+        .keys()
+        .filter_map(|name| registry.comp_id_by_name(name, world))
+        .any(|cid| component_changed_since(world, cid, since, this_run))
+}
+
+/// Snapshot dirty-state helpers on [`World`].
+pub trait SnapshotDirtyExt {
+    /// Whether a registered component changed since the last
+    /// [`mark_snapshot_clean`](Self::mark_snapshot_clean).
+    ///
+    /// Runs [`track_snapshot_dirty`] first, so the answer is current even between
+    /// schedule runs. Worlds without a [`SnapshotDirty`] resource are always dirty.
+    fn is_snapshot_dirty(&mut self) -> bool;
+
+    /// Record that a snapshot was just taken, inserting [`SnapshotDirty`] if needed.
+    fn mark_snapshot_clean(&mut self);
+}
+
+impl SnapshotDirtyExt for World {
+    fn is_snapshot_dirty(&mut self) -> bool {
+        track_snapshot_dirty(self);
+        self.get_resource::<SnapshotDirty>()
+            .is_none_or(SnapshotDirty::is_dirty)
+    }
+
+    fn mark_snapshot_clean(&mut self) {
+        // Changes made from here on carry a newer tick than the one recorded.
+        let tick = self.increment_change_tick();
+        let mut dirty = self.get_resource_or_insert_with(SnapshotDirty::default);
+        dirty.last_snapshot = Some(tick);
+        dirty.changed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hp(u32);
+    #[derive(Component, Debug)]
+    struct Unregistered;
+
+    #[test]
+    fn test_snapshot_dirty_tracking() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        let mut world = World::new();
+        world.insert_resource(registry);
+        let e = world.spawn(Hp(1)).id();
+
+        assert!(world.is_snapshot_dirty());
+        world.mark_snapshot_clean();
+        assert!(!world.is_snapshot_dirty());
+
+        world.spawn(Unregistered);
+        assert!(!world.is_snapshot_dirty());
+
+        world.get_mut::<Hp>(e).unwrap().0 = 2;
+        assert!(world.is_snapshot_dirty());
+        assert!(
+            world
+                .resource::<SnapshotDirty>()
+                .changed_components()
+                .eq(["Hp"])
+        );
+
+        world.mark_snapshot_clean();
+        world.clear_trackers();
+        assert!(!world.is_snapshot_dirty());
+        world.despawn(e);
+        assert!(world.is_snapshot_dirty());
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(track_snapshot_dirty);
+        world.mark_snapshot_clean();
+        world.clear_trackers();
+        world.spawn(Hp(3));
+        schedule.run(&mut world);
+        assert!(world.resource::<SnapshotDirty>().is_dirty());
+    }
+}
@@ -0,0 +1,196 @@
+//! Saving one world in two formats at once, for release transitions where the old format
+//! must keep being written while the new one rolls out, e.g. a TOML + CSV
+//! [`AuroraWorldManifest`](crate::aurora_archive::AuroraWorldManifest) next to a zip +
+//! parquet [`WorldArrowSnapshot`](crate::binary_archive::WorldArrowSnapshot).
+//!
+//! [`DualWrite::save`] encodes the world with both archive types, loads each encoding back
+//! into an empty world and checks with [`diff_world_snapshots`] that both restore the
+//! entities and components of the original. Both files are staged next to their targets
+//! and only renamed into place once both are written and verified, so a failed save leaves
+//! the previous pair untouched.
+
+use std::error::Error;
+use std::ffi::OsString;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use bevy_ecs::prelude::*;
+
+use crate::archetype_archive::{WorldArchSnapshot, save_world_arch_snapshot};
+use crate::bevy_registry::SnapshotRegistry;
+use crate::diff_archive::{SnapshotDiff, diff_world_snapshots};
+use crate::traits::Archive;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DualWriteError {
+    #[error("{side} archive: {source}")]
+    Archive {
+        side: &'static str,
+        #[source]
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// The encoding loads back to different entities or components than were saved.
+    #[error("{side} archive does not load back to the saved world")]
+    Mismatch {
+        side: &'static str,
+        diff: SnapshotDiff,
+    },
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Saves to a `primary` file as `A` and a `secondary` file as `B`.
+pub struct DualWrite<A, B> {
+    pub primary: PathBuf,
+    pub secondary: PathBuf,
+    /// Load both encodings back and compare them with the world before writing. On by
+    /// default.
+    pub verify: bool,
+    marker: PhantomData<fn() -> (A, B)>,
+}
+
+impl<A: Archive, B: Archive> DualWrite<A, B> {
+    pub fn new(primary: impl Into<PathBuf>, secondary: impl Into<PathBuf>) -> Self {
+        Self {
+            primary: primary.into(),
+            secondary: secondary.into(),
+            verify: true,
+            marker: PhantomData,
+        }
+    }
+
+    /// Save `world` to both files.
+    pub fn save(&self, world: &World, registry: &SnapshotRegistry) -> Result<(), DualWriteError> {
+        let primary = encode::<A>(world, registry, "primary")?;
+        let secondary = encode::<B>(world, registry, "secondary")?;
+        if self.verify {
+            let expected = save_world_arch_snapshot(world, registry);
+            verify_archive::<A>(&primary, &expected, registry, "primary")?;
+            verify_archive::<B>(&secondary, &expected, registry, "secondary")?;
+        }
+
+        let staged = [
+            (staging_path(&self.primary), &self.primary, primary),
+            (staging_path(&self.secondary), &self.secondary, secondary),
+        ];
+        let written = staged
+            .iter()
+            .try_for_each(|(tmp, _, bytes)| std::fs::write(tmp, bytes));
+        if let Err(e) = written {
+            for (tmp, _, _) in &staged {
+                let _ = std::fs::remove_file(tmp);
+            }
+            return Err(e.into());
+        }
+        for (tmp, path, _) in &staged {
+            std::fs::rename(tmp, path)?;
+        }
+        Ok(())
+    }
+
+    /// Load the primary file.
+    pub fn load_primary(&self) -> Result<A, DualWriteError> {
+        A::load_from(&self.primary).map_err(|source| DualWriteError::Archive {
+            side: "primary",
+            source,
+        })
+    }
+
+    /// Load the secondary file.
+    pub fn load_secondary(&self) -> Result<B, DualWriteError> {
+        B::load_from(&self.secondary).map_err(|source| DualWriteError::Archive {
+            side: "secondary",
+            source,
+        })
+    }
+}
+
+fn encode<A: Archive>(
+    world: &World,
+    registry: &SnapshotRegistry,
+    side: &'static str,
+) -> Result<Vec<u8>, DualWriteError> {
+    A::create(world, registry)
+        .and_then(|archive| archive.to_bytes())
+        .map_err(|source| DualWriteError::Archive { side, source })
+}
+
+/// Check that `bytes`, an `A` encoding, loads into an empty world as `expected`.
+pub fn verify_archive<A: Archive>(
+    bytes: &[u8],
+    expected: &WorldArchSnapshot,
+    registry: &SnapshotRegistry,
+    side: &'static str,
+) -> Result<(), DualWriteError> {
+    let mut restored = World::new();
+    A::from_bytes(bytes)
+        .and_then(|archive| archive.apply(&mut restored, registry))
+        .map_err(|source| DualWriteError::Archive { side, source })?;
+    let diff = diff_world_snapshots(expected, &save_world_arch_snapshot(&restored, registry));
+    if !diff.is_empty() {
+        return Err(DualWriteError::Mismatch { side, diff });
+    }
+    Ok(())
+}
+
+fn staging_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::aurora_archive::AuroraWorldManifest;
+    use crate::binary_archive::MsgPackArchive;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Level(u32);
+
+    #[test]
+    fn test_dual_write() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Level>();
+        let mut world = World::new();
+        world.spawn(Level(3));
+        world.spawn(Level(7));
+        let dir = std::env::temp_dir().join(format!("bevy_archive_dual_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let dual = DualWrite::<AuroraWorldManifest, MsgPackArchive>::new(
+            dir.join("save.toml"),
+            dir.join("save.bin"),
+        );
+        dual.save(&world, &registry).unwrap();
+        assert!(!staging_path(&dual.primary).exists());
+        let mut old = World::new();
+        dual.load_primary()
+            .unwrap()
+            .apply(&mut old, &registry)
+            .unwrap();
+        let mut new = World::new();
+        dual.load_secondary()
+            .unwrap()
+            .apply(&mut new, &registry)
+            .unwrap();
+        for loaded in [&mut old, &mut new] {
+            assert_eq!(loaded.query::<&Level>().iter(loaded).count(), 2);
+        }
+
+        let bytes = MsgPackArchive::create(&world, &registry)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        world.spawn(Level(9));
+        let expected = save_world_arch_snapshot(&world, &registry);
+        let err = verify_archive::<MsgPackArchive>(&bytes, &expected, &registry, "secondary");
+        assert!(matches!(
+            err,
+            Err(DualWriteError::Mismatch { side: "secondary", diff }) if diff.removed.len() == 1
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
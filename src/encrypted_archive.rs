@@ -0,0 +1,187 @@
+//! Authenticated encryption of archives, e.g. for save games that should not be trivially
+//! editable (requires `encryption`).
+//!
+//! [`EncryptedArchive`] seals the [`to_bytes`](Archive::to_bytes) form of any [`Archive`]
+//! with ChaCha20-Poly1305 under an [`ArchiveKey`]. The sealed bytes are [`ENCRYPTED_MAGIC`],
+//! a random 12-byte nonce and the ciphertext with its 16-byte tag; a wrong key or any
+//! edited byte fails the tag check on load, before the archive is decoded.
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use crate::traits::Archive;
+
+/// Leading bytes of an encrypted archive. Also bound into the tag, so a changed header
+/// fails the check like the rest of the data.
+pub const ENCRYPTED_MAGIC: &[u8; 8] = b"BVARCENC";
+const NONCE_LEN: usize = 12;
+
+/// A 256-bit key for [`EncryptedArchive`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct ArchiveKey([u8; 32]);
+
+impl ArchiveKey {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// A random key from the OS generator.
+    pub fn generate() -> Self {
+        Self(ChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    /// A key derived from `passphrase` with BLAKE3. This is not a password hash, so it only
+    /// keeps casual editors out; prefer a key from a proper KDF for real secrets.
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        Self(blake3::derive_key(
+            "bevy_archive archive key",
+            passphrase.as_bytes(),
+        ))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+}
+
+impl fmt::Debug for ArchiveKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ArchiveKey(..)")
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("not an encrypted archive")]
+    NotEncrypted,
+    /// The tag check failed: the key is wrong or the data was modified.
+    #[error("encrypted archive failed verification: wrong key or modified data")]
+    Verification,
+    #[error("archive error: {0}")]
+    Archive(Box<dyn Error + Send + Sync>),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Seal `plain` under `key`.
+pub fn encrypt_bytes(plain: &[u8], key: &ArchiveKey) -> Result<Vec<u8>, EncryptionError> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let payload = Payload {
+        msg: plain,
+        aad: ENCRYPTED_MAGIC,
+    };
+    let sealed = key
+        .cipher()
+        .encrypt(&nonce, payload)
+        .map_err(|_| EncryptionError::Verification)?;
+    let mut out = Vec::with_capacity(ENCRYPTED_MAGIC.len() + NONCE_LEN + sealed.len());
+    out.extend_from_slice(ENCRYPTED_MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Open bytes sealed by [`encrypt_bytes`], verifying them against `key`.
+pub fn decrypt_bytes(sealed: &[u8], key: &ArchiveKey) -> Result<Vec<u8>, EncryptionError> {
+    let rest = sealed
+        .strip_prefix(ENCRYPTED_MAGIC.as_slice())
+        .ok_or(EncryptionError::NotEncrypted)?;
+    if rest.len() < NONCE_LEN {
+        return Err(EncryptionError::Verification);
+    }
+    let (nonce, msg) = rest.split_at(NONCE_LEN);
+    let payload = Payload {
+        msg,
+        aad: ENCRYPTED_MAGIC,
+    };
+    key.cipher()
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| EncryptionError::Verification)
+}
+
+/// Encrypted byte and file forms of an [`Archive`], implemented for every archive that
+/// supports [`to_bytes`](Archive::to_bytes) / [`from_bytes`](Archive::from_bytes).
+pub trait EncryptedArchive: Archive {
+    fn to_encrypted_bytes(&self, key: &ArchiveKey) -> Result<Vec<u8>, EncryptionError> {
+        let plain = self.to_bytes().map_err(EncryptionError::Archive)?;
+        encrypt_bytes(&plain, key)
+    }
+
+    fn from_encrypted_bytes(bytes: &[u8], key: &ArchiveKey) -> Result<Self, EncryptionError> {
+        let plain = decrypt_bytes(bytes, key)?;
+        Self::from_bytes(&plain).map_err(EncryptionError::Archive)
+    }
+
+    fn save_encrypted(
+        &self,
+        path: impl AsRef<Path>,
+        key: &ArchiveKey,
+    ) -> Result<(), EncryptionError> {
+        std::fs::write(path, self.to_encrypted_bytes(key)?)?;
+        Ok(())
+    }
+
+    fn load_encrypted(path: impl AsRef<Path>, key: &ArchiveKey) -> Result<Self, EncryptionError> {
+        Self::from_encrypted_bytes(&std::fs::read(path)?, key)
+    }
+}
+
+impl<A: Archive> EncryptedArchive for A {}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::bevy_registry::SnapshotRegistry;
+    use crate::binary_archive::MsgPackArchive;
+
+    #[derive(Component, Serialize, Deserialize, Debug, PartialEq)]
+    struct Gold(u32);
+
+    #[test]
+    fn test_encrypted_archive() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Gold>();
+        let mut world = World::new();
+        world.spawn(Gold(250));
+        let archive = MsgPackArchive::create(&world, &registry).unwrap();
+
+        let key = ArchiveKey::from_passphrase("hunter2");
+        let sealed = archive.to_encrypted_bytes(&key).unwrap();
+        assert!(sealed.starts_with(ENCRYPTED_MAGIC));
+        assert_ne!(sealed, archive.to_encrypted_bytes(&key).unwrap());
+
+        let loaded = MsgPackArchive::from_encrypted_bytes(&sealed, &key).unwrap();
+        let mut restored = World::new();
+        loaded.apply(&mut restored, &registry).unwrap();
+        let gold = restored.query::<&Gold>().single(&restored).unwrap();
+        assert_eq!(gold, &Gold(250));
+
+        let wrong = ArchiveKey::generate();
+        assert!(matches!(
+            MsgPackArchive::from_encrypted_bytes(&sealed, &wrong),
+            Err(EncryptionError::Verification)
+        ));
+        let mut edited = sealed.clone();
+        *edited.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            MsgPackArchive::from_encrypted_bytes(&edited, &key),
+            Err(EncryptionError::Verification)
+        ));
+        let plain = archive.to_bytes().unwrap();
+        assert!(matches!(
+            MsgPackArchive::from_encrypted_bytes(&plain, &key),
+            Err(EncryptionError::NotEncrypted)
+        ));
+    }
+}
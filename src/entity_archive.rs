@@ -1,18 +1,22 @@
-use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::Path,
+};
 #[derive(Debug, Deserialize)]
 pub struct SnapshotFile {
     #[serde(rename = "entity")]
     pub entities: Vec<EntitySnapshot>,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ComponentSnapshot {
     pub r#type: String,
     #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
     pub value: serde_json::Value,
 }
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EntitySnapshot {
     pub id: u64,
 
@@ -27,13 +31,32 @@ impl WorldSnapshot {
     pub fn purge_null(&mut self) {
         self.entities.retain(|c| !c.components.is_empty());
     }
+
+    /// Encode as a TOML document. Fails on `null` values, which TOML cannot hold.
+    pub fn to_toml_string(&self) -> Result<String, ArchiveError> {
+        to_toml_string(self)
+    }
+
+    pub fn from_toml_str(s: &str) -> Result<Self, ArchiveError> {
+        from_toml_str(s)
+    }
+
+    pub fn to_json_string(&self) -> Result<String, ArchiveError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, ArchiveError> {
+        Ok(serde_json::from_str(s)?)
+    }
 }
 
 use serde_json::Value as JsonValue;
 use toml::Value as TomlValue;
 
-use crate::{archetype_archive::WorldExt, bevy_registry::{SnapshotRegistry, IDRemapRegistry, EntityRemapper, reserve_entity_slots}, traits::Archive};
-use bevy_ecs::prelude::*;
+use crate::{archetype_archive::LoadReport, error::ArchiveError, bevy_registry::{SnapshotRegistry, IDRemapRegistry, EntityRemapper, LimitError, try_reserve_entity_slots, sort_by_required_components}, traits::Archive};
+use bevy_ecs::{component::ComponentId, prelude::*, resource::IS_RESOURCE};
+use crate::hierarchy::repair_after_load;
+use crate::snapshot_origin::insert_origins;
 
 /// JSON → TOML
 pub fn json_to_toml(json: &JsonValue) -> Result<TomlValue, String> {
@@ -46,6 +69,18 @@ pub fn toml_to_json(toml: &TomlValue) -> Result<JsonValue, String> {
         .map_err(|e| format!("to_json failed: {}", e))
 }
 
+/// Encode `value` as a TOML document, going through JSON so `serde_json::Value` fields
+/// are laid out as TOML tables.
+pub(crate) fn to_toml_string<T: Serialize>(value: &T) -> Result<String, ArchiveError> {
+    let toml = json_to_toml(&serde_json::to_value(value)?).map_err(ArchiveError::Parse)?;
+    toml::to_string(&toml).map_err(ArchiveError::parse)
+}
+
+pub(crate) fn from_toml_str<T: DeserializeOwned>(s: &str) -> Result<T, ArchiveError> {
+    let toml: TomlValue = toml::from_str(s).map_err(ArchiveError::parse)?;
+    Ok(serde_json::from_value(toml_to_json(&toml).map_err(ArchiveError::Parse)?)?)
+}
+
 impl Archive for WorldSnapshot {
     fn create(
         world: &World,
@@ -59,7 +94,7 @@ impl Archive for WorldSnapshot {
         world: &mut World,
         registry: &SnapshotRegistry,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        load_world_snapshot(world, self, registry);
+        load_world_snapshot(world, self, registry)?;
         Ok(())
     }
 
@@ -70,7 +105,7 @@ impl Archive for WorldSnapshot {
         id_registry: &IDRemapRegistry,
         mapper: &dyn EntityRemapper,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        load_world_snapshot_with_remap(world, self, registry, id_registry, mapper);
+        load_world_snapshot_with_remap(world, self, registry, id_registry, mapper)?;
         Ok(())
     }
 
@@ -110,51 +145,167 @@ impl Archive for WorldSnapshot {
              load_snapshot_from_file(path).map_err(|e| e.into())
         }
     }
-}
 
-pub fn save_world_snapshot(world: &World, reg: &SnapshotRegistry) -> WorldSnapshot {
-    let mut entities_snapshot = Vec::new();
-    for e in WorldExt::iter_entities(world) {
-        let mut es = EntitySnapshot::default();
-        es.id = e.index_u32() as u64;
-        for key in reg.type_registry.keys() {
-            if let Some(func) = reg.get_factory(key).map(|x| x.js_value.export) {
-                if let Some(value) = func(world, e) {
-                    es.components.push(ComponentSnapshot {
-                        r#type: key.to_string(),
-                        value,
-                    });
-                }
-            }
-        }
-        entities_snapshot.push(es);
+    fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::to_vec(self)?)
     }
-    WorldSnapshot {
-        // Filter out entities that have no registered components
-        // (Bevy 0.19+ may have internal entities with no registered data)
-        entities: entities_snapshot
-            .into_iter()
-            .filter(|e| !e.components.is_empty())
-            .collect(),
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::from_slice(bytes)?)
     }
 }
 
-pub fn load_world_snapshot(world: &mut World, snapshot: &WorldSnapshot, reg: &SnapshotRegistry) {
+/// Export every entity with at least one registered component, sorted by id.
+///
+/// Walks archetypes and only exports the registered components each one contains, so the
+/// cost is proportional to the stored values rather than entities × registered types.
+pub fn save_world_snapshot(world: &World, reg: &SnapshotRegistry) -> WorldSnapshot {
+    let mut entities: Vec<EntitySnapshot> = iter_entity_snapshots(world, reg).collect();
+    entities.sort_unstable_by_key(|e| e.id);
+    WorldSnapshot { entities }
+}
+
+/// Like [`save_world_snapshot`], but yields the entities lazily in snapshots of at most
+/// `batch_size` entities, archetype by archetype, so a large world never has to be held in
+/// one `WorldSnapshot`.
+pub fn save_world_snapshot_batches<'w>(
+    world: &'w World,
+    reg: &'w SnapshotRegistry,
+    batch_size: usize,
+) -> impl Iterator<Item = WorldSnapshot> + 'w {
+    let batch_size = batch_size.max(1);
+    let mut entities = iter_entity_snapshots(world, reg);
+    std::iter::from_fn(move || {
+        let batch: Vec<_> = entities.by_ref().take(batch_size).collect();
+        (!batch.is_empty()).then_some(WorldSnapshot { entities: batch })
+    })
+}
+
+/// Entity snapshots in archetype order, skipping entities with no registered component.
+fn iter_entity_snapshots<'w>(
+    world: &'w World,
+    reg: &'w SnapshotRegistry,
+) -> impl Iterator<Item = EntitySnapshot> + 'w {
+    let mut registered: Vec<(ComponentId, &'static str)> = reg
+        .type_registry
+        .keys()
+        .filter_map(|&name| reg.comp_id_by_name(name, world).map(|cid| (cid, name)))
+        .collect();
+    registered.sort_unstable_by_key(|(_, name)| *name);
+    world
+        .archetypes()
+        .iter()
+        .filter(|a| !a.is_empty() && !a.contains(IS_RESOURCE))
+        .flat_map(move |archetype| {
+            let present: Vec<_> = registered
+                .iter()
+                .filter(|(cid, _)| archetype.contains(*cid))
+                .filter_map(|&(_, name)| Some((name, &reg.get_factory(name)?.js_value.export)))
+                .collect();
+            archetype.entities().iter().filter_map(move |e| {
+                let e = e.id();
+                let components: Vec<_> = present
+                    .iter()
+                    .filter_map(|(name, export)| {
+                        Some(ComponentSnapshot {
+                            r#type: name.to_string(),
+                            value: export(world, e)?,
+                        })
+                    })
+                    .collect();
+                (!components.is_empty()).then(|| EntitySnapshot {
+                    id: e.index_u32() as u64,
+                    components,
+                })
+            })
+        })
+}
+
+/// Components of `e` in an order where required components are imported before the
+/// components requiring them, so `#[require]` defaults never overwrite snapshot values.
+fn ordered_components<'a>(
+    world: &mut World,
+    e: &'a EntitySnapshot,
+    reg: &SnapshotRegistry,
+) -> Vec<&'a ComponentSnapshot> {
+    let names: Vec<&str> = e.components.iter().map(|c| c.r#type.as_str()).collect();
+    let mut comps: Vec<_> = e
+        .components
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| reg.duplicate_components.keeps(&names, *idx))
+        .map(|(_, c)| {
+            let id = reg.get_factory(&c.r#type).map(|_| {
+                reg.comp_id_by_name(&c.r#type, world)
+                    .unwrap_or_else(|| reg.reg_by_name(&c.r#type, world))
+            });
+            (id, c)
+        })
+        .collect();
+    sort_by_required_components(world, &mut comps, |(id, _)| *id);
+    comps.into_iter().map(|(_, c)| c).collect()
+}
+
+fn snapshot_component_names(snapshot: &WorldSnapshot) -> impl Iterator<Item = &str> {
+    snapshot
+        .entities
+        .iter()
+        .flat_map(|e| e.components.iter().map(|c| c.r#type.as_str()))
+}
+
+/// [`SnapshotRegistry::check_duplicates`] for every entity of `snapshot`.
+fn check_duplicate_components(
+    snapshot: &WorldSnapshot,
+    reg: &SnapshotRegistry,
+) -> Result<(), LimitError> {
+    snapshot
+        .entities
+        .iter()
+        .try_for_each(|e| reg.check_duplicates(e.components.iter().map(|c| c.r#type.as_str())))
+}
+
+pub fn load_world_snapshot(
+    world: &mut World,
+    snapshot: &WorldSnapshot,
+    reg: &SnapshotRegistry,
+) -> Result<LoadReport, LimitError> {
     let mut max_id = 0;
     for e in &snapshot.entities {
         max_id = max_id.max(e.id);
     }
-    reserve_entity_slots(world, (max_id + 1) as u32);
+    let max_index = u32::try_from(max_id).map_err(|_| LimitError::TooManyEntities {
+        requested: max_id.saturating_add(1),
+        max: reg.limits.max_entities,
+    })?;
+    reg.limits.check_entities(max_index as u64 + 1)?;
+    reg.check_known(snapshot_component_names(snapshot))?;
+    check_duplicate_components(snapshot, reg)?;
+    try_reserve_entity_slots(world, max_index, &reg.limits)?;
     world.flush();
+    let mut report = LoadReport::default();
     for e in &snapshot.entities {
         let entity = Entity::from_raw_u32(e.id as u32).unwrap();
-        for c in &e.components {
-            reg.get_factory(&c.r#type.as_str())
-                .map(|x| x.js_value.import)
-                .and_then(|f| Some(f(&c.value, world, entity).unwrap()))
-                .unwrap()
+        for c in ordered_components(world, e, reg) {
+            match reg.get_factory(c.r#type.as_str()) {
+                Some(factory) => {
+                    let value = factory.fill_from_prototype(&c.value);
+                    (factory.js_value.import)(&value, world, entity).unwrap();
+                    report.components_loaded += 1;
+                }
+                None => report.skip(&c.r#type, 1, true),
+            }
         }
+        report.entities_loaded += 1;
     }
+    report.record(world);
+    let loaded: Vec<_> = snapshot
+        .entities
+        .iter()
+        .map(|e| (e.id as u32, Entity::from_raw_u32(e.id as u32).unwrap()))
+        .collect();
+    repair_after_load(world, reg);
+    insert_origins(world, reg, &loaded);
+    Ok(report)
 }
 
 pub fn load_world_snapshot_with_remap(
@@ -163,40 +314,56 @@ pub fn load_world_snapshot_with_remap(
     reg: &SnapshotRegistry,
     id_registry: &IDRemapRegistry,
     mapper: &dyn EntityRemapper,
-) {
+) -> Result<LoadReport, LimitError> {
+    reg.limits.check_entities(snapshot.entities.len() as u64)?;
+    reg.check_known(snapshot_component_names(snapshot))?;
+    check_duplicate_components(snapshot, reg)?;
+    let mut report = LoadReport::default();
     for e in &snapshot.entities {
         let entity = mapper.map(e.id as u32);
         if entity == Entity::PLACEHOLDER {
             continue;
         }
+        report.entities_loaded += 1;
 
-        for c in &e.components {
+        for c in ordered_components(world, e, reg) {
             let type_name = c.r#type.as_str();
             if let Some(factory) = reg.get_factory(type_name) {
-                let import_fn = factory.js_value.import;
-                if let Err(err) = import_fn(&c.value, world, entity) {
-                    eprintln!("Error importing component {}: {}", type_name, err);
+                let import_fn = &factory.js_value.import;
+                if let Err(err) = import_fn(&factory.fill_from_prototype(&c.value), world, entity) {
+                    archive_error!("Error importing component {}: {}", type_name, err);
                     continue;
                 }
+                report.components_loaded += 1;
 
                 // Apply Hook
-                if let Some(type_id) = reg.type_registry.get(type_name) {
-                     if let Some(hook) = id_registry.get_hook(*type_id) {
-                         if let Some(comp_id) = reg.comp_id_by_name(type_name, world) {
-                              // We need to get PtrMut to the component in the world.
-                              // SAFETY: We just inserted it, so it should exist.
-                              // Using world.get_mut_by_id gives us MutUntyped which can be converted to PtrMut?
-                              // world.get_mut_by_id returns Option<MutUntyped>. MutUntyped.into_inner() -> PtrMut.
-                              if let Some(mut mut_untyped) = world.get_mut_by_id(entity, comp_id) {
-                                  let ptr = mut_untyped.as_mut(); // This gives PtrMut
-                                  hook(ptr, mapper);
-                              }
-                         }
-                     }
+                if let Some(type_id) = reg.type_id_by_name(type_name)
+                    && let Some(hook) = id_registry.get_hook(type_id)
+                    && let Some(comp_id) = reg.comp_id_by_name(type_name, world)
+                {
+                    // We need to get PtrMut to the component in the world.
+                    // SAFETY: We just inserted it, so it should exist.
+                    // Using world.get_mut_by_id gives us MutUntyped which can be converted to PtrMut?
+                    // world.get_mut_by_id returns Option<MutUntyped>. MutUntyped.into_inner() -> PtrMut.
+                    if let Some(mut mut_untyped) = world.get_mut_by_id(entity, comp_id) {
+                        let ptr = mut_untyped.as_mut(); // This gives PtrMut
+                        hook(ptr, mapper);
+                    }
                 }
+            } else {
+                report.skip(type_name, 1, true);
             }
         }
     }
+    report.record(world);
+    let loaded: Vec<_> = snapshot
+        .entities
+        .iter()
+        .map(|e| (e.id as u32, mapper.map(e.id as u32)))
+        .collect();
+    repair_after_load(world, reg);
+    insert_origins(world, reg, &loaded);
+    Ok(report)
 }
 
 pub fn save_snapshot_to_file<P: AsRef<Path>>(
@@ -211,10 +378,8 @@ pub fn save_snapshot_to_file_toml<P: AsRef<Path>>(
     snapshot: &WorldSnapshot,
     path: P,
 ) -> Result<(), std::io::Error> {
-    let mut content = json_to_toml(&serde_json::to_value(snapshot).unwrap()).unwrap();
-    let t = content.as_table_mut().unwrap();
-
-    fs::write(path, t.to_string())
+    let content = snapshot.to_toml_string().map_err(std::io::Error::other)?;
+    fs::write(path, content)
 }
 pub fn load_snapshot_from_file<P: AsRef<Path>>(path: P) -> Result<WorldSnapshot, String> {
     let content = fs::read_to_string(path).map_err(|e| format!("I/O error: {}", e))?;
@@ -225,6 +390,68 @@ pub fn load_snapshot_from_file_toml<P: AsRef<Path>>(path: P) -> Result<WorldSnap
     let content = fs::read_to_string(path).map_err(|e| format!("I/O error: {}", e))?;
     toml::from_str(&content).map_err(|e| format!("Deserialization error: {}", e))
 }
+/// Write `snapshot` as JSON Lines: one entity object per line.
+pub fn write_snapshot_jsonl<W: Write>(snapshot: &WorldSnapshot, writer: W) -> std::io::Result<()> {
+    write_entities_jsonl(snapshot.entities.iter(), writer)
+}
+
+/// Stream the entities of `world` to `writer` as JSON Lines without building a
+/// [`WorldSnapshot`] first. Entities are written in archetype order.
+pub fn save_world_jsonl<W: Write>(
+    world: &World,
+    reg: &SnapshotRegistry,
+    writer: W,
+) -> std::io::Result<()> {
+    write_entities_jsonl(iter_entity_snapshots(world, reg), writer)
+}
+
+fn write_entities_jsonl<E, W>(entities: impl Iterator<Item = E>, writer: W) -> std::io::Result<()>
+where
+    E: std::borrow::Borrow<EntitySnapshot>,
+    W: Write,
+{
+    let mut writer = std::io::BufWriter::new(writer);
+    for entity in entities {
+        serde_json::to_writer(&mut writer, entity.borrow())?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Read JSON Lines entities one at a time. Blank lines are skipped; errors carry the
+/// 1-based line number.
+pub fn iter_snapshot_jsonl<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<EntitySnapshot, String>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+        .map(|(idx, line)| {
+            let line = line.map_err(|e| format!("I/O error: {}", e))?;
+            serde_json::from_str(&line)
+                .map_err(|e| format!("Deserialization error at line {}: {}", idx + 1, e))
+        })
+}
+
+/// Read a whole JSON Lines snapshot.
+pub fn read_snapshot_jsonl<R: BufRead>(reader: R) -> Result<WorldSnapshot, String> {
+    let entities = iter_snapshot_jsonl(reader).collect::<Result<_, _>>()?;
+    Ok(WorldSnapshot { entities })
+}
+
+pub fn save_snapshot_to_file_jsonl<P: AsRef<Path>>(
+    snapshot: &WorldSnapshot,
+    path: P,
+) -> Result<(), std::io::Error> {
+    write_snapshot_jsonl(snapshot, fs::File::create(path)?)
+}
+
+pub fn load_snapshot_from_file_jsonl<P: AsRef<Path>>(path: P) -> Result<WorldSnapshot, String> {
+    let file = fs::File::open(path).map_err(|e| format!("I/O error: {}", e))?;
+    read_snapshot_jsonl(std::io::BufReader::new(file))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::bevy_registry::SnapshotRegistry;
@@ -259,6 +486,109 @@ mod tests {
         let _w = save_world_snapshot(&world, &registry);
     }
 
+    #[test]
+    fn test_snapshot_batches() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Resistor>();
+        registry.register::<Port2>();
+        let mut world = World::default();
+        for i in 0..25 {
+            world.spawn_empty();
+            match i % 3 {
+                0 => world.spawn(Resistor(i as f64)),
+                1 => world.spawn((Resistor(i as f64), Port2([i, 0]))),
+                _ => world.spawn(Admittance(i as f64)),
+            };
+        }
+
+        let whole = save_world_snapshot(&world, &registry);
+        assert_eq!(whole.entities.len(), 17);
+        assert!(whole.entities.is_sorted_by_key(|e| e.id));
+        let pair = whole.entities.iter().find(|e| e.components.len() == 2).unwrap();
+        let names: Vec<_> = pair.components.iter().map(|c| c.r#type.as_str()).collect();
+        assert_eq!(names, ["Port2", "Resistor"]);
+
+        let mut lines = Vec::new();
+        save_world_jsonl(&world, &registry, &mut lines).unwrap();
+        assert_eq!(lines.iter().filter(|&&b| b == b'\n').count(), 17);
+        let mut streamed = read_snapshot_jsonl(&lines[..]).unwrap();
+        streamed.entities.sort_unstable_by_key(|e| e.id);
+        assert_eq!(streamed.entities, whole.entities);
+        let mut written = Vec::new();
+        write_snapshot_jsonl(&whole, &mut written).unwrap();
+        written.extend_from_slice(b"\n{not json}\n");
+        let err = read_snapshot_jsonl(&written[..]).unwrap_err();
+        assert!(err.contains("line 19"), "{err}");
+
+        let batches: Vec<_> = save_world_snapshot_batches(&world, &registry, 5).collect();
+        assert_eq!(batches.len(), 4);
+        assert!(batches.iter().all(|b| b.entities.len() <= 5));
+        let mut ids: Vec<u64> = batches
+            .iter()
+            .flat_map(|b| b.entities.iter().map(|e| e.id))
+            .collect();
+        ids.sort_unstable();
+        let expected: Vec<u64> = whole.entities.iter().map(|e| e.id).collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[derive(Serialize, Deserialize, Component, Debug, PartialEq)]
+    struct Health(i32);
+    impl Default for Health {
+        fn default() -> Self {
+            Health(100)
+        }
+    }
+    #[derive(Serialize, Deserialize, Component)]
+    #[require(Health)]
+    struct Player;
+
+    #[derive(Resource, Default)]
+    struct HealthInserts(usize);
+
+    fn required_registry() -> SnapshotRegistry {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Player>();
+        registry.register::<Health>();
+        registry
+    }
+
+    #[test]
+    fn test_required_components_snapshot_wins() {
+        let registry = required_registry();
+        let mut src = World::default();
+        let e = src.spawn((Player, Health(5))).id();
+        let p = src.spawn(Player).id();
+        let snapshot = save_world_snapshot(&src, &registry);
+
+        // Put the requiring component first so a naive import would let the default win.
+        let mut snapshot = snapshot;
+        for ent in &mut snapshot.entities {
+            ent.components.sort_by_key(|c| c.r#type != "Player");
+        }
+
+        // Health must be inserted exactly once per entity, never defaulted then overwritten.
+        let mut world = World::default();
+        world.init_resource::<HealthInserts>();
+        world.add_observer(|_: On<Insert, Health>, mut n: ResMut<HealthInserts>| n.0 += 1);
+        load_world_snapshot(&mut world, &snapshot, &registry).unwrap();
+        assert_eq!(world.resource::<HealthInserts>().0, 2);
+        let loaded = |id: Entity| Entity::from_raw_u32(id.index_u32()).unwrap();
+        assert_eq!(world.get::<Health>(loaded(e)), Some(&Health(5)));
+        assert_eq!(world.get::<Health>(loaded(p)), Some(&Health(100)));
+
+        let mut sparse = WorldSnapshot { entities: vec![] };
+        sparse.entities.push(EntitySnapshot {
+            id: 0,
+            components: vec![ComponentSnapshot { r#type: "Player".into(), value: serde_json::Value::Null }],
+        });
+        let mut world = World::default();
+        load_world_snapshot(&mut world, &sparse, &registry).unwrap();
+        let e0 = Entity::from_raw_u32(0).unwrap();
+        assert!(world.get::<Player>(e0).is_some());
+        assert_eq!(world.get::<Health>(e0), Some(&Health(100)));
+    }
+
     #[test]
     fn test_parse_pretty_toml() {
         let input = r#"[[entities]]
@@ -277,7 +607,7 @@ components = [
 
         let parsed: TomlValue = toml::from_str(input).expect("Failed to parse TOML");
         let snapshot: WorldSnapshot = parsed.try_into().unwrap();
-        load_world_snapshot(&mut world, &snapshot, &registry);
+        load_world_snapshot(&mut world, &snapshot, &registry).unwrap();
     }
 
     #[test]
@@ -0,0 +1,116 @@
+//! Entity id → (archetype, row) lookup stored next to a snapshot.
+//!
+//! Arrow zips keep it in `meta.toml` and Aurora manifests in `metadata`, both under
+//! [`ENTITY_INDEX_KEY`], so a single entity can be read from its own blob instead of
+//! decoding every archetype. Ids are stored as runs of consecutive ids, which keeps the
+//! index small for entities spawned in bulk.
+
+use serde::{Deserialize, Serialize};
+
+/// Key of the index in zip `meta` and manifest `metadata`.
+pub const ENTITY_INDEX_KEY: &str = "entity_index";
+
+/// `len` consecutive ids starting at `first`, stored from `row` on in one archetype.
+///
+/// Serialized as `[first, len, archetype, row]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "[u32; 4]", into = "[u32; 4]")]
+pub struct EntityRun {
+    pub first: u32,
+    pub len: u32,
+    pub archetype: u32,
+    pub row: u32,
+}
+
+impl From<[u32; 4]> for EntityRun {
+    fn from([first, len, archetype, row]: [u32; 4]) -> Self {
+        Self {
+            first,
+            len,
+            archetype,
+            row,
+        }
+    }
+}
+
+impl From<EntityRun> for [u32; 4] {
+    fn from(run: EntityRun) -> Self {
+        [run.first, run.len, run.archetype, run.row]
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EntityLocationIndex {
+    runs: Vec<EntityRun>,
+}
+
+impl EntityLocationIndex {
+    /// Index the entity ids of each archetype, in row order.
+    pub fn build<'a>(archetypes: impl IntoIterator<Item = &'a [u32]>) -> Self {
+        let mut runs: Vec<EntityRun> = Vec::new();
+        for (archetype, ids) in archetypes.into_iter().enumerate() {
+            let mut current: Option<EntityRun> = None;
+            for (row, &id) in ids.iter().enumerate() {
+                match &mut current {
+                    Some(run) if run.first.checked_add(run.len) == Some(id) => run.len += 1,
+                    _ => {
+                        runs.extend(current.take());
+                        current = Some(EntityRun {
+                            first: id,
+                            len: 1,
+                            archetype: archetype as u32,
+                            row: row as u32,
+                        });
+                    }
+                }
+            }
+            runs.extend(current);
+        }
+        runs.sort_unstable_by_key(|run| run.first);
+        Self { runs }
+    }
+
+    /// Archetype and row holding entity `id`.
+    pub fn locate(&self, id: u32) -> Option<(usize, usize)> {
+        let pos = self.runs.partition_point(|run| run.first <= id);
+        let run = self.runs[..pos].last()?;
+        let offset = id - run.first;
+        (offset < run.len).then(|| (run.archetype as usize, (run.row + offset) as usize))
+    }
+
+    pub fn runs(&self) -> &[EntityRun] {
+        &self.runs
+    }
+
+    /// Compact JSON form stored in zip `meta`.
+    pub fn to_meta_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_meta_str(s: &str) -> Result<Self, String> {
+        serde_json::from_str(s).map_err(|e| format!("invalid entity index: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entity_index_runs() {
+        let a: &[u32] = &[0, 1, 2, 7, 8];
+        let b: &[u32] = &[3, 4, 9];
+        let index = EntityLocationIndex::build([a, b]);
+        assert_eq!(index.runs().len(), 4);
+        assert_eq!(index.locate(1), Some((0, 1)));
+        assert_eq!(index.locate(8), Some((0, 4)));
+        assert_eq!(index.locate(4), Some((1, 1)));
+        assert_eq!(index.locate(9), Some((1, 2)));
+        assert_eq!(index.locate(5), None);
+        assert_eq!(index.locate(100), None);
+
+        let back = EntityLocationIndex::from_meta_str(&index.to_meta_string()).unwrap();
+        assert_eq!(back, index);
+    }
+}
@@ -0,0 +1,166 @@
+//! Round trip for Bevy's [`Name`] component.
+//!
+//! [`SnapshotRegistry::register_name`] registers `Name` under the [`NAME_COLUMN`] column as a
+//! plain string, so names survive every archive format, including CSV and Parquet columns.
+//! This mirrors the `NameID` column of the flecs exporter. [`name_remapper`] merges a
+//! snapshot into a world by name, and Aurora manifests fill their
+//! [name table](crate::aurora_archive::WorldWithAurora::names) from either column.
+
+use std::collections::HashMap;
+
+use bevy_ecs::name::Name;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::archetype_archive::WorldArchSnapshot;
+use crate::bevy_registry::SnapshotRegistry;
+
+/// Column name under which [`Name`] is stored in snapshots.
+pub const NAME_COLUMN: &str = "Name";
+
+/// Serialized form of [`Name`]: the bare string.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NameValue(pub String);
+
+impl From<&Name> for NameValue {
+    fn from(name: &Name) -> Self {
+        Self(name.as_str().to_string())
+    }
+}
+
+impl From<NameValue> for Name {
+    fn from(value: NameValue) -> Self {
+        Name::new(value.0)
+    }
+}
+
+impl SnapshotRegistry {
+    /// Register Bevy's [`Name`] under [`NAME_COLUMN`].
+    pub fn register_name(&mut self) {
+        self.register_with_name::<Name, NameValue>(NAME_COLUMN);
+    }
+}
+
+/// Read the [`NAME_COLUMN`] of every archetype in `snapshot`, keyed by entity id.
+pub fn snapshot_names(snapshot: &WorldArchSnapshot) -> HashMap<u32, &str> {
+    let mut out = HashMap::new();
+    for arch in &snapshot.archetypes {
+        let Some(col) = arch.get_column(NAME_COLUMN) else {
+            continue;
+        };
+        for (&id, value) in arch.entities().iter().zip(col) {
+            if let Some(name) = value.as_str() {
+                out.insert(id, name);
+            }
+        }
+    }
+    out
+}
+
+/// Map every entity of `snapshot` onto the entity of `world` with the same [`Name`].
+///
+/// Unnamed snapshot entities, and names that no entity of `world` carries, get a newly
+/// spawned entity. When several entities of `world` share a name, the first one found is
+/// used.
+pub fn name_remapper(world: &mut World, snapshot: &WorldArchSnapshot) -> HashMap<u32, Entity> {
+    let mut by_name: HashMap<String, Entity> = HashMap::new();
+    for (entity, name) in world.query::<(Entity, &Name)>().iter(world) {
+        by_name.entry(name.as_str().to_string()).or_insert(entity);
+    }
+    let names = snapshot_names(snapshot);
+    let mut map = HashMap::new();
+    for arch in &snapshot.archetypes {
+        for &id in arch.entities() {
+            map.entry(id).or_insert_with(|| {
+                names
+                    .get(&id)
+                    .and_then(|name| by_name.get(*name).copied())
+                    .unwrap_or_else(|| world.spawn_empty().id())
+            });
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetype_archive::{load_world_arch_snapshot_with_remap, save_world_arch_snapshot};
+    use crate::aurora_archive::{
+        ExportFormat, ExportGuidance, load_world_manifest, save_world_manifest_with_guidance,
+    };
+    use crate::bevy_registry::IDRemapRegistry;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hp(u32);
+
+    fn registry() -> SnapshotRegistry {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        registry.register_name();
+        registry
+    }
+
+    fn names(world: &mut World) -> Vec<(String, u32)> {
+        let mut out: Vec<_> = world
+            .query::<(&Name, &Hp)>()
+            .iter(world)
+            .map(|(n, hp)| (n.as_str().to_string(), hp.0))
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn test_name_roundtrip_through_columns() {
+        let registry = registry();
+        let mut world = World::new();
+        world.spawn((Hp(1), Name::new("hero")));
+        world.spawn((Hp(2), Name::new("the, \"quoted\" one")));
+        world.spawn(Hp(3));
+
+        let formats = [ExportFormat::Csv, ExportFormat::Json].into_iter();
+        #[cfg(feature = "arrow_rs")]
+        let formats = formats.chain([ExportFormat::Parquet]);
+        for format in formats {
+            let guide = ExportGuidance::embed_all(format);
+            let manifest = save_world_manifest_with_guidance(&world, &registry, &guide).unwrap();
+            let mut loaded = World::new();
+            load_world_manifest(&mut loaded, &manifest, &registry).unwrap();
+            assert_eq!(names(&mut loaded), names(&mut world));
+        }
+
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+        assert_eq!(snapshot_names(&snapshot).len(), 2);
+        let aurora = crate::aurora_archive::WorldWithAurora::from(&snapshot);
+        assert!(aurora.names.contains_key("hero"));
+    }
+
+    #[test]
+    fn test_merge_by_name() {
+        let registry = registry();
+        let mut source = World::new();
+        source.spawn_empty();
+        source.spawn((Hp(50), Name::new("hero")));
+        source.spawn((Hp(7), Name::new("newcomer")));
+        let snapshot = save_world_arch_snapshot(&source, &registry);
+
+        let mut world = World::new();
+        let hero = world.spawn((Hp(1), Name::new("hero"))).id();
+        let mapper = name_remapper(&mut world, &snapshot);
+        load_world_arch_snapshot_with_remap(
+            &mut world,
+            &snapshot,
+            &registry,
+            &IDRemapRegistry::default(),
+            &mapper,
+        )
+        .unwrap();
+        assert_eq!(world.get::<Hp>(hero), Some(&Hp(50)));
+        assert_eq!(
+            names(&mut world),
+            vec![("hero".to_string(), 50), ("newcomer".to_string(), 7)]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! [`ArchiveError`], the error shared by the snapshot formats.
+//!
+//! The Aurora manifest, CSV and archetype snapshot APIs report failures as one of a few
+//! kinds so callers can react to them without parsing messages: retry on
+//! [`Io`](ArchiveError::Io), reject the file on [`Parse`](ArchiveError::Parse), register
+//! the missing type on [`MissingFactory`](ArchiveError::MissingFactory), and so on.
+
+use std::io;
+
+use crate::bevy_registry::LimitError;
+#[cfg(feature = "arrow_rs")]
+use crate::bevy_registry::vec_snapshot_factory::SnapshotError;
+use crate::checksum::ChecksumMismatch;
+use crate::container::ContainerError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    /// Reading or writing a file, blob or container entry failed.
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    /// Bytes could not be decoded from, or encoded to, the expected format.
+    #[error("parse error: {0}")]
+    Parse(String),
+    /// No factory is registered for a component or resource.
+    #[error("missing factory for component/resource: {0}")]
+    MissingFactory(String),
+    /// Decoded data does not have the expected shape, e.g. duplicate or missing columns,
+    /// rows out of range or a failed schema migration.
+    #[error("schema error: {0}")]
+    Schema(String),
+    /// A stored entity id could not be mapped into the target world.
+    #[error("remap error: {0}")]
+    Remap(String),
+    /// A blob does not hash to the checksum recorded when it was saved.
+    #[error(transparent)]
+    Checksum(#[from] ChecksumMismatch),
+    /// A manifest signature is missing, malformed, untrusted or does not match.
+    #[cfg(feature = "signing")]
+    #[error(transparent)]
+    Signature(#[from] crate::manifest_signing::SignatureError),
+}
+
+impl ArchiveError {
+    pub fn parse(e: impl ToString) -> Self {
+        Self::Parse(e.to_string())
+    }
+
+    pub fn schema(e: impl ToString) -> Self {
+        Self::Schema(e.to_string())
+    }
+}
+
+impl From<ContainerError> for ArchiveError {
+    fn from(e: ContainerError) -> Self {
+        let kind = match &e {
+            ContainerError::Io(e) => e.kind(),
+            ContainerError::NotFound(_) => io::ErrorKind::NotFound,
+            ContainerError::Escape(_) => io::ErrorKind::PermissionDenied,
+            ContainerError::Unsupported(_) => io::ErrorKind::Unsupported,
+            ContainerError::TooLarge { .. } | ContainerError::Archive(_) => {
+                io::ErrorKind::InvalidData
+            }
+        };
+        Self::Io(io::Error::new(kind, e))
+    }
+}
+
+impl From<LimitError> for ArchiveError {
+    fn from(e: LimitError) -> Self {
+        match e {
+            LimitError::UnknownType(name) => Self::MissingFactory(name),
+            e @ (LimitError::SchemaChanged(_)
+            | LimitError::IncompatibleSchema(_)
+            | LimitError::DuplicateComponent(_)) => Self::Schema(e.to_string()),
+            e => Self::Io(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ArchiveError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::parse(e)
+    }
+}
+
+impl From<toml::de::Error> for ArchiveError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::parse(e)
+    }
+}
+
+impl From<toml::ser::Error> for ArchiveError {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::parse(e)
+    }
+}
+
+impl From<rmp_serde::decode::Error> for ArchiveError {
+    fn from(e: rmp_serde::decode::Error) -> Self {
+        Self::parse(e)
+    }
+}
+
+impl From<csv::Error> for ArchiveError {
+    fn from(e: csv::Error) -> Self {
+        Self::parse(e)
+    }
+}
+
+#[cfg(feature = "arrow_rs")]
+impl From<SnapshotError> for ArchiveError {
+    fn from(e: SnapshotError) -> Self {
+        match e {
+            SnapshotError::MissingFactory(name) => Self::MissingFactory(name),
+            SnapshotError::InvalidEntityID(id) => {
+                Self::Remap(format!("failed to resolve entity id: {id}"))
+            }
+            SnapshotError::MissingComponent(name) => {
+                Self::Schema(format!("unexpected null component {name}"))
+            }
+            SnapshotError::Limit(e) => e.into(),
+            SnapshotError::Container(e) => e.into(),
+            SnapshotError::Checksum(e) => Self::Checksum(e),
+            e => Self::parse(e),
+        }
+    }
+}
+
+impl From<ArchiveError> for String {
+    fn from(e: ArchiveError) -> Self {
+        e.to_string()
+    }
+}
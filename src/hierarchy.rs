@@ -0,0 +1,171 @@
+//! Repair of [`ChildOf`] / [`Children`] hierarchies after a load.
+//!
+//! A snapshot that stores [`ChildOf`] but not [`Children`], or the other way around, or a
+//! merge that only loads part of a hierarchy, can leave the two sides disagreeing.
+//! [`repair_hierarchy`] treats [`ChildOf`] as the truth: it removes the ones pointing at a
+//! missing entity, reporting those children as orphans, and rebuilds every [`Children`]
+//! list that does not match, keeping the stored order of the children it already listed.
+//!
+//! Set [`SnapshotRegistry::repair_hierarchy`] and the loaders run it after every load,
+//! recording what they fixed in the [`HierarchyReport`] resource.
+
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::relationship::{RelationshipHookMode, RelationshipTarget};
+
+use crate::bevy_registry::SnapshotRegistry;
+
+/// What [`repair_hierarchy`] fixed.
+///
+/// Loads that fix something insert it as a resource, or add to the one already there.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct HierarchyReport {
+    /// Parents whose [`Children`] were rebuilt from the [`ChildOf`] of their children.
+    pub rebuilt: Vec<Entity>,
+    /// Entities whose [`ChildOf`] pointed at a missing entity or at themselves, and was
+    /// removed.
+    pub orphans: Vec<Entity>,
+}
+
+impl HierarchyReport {
+    pub fn is_clean(&self) -> bool {
+        self.rebuilt.is_empty() && self.orphans.is_empty()
+    }
+
+    fn record(self, world: &mut World) {
+        match world.get_resource_mut::<HierarchyReport>() {
+            Some(mut report) => {
+                report.rebuilt.extend(self.rebuilt);
+                report.orphans.extend(self.orphans);
+            }
+            None if !self.is_clean() => world.insert_resource(self),
+            None => {}
+        }
+    }
+}
+
+/// Make every [`Children`] of `world` list exactly the entities whose [`ChildOf`] points
+/// at its parent, dropping [`ChildOf`] links to missing entities first.
+pub fn repair_hierarchy(world: &mut World) -> HierarchyReport {
+    let mut report = HierarchyReport::default();
+    let links: Vec<(Entity, Entity)> = world
+        .query::<(Entity, &ChildOf)>()
+        .iter(world)
+        .map(|(child, child_of)| (child, child_of.parent()))
+        .collect();
+    let mut expected: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (child, parent) in links {
+        if child == parent || world.get_entity(parent).is_err() {
+            world.entity_mut(child).remove::<ChildOf>();
+            report.orphans.push(child);
+        } else {
+            expected.entry(parent).or_default().push(child);
+        }
+    }
+
+    let listed: Vec<(Entity, Vec<Entity>)> = world
+        .query::<(Entity, &Children)>()
+        .iter(world)
+        .map(|(parent, children)| (parent, children.to_vec()))
+        .collect();
+    for (parent, children) in listed {
+        let mut wanted = expected.remove(&parent).unwrap_or_default();
+        // Keep the stored order; children missing from the stored list go last.
+        wanted.sort_by_key(|child| {
+            children
+                .iter()
+                .position(|c| c == child)
+                .unwrap_or(usize::MAX)
+        });
+        if wanted != children {
+            set_children(world, parent, wanted);
+            report.rebuilt.push(parent);
+        }
+    }
+    for (parent, children) in expected {
+        set_children(world, parent, children);
+        report.rebuilt.push(parent);
+    }
+    report.rebuilt.sort();
+    report.orphans.sort();
+    report
+}
+
+/// Overwrite the [`Children`] of `parent`. The relationship hooks are skipped, as they
+/// would edit the [`ChildOf`] side, which is already right.
+fn set_children(world: &mut World, parent: Entity, children: Vec<Entity>) {
+    let empty = children.is_empty();
+    let mut parent = world.entity_mut(parent);
+    parent.insert_with_relationship_hook_mode(
+        Children::from_collection_risky(children),
+        RelationshipHookMode::Skip,
+    );
+    if empty {
+        // The collection is empty now, so the removal hook has no child to unlink.
+        parent.remove::<Children>();
+    }
+}
+
+/// Run [`repair_hierarchy`] after a load when `reg` asks for it.
+pub(crate) fn repair_after_load(world: &mut World, reg: &SnapshotRegistry) {
+    if reg.repair_hierarchy {
+        repair_hierarchy(world).record(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn children(world: &World, parent: Entity) -> Vec<Entity> {
+        world
+            .get::<Children>(parent)
+            .map(|c| c.to_vec())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_repair_hierarchy() {
+        let mut world = World::new();
+        let root = world.spawn_empty().id();
+        let a = world.spawn(ChildOf(root)).id();
+        let b = world.spawn(ChildOf(root)).id();
+        let stray = world.spawn_empty().id();
+        let gone = world.spawn_empty().id();
+        world.despawn(gone);
+        let lone = world.spawn_empty().id();
+        assert!(repair_hierarchy(&mut world).is_clean());
+
+        // A loader writing both sides without hooks: a stale entry under `root`, a
+        // reordered child list, and a link to an entity that was never loaded.
+        let skip = RelationshipHookMode::Skip;
+        world.entity_mut(root).insert_with_relationship_hook_mode(
+            Children::from_collection_risky(vec![b, stray, a]),
+            skip,
+        );
+        world
+            .entity_mut(lone)
+            .insert_with_relationship_hook_mode(ChildOf(gone), skip);
+        let c = world.spawn_empty().id();
+        world
+            .entity_mut(c)
+            .insert_with_relationship_hook_mode(ChildOf(a), skip);
+
+        let mut reg = SnapshotRegistry::default();
+        repair_after_load(&mut world, &reg);
+        assert!(world.get_resource::<HierarchyReport>().is_none());
+        reg.repair_hierarchy = true;
+        repair_after_load(&mut world, &reg);
+
+        let report = world.resource::<HierarchyReport>();
+        let mut rebuilt = vec![root, a];
+        rebuilt.sort();
+        assert_eq!(report.rebuilt, rebuilt);
+        assert_eq!(report.orphans, [lone]);
+        assert_eq!(children(&world, root), [b, a]);
+        assert_eq!(children(&world, a), [c]);
+        assert!(world.get::<ChildOf>(lone).is_none());
+        assert!(world.get::<ChildOf>(stray).is_none());
+    }
+}
@@ -0,0 +1,567 @@
+//! Append-only journal of world changes between full snapshots.
+//!
+//! A [`JournalRecorder`] is filled during a frame the same way a
+//! [`HarvardCommandBuffer`](crate::bevy_cmdbuffer::HarvardCommandBuffer) is: by naming the
+//! entities and registered types that were inserted, removed or despawned. At the end of
+//! the frame [`JournalRecorder::flush`] exports the current values through the registry's
+//! JSON codecs and appends them as one timestamped [`JournalFrame`] to a [`JournalWriter`].
+//!
+//! Crash recovery loads the last full snapshot and then calls [`replay_journal`] with the
+//! journal written since. Start a new journal file after each full save.
+//!
+//! On disk every frame is a little-endian `u32` length followed by a named msgpack record.
+//! A record cut short by a crash is ignored by [`read_journal`].
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy_ecs::entity::EntityIndex;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::bevy_registry::{
+    LimitError, LoadLimits, SnapshotRegistry, sort_by_required_components, try_reserve_entity_slots,
+};
+use crate::entity_archive::ComponentSnapshot;
+use crate::serde_utils::entity_to_index;
+
+#[derive(Debug, thiserror::Error)]
+pub enum JournalError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("type '{0}' is not registered")]
+    UnknownType(String),
+    #[error("journal encode error: {0}")]
+    Encode(String),
+    #[error("journal decode error in frame {frame}: {message}")]
+    Decode { frame: usize, message: String },
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    #[error("replay of frame {frame} failed: {message}")]
+    Apply { frame: u64, message: String },
+}
+
+/// One recorded change. Entities are stored by index, like every other snapshot format.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum JournalOp {
+    Insert {
+        entity: u32,
+        components: Vec<ComponentSnapshot>,
+    },
+    Remove {
+        entity: u32,
+        types: Vec<String>,
+    },
+    Despawn {
+        entity: u32,
+    },
+    InsertResource(ComponentSnapshot),
+    RemoveResource {
+        r#type: String,
+    },
+}
+
+/// All changes recorded during one frame.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JournalFrame {
+    pub frame: u64,
+    /// Milliseconds since the Unix epoch when the frame was flushed.
+    pub timestamp_ms: u64,
+    pub ops: Vec<JournalOp>,
+}
+
+#[derive(Debug)]
+enum PendingOp {
+    Insert(Entity, Vec<String>),
+    Remove(Entity, Vec<String>),
+    Despawn(Entity),
+    InsertResource(String),
+    RemoveResource(String),
+}
+
+/// Collects the changes of a frame until [`flush`](Self::flush) serializes them.
+///
+/// Values are not captured when an op is recorded: `flush` exports whatever the world holds
+/// at that point, so record after (or instead of) queueing the real commands.
+#[derive(Debug, Default)]
+pub struct JournalRecorder {
+    pending: Vec<PendingOp>,
+    frame: u64,
+}
+
+impl JournalRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of the next frame to be flushed.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Record that the registered component `type_name` was inserted on or changed for `entity`.
+    ///
+    /// Consecutive inserts on the same entity are combined into one op.
+    pub fn insert(&mut self, entity: Entity, type_name: &str) {
+        if let Some(PendingOp::Insert(last, types)) = self.pending.last_mut()
+            && *last == entity
+        {
+            if !types.iter().any(|t| t == type_name) {
+                types.push(type_name.to_string());
+            }
+            return;
+        }
+        self.pending
+            .push(PendingOp::Insert(entity, vec![type_name.to_string()]));
+    }
+
+    pub fn remove(&mut self, entity: Entity, type_name: &str) {
+        if let Some(PendingOp::Remove(last, types)) = self.pending.last_mut()
+            && *last == entity
+        {
+            types.push(type_name.to_string());
+            return;
+        }
+        self.pending
+            .push(PendingOp::Remove(entity, vec![type_name.to_string()]));
+    }
+
+    pub fn despawn(&mut self, entity: Entity) {
+        self.pending.push(PendingOp::Despawn(entity));
+    }
+
+    pub fn insert_resource(&mut self, type_name: &str) {
+        self.pending
+            .push(PendingOp::InsertResource(type_name.to_string()));
+    }
+
+    pub fn remove_resource(&mut self, type_name: &str) {
+        self.pending
+            .push(PendingOp::RemoveResource(type_name.to_string()));
+    }
+
+    /// Discard the recorded ops without writing them.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Export the recorded ops from `world` and append them as one frame.
+    ///
+    /// Nothing is written when no ops were recorded, but the frame counter still advances.
+    /// Inserts whose entity or component no longer exists are dropped; the despawn or
+    /// remove that caused it is recorded separately.
+    ///
+    /// Fails with [`JournalError::UnknownType`] before anything is consumed when an insert
+    /// names an unregistered type: the ops and the frame number are kept, so fix the
+    /// registry or [`reset`](Self::reset) the recorder.
+    pub fn flush<W: Write>(
+        &mut self,
+        world: &World,
+        registry: &SnapshotRegistry,
+        writer: &mut JournalWriter<W>,
+    ) -> Result<(), JournalError> {
+        self.check_types(registry)?;
+        let frame = self.frame;
+        self.frame += 1;
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut ops = Vec::with_capacity(self.pending.len());
+        for op in self.pending.drain(..) {
+            match op {
+                PendingOp::Insert(entity, types) => {
+                    if world.get_entity(entity).is_err() {
+                        continue;
+                    }
+                    let mut components = Vec::with_capacity(types.len());
+                    for name in types {
+                        // Checked by `check_types`.
+                        let Some(factory) = registry.get_factory(&name) else {
+                            continue;
+                        };
+                        if let Some(value) = (factory.js_value.export)(world, entity) {
+                            components.push(ComponentSnapshot {
+                                r#type: name,
+                                value,
+                            });
+                        }
+                    }
+                    if !components.is_empty() {
+                        ops.push(JournalOp::Insert {
+                            entity: entity_to_index(&entity),
+                            components,
+                        });
+                    }
+                }
+                PendingOp::Remove(entity, types) => ops.push(JournalOp::Remove {
+                    entity: entity_to_index(&entity),
+                    types,
+                }),
+                PendingOp::Despawn(entity) => ops.push(JournalOp::Despawn {
+                    entity: entity_to_index(&entity),
+                }),
+                PendingOp::InsertResource(name) => {
+                    let Some(factory) = registry.get_res_factory(&name) else {
+                        continue;
+                    };
+                    let placeholder = Entity::from_raw_u32(0).unwrap();
+                    if let Some(value) = (factory.js_value.export)(world, placeholder) {
+                        ops.push(JournalOp::InsertResource(ComponentSnapshot {
+                            r#type: name,
+                            value,
+                        }));
+                    }
+                }
+                PendingOp::RemoveResource(name) => {
+                    ops.push(JournalOp::RemoveResource { r#type: name })
+                }
+            }
+        }
+
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        writer.append(&JournalFrame {
+            frame,
+            timestamp_ms,
+            ops,
+        })
+    }
+
+    /// Fail on the first insert whose type `registry` cannot export.
+    fn check_types(&self, registry: &SnapshotRegistry) -> Result<(), JournalError> {
+        for op in &self.pending {
+            match op {
+                PendingOp::Insert(_, types) => {
+                    if let Some(name) = types.iter().find(|t| registry.get_factory(t).is_none()) {
+                        return Err(JournalError::UnknownType(name.clone()));
+                    }
+                }
+                PendingOp::InsertResource(name) if registry.get_res_factory(name).is_none() => {
+                    return Err(JournalError::UnknownType(name.clone()));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Appends length-prefixed [`JournalFrame`] records to a byte sink.
+pub struct JournalWriter<W: Write> {
+    inner: W,
+}
+
+impl JournalWriter<File> {
+    /// Open `path` for appending, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, JournalError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(file))
+    }
+
+    /// Flush the OS buffers of the journal file to disk.
+    pub fn sync(&mut self) -> Result<(), JournalError> {
+        self.inner.sync_data()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> JournalWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Write one frame as a single record.
+    pub fn append(&mut self, frame: &JournalFrame) -> Result<(), JournalError> {
+        let body =
+            rmp_serde::to_vec_named(frame).map_err(|e| JournalError::Encode(e.to_string()))?;
+        let len = u32::try_from(body.len())
+            .map_err(|_| JournalError::Encode("frame larger than 4 GiB".to_string()))?;
+        let mut record = Vec::with_capacity(4 + body.len());
+        record.extend_from_slice(&len.to_le_bytes());
+        record.extend_from_slice(&body);
+        self.inner.write_all(&record)?;
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Read every complete frame from `reader`.
+///
+/// A truncated last record (a crash during [`JournalWriter::append`]) ends the journal
+/// without an error. Records larger than `limits.max_blob_bytes` are rejected.
+pub fn read_journal(
+    mut reader: impl Read,
+    limits: &LoadLimits,
+) -> Result<Vec<JournalFrame>, JournalError> {
+    let mut frames = Vec::new();
+    loop {
+        let mut len = [0u8; 4];
+        match read_full(&mut reader, &mut len)? {
+            0 => break,
+            n if n < len.len() => break,
+            _ => {}
+        }
+        let len = u32::from_le_bytes(len) as u64;
+        limits.check_blob(len)?;
+        let mut body = Vec::new();
+        (&mut reader).take(len).read_to_end(&mut body)?;
+        if (body.len() as u64) < len {
+            break;
+        }
+        let frame = rmp_serde::from_slice(&body).map_err(|e| JournalError::Decode {
+            frame: frames.len(),
+            message: e.to_string(),
+        })?;
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Apply `frames` in order on top of a world restored from the base snapshot.
+///
+/// Entity slots up to the highest inserted index are reserved first, as the snapshot
+/// loaders do; components of one insert are applied in required-component order.
+pub fn replay_journal(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    frames: &[JournalFrame],
+) -> Result<(), JournalError> {
+    let max_id = frames
+        .iter()
+        .flat_map(|f| &f.ops)
+        .filter_map(|op| match op {
+            JournalOp::Insert { entity, .. } => Some(*entity),
+            _ => None,
+        })
+        .max();
+    if let Some(max_id) = max_id {
+        try_reserve_entity_slots(world, max_id, &registry.limits)?;
+        world.flush();
+    }
+
+    for frame in frames {
+        let fail = |message: String| JournalError::Apply {
+            frame: frame.frame,
+            message,
+        };
+        for op in &frame.ops {
+            match op {
+                JournalOp::Insert { entity, components } => {
+                    let entity = spawned_entity(world, *entity).map_err(fail)?;
+                    let mut comps: Vec<_> = components
+                        .iter()
+                        .map(|c| {
+                            let id = registry.get_factory(&c.r#type).map(|_| {
+                                registry
+                                    .comp_id_by_name(&c.r#type, world)
+                                    .unwrap_or_else(|| registry.reg_by_name(&c.r#type, world))
+                            });
+                            (id, c)
+                        })
+                        .collect();
+                    sort_by_required_components(world, &mut comps, |(id, _)| *id);
+                    for (_, c) in comps {
+                        let factory = registry
+                            .get_factory(&c.r#type)
+                            .ok_or_else(|| JournalError::UnknownType(c.r#type.clone()))?;
+                        (factory.js_value.import)(&c.value, world, entity).map_err(fail)?;
+                    }
+                }
+                JournalOp::Remove { entity, types } => {
+                    let entity = resolve(world, *entity).map_err(fail)?;
+                    let Ok(mut e) = world.get_entity_mut(entity) else {
+                        continue;
+                    };
+                    for name in types {
+                        let factory = registry
+                            .get_factory(name)
+                            .ok_or_else(|| JournalError::UnknownType(name.clone()))?;
+                        if let Some(id) = (factory.comp_id)(e.world()) {
+                            e.remove_by_id(id);
+                        }
+                    }
+                }
+                JournalOp::Despawn { entity } => {
+                    let entity = resolve(world, *entity).map_err(fail)?;
+                    if world.get_entity(entity).is_ok() {
+                        world.despawn(entity);
+                    }
+                }
+                JournalOp::InsertResource(res) => {
+                    let factory = registry
+                        .get_res_factory(&res.r#type)
+                        .ok_or_else(|| JournalError::UnknownType(res.r#type.clone()))?;
+                    let placeholder = Entity::from_raw_u32(0).unwrap();
+                    (factory.js_value.import)(&res.value, world, placeholder).map_err(fail)?;
+                }
+                JournalOp::RemoveResource { r#type } => {
+                    let factory = registry
+                        .get_res_factory(r#type)
+                        .ok_or_else(|| JournalError::UnknownType(r#type.clone()))?;
+                    if let Some(id) = (factory.comp_id)(world) {
+                        world.remove_resource_by_id(id);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// [`read_journal`] from a file followed by [`replay_journal`], using the registry's limits.
+pub fn replay_journal_file(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    path: impl AsRef<Path>,
+) -> Result<(), JournalError> {
+    let file = File::open(path)?;
+    let frames = read_journal(std::io::BufReader::new(file), &registry.limits)?;
+    replay_journal(world, registry, &frames)
+}
+
+fn resolve(world: &World, index: u32) -> Result<Entity, String> {
+    let index =
+        EntityIndex::from_raw_u32(index).ok_or_else(|| format!("invalid entity index {index}"))?;
+    Ok(world.entities().resolve_from_index(index))
+}
+
+/// The live entity at `index`, respawning the slot if an earlier op despawned it.
+fn spawned_entity(world: &mut World, index: u32) -> Result<Entity, String> {
+    let entity = resolve(world, index)?;
+    if world.get_entity(entity).is_err() {
+        world
+            .spawn_empty_at(entity)
+            .map_err(|e| format!("cannot respawn entity {index}: {e}"))?;
+    }
+    Ok(entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_archive::{load_world_snapshot, save_world_snapshot};
+
+    #[derive(Component, Serialize, Deserialize, Debug, PartialEq)]
+    struct Pos(i32, i32);
+    #[derive(Component, Serialize, Deserialize, Debug, PartialEq)]
+    struct Name(String);
+    #[derive(Resource, Serialize, Deserialize, Debug, PartialEq)]
+    struct Tick(u64);
+
+    fn registry() -> SnapshotRegistry {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Pos>();
+        registry.register::<Name>();
+        registry.resource_register::<Tick>();
+        registry
+    }
+
+    #[test]
+    fn test_journal_replay() {
+        let registry = registry();
+        let mut world = World::new();
+        let a = world.spawn(Pos(0, 0)).id();
+        let b = world.spawn((Pos(1, 1), Name("b".into()))).id();
+        let base = save_world_snapshot(&world, &registry);
+
+        let mut recorder = JournalRecorder::new();
+        let mut writer = JournalWriter::new(Vec::new());
+
+        // Frame 0: move `a`, spawn `c`, set a resource.
+        world.entity_mut(a).insert(Pos(5, 5));
+        recorder.insert(a, "Pos");
+        let c = world.spawn((Pos(9, 9), Name("c".into()))).id();
+        recorder.insert(c, "Pos");
+        recorder.insert(c, "Name");
+        world.insert_resource(Tick(1));
+        recorder.insert_resource("Tick");
+        recorder.flush(&world, &registry, &mut writer).unwrap();
+
+        // Frame 1 is empty and writes nothing.
+        recorder.flush(&world, &registry, &mut writer).unwrap();
+
+        // Frame 2: strip `b`'s name and despawn `a`.
+        world.entity_mut(b).remove::<Name>();
+        recorder.remove(b, "Name");
+        world.despawn(a);
+        recorder.despawn(a);
+        recorder.flush(&world, &registry, &mut writer).unwrap();
+
+        let mut bytes = writer.into_inner();
+        // A crash in the middle of the next append leaves a partial record behind.
+        bytes.extend_from_slice(&[200, 0, 0, 0, 1, 2]);
+
+        let frames = read_journal(bytes.as_slice(), &LoadLimits::default()).unwrap();
+        assert_eq!(
+            frames.iter().map(|f| f.frame).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+
+        let mut restored = World::new();
+        load_world_snapshot(&mut restored, &base, &registry).unwrap();
+        replay_journal(&mut restored, &registry, &frames).unwrap();
+
+        let at = |w: &World, e: Entity| w.entities().resolve_from_index(e.index());
+        assert!(restored.get_entity(at(&restored, a)).is_err());
+        assert_eq!(restored.get::<Pos>(at(&restored, b)), Some(&Pos(1, 1)));
+        assert_eq!(restored.get::<Name>(at(&restored, b)), None);
+        assert_eq!(restored.get::<Pos>(at(&restored, c)), Some(&Pos(9, 9)));
+        assert_eq!(
+            restored.get::<Name>(at(&restored, c)),
+            Some(&Name("c".into()))
+        );
+        assert_eq!(restored.get_resource::<Tick>(), Some(&Tick(1)));
+
+        let tight = LoadLimits {
+            max_blob_bytes: 4,
+            ..LoadLimits::default()
+        };
+        assert!(matches!(
+            read_journal(bytes.as_slice(), &tight),
+            Err(JournalError::Limit(_))
+        ));
+    }
+
+    #[test]
+    fn test_flush_unknown_type_keeps_ops() {
+        let registry = registry();
+        let mut world = World::new();
+        let a = world.spawn(Pos(1, 2)).id();
+        let mut recorder = JournalRecorder::new();
+        let mut writer = JournalWriter::new(Vec::new());
+
+        recorder.insert(a, "Pos");
+        recorder.insert_resource("Missing");
+        let err = recorder.flush(&world, &registry, &mut writer);
+        assert!(matches!(err, Err(JournalError::UnknownType(ref name)) if name == "Missing"));
+        assert_eq!((recorder.len(), recorder.frame()), (2, 0));
+        assert!(writer.into_inner().is_empty());
+    }
+}
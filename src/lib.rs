@@ -43,6 +43,10 @@
 //! > function dispatches to the Arrow loader automatically — no separate code path needed.
 //! > See `examples/hybrid_zip.rs`.
 //!
+//! > **Zip archives:** the `zip` feature (implied by `arrow_rs`) enables
+//! > [`ZipBlobLoader`](aurora_archive::ZipBlobLoader), so manifests whose CSV/JSON/msgpack
+//! > blobs live inside a zip can be loaded without pulling in Arrow.
+//!
 //! ## Quick Start
 //!
 //! ```rust
@@ -181,10 +185,42 @@
 //! | [`archetype_archive`] | Core save/load engine: `ArchetypeSnapshot`, `WorldArchSnapshot` |
 //! | [`aurora_archive`] | Aurora manifest format (JSON/TOML + CSV embedding) |
 //! | [`entity_archive`] | Legacy per-entity JSON snapshot |
-//! | [`bevy_registry`] | `SnapshotRegistry`, `IDRemapRegistry`, `reserve_entity_slots` |
+//! | [`any_format`] | `load_any` — load a snapshot file of any format, detected from its magic bytes, extension or content |
+//! | [`checksum`] | BLAKE3 checksums of manifest blobs and snapshot zip entries, verified on load with a `ChecksumMismatch` error |
+//! | [`error`] | `ArchiveError` — IO / parse / missing factory / schema / remap failures of the formats |
+//! | [`bevy_registry`] | `SnapshotRegistry`, named `SnapshotRegistries`, `IDRemapRegistry`, `reserve_entity_slots`, runtime-defined `dynamic` components, `Reflect`-only components (requires `reflect`) |
+//! | [`save_profile`] | `SaveProfile` whitelist/blacklist of components, resources and groups applied on save |
+//! | [`scrub`] | `Scrubber` — per-component `on_export` transformers that hash or redact values for bug-report saves |
 //! | [`serde_utils`] | `entity_to_index`, `entity_from_index`, serde helpers |
 //! | [`bevy_cmdbuffer`] | `HarvardCommandBuffer` — low-level write engine |
-//! | [`binary_archive`] | MessagePack and Arrow/Parquet backends |
+//! | [`crash_snapshot`] | Opt-in panic guard that dumps the world to `crash_snapshot_<ts>.bin` |
+//! | [`scenario`] | `run_scenario` — headless manifest → N ticks → snapshot runs for regression tests |
+//! | [`entity_name`] | `Name` registration, name columns and merging snapshots by name |
+//! | [`diff_archive`] | `diff_world_snapshots` / `apply_patch` — structural snapshot diffs for incremental saves |
+//! | [`undo`] | `UndoRedo` stacks of forward/reverse snapshot diffs for editors |
+//! | [`dirty`] | `SnapshotDirty` change tracking so autosaves can skip unchanged worlds |
+//! | [`world_clone`] | `clone_registered` — copy registered state between live worlds without serializing |
+//! | [`unknown_components`] | Opt-in side-table that keeps unregistered component columns for re-export |
+//! | [`versioned`] | `Versioned<Old, New>` — load components saved before their shape changed |
+//! | [`entity_index`] | Entity id → archetype/row index stored in zip meta and manifest metadata |
+//! | [`load_events`] | Opt-in `EntityLoadedFromSnapshot` / `SnapshotApplied` observer events fired by loaders |
+//! | [`journal`] | Append-only per-frame change journal replayed on top of a snapshot |
+//! | [`container`] | `SnapshotContainer` — directory / zip / tar / in-memory entry stores |
+//! | [`bundle`] | Portable saves: world manifest plus required asset hashes and optionally the assets, verified at load |
+//! | [`hierarchy`] | Opt-in `ChildOf` / `Children` repair after loads, with a `HierarchyReport` of rebuilt parents and orphans |
+//! | [`snapshot_origin`] | Opt-in `SnapshotOrigin` component recording the source and saved id of loaded entities |
+//! | [`scene_stack`] | Snapshots loaded as named layers with a `SceneLayer` provenance component, unloaded or replaced one at a time |
+//! | [`registry_schema`] | Registered names, modes and fields saved with snapshots and checked before loading |
+//! | [`dual_write`] | `DualWrite` — one world saved in two formats at once, both verified to load back before either file is replaced |
+//! | [`multi_world`] | Several worlds, e.g. simulation and presentation, saved as named sections of one manifest or container |
+//! | `scene_interop` | `WorldArchSnapshot` ⇄ Bevy `DynamicWorld` / `.scn.ron` (requires `bevy_scene`) |
+//! | `snapshot_uuid` | Opt-in `SnapshotUuid` identity and `uuid → Entity` index for diffing and merging (requires `uuid`) |
+//! | `manifest_signing` | ed25519 signatures on Aurora manifests, checked by `from_file` and against a trusted key by `from_file_verified` (requires `signing`) |
+//! | `encrypted_archive` | `EncryptedArchive` — archives sealed with ChaCha20-Poly1305 under an `ArchiveKey` and verified on load (requires `encryption`) |
+//! | `checkpoint` | `CheckpointManager` ring buffer and tick-keyed `RollbackBuffer` of in-memory snapshots (requires `arrow_rs`) |
+//! | `auto_register` | `#[derive(Snapshot)]` and `SnapshotRegistry::with_auto_registered` (requires `derive`) |
+//! | `plugin` | `SnapshotPlugin` serving `SaveWorldRequest` / `LoadWorldRequest` messages, `Autosave`, and `ApplySnapshotCommand` / `apply_snapshot_system` (requires `bevy_app`) |
+//! | [`binary_archive`] | MessagePack and Arrow/Parquet backends, `diff_zip` / `apply_zip_patch` for syncing snapshot zips, `write_dataset` for partitioned Parquet datasets |
 //!
 //! ## Examples
 //!
@@ -195,11 +231,39 @@
 //! - `hybrid_zip.rs` — Parquet + CSV hybrid archive (requires `arrow_rs`)
 
 #![allow(unexpected_cfgs)]
+#[macro_use]
+mod trace;
+
+pub mod any_format;
 pub mod archetype_archive;
 pub mod aurora_archive;
 pub mod bevy_registry;
+pub mod bundle;
+pub mod checksum;
+pub mod container;
+pub mod crash_snapshot;
 pub mod csv_archive;
+pub mod diff_archive;
+pub mod dirty;
+pub mod dual_write;
 pub mod entity_archive;
+pub mod entity_index;
+pub mod error;
+pub mod entity_name;
+pub mod hierarchy;
+pub mod journal;
+pub mod load_events;
+pub mod multi_world;
+pub mod registry_schema;
+pub mod save_profile;
+pub mod scenario;
+pub mod scene_stack;
+pub mod scrub;
+pub mod snapshot_origin;
+pub mod undo;
+pub mod unknown_components;
+pub mod versioned;
+pub mod world_clone;
 
 pub mod binary_archive;
 pub mod bevy_cmdbuffer;
@@ -213,17 +277,74 @@ pub mod flecs_registry;
 
 #[cfg(feature = "arrow_rs")]
 pub mod arrow_snapshot;
-
 #[cfg(feature = "arrow_rs")]
+pub mod checkpoint;
+
+#[cfg(feature = "encryption")]
+pub mod encrypted_archive;
+#[cfg(feature = "signing")]
+pub mod manifest_signing;
+#[cfg(feature = "uuid")]
+pub mod snapshot_uuid;
+
+#[cfg(feature = "bevy_scene")]
+pub mod scene_interop;
+
+#[cfg(feature = "bevy_app")]
+pub mod plugin;
+
+#[cfg(feature = "derive")]
+pub mod auto_register;
+// Lets `#[derive(Snapshot)]` output name `::bevy_archive` inside this crate too.
+#[cfg(feature = "derive")]
+extern crate self as bevy_archive;
+
+pub use any_format::load_any;
+#[cfg(feature = "zip")]
 pub use zip;
 
 pub mod prelude {
+    pub use crate::any_format::*;
     pub use crate::aurora_archive::*;
     pub use crate::bevy_registry::*;
+    pub use crate::bundle::*;
+    pub use crate::checksum::*;
+    #[cfg(feature = "derive")]
+    pub use crate::auto_register::Snapshot;
+    #[cfg(feature = "arrow_rs")]
+    pub use crate::checkpoint::*;
+    pub use crate::container::*;
+    pub use crate::diff_archive::*;
+    pub use crate::dirty::*;
+    pub use crate::dual_write::*;
+    #[cfg(feature = "encryption")]
+    pub use crate::encrypted_archive::*;
+    #[cfg(feature = "signing")]
+    pub use crate::manifest_signing::*;
     #[cfg(feature = "flecs")]
     pub use crate::flecs_registry;
 
     pub use crate::entity_archive::*;
+    pub use crate::entity_name::*;
+    pub use crate::error::*;
+    pub use crate::hierarchy::*;
+    pub use crate::journal::*;
+    pub use crate::load_events::*;
+    pub use crate::multi_world::*;
+    pub use crate::registry_schema::*;
+    #[cfg(feature = "bevy_app")]
+    pub use crate::plugin::*;
+    pub use crate::save_profile::*;
+    pub use crate::scenario::*;
+    pub use crate::scene_stack::*;
+    pub use crate::scrub::*;
+    pub use crate::snapshot_origin::*;
+    #[cfg(feature = "uuid")]
+    pub use crate::snapshot_uuid::*;
     pub use crate::serde_utils::*;
     pub use crate::traits::*;
+    pub use crate::undo::*;
+    pub use crate::unknown_components::*;
+    pub use crate::versioned::*;
+    pub use crate::world_clone::*;
 }
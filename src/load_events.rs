@@ -0,0 +1,120 @@
+//! Observer events fired when a snapshot is loaded.
+//!
+//! Set [`SnapshotRegistry::emit_load_events`] and the loaders trigger an
+//! [`EntityLoadedFromSnapshot`] for every entity they wrote, followed by one
+//! [`SnapshotApplied`] for the whole load. Game code can react with
+//! [`World::add_observer`] (or `App::add_observer` under `bevy_app`) instead of polling for
+//! newly added components.
+//!
+//! Events are batched: they fire after every archetype of the snapshot was applied, so an
+//! observer of one entity already sees the components of all the others.
+
+use bevy_ecs::prelude::*;
+
+use crate::bevy_registry::SnapshotRegistry;
+use crate::hierarchy::repair_after_load;
+use crate::snapshot_origin::insert_origins;
+
+/// An entity of a snapshot was loaded into the world.
+#[derive(EntityEvent, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityLoadedFromSnapshot {
+    /// The world entity the row was written to.
+    pub entity: Entity,
+    /// Entity id as stored in the snapshot.
+    pub snapshot_id: u32,
+}
+
+/// A snapshot load finished.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotApplied {
+    /// Number of entities the load wrote to.
+    pub entities: usize,
+}
+
+/// Repair the [hierarchy](crate::hierarchy), insert the [origins](crate::snapshot_origin)
+/// of `loaded` (snapshot id, world entity) pairs and trigger their load events, as far as
+/// `reg` asks for them.
+pub(crate) fn trigger_load_events(
+    world: &mut World,
+    reg: &SnapshotRegistry,
+    loaded: &[(u32, Entity)],
+) {
+    repair_after_load(world, reg);
+    insert_origins(world, reg, loaded);
+    if !reg.emit_load_events {
+        return;
+    }
+    for &(snapshot_id, entity) in loaded {
+        if world.get_entity(entity).is_ok() {
+            world.trigger(EntityLoadedFromSnapshot {
+                entity,
+                snapshot_id,
+            });
+        }
+    }
+    world.trigger(SnapshotApplied {
+        entities: loaded.len(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetype_archive::{load_world_arch_snapshot, save_world_arch_snapshot};
+    use crate::aurora_archive::{load_world_manifest, save_world_manifest};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hp(u32);
+
+    #[derive(Resource, Default)]
+    struct Seen {
+        entities: Vec<(u32, u32)>,
+        applied: Vec<usize>,
+    }
+
+    fn observed_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Seen>();
+        world.add_observer(
+            |ev: On<EntityLoadedFromSnapshot>, hp: Query<&Hp>, mut seen: ResMut<Seen>| {
+                let hp = hp.get(ev.entity).unwrap();
+                seen.entities.push((ev.snapshot_id, hp.0));
+            },
+        );
+        world.add_observer(|ev: On<SnapshotApplied>, mut seen: ResMut<Seen>| {
+            seen.applied.push(ev.entities);
+        });
+        world
+    }
+
+    #[test]
+    fn test_load_events_fire_once_per_load() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        let mut source = World::new();
+        let a = source.spawn(Hp(1)).id();
+        let b = source.spawn(Hp(2)).id();
+        let snapshot = save_world_arch_snapshot(&source, &registry);
+        let manifest = save_world_manifest(&source, &registry).unwrap();
+
+        // Off by default.
+        let mut quiet = observed_world();
+        load_world_arch_snapshot(&mut quiet, &snapshot, &registry).unwrap();
+        assert!(quiet.resource::<Seen>().applied.is_empty());
+
+        registry.emit_load_events = true;
+        let mut world = observed_world();
+        load_world_arch_snapshot(&mut world, &snapshot, &registry).unwrap();
+        let mut manifest_world = observed_world();
+        load_world_manifest(&mut manifest_world, &manifest, &registry).unwrap();
+
+        for world in [&mut world, &mut manifest_world] {
+            let seen = world.resource_mut::<Seen>();
+            let mut entities = seen.entities.clone();
+            entities.sort();
+            assert_eq!(entities, vec![(a.index_u32(), 1), (b.index_u32(), 2)]);
+            assert_eq!(seen.applied, vec![2]);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! ed25519 signatures on Aurora manifests, so distributed content packs can be
+//! authenticated before they are loaded into a world (requires `signing`).
+//!
+//! [`AuroraWorldManifest::sign`] stores the signature and the signer's public key in
+//! `metadata` under [`MANIFEST_SIGNATURE_KEY`]. The signature covers the rest of the
+//! metadata and the world, including embedded blobs; files outside the manifest are
+//! covered through their [blob checksums](crate::checksum), which the loaders verify.
+//!
+//! A signature only authenticates a manifest against a key the caller trusts, so
+//! [`AuroraWorldManifest::from_file`] does not check it: the key named in the manifest
+//! could have been swapped along with the content. Load untrusted packs with
+//! [`from_file_verified`](AuroraWorldManifest::from_file_verified), or call
+//! [`AuroraWorldManifest::verify`] on a manifest read some other way.
+
+use std::collections::BTreeMap;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::{Signature, Signer, Verifier};
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::aurora_archive::{AuroraWorldManifest, ManifestOutputFormat, WorldWithAurora};
+use crate::error::ArchiveError;
+
+/// Manifest metadata key of the signature, an object holding the base64 `public_key` of
+/// the signer and the `ed25519` signature.
+pub const MANIFEST_SIGNATURE_KEY: &str = "signature";
+/// Prefix of the signed bytes, so a manifest signature cannot be replayed as another.
+const SIGNING_CONTEXT: &[u8] = b"bevy_archive manifest\0";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignatureError {
+    #[error("manifest is not signed")]
+    Unsigned,
+    #[error("malformed manifest signature: {0}")]
+    Malformed(String),
+    /// The signature does not match the manifest: it was modified after signing.
+    #[error("manifest signature does not match its content")]
+    Invalid,
+    /// The manifest is signed by a key other than the trusted one.
+    #[error("manifest is signed by an untrusted key")]
+    UntrustedKey,
+    #[error("manifest encode error: {0}")]
+    Encode(String),
+}
+
+/// What gets signed: the manifest without its signature.
+#[derive(Serialize)]
+struct SignedContent<'a> {
+    metadata: BTreeMap<&'a str, &'a Value>,
+    world: &'a WorldWithAurora,
+}
+
+fn signed_bytes(manifest: &AuroraWorldManifest) -> Result<Vec<u8>, SignatureError> {
+    let metadata = manifest
+        .metadata
+        .iter()
+        .flatten()
+        .filter(|(key, _)| *key != MANIFEST_SIGNATURE_KEY)
+        .map(|(key, value)| (key.as_str(), value))
+        .collect();
+    let content = SignedContent {
+        metadata,
+        world: &manifest.world,
+    };
+    let mut bytes = SIGNING_CONTEXT.to_vec();
+    serde_json::to_writer(&mut bytes, &content)
+        .map_err(|e| SignatureError::Encode(e.to_string()))?;
+    Ok(bytes)
+}
+
+fn decode_field<const N: usize>(entry: &Value, field: &str) -> Result<[u8; N], SignatureError> {
+    let text = entry
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| SignatureError::Malformed(format!("missing '{field}'")))?;
+    STANDARD
+        .decode(text)
+        .map_err(|e| SignatureError::Malformed(format!("'{field}': {e}")))?
+        .try_into()
+        .map_err(|_| SignatureError::Malformed(format!("'{field}' is not {N} bytes")))
+}
+
+impl AuroraWorldManifest {
+    /// Sign the manifest with `key`, replacing any previous signature. Sign last: any
+    /// later change to the world or metadata invalidates the signature.
+    pub fn sign(&mut self, key: &SigningKey) -> Result<(), SignatureError> {
+        let bytes = signed_bytes(self)?;
+        let signature = key.sign(&bytes);
+        let entry = serde_json::json!({
+            "public_key": STANDARD.encode(key.verifying_key().as_bytes()),
+            "ed25519": STANDARD.encode(signature.to_bytes()),
+        });
+        self.metadata
+            .get_or_insert_with(BTreeMap::new)
+            .insert(MANIFEST_SIGNATURE_KEY.to_string(), entry);
+        Ok(())
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .is_some_and(|m| m.contains_key(MANIFEST_SIGNATURE_KEY))
+    }
+
+    /// Public key the manifest says it was signed with. Only meaningful once
+    /// [`verify`](Self::verify) has accepted it.
+    pub fn signer(&self) -> Result<VerifyingKey, SignatureError> {
+        let entry = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(MANIFEST_SIGNATURE_KEY))
+            .ok_or(SignatureError::Unsigned)?;
+        VerifyingKey::from_bytes(&decode_field(entry, "public_key")?)
+            .map_err(|e| SignatureError::Malformed(e.to_string()))
+    }
+
+    /// Check that the manifest is signed by `trusted` and unchanged since.
+    pub fn verify(&self, trusted: &VerifyingKey) -> Result<(), SignatureError> {
+        if self.signer()? != *trusted {
+            return Err(SignatureError::UntrustedKey);
+        }
+        self.verify_signature(trusted)
+    }
+
+    fn verify_signature(&self, key: &VerifyingKey) -> Result<(), SignatureError> {
+        let entry = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(MANIFEST_SIGNATURE_KEY))
+            .ok_or(SignatureError::Unsigned)?;
+        let signature = Signature::from_bytes(&decode_field(entry, "ed25519")?);
+        key.verify(&signed_bytes(self)?, &signature)
+            .map_err(|_| SignatureError::Invalid)
+    }
+
+    /// [`from_file`](Self::from_file), also requiring the manifest to be signed by
+    /// `trusted`. The only loader that authenticates a manifest.
+    pub fn from_file_verified(
+        path: &str,
+        format: Option<ManifestOutputFormat>,
+        trusted: &VerifyingKey,
+    ) -> Result<Self, ArchiveError> {
+        let manifest = Self::from_file(path, format)?;
+        manifest.verify(trusted)?;
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::aurora_archive::save_world_manifest;
+    use crate::bevy_registry::SnapshotRegistry;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hull(u32);
+
+    #[test]
+    fn test_manifest_signing() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hull>();
+        let mut world = World::new();
+        world.spawn(Hull(100));
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let other = SigningKey::from_bytes(&[8; 32]);
+
+        let mut manifest = save_world_manifest(&world, &registry).unwrap();
+        assert!(matches!(
+            manifest.verify(&key.verifying_key()),
+            Err(SignatureError::Unsigned)
+        ));
+        manifest.sign(&key).unwrap();
+        manifest.verify(&key.verifying_key()).unwrap();
+        assert!(matches!(
+            manifest.verify(&other.verifying_key()),
+            Err(SignatureError::UntrustedKey)
+        ));
+
+        let dir = std::env::temp_dir().join(format!("bevy_archive_sign_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, format) in [
+            ("pack.toml", ManifestOutputFormat::Toml),
+            ("pack.json", ManifestOutputFormat::Json),
+        ] {
+            let path = dir.join(name);
+            let path = path.to_str().unwrap();
+            manifest.to_file(path, Some(format)).unwrap();
+            AuroraWorldManifest::from_file_verified(path, None, &key.verifying_key()).unwrap();
+
+            let text = std::fs::read_to_string(path).unwrap();
+            std::fs::write(path, text.replace("100", "999")).unwrap();
+            let err = AuroraWorldManifest::from_file_verified(path, None, &key.verifying_key());
+            assert!(matches!(
+                err,
+                Err(ArchiveError::Signature(SignatureError::Invalid))
+            ));
+            AuroraWorldManifest::from_file(path, None).unwrap();
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
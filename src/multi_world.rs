@@ -0,0 +1,280 @@
+//! Several worlds saved together, e.g. the simulation and presentation worlds of an engine
+//! that must checkpoint in lockstep.
+//!
+//! Each world is saved as a named section with the registry of its own components.
+//! [`save_multi_world_manifest`] keeps the sections in one [`MultiWorldManifest`];
+//! [`save_multi_world_to_container`] writes each section under `<name>/` of one
+//! [`SnapshotContainer`], e.g. a zip, and lists them in [`MULTI_WORLD_INDEX`].
+//!
+//! Loading restores each section into the world passed under the same name, after checking
+//! that all of them are present. Sections nobody asks for are ignored. Sections load one
+//! after the other, so a failure leaves the earlier worlds loaded.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::archetype_archive::LoadReport;
+use crate::aurora_archive::{
+    AuroraLoadError, AuroraWorldManifest, ExportGuidance, load_world_manifest,
+    load_world_manifest_from_container, save_world_manifest, save_world_manifest_to_container,
+};
+use crate::bevy_registry::SnapshotRegistry;
+use crate::container::{SnapshotContainer, SubContainer};
+use crate::error::ArchiveError;
+
+/// Entry holding the [`MultiWorldIndex`] of a container.
+pub const MULTI_WORLD_INDEX: &str = "worlds.toml";
+/// Manifest entry of each section, under the `<name>/` prefix.
+pub const SECTION_MANIFEST: &str = "world.toml";
+
+/// A world to save as the section named by the first element.
+pub type WorldSection<'a> = (&'a str, &'a World, &'a SnapshotRegistry);
+/// A world to load the section named by the first element into.
+pub type WorldSectionMut<'a> = (&'a str, &'a mut World, &'a SnapshotRegistry);
+
+/// The manifests of several worlds, by section name.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MultiWorldManifest {
+    #[serde(default)]
+    pub worlds: BTreeMap<String, AuroraWorldManifest>,
+}
+
+/// Table of contents of a multi-world container, stored at [`MULTI_WORLD_INDEX`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiWorldIndex {
+    /// Section names in save order.
+    #[serde(default)]
+    pub sections: Vec<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MultiWorldError {
+    #[error(transparent)]
+    Archive(#[from] ArchiveError),
+    #[error("world section '{0}' is missing")]
+    MissingSection(String),
+    #[error("world section '{section}': {source}")]
+    Load {
+        section: String,
+        /// Boxed, as it is much larger than the other variants.
+        #[source]
+        source: Box<AuroraLoadError>,
+    },
+}
+
+/// Save every world of `sections` into one [`MultiWorldManifest`], with embedded blobs.
+pub fn save_multi_world_manifest<'a>(
+    sections: impl IntoIterator<Item = WorldSection<'a>>,
+) -> Result<MultiWorldManifest, ArchiveError> {
+    let mut manifest = MultiWorldManifest::default();
+    for (name, world, registry) in sections {
+        check_section_name(name)?;
+        let section = save_world_manifest(world, registry)?;
+        if manifest.worlds.insert(name.to_string(), section).is_some() {
+            return Err(duplicate_section(name));
+        }
+    }
+    Ok(manifest)
+}
+
+/// Load the sections of `manifest` into the worlds of `sections` with the same names.
+pub fn load_multi_world_manifest<'a>(
+    manifest: &MultiWorldManifest,
+    sections: impl IntoIterator<Item = WorldSectionMut<'a>>,
+) -> Result<BTreeMap<String, LoadReport>, MultiWorldError> {
+    let sections = require_sections(sections, |name| manifest.worlds.contains_key(name))?;
+    let mut reports = BTreeMap::new();
+    for (name, world, registry) in sections {
+        let report = load_world_manifest(world, &manifest.worlds[name], registry)
+            .map_err(|source| section_error(name, source))?;
+        reports.insert(name.to_string(), report);
+    }
+    Ok(reports)
+}
+
+/// Save every world of `sections` into `container` with [`save_world_manifest_to_container`],
+/// each under its `<name>/` prefix, and write the [`MultiWorldIndex`].
+pub fn save_multi_world_to_container<'a, C: SnapshotContainer>(
+    sections: impl IntoIterator<Item = WorldSection<'a>>,
+    guidance: &ExportGuidance,
+    container: &mut C,
+) -> Result<MultiWorldIndex, ArchiveError> {
+    let mut index = MultiWorldIndex::default();
+    for (name, world, registry) in sections {
+        check_section_name(name)?;
+        if index.sections.iter().any(|s| s == name) {
+            return Err(duplicate_section(name));
+        }
+        let mut section = SubContainer::new(container, section_prefix(name));
+        save_world_manifest_to_container(
+            world,
+            registry,
+            guidance,
+            &mut section,
+            SECTION_MANIFEST,
+        )?;
+        index.sections.push(name.to_string());
+    }
+    let content = toml::to_string_pretty(&index)?;
+    container.write_entry(MULTI_WORLD_INDEX, content.as_bytes())?;
+    Ok(index)
+}
+
+/// Read the [`MultiWorldIndex`] of `container`.
+pub fn read_multi_world_index<C: SnapshotContainer>(
+    container: &mut C,
+) -> Result<MultiWorldIndex, ArchiveError> {
+    let bytes = container.read_entry(MULTI_WORLD_INDEX)?;
+    let content = String::from_utf8(bytes).map_err(ArchiveError::parse)?;
+    Ok(toml::from_str(&content)?)
+}
+
+/// Load the sections of a container written by [`save_multi_world_to_container`] into the
+/// worlds of `sections` with the same names.
+pub fn load_multi_world_from_container<'a, C: SnapshotContainer>(
+    sections: impl IntoIterator<Item = WorldSectionMut<'a>>,
+    container: &mut C,
+) -> Result<BTreeMap<String, LoadReport>, MultiWorldError> {
+    let index = read_multi_world_index(container)?;
+    let sections = require_sections(sections, |name| index.sections.iter().any(|s| s == name))?;
+    let mut reports = BTreeMap::new();
+    for (name, world, registry) in sections {
+        let mut section = SubContainer::new(&mut *container, section_prefix(name));
+        let report =
+            load_world_manifest_from_container(world, registry, &mut section, SECTION_MANIFEST)
+                .map_err(|source| section_error(name, source))?;
+        reports.insert(name.to_string(), report);
+    }
+    Ok(reports)
+}
+
+fn require_sections<'a>(
+    sections: impl IntoIterator<Item = WorldSectionMut<'a>>,
+    saved: impl Fn(&str) -> bool,
+) -> Result<Vec<WorldSectionMut<'a>>, MultiWorldError> {
+    let sections: Vec<_> = sections.into_iter().collect();
+    match sections.iter().find(|(name, ..)| !saved(name)) {
+        Some((name, ..)) => Err(MultiWorldError::MissingSection(name.to_string())),
+        None => Ok(sections),
+    }
+}
+
+/// Section names become entry prefixes, so they must be a single path component.
+fn check_section_name(name: &str) -> Result<(), ArchiveError> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(ArchiveError::schema(format!(
+            "invalid world section name '{name}'"
+        )));
+    }
+    Ok(())
+}
+
+fn duplicate_section(name: &str) -> ArchiveError {
+    ArchiveError::schema(format!("duplicate world section '{name}'"))
+}
+
+fn section_prefix(name: &str) -> String {
+    format!("{name}/")
+}
+
+fn section_error(name: &str, source: AuroraLoadError) -> MultiWorldError {
+    MultiWorldError::Load {
+        section: name.to_string(),
+        source: Box::new(source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aurora_archive::ExportFormat;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Body(f32);
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Sprite(String);
+
+    fn setup() -> (World, SnapshotRegistry, World, SnapshotRegistry) {
+        let mut sim_registry = SnapshotRegistry::default();
+        sim_registry.register::<Body>();
+        let mut sim = World::new();
+        sim.spawn(Body(1.5));
+        sim.spawn(Body(2.5));
+
+        let mut render_registry = SnapshotRegistry::default();
+        render_registry.register::<Sprite>();
+        let mut render = World::new();
+        render.spawn(Sprite("ship.png".to_string()));
+        (sim, sim_registry, render, render_registry)
+    }
+
+    fn count<T: Component>(world: &mut World) -> usize {
+        world.query::<&T>().iter(world).count()
+    }
+
+    #[test]
+    fn test_multi_world_manifest_roundtrip() {
+        let (sim, sim_registry, render, render_registry) = setup();
+        let manifest = save_multi_world_manifest([
+            ("sim", &sim, &sim_registry),
+            ("render", &render, &render_registry),
+        ])
+        .unwrap();
+        let text = toml::to_string_pretty(&manifest).unwrap();
+        let manifest: MultiWorldManifest = toml::from_str(&text).unwrap();
+
+        let (mut sim2, mut render2) = (World::new(), World::new());
+        let reports = load_multi_world_manifest(
+            &manifest,
+            [
+                ("sim", &mut sim2, &sim_registry),
+                ("render", &mut render2, &render_registry),
+            ],
+        )
+        .unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(count::<Body>(&mut sim2), 2);
+        assert_eq!(count::<Sprite>(&mut render2), 1);
+        assert_eq!(count::<Body>(&mut render2), 0);
+
+        let mut ui = World::new();
+        let err = load_multi_world_manifest(&manifest, [("ui", &mut ui, &sim_registry)]);
+        assert!(matches!(err, Err(MultiWorldError::MissingSection(ref name)) if name == "ui"));
+        let err = save_multi_world_manifest([("a/b", &sim, &sim_registry)]);
+        assert!(matches!(err, Err(ArchiveError::Schema(_))));
+    }
+
+    #[test]
+    fn test_multi_world_container_roundtrip() {
+        let (sim, sim_registry, render, render_registry) = setup();
+        let mut container = BTreeMap::new();
+        let guidance = ExportGuidance::file_all(ExportFormat::Csv, "blobs");
+        let index = save_multi_world_to_container(
+            [
+                ("sim", &sim, &sim_registry),
+                ("render", &render, &render_registry),
+            ],
+            &guidance,
+            &mut container,
+        )
+        .unwrap();
+        assert_eq!(index.sections, vec!["sim", "render"]);
+        assert!(container.contains_key("sim/world.toml"));
+        assert!(container.contains_key("render/world.toml"));
+
+        let (mut sim2, mut render2) = (World::new(), World::new());
+        load_multi_world_from_container(
+            [
+                ("render", &mut render2, &render_registry),
+                ("sim", &mut sim2, &sim_registry),
+            ],
+            &mut container,
+        )
+        .unwrap();
+        assert_eq!(count::<Body>(&mut sim2), 2);
+        assert_eq!(count::<Sprite>(&mut render2), 1);
+    }
+}
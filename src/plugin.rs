@@ -0,0 +1,821 @@
+//! Bevy [`App`] integration (`bevy_app` feature).
+//!
+//! [`SnapshotPlugin`] inserts a [`SnapshotRegistry`] resource and serves file requests sent
+//! as messages: write a [`SaveWorldRequest`] or [`LoadWorldRequest`] from any system and an
+//! exclusive system in [`Last`] runs the Aurora or MessagePack pipeline on the whole world.
+//! Each request is answered with a [`WorldIoFinished`] message, readable on the next frame.
+//!
+//! The registry resource is read when the request is served, so components can still be
+//! registered on it after the plugin was added.
+//!
+//! Insert an [`Autosave`] resource to also save the world periodically; see its docs for
+//! the interval, debounce and rotation rules.
+//!
+//! The duration, size and entity count of the latest successful save and load are kept in
+//! the [`SnapshotMetrics`] resource, e.g. for a "Saving… 1.2s, 34 MB" line, and with the
+//! `tracing` feature also emitted as `info` events on the `bevy_archive::metrics` target.
+//!
+//! Gameplay code that would rather not wait for [`Last`] can queue an
+//! [`ApplySnapshotCommand`], or schedule the exclusive system built by
+//! [`apply_snapshot_system`]. Both load with the registry resource and its
+//! [limits](crate::bevy_registry::LoadLimits) and [options](crate::bevy_registry::LoadOptions),
+//! and need no plugin.
+
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use bevy_app::{App, Last, Plugin};
+use bevy_ecs::change_detection::Tick;
+use bevy_ecs::prelude::*;
+
+use crate::aurora_archive::{AuroraWorldManifest, ManifestOutputFormat, load_world_manifest};
+use crate::bevy_registry::SnapshotRegistry;
+use crate::binary_archive::msgpack_archive::MsgPackArchive;
+use crate::dirty::{SnapshotDirtyExt, registered_changed_since};
+use crate::traits::Archive;
+
+/// File format of a save or load request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnapshotFileFormat {
+    /// Aurora manifest as TOML.
+    #[default]
+    AuroraToml,
+    /// Aurora manifest as JSON.
+    AuroraJson,
+    /// [`MsgPackArchive`].
+    MsgPack,
+}
+
+impl SnapshotFileFormat {
+    /// Guess the format from the extension of `path`: `json`, `msgpack`/`mpk`, TOML otherwise.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let ext = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match ext.as_str() {
+            "json" => Self::AuroraJson,
+            "msgpack" | "mpk" => Self::MsgPack,
+            _ => Self::AuroraToml,
+        }
+    }
+}
+
+/// Save the world to `path`.
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct SaveWorldRequest {
+    pub path: PathBuf,
+    pub format: SnapshotFileFormat,
+}
+
+/// Load `path` into the world.
+///
+/// Entities are written by their stored index, like
+/// [`load_world_manifest`](crate::aurora_archive::load_world_manifest).
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct LoadWorldRequest {
+    pub path: PathBuf,
+    pub format: SnapshotFileFormat,
+}
+
+/// Which request a [`WorldIoFinished`] answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldIoKind {
+    Save,
+    Load,
+    /// A save written by [`Autosave`].
+    Autosave,
+}
+
+/// Outcome of a [`SaveWorldRequest`], a [`LoadWorldRequest`] or an [`Autosave`].
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct WorldIoFinished {
+    pub kind: WorldIoKind,
+    pub path: PathBuf,
+    pub result: Result<(), String>,
+}
+
+/// Duration, size and entity count of one successful save or load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotIoMetrics {
+    pub kind: WorldIoKind,
+    /// File written or read; `None` for snapshots applied from memory.
+    pub path: Option<PathBuf>,
+    pub duration: Duration,
+    /// Size of the file or bytes; `None` for an in-memory manifest.
+    pub bytes: Option<u64>,
+    /// Entities saved, or loaded from the snapshot.
+    pub entities: usize,
+}
+
+/// The latest successful save and load, updated by the [`SnapshotPlugin`] systems,
+/// [`Autosave`] and [`ApplySnapshotCommand`] while the resource exists. Inserted by the
+/// plugin.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SnapshotMetrics {
+    /// Latest save, including autosaves.
+    pub last_save: Option<SnapshotIoMetrics>,
+    pub last_load: Option<SnapshotIoMetrics>,
+}
+
+/// Store `metrics` in [`SnapshotMetrics`], if present, and emit them with `tracing`.
+fn record_metrics(world: &mut World, metrics: SnapshotIoMetrics) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        target: "bevy_archive::metrics",
+        kind = ?metrics.kind,
+        path = ?metrics.path,
+        duration_ms = metrics.duration.as_secs_f64() * 1000.0,
+        bytes = metrics.bytes,
+        entities = metrics.entities,
+        "snapshot io finished"
+    );
+    if let Some(mut last) = world.get_resource_mut::<SnapshotMetrics>() {
+        match metrics.kind {
+            WorldIoKind::Load => last.last_load = Some(metrics),
+            WorldIoKind::Save | WorldIoKind::Autosave => last.last_save = Some(metrics),
+        }
+    }
+}
+
+/// [`SnapshotIoMetrics`] of a save to `path` that took `duration`.
+fn save_metrics(
+    world: &World,
+    registry: &SnapshotRegistry,
+    kind: WorldIoKind,
+    path: &Path,
+    duration: Duration,
+) -> SnapshotIoMetrics {
+    SnapshotIoMetrics {
+        kind,
+        path: Some(path.to_path_buf()),
+        duration,
+        bytes: std::fs::metadata(path).ok().map(|m| m.len()),
+        entities: registered_entity_count(world, registry),
+    }
+}
+
+/// Entities of `world` with at least one registered component, i.e. those a save writes.
+fn registered_entity_count(world: &World, registry: &SnapshotRegistry) -> usize {
+    let ids: HashSet<_> = registry
+        .type_registry
+        .keys()
+        .filter_map(|name| registry.comp_id_by_name(name, world))
+        .collect();
+    world
+        .archetypes()
+        .iter()
+        .filter(|arch| arch.components().iter().any(|id| ids.contains(id)))
+        .map(|arch| arch.len() as usize)
+        .sum()
+}
+
+/// Installs the registry resource, the request messages and the systems serving them.
+#[derive(Default, Clone)]
+pub struct SnapshotPlugin {
+    pub registry: SnapshotRegistry,
+}
+
+impl SnapshotPlugin {
+    pub fn new(registry: SnapshotRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.registry.clone())
+            .init_resource::<SnapshotMetrics>()
+            .add_message::<SaveWorldRequest>()
+            .add_message::<LoadWorldRequest>()
+            .add_message::<WorldIoFinished>()
+            .add_systems(
+                Last,
+                (
+                    save_world_requests,
+                    load_world_requests,
+                    autosave.run_if(resource_exists::<Autosave>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+fn save_file(
+    world: &World,
+    registry: &SnapshotRegistry,
+    req: &SaveWorldRequest,
+) -> Result<(), BoxError> {
+    match req.format {
+        SnapshotFileFormat::AuroraToml | SnapshotFileFormat::AuroraJson => {
+            let format = if req.format == SnapshotFileFormat::AuroraJson {
+                ManifestOutputFormat::Json
+            } else {
+                ManifestOutputFormat::Toml
+            };
+            let path = req.path.to_str().ok_or("Invalid path")?;
+            AuroraWorldManifest::create(world, registry)?.to_file(path, Some(format))?;
+        }
+        SnapshotFileFormat::MsgPack => {
+            MsgPackArchive::create(world, registry)?.save_to(&req.path)?
+        }
+    }
+    Ok(())
+}
+
+/// Load the file of `req`, returning the number of entities loaded.
+fn load_file(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+    req: &LoadWorldRequest,
+) -> Result<usize, BoxError> {
+    match req.format {
+        SnapshotFileFormat::AuroraToml | SnapshotFileFormat::AuroraJson => {
+            let format = if req.format == SnapshotFileFormat::AuroraJson {
+                ManifestOutputFormat::Json
+            } else {
+                ManifestOutputFormat::Toml
+            };
+            let path = req.path.to_str().ok_or("Invalid path")?;
+            let manifest = AuroraWorldManifest::from_file(path, Some(format))?;
+            Ok(load_world_manifest(world, &manifest, registry)?.entities_loaded)
+        }
+        SnapshotFileFormat::MsgPack => {
+            let archive = MsgPackArchive::load_from(&req.path)?;
+            Ok(archive.to_world(world, registry)?.entities_loaded)
+        }
+    }
+}
+
+/// Serve every pending [`SaveWorldRequest`].
+pub fn save_world_requests(world: &mut World) {
+    let requests: Vec<_> = world
+        .resource_mut::<Messages<SaveWorldRequest>>()
+        .drain()
+        .collect();
+    if requests.is_empty() {
+        return;
+    }
+    let registry = world.resource::<SnapshotRegistry>().clone();
+    for req in requests {
+        let start = Instant::now();
+        let result = save_file(world, &registry, &req).map_err(|e| e.to_string());
+        if result.is_ok() {
+            let metrics = save_metrics(
+                world,
+                &registry,
+                WorldIoKind::Save,
+                &req.path,
+                start.elapsed(),
+            );
+            record_metrics(world, metrics);
+        }
+        world.write_message(WorldIoFinished {
+            kind: WorldIoKind::Save,
+            path: req.path,
+            result,
+        });
+    }
+}
+
+/// Serve every pending [`LoadWorldRequest`].
+pub fn load_world_requests(world: &mut World) {
+    let requests: Vec<_> = world
+        .resource_mut::<Messages<LoadWorldRequest>>()
+        .drain()
+        .collect();
+    if requests.is_empty() {
+        return;
+    }
+    let registry = world.resource::<SnapshotRegistry>().clone();
+    for req in requests {
+        let start = Instant::now();
+        let bytes = std::fs::metadata(&req.path).ok().map(|m| m.len());
+        let result = load_file(world, &registry, &req).map_err(|e| e.to_string());
+        if let Ok(entities) = result {
+            let metrics = SnapshotIoMetrics {
+                kind: WorldIoKind::Load,
+                path: Some(req.path.clone()),
+                duration: start.elapsed(),
+                bytes,
+                entities,
+            };
+            record_metrics(world, metrics);
+        }
+        let result = result.map(|_| ());
+        world.write_message(WorldIoFinished {
+            kind: WorldIoKind::Load,
+            path: req.path,
+            result,
+        });
+    }
+}
+
+/// What an [`ApplySnapshotCommand`] loads.
+#[derive(Debug, Clone)]
+pub enum SnapshotSource {
+    /// A file, read when the command is applied.
+    Path(PathBuf, SnapshotFileFormat),
+    /// A manifest already in memory. Blobs it references by path are resolved against the
+    /// working directory.
+    Manifest(Arc<AuroraWorldManifest>),
+    /// The content of a file in `format`.
+    Bytes(Arc<[u8]>, SnapshotFileFormat),
+}
+
+/// Loads a snapshot into the world when applied, with the [`SnapshotRegistry`] resource.
+///
+/// ```
+/// # use bevy_archive::plugin::ApplySnapshotCommand;
+/// # use bevy_ecs::prelude::*;
+/// fn load_slot(mut commands: Commands) {
+///     commands.queue(ApplySnapshotCommand::from_path("save1.toml"));
+/// }
+/// # let _ = IntoSystem::into_system(load_slot);
+/// ```
+///
+/// Failures, including a missing registry resource, go to Bevy's error handler. Entities
+/// are written by their stored index, like a [`LoadWorldRequest`].
+#[derive(Debug, Clone)]
+pub struct ApplySnapshotCommand(pub SnapshotSource);
+
+impl ApplySnapshotCommand {
+    /// Load the file at `path`, in the format its extension suggests.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let format = SnapshotFileFormat::from_path(&path);
+        Self(SnapshotSource::Path(path, format))
+    }
+
+    pub fn from_manifest(manifest: impl Into<Arc<AuroraWorldManifest>>) -> Self {
+        Self(SnapshotSource::Manifest(manifest.into()))
+    }
+
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>, format: SnapshotFileFormat) -> Self {
+        Self(SnapshotSource::Bytes(bytes.into(), format))
+    }
+
+    /// Load the snapshot, returning the number of entities loaded.
+    fn load(&self, world: &mut World, registry: &SnapshotRegistry) -> Result<usize, BoxError> {
+        match &self.0 {
+            SnapshotSource::Path(path, format) => load_file(
+                world,
+                registry,
+                &LoadWorldRequest {
+                    path: path.clone(),
+                    format: *format,
+                },
+            ),
+            SnapshotSource::Manifest(manifest) => {
+                Ok(load_world_manifest(world, manifest, registry)?.entities_loaded)
+            }
+            SnapshotSource::Bytes(bytes, format) => match format {
+                SnapshotFileFormat::AuroraToml => {
+                    let manifest: AuroraWorldManifest = toml::from_slice(bytes)?;
+                    Ok(load_world_manifest(world, &manifest, registry)?.entities_loaded)
+                }
+                SnapshotFileFormat::AuroraJson => {
+                    let manifest: AuroraWorldManifest = serde_json::from_slice(bytes)?;
+                    Ok(load_world_manifest(world, &manifest, registry)?.entities_loaded)
+                }
+                SnapshotFileFormat::MsgPack => {
+                    let archive = MsgPackArchive(rmp_serde::from_slice(bytes)?);
+                    Ok(archive.to_world(world, registry)?.entities_loaded)
+                }
+            },
+        }
+    }
+
+    /// [`SnapshotIoMetrics`] of a load of this command that took `duration`.
+    fn metrics(&self, duration: Duration, entities: usize) -> SnapshotIoMetrics {
+        let (path, bytes) = match &self.0 {
+            SnapshotSource::Path(path, _) => (
+                Some(path.clone()),
+                std::fs::metadata(path).ok().map(|m| m.len()),
+            ),
+            SnapshotSource::Manifest(_) => (None, None),
+            SnapshotSource::Bytes(bytes, _) => (None, Some(bytes.len() as u64)),
+        };
+        SnapshotIoMetrics {
+            kind: WorldIoKind::Load,
+            path,
+            duration,
+            bytes,
+            entities,
+        }
+    }
+}
+
+impl Command for ApplySnapshotCommand {
+    type Out = Result;
+
+    fn apply(self, world: &mut World) -> Result {
+        if !world.contains_resource::<SnapshotRegistry>() {
+            return Err("no SnapshotRegistry resource to load the snapshot with".into());
+        }
+        let start = Instant::now();
+        let entities = world
+            .resource_scope(|world, registry: Mut<SnapshotRegistry>| self.load(world, &registry))?;
+        record_metrics(world, self.metrics(start.elapsed(), entities));
+        Ok(())
+    }
+}
+
+/// Exclusive system applying `command` each time it runs, e.g. to load a level on entering
+/// a state:
+///
+/// ```
+/// # use bevy_archive::plugin::{ApplySnapshotCommand, apply_snapshot_system};
+/// # use bevy_ecs::prelude::*;
+/// let mut schedule = Schedule::default();
+/// schedule.add_systems(apply_snapshot_system(ApplySnapshotCommand::from_path("level1.toml")));
+/// ```
+pub fn apply_snapshot_system(
+    command: ApplySnapshotCommand,
+) -> impl FnMut(&mut World) -> Result + Send + Sync + 'static {
+    move |world: &mut World| command.clone().apply(world)
+}
+
+/// Writes `A` created from the world to `path`.
+pub type AutosaveFn = fn(&World, &SnapshotRegistry, &Path) -> Result<(), BoxError>;
+
+fn save_archive<A: Archive>(
+    world: &World,
+    registry: &SnapshotRegistry,
+    path: &Path,
+) -> Result<(), BoxError> {
+    A::create(world, registry)?.save_to(path)
+}
+
+/// Periodic saves of the whole world, served by [`SnapshotPlugin`] while the resource exists.
+///
+/// A save happens when all of these hold:
+/// - `interval` elapsed since the previous autosave (or since the first check);
+/// - a registered component changed since the previous autosave, as tracked by
+///   [`dirty`](crate::dirty);
+/// - no registered component changed during the last `debounce`, so a burst of edits is
+///   saved once it settles.
+///
+/// Files are named `<stem>_<unix ms>.<extension>` in `directory`. With `max_kept` set, the
+/// oldest autosaves written by this resource are deleted past that count; files from
+/// earlier runs are left alone.
+#[derive(Resource, Debug, Clone)]
+pub struct Autosave {
+    pub directory: PathBuf,
+    pub stem: String,
+    pub extension: String,
+    pub interval: Duration,
+    pub debounce: Duration,
+    pub max_kept: Option<usize>,
+    save: AutosaveFn,
+    last_save: Option<Instant>,
+    last_change: Option<Instant>,
+    last_check: Option<Tick>,
+    kept: VecDeque<PathBuf>,
+}
+
+impl Autosave {
+    /// Autosave through archive `A` every `interval`, without debounce and keeping every
+    /// file. The extension defaults to `toml`.
+    pub fn new<A: Archive>(directory: impl Into<PathBuf>, interval: Duration) -> Self {
+        Self {
+            directory: directory.into(),
+            stem: "autosave".to_string(),
+            extension: "toml".to_string(),
+            interval,
+            debounce: Duration::ZERO,
+            max_kept: None,
+            save: save_archive::<A>,
+            last_save: None,
+            last_change: None,
+            last_check: None,
+            kept: VecDeque::new(),
+        }
+    }
+
+    pub fn with_file_name(mut self, stem: impl Into<String>, extension: impl Into<String>) -> Self {
+        self.stem = stem.into();
+        self.extension = extension.into();
+        self
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn with_max_kept(mut self, max_kept: usize) -> Self {
+        self.max_kept = Some(max_kept);
+        self
+    }
+
+    /// Autosave files written so far and not rotated out, oldest first.
+    pub fn kept_files(&self) -> impl Iterator<Item = &Path> {
+        self.kept.iter().map(PathBuf::as_path)
+    }
+
+    fn next_path(&self) -> PathBuf {
+        let ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let mut path = self
+            .directory
+            .join(format!("{}_{ms}.{}", self.stem, self.extension));
+        // Several saves within one millisecond get a counter.
+        let mut n = 1;
+        while self.kept.contains(&path) {
+            path = self
+                .directory
+                .join(format!("{}_{ms}_{n}.{}", self.stem, self.extension));
+            n += 1;
+        }
+        path
+    }
+}
+
+/// Exclusive system behind [`Autosave`]; a no-op without the resource.
+pub fn autosave(world: &mut World) {
+    autosave_at(world, Instant::now());
+}
+
+fn autosave_at(world: &mut World, now: Instant) {
+    if !world.contains_resource::<Autosave>() {
+        return;
+    }
+    world.resource_scope(|world, mut state: Mut<Autosave>| {
+        let registry = world.get_resource::<SnapshotRegistry>().cloned();
+        if let (Some(registry), Some(since)) = (&registry, state.last_check)
+            && registered_changed_since(world, registry, since)
+        {
+            state.last_change = Some(now);
+        }
+        let last_save = *state.last_save.get_or_insert(now);
+        let quiet = state
+            .last_change
+            .is_none_or(|t| now.duration_since(t) >= state.debounce);
+        if let Some(registry) = registry
+            && now.duration_since(last_save) >= state.interval
+            && quiet
+            && world.is_snapshot_dirty()
+        {
+            // A failed save waits for the next interval too.
+            state.last_save = Some(now);
+            let path = state.next_path();
+            let start = Instant::now();
+            let result = std::fs::create_dir_all(&state.directory)
+                .map_err(BoxError::from)
+                .and_then(|_| (state.save)(world, &registry, &path))
+                .map_err(|e| e.to_string());
+            if result.is_ok() {
+                let metrics = save_metrics(
+                    world,
+                    &registry,
+                    WorldIoKind::Autosave,
+                    &path,
+                    start.elapsed(),
+                );
+                record_metrics(world, metrics);
+                world.mark_snapshot_clean();
+                state.kept.push_back(path.clone());
+                while state.max_kept.is_some_and(|max| state.kept.len() > max) {
+                    if let Some(old) = state.kept.pop_front() {
+                        std::fs::remove_file(old).ok();
+                    }
+                }
+            }
+            if world.contains_resource::<Messages<WorldIoFinished>>() {
+                world.write_message(WorldIoFinished {
+                    kind: WorldIoKind::Autosave,
+                    path,
+                    result,
+                });
+            }
+        }
+        state.last_check = Some(world.increment_change_tick());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hp(u32);
+
+    fn registry() -> SnapshotRegistry {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        registry
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(SnapshotPlugin::new(registry()));
+        app
+    }
+
+    fn finished(app: &mut App) -> Vec<WorldIoFinished> {
+        app.world_mut()
+            .resource_mut::<Messages<WorldIoFinished>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn test_plugin_save_and_load_requests() {
+        let dir = std::env::temp_dir().join(format!("bevy_archive_plugin_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for name in ["world.toml", "world.json", "world.msgpack"] {
+            let path = dir.join(name);
+            let format = SnapshotFileFormat::from_path(&path);
+
+            let mut source = app();
+            source.world_mut().spawn(Hp(7));
+            source.world_mut().spawn(Hp(9));
+            source.world_mut().write_message(SaveWorldRequest {
+                path: path.clone(),
+                format,
+            });
+            source.update();
+            let done = finished(&mut source);
+            assert_eq!(done.len(), 1);
+            assert_eq!(done[0].kind, WorldIoKind::Save);
+            assert_eq!(done[0].result, Ok(()));
+
+            let mut target = app();
+            target
+                .world_mut()
+                .write_message(LoadWorldRequest { path, format });
+            target.update();
+            assert_eq!(finished(&mut target)[0].result, Ok(()));
+            let world = target.world_mut();
+            let mut hp: Vec<u32> = world.query::<&Hp>().iter(world).map(|h| h.0).collect();
+            hp.sort();
+            assert_eq!(hp, vec![7, 9], "{name}");
+        }
+
+        let mut missing = app();
+        missing.world_mut().write_message(LoadWorldRequest {
+            path: dir.join("missing.msgpack"),
+            format: SnapshotFileFormat::MsgPack,
+        });
+        missing.update();
+        assert!(finished(&mut missing)[0].result.is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_snapshot_metrics() {
+        let dir = std::env::temp_dir().join(format!("bevy_archive_metrics_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("world.msgpack");
+        let format = SnapshotFileFormat::MsgPack;
+
+        let mut source = app();
+        source.world_mut().spawn(Hp(1));
+        source.world_mut().spawn(Hp(2));
+        source.world_mut().spawn_empty();
+        source.world_mut().write_message(SaveWorldRequest {
+            path: path.clone(),
+            format,
+        });
+        source.update();
+        let size = std::fs::metadata(&path).unwrap().len();
+        let metrics = source.world().resource::<SnapshotMetrics>();
+        let save = metrics.last_save.as_ref().unwrap();
+        assert_eq!(save.kind, WorldIoKind::Save);
+        assert_eq!(save.path.as_deref(), Some(path.as_path()));
+        assert_eq!((save.bytes, save.entities), (Some(size), 2));
+        assert!(metrics.last_load.is_none());
+
+        let mut target = app();
+        target.world_mut().write_message(LoadWorldRequest {
+            path: path.clone(),
+            format,
+        });
+        target.update();
+        let load = target
+            .world()
+            .resource::<SnapshotMetrics>()
+            .last_load
+            .clone()
+            .unwrap();
+        assert_eq!(load.kind, WorldIoKind::Load);
+        assert_eq!((load.bytes, load.entities), (Some(size), 2));
+
+        let bytes = std::fs::read(&path).unwrap();
+        let world = target.world_mut();
+        ApplySnapshotCommand::from_bytes(bytes, format)
+            .apply(world)
+            .unwrap();
+        let load = world
+            .resource::<SnapshotMetrics>()
+            .last_load
+            .clone()
+            .unwrap();
+        assert_eq!(
+            (load.path, load.bytes, load.entities),
+            (None, Some(size), 2)
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_apply_snapshot_command_and_system() {
+        let dir = std::env::temp_dir().join(format!("bevy_archive_apply_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("save1.msgpack");
+        let mut source = World::new();
+        source.spawn(Hp(4));
+        MsgPackArchive::create(&source, &registry())
+            .unwrap()
+            .save_to(&path)
+            .unwrap();
+        let manifest = AuroraWorldManifest::create(&source, &registry()).unwrap();
+        let json = serde_json::to_vec(&manifest).unwrap();
+
+        let hp = |world: &mut World| world.query::<&Hp>().iter(world).map(|h| h.0).sum::<u32>();
+        let commands = [
+            ApplySnapshotCommand::from_path(&path),
+            ApplySnapshotCommand::from_manifest(manifest),
+            ApplySnapshotCommand::from_bytes(json, SnapshotFileFormat::AuroraJson),
+        ];
+        for command in commands {
+            let mut world = World::new();
+            world.insert_resource(registry());
+            world.commands().queue(command);
+            world.flush();
+            assert_eq!(hp(&mut world), 4);
+        }
+
+        let mut world = World::new();
+        world.insert_resource(registry());
+        let mut schedule = Schedule::default();
+        schedule.add_systems(apply_snapshot_system(ApplySnapshotCommand::from_path(
+            &path,
+        )));
+        schedule.run(&mut world);
+        assert_eq!(hp(&mut world), 4);
+
+        let no_registry = ApplySnapshotCommand::from_path(&path).apply(&mut World::new());
+        assert!(no_registry.is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_autosave_interval_debounce_and_rotation() {
+        let dir =
+            std::env::temp_dir().join(format!("bevy_archive_autosave_{}", std::process::id()));
+        let mut world = World::new();
+        world.insert_resource(registry());
+        let hp = world.spawn(Hp(1)).id();
+        world.insert_resource(
+            Autosave::new::<MsgPackArchive>(&dir, Duration::from_secs(10))
+                .with_file_name("auto", "msgpack")
+                .with_debounce(Duration::from_secs(2))
+                .with_max_kept(2),
+        );
+        let t0 = Instant::now();
+        let at = |s: f32| t0 + Duration::from_secs_f32(s);
+        let kept = |world: &World| world.resource::<Autosave>().kept.len();
+
+        autosave_at(&mut world, at(0.0));
+        assert_eq!(kept(&world), 0, "interval not elapsed");
+        autosave_at(&mut world, at(10.0));
+        assert_eq!(kept(&world), 1);
+        autosave_at(&mut world, at(20.0));
+        assert_eq!(kept(&world), 1, "nothing changed");
+
+        world.get_mut::<Hp>(hp).unwrap().0 = 2;
+        autosave_at(&mut world, at(21.0));
+        autosave_at(&mut world, at(22.0));
+        assert_eq!(kept(&world), 1, "still debouncing");
+        autosave_at(&mut world, at(23.5));
+        assert_eq!(kept(&world), 2);
+
+        let first = world.resource::<Autosave>().kept[0].clone();
+        world.get_mut::<Hp>(hp).unwrap().0 = 3;
+        autosave_at(&mut world, at(40.0));
+        autosave_at(&mut world, at(45.0));
+        let state = world.resource::<Autosave>();
+        assert_eq!(state.kept.len(), 2);
+        assert!(!first.exists());
+        assert!(state.kept_files().all(Path::exists));
+
+        let latest = MsgPackArchive::load_from(state.kept.back().unwrap()).unwrap();
+        let mut loaded = World::new();
+        latest.apply(&mut loaded, &registry()).unwrap();
+        let hp: Vec<u32> = loaded.query::<&Hp>().iter(&loaded).map(|h| h.0).collect();
+        assert_eq!(hp, vec![3]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
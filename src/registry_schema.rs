@@ -0,0 +1,226 @@
+//! The registry a snapshot was saved with, embedded in the snapshot so a load can list
+//! every incompatibility before it touches the world.
+//!
+//! Aurora manifests keep the [`RegistrySchema`] in `metadata` under
+//! [`REGISTRY_SCHEMA_KEY`]; Arrow snapshots keep it as JSON in
+//! [`WorldArrowSnapshot::meta`](crate::binary_archive::WorldArrowSnapshot::meta), which zips
+//! write to `meta.toml`. Loaders compare it with their registry for the components the
+//! snapshot holds:
+//! - a component whose registered fields differ from the saved ones is *changed*, unless it
+//!   was saved at an older version, whose migration bridges the change;
+//! - a component without a factory is *missing*.
+//!
+//! Changed components always fail the load; missing ones only under a
+//! [strict](crate::bevy_registry::LoadOptions::strict) load, as other loads skip them and
+//! list them in the [`LoadReport`](crate::archetype_archive::LoadReport). Fields come from
+//! the Arrow schema, so without the `arrow_rs` feature only missing components are found.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bevy_registry::{LimitError, SnapshotMode, SnapshotRegistry};
+
+/// Metadata key of the [`RegistrySchema`] of a manifest or Arrow snapshot.
+pub const REGISTRY_SCHEMA_KEY: &str = "registry_schema";
+
+/// How a component was registered when a snapshot was saved.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ComponentSchema {
+    #[serde(default)]
+    pub mode: SnapshotMode,
+    #[serde(default)]
+    pub version: u32,
+    /// `name: type` of each Arrow field, `?` marking nullable ones. Empty for components
+    /// Arrow skips and without the `arrow_rs` feature.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+/// The registered components of a registry, by name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RegistrySchema {
+    #[serde(default)]
+    pub components: BTreeMap<String, ComponentSchema>,
+}
+
+/// Saved components a registry cannot load as they were saved, sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// Components with no registered factory.
+    pub missing: Vec<String>,
+    /// Components whose registered fields differ from the saved ones.
+    pub changed: Vec<String>,
+}
+
+impl ComponentSchema {
+    fn of(registry: &SnapshotRegistry, name: &str) -> Option<Self> {
+        let factory = registry.get_factory(name)?;
+        #[cfg(feature = "arrow_rs")]
+        let fields = factory
+            .arrow
+            .as_ref()
+            .map(|arrow| {
+                arrow
+                    .schema
+                    .iter()
+                    .map(|f| {
+                        let nullable = if f.is_nullable() { "?" } else { "" };
+                        format!("{}: {}{nullable}", f.name(), f.data_type())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        #[cfg(not(feature = "arrow_rs"))]
+        let fields = Vec::new();
+        Some(Self {
+            mode: factory.mode,
+            version: factory.version,
+            fields,
+        })
+    }
+}
+
+impl RegistrySchema {
+    /// The schema of every component registered in `registry`.
+    pub fn of(registry: &SnapshotRegistry) -> Self {
+        let components = registry
+            .entries
+            .keys()
+            .filter_map(|&name| Some((name.to_string(), ComponentSchema::of(registry, name)?)))
+            .collect();
+        Self { components }
+    }
+
+    /// Rename saved components, e.g. with
+    /// [`AuroraWorldManifest::component_renames`](crate::aurora_archive::AuroraWorldManifest::component_renames).
+    pub fn renamed(mut self, renames: &BTreeMap<String, String>) -> Self {
+        for (old, new) in renames {
+            if let Some(schema) = self.components.remove(old) {
+                self.components.insert(new.clone(), schema);
+            }
+        }
+        self
+    }
+
+    /// Compare the saved components of `names` with how `registry` would load them. Names
+    /// the schema does not list are left out, as are components saved at another version
+    /// than the registered one.
+    pub fn compare<'a>(
+        &self,
+        registry: &SnapshotRegistry,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> SchemaMismatch {
+        let mut mismatch = SchemaMismatch::default();
+        for name in names {
+            let Some(saved) = self.components.get(name) else {
+                continue;
+            };
+            if mismatch
+                .missing
+                .iter()
+                .chain(&mismatch.changed)
+                .any(|n| n == name)
+            {
+                continue;
+            }
+            let Some(current) = ComponentSchema::of(registry, registry.resolve_name(name)) else {
+                mismatch.missing.push(name.to_string());
+                continue;
+            };
+            let comparable = saved.version == current.version
+                && !saved.fields.is_empty()
+                && !current.fields.is_empty();
+            if comparable && saved.fields != current.fields {
+                mismatch.changed.push(name.to_string());
+            }
+        }
+        mismatch.missing.sort();
+        mismatch.changed.sort();
+        mismatch
+    }
+
+    /// [`compare`](Self::compare), failing with [`LimitError::IncompatibleSchema`] on
+    /// changed components, and on missing ones under a strict load.
+    pub fn check<'a>(
+        &self,
+        registry: &SnapshotRegistry,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), LimitError> {
+        let mut mismatch = self.compare(registry, names);
+        if !registry.load_options.strict {
+            mismatch.missing.clear();
+        }
+        if mismatch.is_empty() {
+            Ok(())
+        } else {
+            Err(LimitError::IncompatibleSchema(mismatch))
+        }
+    }
+}
+
+impl SchemaMismatch {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        if !self.missing.is_empty() {
+            write!(f, "missing {}", self.missing.join(", "))?;
+            sep = "; ";
+        }
+        if !self.changed.is_empty() {
+            write!(f, "{sep}changed {}", self.changed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+
+    use super::*;
+    use crate::aurora_archive::{AuroraLoadError, load_world_manifest, save_world_manifest};
+
+    #[derive(Component, Serialize, Deserialize)]
+    struct Hp(u32);
+
+    #[derive(Component, Serialize, Deserialize)]
+    struct Armor(u32);
+
+    #[test]
+    fn test_manifest_registry_schema() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        registry.register::<Armor>();
+        registry.get_factory_mut("Armor").unwrap().mode = SnapshotMode::EmplaceIfNotExists;
+        let mut world = World::new();
+        world.spawn((Hp(3), Armor(1)));
+        let manifest = save_world_manifest(&world, &registry).unwrap();
+        let toml = toml::to_string_pretty(&manifest).unwrap();
+        let manifest: crate::aurora_archive::AuroraWorldManifest = toml::from_str(&toml).unwrap();
+        let schema = manifest.registry_schema().unwrap();
+        assert!(matches!(
+            schema.components["Armor"].mode,
+            SnapshotMode::EmplaceIfNotExists
+        ));
+        assert!(schema.compare(&registry, ["Hp", "Armor"]).is_empty());
+
+        let mut narrowed = registry.clone();
+        narrowed.unregister("Armor");
+        load_world_manifest(&mut World::new(), &manifest, &narrowed).unwrap();
+        narrowed.load_options.strict = true;
+        let mut loaded = World::new();
+        let err = load_world_manifest(&mut loaded, &manifest, &narrowed).unwrap_err();
+        let AuroraLoadError::Limit(LimitError::IncompatibleSchema(mismatch)) = err else {
+            panic!("expected a schema mismatch, got {err:?}");
+        };
+        assert_eq!(mismatch.to_string(), "missing Armor");
+        assert_eq!(loaded.query::<&Hp>().iter(&loaded).count(), 0);
+    }
+}
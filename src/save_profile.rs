@@ -0,0 +1,151 @@
+//! Save profiles: which registered components and resources a save includes.
+//!
+//! A [`SaveProfile`] is a whitelist and a blacklist of registered names. Names may be
+//! components, resources or [component groups](SnapshotRegistry::register_group). Saving
+//! with a profile uses a copy of the registry narrowed by [`SaveProfile::apply`], so every
+//! save path honours it the same way, e.g. a `"network"` profile that only keeps gameplay
+//! state and a `"disk"` profile that keeps everything but caches.
+//!
+//! ```
+//! use bevy_archive::save_profile::SaveProfile;
+//!
+//! let network = SaveProfile::new("network").include("physics").exclude("Velocity");
+//! let disk = SaveProfile::new("disk").exclude("render");
+//! # let _ = (network, disk);
+//! ```
+
+use std::collections::BTreeSet;
+
+use crate::bevy_registry::SnapshotRegistry;
+
+/// Named filter over the components and resources of a [`SnapshotRegistry`].
+///
+/// Without any [`include`](Self::include) everything is allowed; once a name is included,
+/// only included names are. [`exclude`](Self::exclude) always wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveProfile {
+    pub name: String,
+    include: Option<BTreeSet<String>>,
+    exclude: BTreeSet<String>,
+}
+
+impl SaveProfile {
+    /// A profile that includes everything.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Whitelist a component, resource or group name.
+    pub fn include(mut self, name: impl Into<String>) -> Self {
+        self.include.get_or_insert_default().insert(name.into());
+        self
+    }
+
+    /// Blacklist a component, resource or group name.
+    pub fn exclude(mut self, name: impl Into<String>) -> Self {
+        self.exclude.insert(name.into());
+        self
+    }
+
+    /// Whether the registered component or resource `name` is saved under this profile.
+    pub fn allows(&self, registry: &SnapshotRegistry, name: &str) -> bool {
+        let listed = |set: &BTreeSet<String>| {
+            set.contains(name)
+                || registry
+                    .groups
+                    .iter()
+                    .any(|(group, members)| members.contains(&name) && set.contains(*group))
+        };
+        if listed(&self.exclude) {
+            return false;
+        }
+        self.include.as_ref().is_none_or(listed)
+    }
+
+    /// A copy of `registry` with only the components and resources this profile allows.
+    pub fn apply(&self, registry: &SnapshotRegistry) -> SnapshotRegistry {
+        let mut narrowed = registry.clone();
+        narrowed
+            .entries
+            .retain(|name, _| self.allows(registry, name));
+        narrowed
+            .type_registry
+            .retain(|name, _| self.allows(registry, name));
+        narrowed
+            .resource_entries
+            .retain(|name, _| self.allows(registry, name));
+        narrowed
+            .resource_types
+            .retain(|name, _| self.allows(registry, name));
+        narrowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use crate::archetype_archive::save_world_arch_snapshot;
+    use crate::aurora_archive::save_world_manifest_with_profile;
+
+    #[derive(Component, Serialize, Deserialize)]
+    struct Position(f32);
+    #[derive(Component, Serialize, Deserialize)]
+    struct Velocity(f32);
+    #[derive(Component, Serialize, Deserialize)]
+    struct Sprite(String);
+    #[derive(Resource, Serialize, Deserialize)]
+    struct Score(u32);
+
+    #[test]
+    fn test_save_profile() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register_group::<(Position, Velocity)>("physics");
+        registry.register_group::<(Sprite,)>("render");
+        registry.resource_register::<Score>();
+        let mut world = World::new();
+        world.insert_resource(Score(3));
+        world.spawn((Position(1.0), Velocity(2.0), Sprite("ship".into())));
+        world.spawn(Sprite("cloud".into()));
+
+        let network = SaveProfile::new("network")
+            .include("physics")
+            .exclude("Velocity");
+        assert!(network.allows(&registry, "Position"));
+        assert!(!network.allows(&registry, "Velocity"));
+        assert!(!network.allows(&registry, "Score"));
+
+        let snapshot = save_world_arch_snapshot(&world, &network.apply(&registry));
+        assert_eq!(snapshot.entities.len(), 1);
+        assert_eq!(snapshot.archetypes[0].component_types, vec!["Position"]);
+        #[cfg(feature = "arrow_rs")]
+        {
+            use crate::binary_archive::WorldArrowSnapshot;
+            let arrow =
+                WorldArrowSnapshot::from_world_profile(&world, &registry, &network).unwrap();
+            assert!(arrow.resources.is_empty());
+            assert!(
+                arrow
+                    .archetypes
+                    .iter()
+                    .all(|t| !t.columns.contains_key("Sprite"))
+            );
+        }
+
+        let disk = SaveProfile::new("disk").exclude("render");
+        let manifest = save_world_manifest_with_profile(&world, &registry, &disk).unwrap();
+        assert!(manifest.world.resources.contains_key("Score"));
+        let components: Vec<_> = manifest
+            .world
+            .archetypes
+            .iter()
+            .flat_map(|a| a.components.clone())
+            .collect();
+        assert_eq!(components, vec!["Position", "Velocity"]);
+    }
+}
@@ -0,0 +1,312 @@
+//! Headless helpers for regression-testing simulations against snapshots.
+//!
+//! [`run_scenario`] loads an Aurora manifest into a fresh world, runs a schedule for a
+//! fixed number of ticks and returns the resulting [`WorldArchSnapshot`], which can then be
+//! compared with a golden snapshot using [`assert_snapshot_matches`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::ScheduleLabel;
+use bevy_ecs::system::ScheduleSystem;
+use serde_json::Value;
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
+
+use crate::archetype_archive::{WorldArchSnapshot, save_world_arch_snapshot};
+use crate::aurora_archive::{AuroraLoadError, AuroraWorldManifest, load_world_manifest};
+use crate::bevy_registry::SnapshotRegistry;
+#[cfg(feature = "uuid")]
+use crate::snapshot_uuid::snapshot_uuids;
+
+/// Label of the schedule [`run_scenario`] builds from the provided systems.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScenarioSchedule;
+
+/// Load `manifest` into a fresh world, run `systems` for `ticks` ticks and snapshot the result.
+///
+/// Blobs referenced by path are resolved like [`load_world_manifest`] does. The registry is
+/// inserted as a resource before the first tick so systems can reach it.
+pub fn run_scenario<M>(
+    manifest: &AuroraWorldManifest,
+    registry: &SnapshotRegistry,
+    systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ticks: usize,
+) -> Result<WorldArchSnapshot, AuroraLoadError> {
+    let mut world = World::new();
+    load_world_manifest(&mut world, manifest, registry)?;
+    world.insert_resource(registry.clone());
+
+    let mut schedule = Schedule::new(ScenarioSchedule);
+    schedule.add_systems(systems);
+    for _ in 0..ticks {
+        schedule.run(&mut world);
+    }
+    Ok(save_world_arch_snapshot(&world, registry))
+}
+
+/// How strictly [`diff_snapshots`] compares two snapshots.
+#[derive(Debug, Clone, Default)]
+pub struct Tolerance {
+    /// Largest absolute difference at which two numbers still count as equal.
+    pub float_eps: f64,
+    /// Component type names left out of the comparison.
+    pub ignore_components: Vec<String>,
+}
+
+/// Most differences listed by [`assert_snapshot_matches`] before the rest is summarized.
+const MAX_REPORTED_DIFFS: usize = 50;
+
+/// Identity under which [`diff_snapshots`] pairs entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum EntityKey {
+    #[cfg(feature = "uuid")]
+    Uuid(Uuid),
+    Index(u32),
+}
+
+impl fmt::Display for EntityKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "uuid")]
+            EntityKey::Uuid(uuid) => write!(f, "entity {uuid}"),
+            EntityKey::Index(index) => write!(f, "entity {index}"),
+        }
+    }
+}
+
+fn components_by_entity<'a>(
+    snapshot: &'a WorldArchSnapshot,
+    tolerance: &Tolerance,
+    keys: &HashMap<u32, EntityKey>,
+) -> BTreeMap<EntityKey, BTreeMap<&'a str, &'a Value>> {
+    let mut out: BTreeMap<EntityKey, BTreeMap<&str, &Value>> = BTreeMap::new();
+    for arch in &snapshot.archetypes {
+        for (row, &entity) in arch.entities.iter().enumerate() {
+            let key = keys
+                .get(&entity)
+                .copied()
+                .unwrap_or(EntityKey::Index(entity));
+            let comps = out.entry(key).or_default();
+            for (ty, value) in arch.get_row(row) {
+                if !tolerance.ignore_components.iter().any(|i| i == ty) {
+                    comps.insert(ty, value);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// UUID keys of the entities of both snapshots, or none unless both carry UUIDs.
+#[cfg(feature = "uuid")]
+fn uuid_keys(
+    actual: &WorldArchSnapshot,
+    golden: &WorldArchSnapshot,
+) -> (HashMap<u32, EntityKey>, HashMap<u32, EntityKey>) {
+    let (actual, golden) = (snapshot_uuids(actual), snapshot_uuids(golden));
+    if actual.is_empty() || golden.is_empty() {
+        return Default::default();
+    }
+    let keys = |uuids: HashMap<u32, Uuid>| {
+        uuids
+            .into_iter()
+            .map(|(entity, uuid)| (entity, EntityKey::Uuid(uuid)))
+            .collect()
+    };
+    (keys(actual), keys(golden))
+}
+
+#[cfg(not(feature = "uuid"))]
+fn uuid_keys(
+    _actual: &WorldArchSnapshot,
+    _golden: &WorldArchSnapshot,
+) -> (HashMap<u32, EntityKey>, HashMap<u32, EntityKey>) {
+    Default::default()
+}
+
+fn diff_values(path: &str, actual: &Value, golden: &Value, eps: f64, out: &mut Vec<String>) {
+    match (actual, golden) {
+        (Value::Number(a), Value::Number(g)) => {
+            let (a, g) = (
+                a.as_f64().unwrap_or(f64::NAN),
+                g.as_f64().unwrap_or(f64::NAN),
+            );
+            if !(a == g || (a - g).abs() <= eps || (a.is_nan() && g.is_nan())) {
+                out.push(format!(
+                    "{path}: {a} != {g} (diff {}, eps {eps})",
+                    (a - g).abs()
+                ));
+            }
+        }
+        (Value::Array(a), Value::Array(g)) => {
+            if a.len() != g.len() {
+                out.push(format!("{path}: length {} != {}", a.len(), g.len()));
+                return;
+            }
+            for (i, (a, g)) in a.iter().zip(g).enumerate() {
+                diff_values(&format!("{path}[{i}]"), a, g, eps, out);
+            }
+        }
+        (Value::Object(a), Value::Object(g)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(g.keys()).collect();
+            for key in keys {
+                match (a.get(key), g.get(key)) {
+                    (Some(a), Some(g)) => diff_values(&format!("{path}.{key}"), a, g, eps, out),
+                    (Some(_), None) => out.push(format!("{path}.{key}: not in golden")),
+                    (None, _) => out.push(format!("{path}.{key}: missing")),
+                }
+            }
+        }
+        _ if actual == golden => {}
+        _ => out.push(format!("{path}: {actual} != {golden}")),
+    }
+}
+
+/// List every difference between `actual` and `golden`, one readable line each.
+///
+/// Entities are matched by `SnapshotUuid` when the `uuid` feature is enabled and both
+/// snapshots carry UUIDs, and by index otherwise; components are matched by type name,
+/// regardless of which archetype holds them. An empty result means the snapshots match.
+pub fn diff_snapshots(
+    actual: &WorldArchSnapshot,
+    golden: &WorldArchSnapshot,
+    tolerance: &Tolerance,
+) -> Vec<String> {
+    let (actual_keys, golden_keys) = uuid_keys(actual, golden);
+    let actual = components_by_entity(actual, tolerance, &actual_keys);
+    let golden = components_by_entity(golden, tolerance, &golden_keys);
+    let mut diffs = Vec::new();
+    let entities: BTreeSet<EntityKey> = actual.keys().chain(golden.keys()).copied().collect();
+    for entity in entities {
+        let (a, g) = match (actual.get(&entity), golden.get(&entity)) {
+            (Some(a), Some(g)) => (a, g),
+            (Some(_), None) => {
+                diffs.push(format!("{entity}: not in golden"));
+                continue;
+            }
+            (None, _) => {
+                diffs.push(format!("{entity}: missing"));
+                continue;
+            }
+        };
+        let types: BTreeSet<&str> = a.keys().chain(g.keys()).copied().collect();
+        for ty in types {
+            let path = format!("{entity}: {ty}");
+            match (a.get(ty), g.get(ty)) {
+                (Some(a), Some(g)) => diff_values(&path, a, g, tolerance.float_eps, &mut diffs),
+                (Some(_), None) => diffs.push(format!("{path}: not in golden")),
+                (None, _) => diffs.push(format!("{path}: missing")),
+            }
+        }
+    }
+    diffs
+}
+
+/// Panic with a readable diff unless `actual` matches `golden` within `tolerance`.
+#[track_caller]
+pub fn assert_snapshot_matches(
+    actual: &WorldArchSnapshot,
+    golden: &WorldArchSnapshot,
+    tolerance: Tolerance,
+) {
+    let diffs = diff_snapshots(actual, golden, &tolerance);
+    if diffs.is_empty() {
+        return;
+    }
+    let mut message = format!(
+        "snapshot does not match golden ({} differences):\n",
+        diffs.len()
+    );
+    for line in diffs.iter().take(MAX_REPORTED_DIFFS) {
+        message.push_str("  ");
+        message.push_str(line);
+        message.push('\n');
+    }
+    if diffs.len() > MAX_REPORTED_DIFFS {
+        message.push_str(&format!(
+            "  ... and {} more\n",
+            diffs.len() - MAX_REPORTED_DIFFS
+        ));
+    }
+    panic!("{message}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aurora_archive::save_world_manifest;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Pos(f64);
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Vel(f64);
+
+    fn integrate(mut q: Query<(&mut Pos, &Vel)>) {
+        for (mut p, v) in &mut q {
+            p.0 += v.0;
+        }
+    }
+
+    #[test]
+    fn test_run_scenario() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Pos>();
+        registry.register::<Vel>();
+        let mut world = World::new();
+        world.spawn((Pos(0.0), Vel(0.5)));
+        world.spawn(Pos(2.0));
+        let manifest = save_world_manifest(&world, &registry).unwrap();
+
+        let snapshot = run_scenario(&manifest, &registry, integrate, 4).unwrap();
+        let mut positions: Vec<f64> = snapshot
+            .archetypes
+            .iter()
+            .filter_map(|a| a.get_column("Pos"))
+            .flatten()
+            .map(|v| v.as_f64().unwrap())
+            .collect();
+        positions.sort_by(f64::total_cmp);
+        assert_eq!(positions, vec![2.0, 2.0]);
+    }
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Label(String);
+
+    #[test]
+    fn test_snapshot_tolerance() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Pos>();
+        registry.register::<Vel>();
+        registry.register::<Label>();
+        let mut world = World::new();
+        let e = world.spawn((Pos(1.0), Vel(0.1), Label("a".into()))).id();
+        let golden = save_world_arch_snapshot(&world, &registry);
+
+        world
+            .entity_mut(e)
+            .insert((Pos(1.0 + 1e-9), Label("b".into())));
+        world.entity_mut(e).remove::<Vel>();
+        let actual = save_world_arch_snapshot(&world, &registry);
+
+        let strict = diff_snapshots(&actual, &golden, &Tolerance::default());
+        assert_eq!(strict.len(), 3, "{strict:?}");
+        assert!(strict.iter().any(|d| d.contains("Vel: missing")));
+        assert!(strict.iter().any(|d| d.contains("Label: \"b\" != \"a\"")));
+
+        assert_snapshot_matches(
+            &actual,
+            &golden,
+            Tolerance {
+                float_eps: 1e-6,
+                ignore_components: vec!["Vel".into(), "Label".into()],
+            },
+        );
+        let result = std::panic::catch_unwind(|| {
+            assert_snapshot_matches(&actual, &golden, Tolerance::default())
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Interop with Bevy's reflection-based scenes (`bevy_scene` feature).
+//!
+//! Bevy 0.19 calls the old `DynamicScene` a [`DynamicWorld`] (crate
+//! `bevy_world_serialization`); its text form is the `.scn.ron` format. The conversions
+//! here go through a scratch [`World`]: a snapshot is loaded with the [`SnapshotRegistry`]
+//! codecs and extracted with the reflection [`TypeRegistry`], or the other way around. A
+//! component survives the trip only if it is registered in both registries, with
+//! `#[reflect(Component)]` on the Bevy side.
+//!
+//! Entity indices are kept in both directions. Resources are not converted, since
+//! [`WorldArchSnapshot`] carries none.
+
+use bevy_asset::{LoadFromPath, UntypedHandle, uuid::Uuid};
+use bevy_ecs::entity::{EntityHashMap, EntityIndex};
+use bevy_ecs::prelude::*;
+use bevy_reflect::TypeRegistry;
+use bevy_world_serialization::serde::WorldDeserializer;
+use bevy_world_serialization::{DynamicWorld, DynamicWorldBuilder, WorldInstanceSpawnError};
+use serde::de::DeserializeSeed;
+
+use crate::archetype_archive::{
+    WorldArchSnapshot, load_world_arch_snapshot, save_world_arch_snapshot,
+};
+use crate::bevy_registry::{LimitError, SnapshotRegistry, try_reserve_entity_slots};
+
+pub use bevy_world_serialization::DynamicWorld as DynamicScene;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SceneInteropError {
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    #[error(transparent)]
+    Spawn(#[from] WorldInstanceSpawnError),
+    #[error("ron error: {0}")]
+    Ron(String),
+}
+
+/// Convert a snapshot into a [`DynamicWorld`] with one dynamic entity per snapshot entity.
+pub fn snapshot_to_dynamic_world(
+    snapshot: &WorldArchSnapshot,
+    registry: &SnapshotRegistry,
+    type_registry: &TypeRegistry,
+) -> Result<DynamicWorld, SceneInteropError> {
+    let mut world = World::new();
+    load_world_arch_snapshot(&mut world, snapshot, registry)?;
+    let entities: Vec<Entity> = snapshot
+        .archetypes
+        .iter()
+        .flat_map(|arch| arch.entities())
+        .filter_map(|&id| EntityIndex::from_raw_u32(id))
+        .map(|index| world.entities().resolve_from_index(index))
+        .collect();
+    Ok(DynamicWorldBuilder::from_world(&world, type_registry)
+        .extract_entities(entities.into_iter())
+        .build())
+}
+
+/// Convert a [`DynamicWorld`] into a snapshot, keeping each dynamic entity's index.
+pub fn dynamic_world_to_snapshot(
+    scene: &DynamicWorld,
+    registry: &SnapshotRegistry,
+    type_registry: &TypeRegistry,
+) -> Result<WorldArchSnapshot, SceneInteropError> {
+    let mut world = World::new();
+    if let Some(max_id) = scene.entities.iter().map(|e| e.entity.index_u32()).max() {
+        try_reserve_entity_slots(&mut world, max_id, &registry.limits)?;
+        world.flush();
+    }
+    let mut entity_map: EntityHashMap<Entity> = scene
+        .entities
+        .iter()
+        .map(|e| {
+            (
+                e.entity,
+                world.entities().resolve_from_index(e.entity.index()),
+            )
+        })
+        .collect();
+    scene.write_to_world_with(&mut world, &mut entity_map, type_registry)?;
+    Ok(save_world_arch_snapshot(&world, registry))
+}
+
+/// Serialize a snapshot as `.scn.ron` text.
+pub fn snapshot_to_scene_ron(
+    snapshot: &WorldArchSnapshot,
+    registry: &SnapshotRegistry,
+    type_registry: &TypeRegistry,
+) -> Result<String, SceneInteropError> {
+    snapshot_to_dynamic_world(snapshot, registry, type_registry)?
+        .serialize(type_registry)
+        .map_err(|e| SceneInteropError::Ron(e.to_string()))
+}
+
+/// Parse `.scn.ron` text into a snapshot.
+///
+/// Asset handles in the scene cannot be resolved here and become placeholder handles.
+pub fn snapshot_from_scene_ron(
+    ron: &str,
+    registry: &SnapshotRegistry,
+    type_registry: &TypeRegistry,
+) -> Result<WorldArchSnapshot, SceneInteropError> {
+    let mut deserializer =
+        ron::de::Deserializer::from_str(ron).map_err(|e| SceneInteropError::Ron(e.to_string()))?;
+    let scene = WorldDeserializer {
+        type_registry,
+        load_from_path: &mut PlaceholderAssets,
+    }
+    .deserialize(&mut deserializer)
+    .map_err(|e| SceneInteropError::Ron(e.to_string()))?;
+    dynamic_world_to_snapshot(&scene, registry, type_registry)
+}
+
+/// Resolves every asset path to a nil UUID handle instead of loading it.
+struct PlaceholderAssets;
+
+impl LoadFromPath for PlaceholderAssets {
+    fn load_from_path_erased(
+        &mut self,
+        type_id: std::any::TypeId,
+        _path: bevy_asset::AssetPath<'static>,
+    ) -> UntypedHandle {
+        UntypedHandle::Uuid {
+            type_id,
+            uuid: Uuid::nil(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_reflect::Reflect;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Reflect, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    #[reflect(Component)]
+    struct Pos {
+        x: f32,
+        y: f32,
+    }
+    #[derive(Component, Reflect, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    #[reflect(Component)]
+    struct Hp(u32);
+
+    #[test]
+    fn test_scene_ron_roundtrip() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Pos>();
+        registry.register::<Hp>();
+        let mut type_registry = TypeRegistry::default();
+        type_registry.register::<Pos>();
+        type_registry.register::<Hp>();
+
+        let mut world = World::new();
+        let a = world.spawn((Pos { x: 1.0, y: 2.0 }, Hp(3))).id();
+        let b = world.spawn(Pos { x: -1.0, y: 0.5 }).id();
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+
+        let ron = snapshot_to_scene_ron(&snapshot, &registry, &type_registry).unwrap();
+        assert!(ron.contains("Pos") && ron.contains("Hp"));
+        let back = snapshot_from_scene_ron(&ron, &registry, &type_registry).unwrap();
+
+        let mut restored = World::new();
+        load_world_arch_snapshot(&mut restored, &back, &registry).unwrap();
+        let at = |e: Entity| restored.entities().resolve_from_index(e.index());
+        assert_eq!(restored.get::<Pos>(at(a)), Some(&Pos { x: 1.0, y: 2.0 }));
+        assert_eq!(restored.get::<Hp>(at(a)), Some(&Hp(3)));
+        assert_eq!(restored.get::<Pos>(at(b)), Some(&Pos { x: -1.0, y: 0.5 }));
+        assert_eq!(restored.get::<Hp>(at(b)), None);
+    }
+}
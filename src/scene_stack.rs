@@ -0,0 +1,248 @@
+//! Snapshots loaded as named layers of one world, e.g. a base level, a lighting pass and a
+//! quest overlay, that can be unloaded or swapped one at a time.
+//!
+//! [`SceneStackExt::push_scene`] loads an archive into fresh entities, like
+//! [`apply_with_remap`](crate::traits::Archive::apply_with_remap), and tags each of them
+//! with a [`SceneLayer`] naming the layer. [`SceneStackExt::unload_scene`] despawns the
+//! tagged entities; [`SceneStackExt::replace_scene`] unloads a layer and loads another
+//! archive in its place in the stack. The layer order is kept in the [`SceneStack`]
+//! resource, inserted on first use.
+//!
+//! Resources saved in a layer are loaded with it but not removed by an unload. Entities
+//! spawned at runtime carry no [`SceneLayer`] and survive every unload.
+
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+
+use crate::bevy_registry::{IDRemapRegistry, SnapshotRegistry};
+use crate::traits::Archive;
+
+/// The layer an entity was loaded with.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SceneLayer(pub String);
+
+/// Loaded layers, bottom first.
+#[derive(Resource, Default)]
+pub struct SceneStack {
+    layers: Vec<String>,
+    /// Remap hooks applied while loading layers, e.g. for components holding entities.
+    pub id_registry: IDRemapRegistry,
+}
+
+impl SceneStack {
+    /// Layer names, bottom first.
+    pub fn layers(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(String::as_str)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.layers.iter().any(|layer| layer == name)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SceneStackError {
+    #[error("scene layer '{0}' is already loaded")]
+    DuplicateLayer(String),
+    #[error("scene layer '{0}' is not loaded")]
+    MissingLayer(String),
+    #[error("scene layer '{layer}': {source}")]
+    Load {
+        layer: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+/// Layered loading on a [`World`].
+pub trait SceneStackExt {
+    /// Load `archive` as the new top layer `name`, returning its entities.
+    ///
+    /// A failed load despawns the entities it spawned and leaves the stack unchanged.
+    fn push_scene<A: Archive>(
+        &mut self,
+        name: &str,
+        archive: &A,
+        registry: &SnapshotRegistry,
+    ) -> Result<Vec<Entity>, SceneStackError>;
+
+    /// Despawn the entities of layer `name` and remove it from the stack, returning how
+    /// many were despawned.
+    fn unload_scene(&mut self, name: &str) -> Result<usize, SceneStackError>;
+
+    /// Unload layer `name` and load `archive` at its place in the stack.
+    ///
+    /// If the new archive fails to load, the layer stays out of the stack.
+    fn replace_scene<A: Archive>(
+        &mut self,
+        name: &str,
+        archive: &A,
+        registry: &SnapshotRegistry,
+    ) -> Result<Vec<Entity>, SceneStackError>;
+
+    /// Entities of layer `name`.
+    fn scene_entities(&mut self, name: &str) -> Vec<Entity>;
+}
+
+impl SceneStackExt for World {
+    fn push_scene<A: Archive>(
+        &mut self,
+        name: &str,
+        archive: &A,
+        registry: &SnapshotRegistry,
+    ) -> Result<Vec<Entity>, SceneStackError> {
+        if self.get_resource_or_init::<SceneStack>().contains(name) {
+            return Err(SceneStackError::DuplicateLayer(name.to_string()));
+        }
+        let entities = load_layer(self, name, archive, registry)?;
+        self.resource_mut::<SceneStack>()
+            .layers
+            .push(name.to_string());
+        Ok(entities)
+    }
+
+    fn unload_scene(&mut self, name: &str) -> Result<usize, SceneStackError> {
+        let mut stack = self.get_resource_or_init::<SceneStack>();
+        let Some(pos) = stack.layers.iter().position(|layer| layer == name) else {
+            return Err(SceneStackError::MissingLayer(name.to_string()));
+        };
+        stack.layers.remove(pos);
+        Ok(despawn_layer(self, name))
+    }
+
+    fn replace_scene<A: Archive>(
+        &mut self,
+        name: &str,
+        archive: &A,
+        registry: &SnapshotRegistry,
+    ) -> Result<Vec<Entity>, SceneStackError> {
+        let mut stack = self.get_resource_or_init::<SceneStack>();
+        let Some(pos) = stack.layers.iter().position(|layer| layer == name) else {
+            return Err(SceneStackError::MissingLayer(name.to_string()));
+        };
+        stack.layers.remove(pos);
+        despawn_layer(self, name);
+        let entities = load_layer(self, name, archive, registry)?;
+        self.resource_mut::<SceneStack>()
+            .layers
+            .insert(pos, name.to_string());
+        Ok(entities)
+    }
+
+    fn scene_entities(&mut self, name: &str) -> Vec<Entity> {
+        self.query::<(Entity, &SceneLayer)>()
+            .iter(self)
+            .filter(|(_, layer)| layer.0 == name)
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+}
+
+fn load_layer<A: Archive>(
+    world: &mut World,
+    name: &str,
+    archive: &A,
+    registry: &SnapshotRegistry,
+) -> Result<Vec<Entity>, SceneStackError> {
+    let mapper: HashMap<u32, Entity> = archive
+        .get_entities()
+        .into_iter()
+        .map(|old| (old, world.spawn(SceneLayer(name.to_string())).id()))
+        .collect();
+    let result = world.resource_scope(|world, stack: Mut<SceneStack>| {
+        archive.apply_with_remap(world, registry, &stack.id_registry, &mapper)
+    });
+    if let Err(source) = result {
+        despawn_layer(world, name);
+        return Err(SceneStackError::Load {
+            layer: name.to_string(),
+            source,
+        });
+    }
+    let mut entities: Vec<Entity> = mapper.into_values().collect();
+    entities.sort();
+    Ok(entities)
+}
+
+fn despawn_layer(world: &mut World, name: &str) -> usize {
+    let entities = world.scene_entities(name);
+    let mut despawned = 0;
+    for entity in entities {
+        // Despawning a parent may already have taken its children along.
+        if let Ok(entity) = world.get_entity_mut(entity) {
+            entity.despawn();
+            despawned += 1;
+        }
+    }
+    despawned
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::aurora_archive::AuroraWorldManifest;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Prop(String);
+
+    fn layer(props: &[&str]) -> (AuroraWorldManifest, SnapshotRegistry) {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Prop>();
+        let mut world = World::new();
+        for prop in props {
+            world.spawn(Prop(prop.to_string()));
+        }
+        (
+            AuroraWorldManifest::create(&world, &registry).unwrap(),
+            registry,
+        )
+    }
+
+    fn props(world: &mut World) -> Vec<String> {
+        let mut props: Vec<_> = world
+            .query::<&Prop>()
+            .iter(world)
+            .map(|p| p.0.clone())
+            .collect();
+        props.sort();
+        props
+    }
+
+    #[test]
+    fn test_scene_stack_layers() {
+        let (base, registry) = layer(&["tree", "rock"]);
+        let (quest, _) = layer(&["chest"]);
+        let (quest2, _) = layer(&["chest", "key"]);
+        let mut world = World::new();
+        world.spawn(Prop("player".to_string()));
+
+        assert_eq!(world.push_scene("base", &base, &registry).unwrap().len(), 2);
+        world.push_scene("quest", &quest, &registry).unwrap();
+        assert!(matches!(
+            world.push_scene("base", &base, &registry),
+            Err(SceneStackError::DuplicateLayer(_))
+        ));
+        assert_eq!(props(&mut world), ["chest", "player", "rock", "tree"]);
+
+        world
+            .push_scene("lighting", &layer(&[]).0, &registry)
+            .unwrap();
+        world.replace_scene("quest", &quest2, &registry).unwrap();
+        let stack = world.resource::<SceneStack>();
+        assert_eq!(
+            stack.layers().collect::<Vec<_>>(),
+            ["base", "quest", "lighting"]
+        );
+        assert_eq!(world.scene_entities("quest").len(), 2);
+
+        assert_eq!(world.unload_scene("base").unwrap(), 2);
+        assert_eq!(props(&mut world), ["chest", "key", "player"]);
+        assert!(matches!(
+            world.unload_scene("base"),
+            Err(SceneStackError::MissingLayer(_))
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+//! Export-time value transformers, e.g. to hash or redact player names and chat logs in
+//! snapshots attached to bug reports.
+//!
+//! A [`Scrubber`] maps registered component and resource names to `on_export` functions
+//! applied to every value they save. Like a [`SaveProfile`](crate::save_profile::SaveProfile)
+//! it is used by saving with the copy of the registry returned by [`Scrubber::apply`], so
+//! the registry and the normal save path stay untouched.
+//!
+//! Transformers see the JSON value of a component, so they apply to the JSON-based formats
+//! (JSON, TOML, CSV, MessagePack). Arrow and Parquet write columns without going through
+//! JSON and skip scrubbed components instead of saving them unscrubbed.
+//!
+//! ```
+//! use bevy_archive::scrub::{Scrubber, hash_strings};
+//! use serde_json::Value;
+//!
+//! let scrubber = Scrubber::new()
+//!     .on_export("PlayerName", hash_strings)
+//!     .on_export("ChatLog", |_| Value::Null);
+//! # let _ = scrubber;
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::bevy_registry::{SnapshotFactory, SnapshotRegistry};
+
+pub type ExportTransformFn = Arc<dyn Fn(Value) -> Value + Send + Sync>;
+
+/// `on_export` transformers by registered component or resource name.
+#[derive(Clone, Default)]
+pub struct Scrubber {
+    transforms: BTreeMap<String, ExportTransformFn>,
+}
+
+impl fmt::Debug for Scrubber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scrubber")
+            .field("components", &self.transforms.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Scrubber {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pass every saved value of the component or resource `name` through `transform`.
+    /// A second transformer for the same name replaces the first.
+    pub fn on_export(
+        mut self,
+        name: impl Into<String>,
+        transform: impl Fn(Value) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        self.transforms.insert(name.into(), Arc::new(transform));
+        self
+    }
+
+    /// Whether values of `name` are transformed.
+    pub fn scrubs(&self, name: &str) -> bool {
+        self.transforms.contains_key(name)
+    }
+
+    /// A copy of `registry` whose factories export values through the transformers.
+    /// Loading with the copy is unaffected.
+    pub fn apply(&self, registry: &SnapshotRegistry) -> SnapshotRegistry {
+        let mut scrubbed = registry.clone();
+        let factories = scrubbed
+            .entries
+            .iter_mut()
+            .chain(scrubbed.resource_entries.iter_mut());
+        for (name, factory) in factories {
+            if let Some(transform) = self.transforms.get(*name) {
+                scrub_factory(factory, transform.clone());
+            }
+        }
+        scrubbed
+    }
+}
+
+fn scrub_factory(factory: &mut SnapshotFactory, transform: ExportTransformFn) {
+    let export = factory.js_value.export.clone();
+    factory.js_value.export = Arc::new(move |world, entity| export(world, entity).map(&*transform));
+    #[cfg(feature = "arrow_rs")]
+    {
+        factory.arrow = None;
+    }
+}
+
+/// Replace every string in `value` with the lowercase hex BLAKE3 hash of its bytes, keeping the
+/// shape of the value. Equal strings stay equal, so a bug report can still tell which
+/// entities share a name.
+pub fn hash_strings(value: Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(blake3::hash(s.as_bytes()).to_hex().to_string()),
+        Value::Array(items) => Value::Array(items.into_iter().map(hash_strings).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, field)| (key, hash_strings(field)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::*;
+    use crate::archetype_archive::{save_world_arch_snapshot, save_world_resource};
+
+    #[derive(Component, Serialize, Deserialize)]
+    struct PlayerName(String);
+
+    #[derive(Component, Serialize, Deserialize)]
+    struct Health(f32);
+
+    #[derive(Resource, Serialize, Deserialize)]
+    struct ChatLog(Vec<String>);
+
+    #[test]
+    fn test_scrubber_only_changes_scrubbed_saves() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<PlayerName>();
+        registry.register::<Health>();
+        registry.resource_register::<ChatLog>();
+        let mut world = World::new();
+        world.insert_resource(ChatLog(vec!["gg".to_string()]));
+        world.spawn((PlayerName("alice".to_string()), Health(3.0)));
+
+        let scrubber = Scrubber::new()
+            .on_export("PlayerName", hash_strings)
+            .on_export("ChatLog", |_| Value::Null);
+        let scrubbed = scrubber.apply(&registry);
+        let snapshot = save_world_arch_snapshot(&world, &scrubbed);
+        let names = snapshot.archetypes[0].get_column("PlayerName").unwrap();
+        assert_eq!(names, &vec![hash_strings(json!("alice"))]);
+        assert_ne!(names[0], json!("alice"));
+        assert_eq!(
+            snapshot.archetypes[0].get_column("Health").unwrap(),
+            &vec![json!(3.0)]
+        );
+        assert_eq!(
+            save_world_resource(&world, &scrubbed)["ChatLog"],
+            Value::Null
+        );
+
+        let normal = save_world_arch_snapshot(&world, &registry);
+        assert_eq!(
+            normal.archetypes[0].get_column("PlayerName").unwrap(),
+            &vec![json!("alice")]
+        );
+        assert_eq!(
+            save_world_resource(&world, &registry)["ChatLog"],
+            json!(["gg"])
+        );
+    }
+}
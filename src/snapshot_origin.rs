@@ -0,0 +1,115 @@
+//! Provenance of loaded entities.
+//!
+//! Set [`SnapshotRegistry::origin`] to a source label, e.g. the path of the file being
+//! loaded, and the loaders insert a [`SnapshotOrigin`] on every entity they write, holding
+//! the label and the entity id stored in the snapshot. Entities loaded through a remapper
+//! keep their saved id, so a merged or instanced prefab can be traced back to its rows, and
+//! everything one load wrote can be found again, e.g. to unload it.
+//!
+//! The origin is inserted before the [`load_events`](crate::load_events) fire, so observers
+//! already see it.
+
+use bevy_ecs::prelude::*;
+
+use crate::bevy_registry::SnapshotRegistry;
+
+/// Where a loaded entity came from.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotOrigin {
+    /// The [`SnapshotRegistry::origin`] of the load.
+    pub source: String,
+    /// Entity id as stored in the snapshot.
+    pub original_id: u32,
+}
+
+impl SnapshotRegistry {
+    /// Whether loaders must collect the (snapshot id, entity) pairs they wrote, for load
+    /// events or origins.
+    pub(crate) fn tracks_loaded(&self) -> bool {
+        self.emit_load_events || self.origin.is_some()
+    }
+}
+
+/// Insert a [`SnapshotOrigin`] on the entities of `loaded` (snapshot id, world entity) pairs
+/// when `reg` has an [`origin`](SnapshotRegistry::origin).
+pub(crate) fn insert_origins(world: &mut World, reg: &SnapshotRegistry, loaded: &[(u32, Entity)]) {
+    let Some(source) = &reg.origin else {
+        return;
+    };
+    for &(original_id, entity) in loaded {
+        if let Ok(mut entity) = world.get_entity_mut(entity) {
+            entity.insert(SnapshotOrigin {
+                source: source.clone(),
+                original_id,
+            });
+        }
+    }
+}
+
+/// Entities of `world` loaded from `source`, with their snapshot ids.
+pub fn entities_from_source(world: &mut World, source: &str) -> Vec<(u32, Entity)> {
+    let mut entities: Vec<_> = world
+        .query::<(Entity, &SnapshotOrigin)>()
+        .iter(world)
+        .filter(|(_, origin)| origin.source == source)
+        .map(|(entity, origin)| (origin.original_id, entity))
+        .collect();
+    entities.sort();
+    entities
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::archetype_archive::{load_world_arch_snapshot, save_world_arch_snapshot};
+    use crate::aurora_archive::AuroraWorldManifest;
+    use crate::bevy_registry::IDRemapRegistry;
+    use crate::entity_archive::{load_world_snapshot, save_world_snapshot};
+    use crate::traits::Archive;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Crate(u32);
+
+    #[test]
+    fn test_snapshot_origin() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Crate>();
+        let mut world = World::new();
+        let first = world.spawn(Crate(1)).id();
+        world.spawn(Crate(2));
+        let snapshot = save_world_arch_snapshot(&world, &registry);
+
+        let mut loaded = World::new();
+        load_world_arch_snapshot(&mut loaded, &snapshot, &registry).unwrap();
+        assert_eq!(loaded.query::<&SnapshotOrigin>().iter(&loaded).count(), 0);
+
+        registry.origin = Some("level.json".to_string());
+        let json = save_world_snapshot(&world, &registry);
+        let mut loaded = World::new();
+        load_world_snapshot(&mut loaded, &json, &registry).unwrap();
+        assert_eq!(entities_from_source(&mut loaded, "level.json").len(), 2);
+
+        registry.origin = Some("prefab".to_string());
+        let manifest = AuroraWorldManifest::create(&world, &registry).unwrap();
+        let mapper: HashMap<u32, Entity> = manifest
+            .get_entities()
+            .into_iter()
+            .map(|old| (old, loaded.spawn_empty().id()))
+            .collect();
+        manifest
+            .apply_with_remap(&mut loaded, &registry, &IDRemapRegistry::default(), &mapper)
+            .unwrap();
+        let prefab = entities_from_source(&mut loaded, "prefab");
+        assert_eq!(prefab.len(), 2);
+        let (_, copy) = prefab
+            .iter()
+            .find(|(id, _)| *id == first.index_u32())
+            .unwrap();
+        assert_eq!(loaded.get::<Crate>(*copy), Some(&Crate(1)));
+        assert_eq!(entities_from_source(&mut loaded, "level.json").len(), 2);
+    }
+}
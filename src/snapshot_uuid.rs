@@ -0,0 +1,274 @@
+//! Opt-in stable entity identity that survives saves, loads and merges.
+//!
+//! Entity indices are reassigned whenever a world is rebuilt, so they cannot tell whether
+//! two snapshots describe "the same" entity. A [`SnapshotUuid`] can: register it like any
+//! other component and it is written by every archive format as a hyphenated string.
+//! [`save_world_arch_snapshot_with_uuids`] gives entities that lack one a fresh UUID before
+//! saving, so identities are fixed on the first save and reused afterwards.
+//!
+//! While a [`SnapshotUuidIndex`] resource exists, component hooks keep it in sync with every
+//! `SnapshotUuid` inserted or removed, including those written by snapshot loaders.
+//! [`diff_snapshots`](crate::scenario::diff_snapshots) matches entities by UUID when both
+//! snapshots carry them, and [`SnapshotUuidIndex::remapper`] merges a snapshot into a world
+//! by UUID through [`Archive::apply_with_remap`](crate::traits::Archive::apply_with_remap).
+
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::component::ComponentId;
+use bevy_ecs::lifecycle::HookContext;
+use bevy_ecs::prelude::*;
+use bevy_ecs::world::DeferredWorld;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use uuid::Uuid;
+
+use crate::archetype_archive::{WorldArchSnapshot, save_world_arch_snapshot};
+use crate::bevy_registry::SnapshotRegistry;
+
+/// Column name under which [`SnapshotUuid`] is stored in snapshots.
+pub const SNAPSHOT_UUID_COLUMN: &str = "SnapshotUuid";
+
+/// Stable identity of an entity across saves.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[component(on_insert = index_uuid, on_discard = unindex_uuid)]
+pub struct SnapshotUuid(pub Uuid);
+
+impl SnapshotUuid {
+    /// A fresh random (v4) identity.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for SnapshotUuid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serialize for SnapshotUuid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0.hyphenated())
+    }
+}
+
+impl<'de> Deserialize<'de> for SnapshotUuid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Uuid::parse_str(&s)
+            .map(Self)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Lookup from [`SnapshotUuid`] to the entity currently carrying it.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct SnapshotUuidIndex {
+    entities: HashMap<Uuid, Entity>,
+}
+
+impl SnapshotUuidIndex {
+    /// Insert the index into `world`, filled from the UUIDs already present.
+    pub fn install(world: &mut World) {
+        let entities = world
+            .query::<(Entity, &SnapshotUuid)>()
+            .iter(world)
+            .map(|(e, uuid)| (uuid.0, e))
+            .collect();
+        world.insert_resource(Self { entities });
+    }
+
+    pub fn get(&self, uuid: Uuid) -> Option<Entity> {
+        self.entities.get(&uuid).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Map every entity of `snapshot` to its counterpart in `world`.
+    ///
+    /// Entities whose UUID is already indexed map onto the existing entity; all others
+    /// (including those without a UUID) get a newly spawned entity.
+    pub fn remapper(
+        &self,
+        world: &mut World,
+        snapshot: &WorldArchSnapshot,
+    ) -> HashMap<u32, Entity> {
+        let uuids = snapshot_uuids(snapshot);
+        let mut map = HashMap::new();
+        for arch in &snapshot.archetypes {
+            for &id in arch.entities() {
+                map.entry(id).or_insert_with(|| {
+                    uuids
+                        .get(&id)
+                        .and_then(|uuid| self.get(*uuid))
+                        .unwrap_or_else(|| world.spawn_empty().id())
+                });
+            }
+        }
+        map
+    }
+}
+
+fn index_uuid(mut world: DeferredWorld, ctx: HookContext) {
+    let Some(uuid) = world.get::<SnapshotUuid>(ctx.entity).map(|u| u.0) else {
+        return;
+    };
+    if let Some(mut index) = world.get_resource_mut::<SnapshotUuidIndex>() {
+        index.entities.insert(uuid, ctx.entity);
+    }
+}
+
+fn unindex_uuid(mut world: DeferredWorld, ctx: HookContext) {
+    let Some(uuid) = world.get::<SnapshotUuid>(ctx.entity).map(|u| u.0) else {
+        return;
+    };
+    if let Some(mut index) = world.get_resource_mut::<SnapshotUuidIndex>()
+        && index.entities.get(&uuid) == Some(&ctx.entity)
+    {
+        index.entities.remove(&uuid);
+    }
+}
+
+/// Read the `SnapshotUuid` column of every archetype in `snapshot`, keyed by entity id.
+///
+/// Values that do not parse as a UUID are skipped.
+pub fn snapshot_uuids(snapshot: &WorldArchSnapshot) -> HashMap<u32, Uuid> {
+    let mut out = HashMap::new();
+    for arch in &snapshot.archetypes {
+        let Some(col) = arch.get_column(SNAPSHOT_UUID_COLUMN) else {
+            continue;
+        };
+        for (&id, value) in arch.entities().iter().zip(col) {
+            if let Some(uuid) = value.as_str().and_then(|s| Uuid::parse_str(s).ok()) {
+                out.insert(id, uuid);
+            }
+        }
+    }
+    out
+}
+
+/// Give a fresh [`SnapshotUuid`] to every entity that will be saved but has none yet.
+///
+/// Only entities holding at least one component known to `registry` are considered.
+/// Returns how many UUIDs were assigned.
+pub fn assign_snapshot_uuids(world: &mut World, registry: &SnapshotRegistry) -> usize {
+    let registered: HashSet<ComponentId> = registry
+        .type_registry
+        .keys()
+        .filter_map(|&name| registry.comp_id_by_name(name, world))
+        .collect();
+    let uuid_id = world.component_id::<SnapshotUuid>();
+    let missing: Vec<Entity> = world
+        .archetypes()
+        .iter()
+        .filter(|arch| !arch.contains(bevy_ecs::resource::IS_RESOURCE))
+        .filter(|arch| uuid_id.is_none_or(|id| !arch.contains(id)))
+        .filter(|arch| arch.components().iter().any(|c| registered.contains(c)))
+        .flat_map(|arch| arch.entities().iter().map(|e| e.id()))
+        .collect();
+    for &entity in &missing {
+        world.entity_mut(entity).insert(SnapshotUuid::new());
+    }
+    missing.len()
+}
+
+/// [`save_world_arch_snapshot`] after [`assign_snapshot_uuids`].
+///
+/// `SnapshotUuid` must be registered in `registry` for the UUIDs to end up in the snapshot.
+pub fn save_world_arch_snapshot_with_uuids(
+    world: &mut World,
+    registry: &SnapshotRegistry,
+) -> WorldArchSnapshot {
+    assign_snapshot_uuids(world, registry);
+    save_world_arch_snapshot(world, registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archetype_archive::load_world_arch_snapshot_with_remap;
+    use crate::aurora_archive::{load_world_manifest, save_world_manifest};
+    use crate::bevy_registry::IDRemapRegistry;
+    use crate::scenario::diff_snapshots;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hp(u32);
+
+    fn registry() -> SnapshotRegistry {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Hp>();
+        registry.register::<SnapshotUuid>();
+        registry
+    }
+
+    #[test]
+    fn test_uuid_survives_manifest_roundtrip() {
+        let registry = registry();
+        let mut world = World::new();
+        let a = world.spawn(Hp(1)).id();
+        world.spawn(Hp(2));
+        world.spawn_empty();
+
+        assert_eq!(assign_snapshot_uuids(&mut world, &registry), 2);
+        assert_eq!(assign_snapshot_uuids(&mut world, &registry), 0);
+        let uuid_a = *world.get::<SnapshotUuid>(a).unwrap();
+
+        let manifest = save_world_manifest(&world, &registry).unwrap();
+        let mut loaded = World::new();
+        SnapshotUuidIndex::install(&mut loaded);
+        load_world_manifest(&mut loaded, &manifest, &registry).unwrap();
+
+        let index = loaded.resource::<SnapshotUuidIndex>();
+        assert_eq!(index.len(), 2);
+        let e = index.get(uuid_a.0).unwrap();
+        assert_eq!(loaded.get::<Hp>(e), Some(&Hp(1)));
+
+        loaded.entity_mut(e).remove::<SnapshotUuid>();
+        assert_eq!(loaded.resource::<SnapshotUuidIndex>().get(uuid_a.0), None);
+    }
+
+    #[test]
+    fn test_merge_and_diff_by_uuid() {
+        let registry = registry();
+        let mut world = World::new();
+        let a = world.spawn(Hp(1)).id();
+        let golden = save_world_arch_snapshot_with_uuids(&mut world, &registry);
+        let uuid_a = world.get::<SnapshotUuid>(a).unwrap().0;
+
+        // Same identity at a different index, plus one new entity.
+        let mut other = World::new();
+        other.spawn_empty();
+        other.spawn((Hp(5), SnapshotUuid(uuid_a)));
+        other.spawn(Hp(7));
+        let incoming = save_world_arch_snapshot_with_uuids(&mut other, &registry);
+
+        let diffs = diff_snapshots(&incoming, &golden, &Default::default());
+        assert_eq!(diffs.len(), 2, "{diffs:?}");
+        assert!(
+            diffs
+                .iter()
+                .any(|d| d.contains(&format!("entity {uuid_a}: Hp")))
+        );
+
+        SnapshotUuidIndex::install(&mut world);
+        let mapper = world
+            .resource::<SnapshotUuidIndex>()
+            .clone()
+            .remapper(&mut world, &incoming);
+        load_world_arch_snapshot_with_remap(
+            &mut world,
+            &incoming,
+            &registry,
+            &IDRemapRegistry::default(),
+            &mapper,
+        )
+        .unwrap();
+        assert_eq!(world.get::<Hp>(a), Some(&Hp(5)));
+        assert_eq!(world.resource::<SnapshotUuidIndex>().len(), 2);
+    }
+}
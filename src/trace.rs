@@ -0,0 +1,48 @@
+//! Diagnostics routed through [`tracing`](https://docs.rs/tracing) when the `tracing`
+//! feature is enabled, and to stderr otherwise.
+//!
+//! Save and load paths open a `debug` span per archetype (`save_archetype`,
+//! `load_archetype`, …) so a subscriber can attribute time and warnings to the archetype
+//! being processed. Without the feature the spans compile to nothing.
+
+/// Span guard returned by `archive_span!`; a no-op without the `tracing` feature.
+#[cfg(feature = "tracing")]
+pub(crate) type SpanGuard = tracing::span::EnteredSpan;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct SpanGuard;
+
+/// Enter a `debug` span named `$name` with `key = value` fields for the rest of the scope.
+macro_rules! archive_span {
+    ($name:literal $(, $key:ident = $value:expr)* $(,)?) => {{
+        #[cfg(feature = "tracing")]
+        let guard: $crate::trace::SpanGuard =
+            tracing::debug_span!($name $(, $key = $value)*).entered();
+        #[cfg(not(feature = "tracing"))]
+        let guard = {
+            $(let _ = &$value;)*
+            $crate::trace::SpanGuard
+        };
+        guard
+    }};
+}
+
+/// `tracing::warn!` with the `tracing` feature, `eprintln!` without.
+macro_rules! archive_warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        tracing::warn!($($arg)*);
+        #[cfg(not(feature = "tracing"))]
+        eprintln!($($arg)*);
+    }};
+}
+
+/// `tracing::error!` with the `tracing` feature, `eprintln!` without.
+macro_rules! archive_error {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        tracing::error!($($arg)*);
+        #[cfg(not(feature = "tracing"))]
+        eprintln!($($arg)*);
+    }};
+}
@@ -40,6 +40,17 @@ pub trait Archive: Sized {
         path: impl AsRef<Path>,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>>;
 
+    /// Encode the archive in memory, e.g. to send it over a channel or socket. Uses the
+    /// format [`save_to`](Self::save_to) picks for a path without a known extension.
+    fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Err("In-memory encoding not implemented for this archive format".into())
+    }
+
+    /// Decode an archive encoded by [`to_bytes`](Self::to_bytes).
+    fn from_bytes(_bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Err("In-memory decoding not implemented for this archive format".into())
+    }
+
     /// Get all entity IDs present in this archive.
     fn get_entities(&self) -> Vec<u32> {
         vec![]
@@ -54,3 +65,52 @@ pub trait Archive: Sized {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Component, Serialize, Deserialize, Debug, PartialEq)]
+    struct Score(u32);
+
+    fn roundtrip<A: Archive>() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Score>();
+        let mut world = World::new();
+        world.spawn(Score(5));
+        world.spawn(Score(8));
+
+        let bytes = A::create(&world, &registry).unwrap().to_bytes().unwrap();
+        let mut loaded = World::new();
+        A::from_bytes(&bytes)
+            .unwrap()
+            .apply(&mut loaded, &registry)
+            .unwrap();
+        let mut scores: Vec<u32> = loaded
+            .query::<&Score>()
+            .iter(&loaded)
+            .map(|s| s.0)
+            .collect();
+        scores.sort();
+        assert_eq!(scores, [5, 8], "{}", std::any::type_name::<A>());
+    }
+
+    #[test]
+    fn test_archive_bytes_roundtrip() {
+        roundtrip::<crate::entity_archive::WorldSnapshot>();
+        roundtrip::<crate::aurora_archive::AuroraWorldManifest>();
+        roundtrip::<crate::binary_archive::msgpack_archive::MsgPackArchive>();
+        #[cfg(feature = "arrow_rs")]
+        roundtrip::<crate::binary_archive::WorldArrowSnapshot>();
+
+        let json = serde_json::to_vec(
+            &crate::aurora_archive::AuroraWorldManifest::create(&World::new(), &Default::default())
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(crate::aurora_archive::AuroraWorldManifest::from_bytes(&json).is_ok());
+    }
+}
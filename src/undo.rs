@@ -0,0 +1,228 @@
+//! Undo/redo for editors, built on [`diff_archive`](crate::diff_archive).
+//!
+//! [`UndoRedo`] remembers the world at the last save point. Each
+//! [`save_point`](UndoRedo::save_point) diffs the world against it and pushes the forward
+//! and reverse [`SnapshotDiff`]s as one step; [`undo`](UndoRedo::undo) and
+//! [`redo`](UndoRedo::redo) replay them through the registry's JSON codecs with
+//! [`apply_patch`]. Only registered components are recorded.
+//!
+//! Changes made after the last save point are discarded by `undo` and `redo`, the way an
+//! editor drops an unsaved edit when the user steps through history.
+
+use bevy_ecs::prelude::*;
+
+use crate::archetype_archive::{WorldArchSnapshot, save_world_arch_snapshot};
+use crate::bevy_registry::SnapshotRegistry;
+use crate::diff_archive::{SnapshotDiff, apply_patch, diff_world_snapshots};
+use crate::journal::JournalError;
+
+/// One recorded edit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoStep {
+    /// Turns the state before the edit into the state after it.
+    pub forward: SnapshotDiff,
+    /// Turns the state after the edit back into the state before it.
+    pub reverse: SnapshotDiff,
+}
+
+/// Undo and redo stacks of [`UndoStep`]s.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct UndoRedo {
+    base: Option<WorldArchSnapshot>,
+    undo: Vec<UndoStep>,
+    redo: Vec<UndoStep>,
+    /// Most undo steps kept; the oldest are dropped past it. `None` keeps all.
+    pub max_depth: Option<usize>,
+}
+
+impl UndoRedo {
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Recorded steps, oldest first.
+    pub fn undo_steps(&self) -> &[UndoStep] {
+        &self.undo
+    }
+
+    /// Undone steps, the next one to redo last.
+    pub fn redo_steps(&self) -> &[UndoStep] {
+        &self.redo
+    }
+
+    /// Record the changes since the previous save point as an undo step, using the
+    /// [`SnapshotRegistry`] resource of `world`.
+    ///
+    /// The first call only remembers the current state. Returns whether a step was pushed;
+    /// nothing is pushed when no registered component changed. A new step clears the redo
+    /// stack.
+    pub fn save_point(&mut self, world: &World) -> bool {
+        self.save_point_with(world, world.resource::<SnapshotRegistry>())
+    }
+
+    pub fn save_point_with(&mut self, world: &World, registry: &SnapshotRegistry) -> bool {
+        let current = save_world_arch_snapshot(world, registry);
+        let Some(base) = self.base.replace(current) else {
+            return false;
+        };
+        let current = self.base.as_ref().unwrap();
+        let forward = diff_world_snapshots(&base, current);
+        if forward.is_empty() {
+            return false;
+        }
+        let reverse = diff_world_snapshots(current, &base);
+        self.undo.push(UndoStep { forward, reverse });
+        if let Some(max) = self.max_depth {
+            let excess = self.undo.len().saturating_sub(max);
+            self.undo.drain(..excess);
+        }
+        self.redo.clear();
+        true
+    }
+
+    /// Revert the latest step, using the [`SnapshotRegistry`] resource of `world`.
+    ///
+    /// Returns `false` when there is nothing to undo.
+    pub fn undo(&mut self, world: &mut World) -> Result<bool, JournalError> {
+        world.resource_scope(|world, registry: Mut<SnapshotRegistry>| {
+            self.undo_with(world, &registry)
+        })
+    }
+
+    pub fn undo_with(
+        &mut self,
+        world: &mut World,
+        registry: &SnapshotRegistry,
+    ) -> Result<bool, JournalError> {
+        let Some(step) = self.undo.pop() else {
+            return Ok(false);
+        };
+        self.step(world, registry, &step.reverse)?;
+        self.redo.push(step);
+        Ok(true)
+    }
+
+    /// Reapply the latest undone step, using the [`SnapshotRegistry`] resource of `world`.
+    ///
+    /// Returns `false` when there is nothing to redo.
+    pub fn redo(&mut self, world: &mut World) -> Result<bool, JournalError> {
+        world.resource_scope(|world, registry: Mut<SnapshotRegistry>| {
+            self.redo_with(world, &registry)
+        })
+    }
+
+    pub fn redo_with(
+        &mut self,
+        world: &mut World,
+        registry: &SnapshotRegistry,
+    ) -> Result<bool, JournalError> {
+        let Some(step) = self.redo.pop() else {
+            return Ok(false);
+        };
+        self.step(world, registry, &step.forward)?;
+        self.undo.push(step);
+        Ok(true)
+    }
+
+    /// Return to the last save point, then apply `patch` and make the result the new one.
+    fn step(
+        &mut self,
+        world: &mut World,
+        registry: &SnapshotRegistry,
+        patch: &SnapshotDiff,
+    ) -> Result<(), JournalError> {
+        if let Some(base) = &self.base {
+            let unsaved = diff_world_snapshots(&save_world_arch_snapshot(world, registry), base);
+            if !unsaved.is_empty() {
+                apply_patch(world, registry, &unsaved)?;
+            }
+        }
+        apply_patch(world, registry, patch)?;
+        self.base = Some(save_world_arch_snapshot(world, registry));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Pos(i32, i32);
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Selected;
+
+    fn state(world: &mut World) -> Vec<(i32, i32, bool)> {
+        let mut out: Vec<_> = world
+            .query::<(&Pos, Has<Selected>)>()
+            .iter(world)
+            .map(|(p, s)| (p.0, p.1, s))
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Pos>();
+        registry.register::<Selected>();
+        let mut world = World::new();
+        world.insert_resource(registry);
+        let mut history = UndoRedo::default();
+
+        let a = world.spawn(Pos(0, 0)).id();
+        assert!(!history.save_point(&world));
+        let s0 = state(&mut world);
+
+        world.entity_mut(a).insert((Pos(1, 0), Selected));
+        assert!(history.save_point(&world));
+        let s1 = state(&mut world);
+
+        let b = world.spawn(Pos(5, 5)).id();
+        world.entity_mut(a).remove::<Selected>();
+        assert!(history.save_point(&world));
+        let s2 = state(&mut world);
+        assert!(!history.save_point(&world));
+
+        assert!(history.undo(&mut world).unwrap());
+        assert_eq!(state(&mut world), s1);
+        assert!(world.get_entity(b).is_err());
+        assert!(history.undo(&mut world).unwrap());
+        assert_eq!(state(&mut world), s0);
+        assert!(!history.undo(&mut world).unwrap());
+
+        assert!(history.redo(&mut world).unwrap());
+        assert_eq!(state(&mut world), s1);
+        // An unsaved edit is dropped when stepping through history.
+        world.get_mut::<Pos>(a).unwrap().0 = 99;
+        assert!(history.redo(&mut world).unwrap());
+        assert_eq!(state(&mut world), s2);
+        assert!(!history.can_redo());
+
+        // A new save point clears the redo stack.
+        history.undo(&mut world).unwrap();
+        world.get_mut::<Pos>(a).unwrap().1 = 7;
+        assert!(history.save_point(&world));
+        assert!(!history.can_redo());
+        assert_eq!(history.undo_steps().len(), 2);
+
+        let mut short = UndoRedo::default().with_max_depth(1);
+        short.save_point(&world);
+        for i in 0..3 {
+            world.get_mut::<Pos>(a).unwrap().0 = i;
+            short.save_point(&world);
+        }
+        assert_eq!(short.undo_steps().len(), 1);
+    }
+}
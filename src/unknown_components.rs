@@ -0,0 +1,257 @@
+//! Passthrough for component columns the registry does not know.
+//!
+//! By default a loader skips every column without a registered factory, so loading a
+//! snapshot with a reduced registry and saving it again drops other plugins' data. Set
+//! [`SnapshotRegistry::keep_unknown`] to opt in to keeping it: the archetype loaders then
+//! stash each unrecognized value as an [`UnknownComponent`] of its entity in the
+//! [`UnknownComponents`] resource, and while that resource exists
+//! [`save_world_arch_snapshot`](crate::archetype_archive::save_world_arch_snapshot) and
+//! [`MsgPackArchive::from_world`](crate::binary_archive::msgpack_archive::MsgPackArchive::from_world)
+//! write the stashed values back as ordinary columns.
+//!
+//! Stashed values stay raw JSON; if the component gets registered later, the next load
+//! decodes the re-exported column like any other.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bevy_ecs::prelude::*;
+use serde_json::Value;
+
+use crate::archetype_archive::ArchetypeSnapshot;
+use crate::bevy_registry::SnapshotRegistry;
+
+/// A component value whose type was not registered when it was loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownComponent {
+    pub name: String,
+    pub value: Value,
+}
+
+/// Side-table of [`UnknownComponent`]s per entity.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct UnknownComponents {
+    entities: HashMap<Entity, Vec<UnknownComponent>>,
+}
+
+impl UnknownComponents {
+    /// Unknown components stashed for `entity`.
+    pub fn get(&self, entity: Entity) -> &[UnknownComponent] {
+        self.entities.get(&entity).map_or(&[], Vec::as_slice)
+    }
+
+    /// Stash `component` for `entity`, replacing a value of the same name.
+    pub fn insert(&mut self, entity: Entity, component: UnknownComponent) {
+        let stash = self.entities.entry(entity).or_default();
+        match stash.iter_mut().find(|c| c.name == component.name) {
+            Some(existing) => *existing = component,
+            None => stash.push(component),
+        }
+    }
+
+    /// Drop and return everything stashed for `entity`.
+    pub fn remove_entity(&mut self, entity: Entity) -> Vec<UnknownComponent> {
+        self.entities.remove(&entity).unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &[UnknownComponent])> {
+        self.entities.iter().map(|(e, c)| (*e, c.as_slice()))
+    }
+
+    /// Number of entities with stashed components.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+/// Stash the columns of `arch` that `reg` cannot load when [`SnapshotRegistry::keep_unknown`]
+/// is set.
+///
+/// `targets[row]` is the world entity that row was loaded into. The resource is inserted on
+/// first use, after the snapshot's entity slots are taken, so it never lands on one of them.
+pub(crate) fn stash_unknown_columns(
+    world: &mut World,
+    reg: &SnapshotRegistry,
+    arch: &ArchetypeSnapshot,
+    targets: &[Entity],
+) {
+    if !reg.keep_unknown {
+        return;
+    }
+    let unknown: Vec<usize> = (0..arch.component_types.len())
+        .filter(|&col| reg.get_factory(&arch.component_types[col]).is_none())
+        .collect();
+    if unknown.is_empty() {
+        return;
+    }
+    let mut table = world.get_resource_or_insert_with(UnknownComponents::default);
+    for (row, &entity) in targets.iter().enumerate() {
+        for &col in &unknown {
+            table.insert(
+                entity,
+                UnknownComponent {
+                    name: arch.component_types[col].clone(),
+                    value: arch.columns[col][row].clone(),
+                },
+            );
+        }
+    }
+}
+
+/// Add the stashed [`UnknownComponents`] of `world` to `archetypes` as extra columns.
+///
+/// Rows of one archetype that carry different unknown components are split into separate
+/// archetypes, and entities that only have unknown components get archetypes of their own.
+/// Columns an archetype already has win over stashed values of the same name. Does nothing
+/// when the resource is absent.
+pub fn attach_unknown_components(world: &World, archetypes: &mut Vec<ArchetypeSnapshot>) {
+    let Some(table) = world.get_resource::<UnknownComponents>() else {
+        return;
+    };
+    let mut pending: BTreeMap<u32, &[UnknownComponent]> = table
+        .iter()
+        .filter(|(e, stash)| !stash.is_empty() && world.get_entity(*e).is_ok())
+        .map(|(e, stash)| (e.index_u32(), stash))
+        .collect();
+    if pending.is_empty() {
+        return;
+    }
+
+    let mut out = Vec::with_capacity(archetypes.len());
+    for arch in archetypes.drain(..) {
+        let mut groups: BTreeMap<Vec<&str>, Vec<usize>> = BTreeMap::new();
+        let mut extras: HashMap<usize, &[UnknownComponent]> = HashMap::new();
+        for (row, id) in arch.entities.iter().enumerate() {
+            let stash = pending.remove(id).unwrap_or_default();
+            let mut names: Vec<&str> = stash
+                .iter()
+                .map(|c| c.name.as_str())
+                .filter(|name| !arch.has_component(name))
+                .collect();
+            names.sort_unstable();
+            extras.insert(row, stash);
+            groups.entry(names).or_default().push(row);
+        }
+        if groups.len() == 1 && groups.contains_key(&Vec::new()) {
+            out.push(arch);
+            continue;
+        }
+        for (names, rows) in groups {
+            let mut split = select_rows(&arch, &rows);
+            let stashes: Vec<&[UnknownComponent]> = rows.iter().map(|r| extras[r]).collect();
+            append_columns(&mut split, &names, &stashes);
+            out.push(split);
+        }
+    }
+
+    let mut orphans: BTreeMap<Vec<&str>, Vec<u32>> = BTreeMap::new();
+    for (&id, stash) in &pending {
+        let mut names: Vec<&str> = stash.iter().map(|c| c.name.as_str()).collect();
+        names.sort_unstable();
+        orphans.entry(names).or_default().push(id);
+    }
+    for (names, ids) in orphans {
+        let stashes: Vec<&[UnknownComponent]> = ids.iter().map(|id| pending[id]).collect();
+        let mut arch = ArchetypeSnapshot {
+            entities: ids,
+            ..Default::default()
+        };
+        append_columns(&mut arch, &names, &stashes);
+        out.push(arch);
+    }
+    *archetypes = out;
+}
+
+/// Copy `rows` of `arch` into a new archetype.
+fn select_rows(arch: &ArchetypeSnapshot, rows: &[usize]) -> ArchetypeSnapshot {
+    ArchetypeSnapshot {
+        component_types: arch.component_types.clone(),
+        storage_types: arch.storage_types.clone(),
+        columns: arch
+            .columns
+            .iter()
+            .map(|col| rows.iter().map(|&r| col[r].clone()).collect())
+            .collect(),
+        entities: rows.iter().map(|&r| arch.entities[r]).collect(),
+    }
+}
+
+/// Append a column per name, filled from the stash of each row.
+fn append_columns(arch: &mut ArchetypeSnapshot, names: &[&str], stashes: &[&[UnknownComponent]]) {
+    for name in names {
+        arch.add_type(name, None);
+        let col = arch.get_column_mut(name).unwrap();
+        for (slot, stash) in col.iter_mut().zip(stashes) {
+            if let Some(c) = stash.iter().find(|c| c.name == *name) {
+                *slot = c.value.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aurora_archive::{load_world_manifest, save_world_manifest};
+    use crate::binary_archive::msgpack_archive::MsgPackArchive;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Hp(u32);
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Mana {
+        current: u32,
+        max: u32,
+    }
+
+    #[test]
+    fn test_unknown_components_survive_reduced_registry() {
+        let mut full = SnapshotRegistry::default();
+        full.register::<Hp>();
+        full.register::<Mana>();
+        let mut reduced = SnapshotRegistry::default();
+        reduced.register::<Hp>();
+        reduced.keep_unknown = true;
+
+        let mut world = World::new();
+        world.spawn((Hp(1), Mana { current: 3, max: 9 }));
+        world.spawn(Hp(2));
+        world.spawn(Mana { current: 0, max: 5 });
+        let manifest = save_world_manifest(&world, &full).unwrap();
+
+        let mut reduced_world = World::new();
+        load_world_manifest(&mut reduced_world, &manifest, &reduced).unwrap();
+        let stashed = reduced_world.resource::<UnknownComponents>();
+        assert_eq!(stashed.len(), 2);
+        assert!(stashed.iter().all(|(_, s)| s[0].name == "Mana"));
+
+        let resaved = MsgPackArchive::from_world(&reduced_world, &reduced).unwrap();
+        let mut back = World::new();
+        resaved.to_world(&mut back, &full).unwrap();
+        let mut mana: Vec<_> = back
+            .query::<(Option<&Hp>, &Mana)>()
+            .iter(&back)
+            .map(|(h, m)| (h.cloned(), m.clone()))
+            .collect();
+        mana.sort_by_key(|(_, m)| m.max);
+        assert_eq!(
+            mana,
+            vec![
+                (None, Mana { current: 0, max: 5 }),
+                (Some(Hp(1)), Mana { current: 3, max: 9 }),
+            ]
+        );
+        assert_eq!(back.query::<&Hp>().iter(&back).count(), 2);
+
+        // Without the option the column is dropped as before.
+        reduced.keep_unknown = false;
+        let mut plain = World::new();
+        load_world_manifest(&mut plain, &manifest, &reduced).unwrap();
+        assert!(!plain.contains_resource::<UnknownComponents>());
+        let snapshot = crate::archetype_archive::save_world_arch_snapshot(&plain, &reduced);
+        assert!(snapshot.archetypes.iter().all(|a| !a.has_component("Mana")));
+    }
+}
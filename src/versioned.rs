@@ -0,0 +1,135 @@
+//! [`Versioned`], for components whose saved shape changed between releases.
+//!
+//! Give the component a `From<Old>` impl and register it with
+//! [`SnapshotRegistry::register_versioned`](crate::bevy_registry::SnapshotRegistry::register_versioned).
+//! Values are always saved in the current shape. On load each value is decoded as the
+//! current shape first and, failing that, as `Old` upgraded with `T::from`.
+//!
+//! Untagged decoding takes the first shape that fits, so the current shape must reject old
+//! values, e.g. through a required field `Old` lacks. Chain more versions by nesting:
+//! `Versioned<Versioned<V1, V2>, V3>`, with `V3: From<Versioned<V1, V2>>` calling
+//! [`into_current`](Versioned::into_current).
+//!
+//! Only the JSON-based formats (JSON, TOML, CSV, MessagePack) read old values; Arrow and
+//! Parquet columns are decoded with the current schema.
+
+use serde::{Deserialize, Serialize};
+
+/// A value saved either in the current shape `New` or the older shape `Old`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Versioned<Old, New> {
+    Current(New),
+    Legacy(Old),
+}
+
+impl<Old, New: From<Old>> Versioned<Old, New> {
+    /// The value in the current shape, upgrading a legacy one.
+    pub fn into_current(self) -> New {
+        match self {
+            Versioned::Current(new) => new,
+            Versioned::Legacy(old) => New::from(old),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+    use serde_json::json;
+
+    use super::*;
+    use crate::archetype_archive::{load_world_arch_snapshot, save_world_arch_snapshot};
+    use crate::bevy_registry::SnapshotRegistry;
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct PosV1 {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct PosV2 {
+        x: f32,
+        y: f32,
+        z: f32,
+    }
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Pos {
+        xyz: [f32; 3],
+    }
+
+    impl From<PosV1> for PosV2 {
+        fn from(p: PosV1) -> Self {
+            PosV2 {
+                x: p.x,
+                y: p.y,
+                z: 0.0,
+            }
+        }
+    }
+
+    impl From<Versioned<PosV1, PosV2>> for Pos {
+        fn from(p: Versioned<PosV1, PosV2>) -> Self {
+            let p = p.into_current();
+            Pos {
+                xyz: [p.x, p.y, p.z],
+            }
+        }
+    }
+
+    #[test]
+    fn test_versioned_newest_first() {
+        let v: Versioned<PosV1, PosV2> =
+            serde_json::from_value(json!({"x": 1.0, "y": 2.0, "z": 3.0})).unwrap();
+        assert!(matches!(v, Versioned::Current(_)));
+        let v: Versioned<PosV1, PosV2> =
+            serde_json::from_value(json!({"x": 1.0, "y": 2.0})).unwrap();
+        assert_eq!(
+            v.into_current(),
+            PosV2 {
+                x: 1.0,
+                y: 2.0,
+                z: 0.0
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(Versioned::<PosV1, _>::Current(PosV2 {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0
+            }))
+            .unwrap(),
+            json!({"x": 1.0, "y": 2.0, "z": 3.0})
+        );
+    }
+
+    #[test]
+    fn test_register_versioned_upgrades_old_saves() {
+        let mut old_registry = SnapshotRegistry::default();
+        old_registry.register_named::<PosV1>("Pos");
+        let mut old_world = World::new();
+        old_world.spawn(PosV1 { x: 1.0, y: 2.0 });
+        let old_save = save_world_arch_snapshot(&old_world, &old_registry);
+
+        let mut registry = SnapshotRegistry::default();
+        registry.register_versioned::<Pos, Versioned<PosV1, PosV2>>();
+        let mut world = World::new();
+        world.spawn(Pos {
+            xyz: [4.0, 5.0, 6.0],
+        });
+        let new_save = save_world_arch_snapshot(&world, &registry);
+
+        for (save, xyz) in [(old_save, [1.0, 2.0, 0.0]), (new_save, [4.0, 5.0, 6.0])] {
+            let mut loaded = World::new();
+            load_world_arch_snapshot(&mut loaded, &save, &registry).unwrap();
+            let values: Vec<_> = loaded
+                .query::<&Pos>()
+                .iter(&loaded)
+                .map(|p| p.xyz)
+                .collect();
+            assert_eq!(values, vec![xyz]);
+        }
+    }
+}
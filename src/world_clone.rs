@@ -0,0 +1,203 @@
+//! Copy registered state between two live worlds.
+//!
+//! [`clone_registered`] walks the archetypes of the source world and stages every
+//! registered component into a [`HarvardCommandBuffer`] for the destination, the same way
+//! the loaders do, but without writing a snapshot in between. Components registered with
+//! [`SnapshotRegistry::register_clone`] are copied with [`Clone`]; the others go through
+//! their JSON codec as an in-memory [`serde_json::Value`], never as bytes. Registered
+//! resources are copied through their JSON codec.
+//!
+//! Useful for forking a headless world, or for keeping a live copy as a rollback buffer.
+
+use bevy_ecs::component::ComponentId;
+use bevy_ecs::prelude::*;
+use bevy_ecs::resource::IS_RESOURCE;
+
+use crate::bevy_cmdbuffer::HarvardCommandBuffer;
+use crate::bevy_registry::{
+    EntityRemapper, LimitError, SnapshotFactory, SnapshotMode, SnapshotRegistry,
+    try_reserve_entity_slots,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CloneError {
+    #[error(transparent)]
+    Limit(#[from] LimitError),
+    #[error("entity {0} is mapped to no destination entity")]
+    Unmapped(u32),
+    #[error("cannot copy {name}: {message}")]
+    Component { name: String, message: String },
+}
+
+/// Copy every registered component and resource of `src` into `dst`.
+///
+/// With `remap`, each source entity index is written to the entity it maps to. Without it
+/// entities keep their indices, like a snapshot load, and the slots are reserved in `dst`
+/// first. Entity fields inside components are copied as they are. Components follow the
+/// [`SnapshotMode`] of their factory.
+pub fn clone_registered(
+    src: &World,
+    dst: &mut World,
+    registry: &SnapshotRegistry,
+    remap: Option<&dyn EntityRemapper>,
+) -> Result<(), CloneError> {
+    let archetypes: Vec<_> = src
+        .archetypes()
+        .iter()
+        .filter(|a| !a.is_empty() && !a.contains(IS_RESOURCE))
+        .collect();
+    if remap.is_none() {
+        let max_index = archetypes
+            .iter()
+            .flat_map(|a| a.entities())
+            .map(|e| e.id().index_u32())
+            .max();
+        if let Some(max_index) = max_index {
+            try_reserve_entity_slots(dst, max_index.saturating_add(1), &registry.limits)?;
+            dst.flush();
+        }
+    }
+
+    // After the reservation, so new resources do not take a snapshot index. Resource codecs
+    // ignore the entity argument.
+    let unused = Entity::from_raw_u32(0).unwrap();
+    for (name, factory) in &registry.resource_entries {
+        if let Some(value) = (factory.js_value.export)(src, unused) {
+            (factory.js_value.import)(&value, dst, unused).map_err(|message| {
+                CloneError::Component {
+                    name: name.to_string(),
+                    message,
+                }
+            })?;
+        }
+    }
+
+    let columns: Vec<(&str, ComponentId, ComponentId, &SnapshotFactory)> = registry
+        .entries
+        .iter()
+        .filter_map(|(name, factory)| {
+            let src_id = (factory.comp_id)(src)?;
+            let dst_id = (factory.comp_id)(dst).unwrap_or_else(|| (factory.register)(dst));
+            Some((*name, src_id, dst_id, factory))
+        })
+        .collect();
+
+    let mut buffer = HarvardCommandBuffer::new();
+    for archetype in archetypes {
+        let present: Vec<_> = columns
+            .iter()
+            .filter(|(_, src_id, ..)| archetype.contains(*src_id))
+            .collect();
+        if present.is_empty() {
+            continue;
+        }
+        buffer.record(|rec| {
+            for entity in archetype.entities() {
+                let entity = entity.id();
+                let target = match remap {
+                    Some(mapper) => mapper.map(entity.index_u32()),
+                    None => dst.entities().resolve_from_index(entity.index()),
+                };
+                if target == Entity::PLACEHOLDER {
+                    return Err(CloneError::Unmapped(entity.index_u32()));
+                }
+                for &&(name, _, dst_id, factory) in &present {
+                    let bump = rec.bump();
+                    let fail = |message: String| CloneError::Component {
+                        name: name.to_string(),
+                        message,
+                    };
+                    let boxed = match factory.clone_value {
+                        Some(clone) => clone(src, entity, bump),
+                        None => (factory.js_value.export)(src, entity)
+                            .map(|value| (factory.js_value.dyn_ctor)(&value, bump))
+                            .transpose()
+                            .map_err(fail)?,
+                    };
+                    let Some(boxed) = boxed else {
+                        return Err(fail("export failed".to_string()));
+                    };
+                    // SAFETY: `boxed` was built by the factory registered for `dst_id`.
+                    match factory.mode {
+                        SnapshotMode::Full => unsafe { rec.insert_box(target, dst_id, boxed) },
+                        SnapshotMode::EmplaceIfNotExists => {
+                            if dst.get_entity(target).is_ok_and(|e| e.contains_id(dst_id)) {
+                                boxed.manual_drop();
+                            } else {
+                                unsafe { rec.insert_box(target, dst_id, boxed) };
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(())
+        })?;
+        buffer.apply(dst);
+        buffer.reset();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Pos(f32, f32);
+    #[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Name(String);
+    #[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Tick(u64);
+
+    fn registry() -> SnapshotRegistry {
+        let mut registry = SnapshotRegistry::default();
+        registry.register::<Pos>();
+        registry.register::<Name>();
+        registry.register_clone::<Pos>();
+        registry.resource_register::<Tick>();
+        registry
+    }
+
+    #[test]
+    fn test_clone_registered() {
+        let registry = registry();
+        let mut src = World::new();
+        src.insert_resource(Tick(42));
+        src.spawn_empty();
+        let a = src.spawn((Pos(1.0, 2.0), Name("a".into()))).id();
+        let b = src.spawn(Pos(3.0, 4.0)).id();
+
+        // Same indices.
+        let mut fork = World::new();
+        clone_registered(&src, &mut fork, &registry, None).unwrap();
+        assert_eq!(fork.resource::<Tick>(), &Tick(42));
+        for e in [a, b] {
+            let at = fork.entities().resolve_from_index(e.index());
+            assert_eq!(fork.get::<Pos>(at), src.get::<Pos>(e));
+            assert_eq!(fork.get::<Name>(at), src.get::<Name>(e));
+        }
+
+        // Into fresh entities of a populated world.
+        let mut dst = World::new();
+        dst.spawn(Pos(9.0, 9.0));
+        let mapper: HashMap<u32, Entity> = [a, b]
+            .iter()
+            .map(|e| (e.index_u32(), dst.spawn_empty().id()))
+            .collect();
+        clone_registered(&src, &mut dst, &registry, Some(&mapper)).unwrap();
+        assert_eq!(
+            dst.get::<Name>(mapper[&a.index_u32()]),
+            Some(&Name("a".into()))
+        );
+        assert_eq!(dst.query::<&Pos>().iter(&dst).count(), 3);
+
+        let partial: HashMap<u32, Entity> = HashMap::new();
+        assert!(matches!(
+            clone_registered(&src, &mut World::new(), &registry, Some(&partial)),
+            Err(CloneError::Unmapped(_))
+        ));
+    }
+}